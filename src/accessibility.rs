@@ -0,0 +1,118 @@
+//! Semantic regions describing what a canvas shows, for screen readers.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Canvas, Rect};
+
+/// A labelled part of a canvas with the value it shows, such as a gauge reading "CPU: 42%".
+///
+/// Pixels mean nothing to a screen reader, so apps register regions on the canvas with
+/// [`Canvas::set_region`], and [`Terminal::set_screen_reader_mode`] shows them as text instead
+/// of the pixels.
+///
+/// [`Terminal::set_screen_reader_mode`]: crate::Terminal::set_screen_reader_mode
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Rect};
+///
+/// let mut canvas = Canvas::new(40, 10, Color { r: 0, g: 0, b: 0 });
+/// canvas.set_region("Memory", "2.1 GB", Rect::new(0, 4, 20, 2));
+/// canvas.set_region("CPU", "42%", Rect::new(0, 0, 20, 2));
+/// canvas.set_focus(Some("Memory"));
+/// assert_eq!(canvas.accessible_text(), ["CPU: 42%", "Memory: 2.1 GB (focused)"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticRegion {
+    /// What the region shows, such as "CPU". Labels identify regions, so they are unique.
+    pub label: String,
+    /// The value the region shows, such as "42%". May be empty, for headings and buttons.
+    pub value: String,
+    /// Where the region is on the canvas, which orders regions when they are read.
+    pub area: Rect,
+}
+
+impl SemanticRegion {
+    /// Returns the line of text read out for the region, mentioning whether it is focused.
+    pub fn text(&self, focused: bool) -> String {
+        let mut text = if self.value.is_empty() {
+            self.label.clone()
+        } else {
+            format!("{}: {}", self.label, self.value)
+        };
+        if focused {
+            text.push_str(" (focused)");
+        }
+        text
+    }
+}
+
+impl Canvas {
+    /// Registers the region labelled `label`, or updates it if it exists. Regions stay until
+    /// removed, clearing the canvas keeps them.
+    pub fn set_region(&mut self, label: &str, value: &str, area: Rect) {
+        match self.regions.iter_mut().find(|region| region.label == label) {
+            Some(region) => {
+                region.value = value.to_string();
+                region.area = area;
+            }
+            None => self.regions.push(SemanticRegion {
+                label: label.to_string(),
+                value: value.to_string(),
+                area,
+            }),
+        }
+    }
+
+    /// Removes the region labelled `label`, returning whether it existed.
+    pub fn remove_region(&mut self, label: &str) -> bool {
+        let count = self.regions.len();
+        self.regions.retain(|region| region.label != label);
+        if self.focused_region.as_deref() == Some(label) {
+            self.focused_region = None;
+        }
+        self.regions.len() != count
+    }
+
+    /// Removes every region.
+    pub fn clear_regions(&mut self) {
+        self.regions.clear();
+        self.focused_region = None;
+    }
+
+    /// Returns the regions, in the order they were registered.
+    pub fn regions(&self) -> &[SemanticRegion] {
+        &self.regions
+    }
+
+    /// Sets the region that has the focus, such as the selected item of a menu, by label, or
+    /// none.
+    pub fn set_focus(&mut self, label: Option<&str>) {
+        self.focused_region = label.map(str::to_string);
+    }
+
+    /// Returns the region that has the focus, if any.
+    pub fn focus(&self) -> Option<&SemanticRegion> {
+        let label = self.focused_region.as_deref()?;
+        self.regions.iter().find(|region| region.label == label)
+    }
+
+    /// Returns the regions in reading order, top to bottom and left to right.
+    pub fn regions_in_reading_order(&self) -> Vec<&SemanticRegion> {
+        let mut regions: Vec<_> = self.regions.iter().collect();
+        regions.sort_by_key(|region| (region.area.y, region.area.x));
+        regions
+    }
+
+    /// Returns the text of every region in reading order, one line per region.
+    pub fn accessible_text(&self) -> Vec<String> {
+        let focus = self.focused_region.as_deref();
+        self.regions_in_reading_order()
+            .into_iter()
+            .map(|region| region.text(focus == Some(region.label.as_str())))
+            .collect()
+    }
+}
@@ -0,0 +1,230 @@
+//! Bitmask autotiling: picking terrain and wall tiles from the configuration of their neighbors.
+
+use crate::TileMap;
+
+// The bits of the neighbor mask used by `AutotileMode::Edges16`.
+const NORTH: u8 = 1;
+const EAST: u8 = 2;
+const SOUTH: u8 = 4;
+const WEST: u8 = 8;
+
+/// The offsets of the eight neighbors of a cell, in the order of their bits in the mask used by
+/// [`AutotileMode::Blob47`]: north, north-east, east, south-east, south, south-west, west and
+/// north-west.
+const NEIGHBORS: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// Clears the corner bits of an eight-neighbor mask whose two adjacent edges are not both set,
+/// since such corners do not change the shape of the tile.
+const fn reduce(mask: u8) -> u8 {
+    let mut reduced = mask & 0b0101_0101;
+    let mut corner = 1;
+    while corner < 8 {
+        let before = 1 << (corner - 1);
+        let after = 1 << ((corner + 1) % 8);
+        if mask & (1 << corner) != 0 && mask & before != 0 && mask & after != 0 {
+            reduced |= 1 << corner;
+        }
+        corner += 2;
+    }
+    reduced
+}
+
+const fn blob_masks() -> [u8; 47] {
+    let mut masks = [0; 47];
+    let mut count = 0;
+    let mut mask = 0;
+    while mask < 256 {
+        if reduce(mask as u8) == mask as u8 {
+            masks[count] = mask as u8;
+            count += 1;
+        }
+        mask += 1;
+    }
+    masks
+}
+
+/// How an [`Autotile`] looks at the neighbors of a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AutotileMode {
+    /// Only the four edge neighbors are considered, giving 16 tiles indexed by a mask with
+    /// north = 1, east = 2, south = 4 and west = 8.
+    Edges16,
+    /// All eight neighbors are considered, with a corner only counted when both edges next to it
+    /// are connected, giving the 47 tiles of a "blob" set. The tiles are ordered as in
+    /// [`Autotile::BLOB_MASKS`].
+    Blob47,
+}
+
+impl AutotileMode {
+    /// Returns the number of tiles a set for this mode holds.
+    pub fn tile_count(self) -> usize {
+        match self {
+            AutotileMode::Edges16 => 16,
+            AutotileMode::Blob47 => 47,
+        }
+    }
+}
+
+/// A set of tiles for one kind of terrain or wall, picked according to which neighboring cells
+/// hold the same terrain.
+///
+/// Every cell of a [`TileMap`] holding one of the tiles of the set counts as that terrain.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Autotile, AutotileMode, Sprite, TileMap, Tileset};
+///
+/// // A 16-tile wall set stored in tiles 0 to 15 of the tileset.
+/// let sheet = Sprite::new(4 * 16, 4);
+/// let walls = Autotile::sequential(AutotileMode::Edges16, 0);
+/// let mut map = TileMap::new(20, 10, Tileset::from_sheet(&sheet, 4, 4));
+/// for x in 2..8 {
+///     map.paint_autotile(x, 3, &walls);
+/// }
+/// assert_eq!(map.get(2, 3), Some(2)); // Connected to the east only.
+/// assert_eq!(map.get(4, 3), Some(10)); // Connected to the east and west.
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Autotile {
+    mode: AutotileMode,
+    tiles: Vec<usize>,
+    connect_edges: bool,
+}
+
+impl Autotile {
+    /// The 47 reduced neighbor masks of [`AutotileMode::Blob47`] in ascending order, which is the
+    /// order of the tiles of a blob set. Bit 0 is the north neighbor and the following bits go
+    /// clockwise: north-east, east, south-east, south, south-west, west and north-west.
+    pub const BLOB_MASKS: [u8; 47] = blob_masks();
+
+    /// Creates a 16-tile set, where `tiles[mask]` is the tile index for each edge mask.
+    pub fn edges(tiles: [usize; 16]) -> Self {
+        Self {
+            mode: AutotileMode::Edges16,
+            tiles: tiles.to_vec(),
+            connect_edges: false,
+        }
+    }
+
+    /// Creates a 47-tile blob set, where `tiles[i]` is the tile index for the mask
+    /// `Autotile::BLOB_MASKS[i]`.
+    pub fn blob(tiles: [usize; 47]) -> Self {
+        Self {
+            mode: AutotileMode::Blob47,
+            tiles: tiles.to_vec(),
+            connect_edges: false,
+        }
+    }
+
+    /// Creates a set whose tiles are stored one after the other in the tileset, starting at
+    /// index `first`.
+    pub fn sequential(mode: AutotileMode, first: usize) -> Self {
+        Self {
+            mode,
+            tiles: (first..first + mode.tile_count()).collect(),
+            connect_edges: false,
+        }
+    }
+
+    /// Sets whether cells on the border of the map connect to the outside, which suits walls
+    /// that continue past the map. Off by default.
+    pub fn connect_edges(mut self, connect_edges: bool) -> Self {
+        self.connect_edges = connect_edges;
+        self
+    }
+
+    /// Returns the mode of the set.
+    pub fn mode(&self) -> AutotileMode {
+        self.mode
+    }
+
+    /// Returns `true` if `tile` belongs to the set.
+    pub fn contains(&self, tile: usize) -> bool {
+        self.tiles.contains(&tile)
+    }
+
+    /// Returns the tile index for a neighbor mask. Blob masks are reduced first, so any
+    /// eight-neighbor mask is accepted.
+    pub fn tile_for(&self, mask: u8) -> usize {
+        match self.mode {
+            AutotileMode::Edges16 => self.tiles[(mask & 0b1111) as usize],
+            AutotileMode::Blob47 => {
+                let mask = reduce(mask);
+                let index = Self::BLOB_MASKS
+                    .binary_search(&mask)
+                    .expect("reduced masks are blob masks");
+                self.tiles[index]
+            }
+        }
+    }
+
+    /// Returns the neighbor mask of the cell at (`x`, `y`) of `map`.
+    pub fn mask(&self, map: &TileMap, x: usize, y: usize) -> u8 {
+        let connected = |(dx, dy): (isize, isize)| {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if nx < 0 || ny < 0 || nx as usize >= map.width() || ny as usize >= map.height() {
+                return self.connect_edges;
+            }
+            map.get(nx as usize, ny as usize)
+                .is_some_and(|tile| self.contains(tile))
+        };
+        match self.mode {
+            AutotileMode::Edges16 => [(NORTH, 0), (EAST, 2), (SOUTH, 4), (WEST, 6)]
+                .into_iter()
+                .filter(|(_, neighbor)| connected(NEIGHBORS[*neighbor]))
+                .fold(0, |mask, (bit, _)| mask | bit),
+            AutotileMode::Blob47 => (0..8)
+                .filter(|bit| connected(NEIGHBORS[*bit]))
+                .fold(0, |mask, bit| mask | 1 << bit),
+        }
+    }
+}
+
+impl TileMap {
+    /// Replaces every cell holding a tile of `autotile` with the tile matching its neighbors.
+    pub fn autotile(&mut self, autotile: &Autotile) {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                self.autotile_cell(x, y, autotile);
+            }
+        }
+    }
+
+    /// Updates the cell at (`x`, `y`) and its eight neighbors after the cell has been edited,
+    /// without visiting the rest of the map.
+    pub fn autotile_around(&mut self, x: usize, y: usize, autotile: &Autotile) {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if nx >= 0 && ny >= 0 {
+                    self.autotile_cell(nx as usize, ny as usize, autotile);
+                }
+            }
+        }
+    }
+
+    /// Paints the terrain of `autotile` at (`x`, `y`) and updates the surrounding tiles.
+    pub fn paint_autotile(&mut self, x: usize, y: usize, autotile: &Autotile) {
+        if x < self.width() && y < self.height() {
+            self.set(x, y, Some(autotile.tile_for(0)));
+            self.autotile_around(x, y, autotile);
+        }
+    }
+
+    fn autotile_cell(&mut self, x: usize, y: usize, autotile: &Autotile) {
+        if self.get(x, y).is_some_and(|tile| autotile.contains(tile)) {
+            let tile = autotile.tile_for(autotile.mask(self, x, y));
+            self.set(x, y, Some(tile));
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Terminal control behind a trait, so Rael can drive terminals through other libraries.
+
+#[cfg(unix)]
+use std::fs::OpenOptions;
+#[cfg(unix)]
+use std::io::Read;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+#[cfg(unix)]
+use std::thread;
+use std::time::Duration;
+#[cfg(unix)]
+use std::time::Instant;
+
+use crossterm::event::{self, Event};
+use crossterm::style::{self, Color as ConsoleColor};
+use crossterm::{cursor, execute, queue, terminal};
+
+#[cfg(unix)]
+use crate::capabilities::is_complete_response;
+use crate::{Color, Frame};
+
+/// The 16 colors of the classic Windows console, with their RGB values.
+const CONSOLE_PALETTE: [(ConsoleColor, [u8; 3]); 16] = [
+    (ConsoleColor::Black, [0, 0, 0]),
+    (ConsoleColor::DarkRed, [128, 0, 0]),
+    (ConsoleColor::DarkGreen, [0, 128, 0]),
+    (ConsoleColor::DarkYellow, [128, 128, 0]),
+    (ConsoleColor::DarkBlue, [0, 0, 128]),
+    (ConsoleColor::DarkMagenta, [128, 0, 128]),
+    (ConsoleColor::DarkCyan, [0, 128, 128]),
+    (ConsoleColor::Grey, [192, 192, 192]),
+    (ConsoleColor::DarkGrey, [128, 128, 128]),
+    (ConsoleColor::Red, [255, 0, 0]),
+    (ConsoleColor::Green, [0, 255, 0]),
+    (ConsoleColor::Yellow, [255, 255, 0]),
+    (ConsoleColor::Blue, [0, 0, 255]),
+    (ConsoleColor::Magenta, [255, 0, 255]),
+    (ConsoleColor::Cyan, [0, 255, 255]),
+    (ConsoleColor::White, [255, 255, 255]),
+];
+
+/// The terminal operations a [`Terminal`](crate::Terminal) needs: switching modes, querying
+/// the size, reading events and writing output.
+///
+/// [`CrosstermBackend`] is the default implementation. Other libraries such as termion or
+/// termwiz, or a custom pty, can be plugged in by implementing this trait. Events use
+/// crossterm's event types, which other backends translate theirs to.
+pub trait Backend {
+    /// Switches the terminal to raw mode, where key presses arrive immediately and are not
+    /// echoed.
+    fn enable_raw_mode(&mut self) -> io::Result<()>;
+
+    /// Switches the terminal back from raw mode.
+    fn disable_raw_mode(&mut self) -> io::Result<()>;
+
+    /// Switches to the alternate screen, keeping what the terminal showed before.
+    fn enter_alternate_screen(&mut self) -> io::Result<()>;
+
+    /// Switches back from the alternate screen.
+    fn leave_alternate_screen(&mut self) -> io::Result<()>;
+
+    /// Hides the cursor.
+    fn hide_cursor(&mut self) -> io::Result<()>;
+
+    /// Shows the cursor.
+    fn show_cursor(&mut self) -> io::Result<()>;
+
+    /// Returns the size of the terminal, in columns and rows.
+    fn size(&self) -> io::Result<(usize, usize)>;
+
+    /// Moves the cursor to (`column`, `row`). Writes an ANSI escape code by default.
+    fn move_cursor(&mut self, column: usize, row: usize) -> io::Result<()> {
+        self.write(format!("\x1b[{};{}H", row + 1, column + 1).as_bytes())
+    }
+
+    /// Asks the terminal where the cursor is, in columns and rows. Unsupported by default.
+    fn cursor_position(&mut self) -> io::Result<(usize, usize)> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Sets the title of the terminal window. Writes an OSC escape code by default.
+    fn set_title(&mut self, title: &str) -> io::Result<()> {
+        self.write(format!("\x1b]0;{title}\x07").as_bytes())
+    }
+
+    /// Writes `query`, escape codes asking the terminal about itself such as
+    /// [`CAPABILITY_QUERY`](crate::CAPABILITY_QUERY), and returns what the terminal answers
+    /// until it sends a device attributes answer or `timeout` passes. Unsupported by default.
+    fn query(&mut self, query: &[u8], timeout: Duration) -> io::Result<Vec<u8>> {
+        let _ = (query, timeout);
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Returns whether the terminal understands ANSI escape codes. Backends for terminals that
+    /// do not should override [`Backend::clear`] and [`Backend::draw_frame`] too. Defaults to
+    /// `true`.
+    fn supports_ansi(&self) -> bool {
+        true
+    }
+
+    /// Resets colors and clears the screen. Writes ANSI escape codes by default.
+    fn clear(&mut self) -> io::Result<()> {
+        self.write(b"\x1b[0m\x1b[2J")
+    }
+
+    /// Draws `frame` over `previous`, the frame the terminal shows if it is known, so only the
+    /// cells that changed need drawing. Writes ANSI escape codes by default.
+    fn draw_frame(&mut self, frame: &Frame, previous: Option<&Frame>) -> io::Result<()> {
+        self.write(frame.ansi_since(previous).as_bytes())
+    }
+
+    /// Returns how many bytes of output were sent through the backend so far, if it counts
+    /// them. Returns `None` by default.
+    fn bytes_written(&self) -> Option<u64> {
+        None
+    }
+
+    /// Waits up to `timeout` for an event, returning `None` if there was none.
+    fn poll_event(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+
+    /// Writes output, such as the escape codes returned by [`Canvas::render`](crate::Canvas).
+    fn write(&mut self, output: &[u8]) -> io::Result<()>;
+
+    /// Sends written output to the terminal.
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// A [`Backend`] using crossterm, writing to `W`, usually standard output.
+///
+/// On Windows, the backend enables the processing of ANSI escape codes in the console. Older
+/// consoles that cannot process them are drawn through console API calls instead, with every
+/// color reduced to the nearest of the 16 console colors.
+#[derive(Debug)]
+pub struct CrosstermBackend<W: Write> {
+    writer: Counted<W>,
+    legacy: bool,
+}
+
+/// A writer counting the bytes written through it.
+#[derive(Debug)]
+struct Counted<W> {
+    inner: W,
+    bytes: u64,
+}
+
+impl<W: Write> Write for Counted<W> {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        let count = self.inner.write(buffer)?;
+        self.bytes += count as u64;
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> CrosstermBackend<W> {
+    /// Creates a backend writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        #[cfg(windows)]
+        let legacy = !crossterm::ansi_support::supports_ansi();
+        #[cfg(not(windows))]
+        let legacy = false;
+        Self {
+            writer: Counted {
+                inner: writer,
+                bytes: 0,
+            },
+            legacy,
+        }
+    }
+
+    /// Sets whether to draw with the 16 console colors and no ANSI escape codes other than
+    /// crossterm's own, as is done on consoles without ANSI support. Defaults to whether the
+    /// console lacks ANSI support.
+    pub fn legacy_console(mut self, legacy: bool) -> Self {
+        self.legacy = legacy;
+        self
+    }
+
+    /// Returns the writer.
+    pub fn writer(&self) -> &W {
+        &self.writer.inner
+    }
+
+    /// Returns the writer, for writing to it directly. What is written this way is not counted
+    /// by [`Backend::bytes_written`].
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer.inner
+    }
+}
+
+impl<W: Write> Backend for CrosstermBackend<W> {
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::disable_raw_mode()
+    }
+
+    fn enter_alternate_screen(&mut self) -> io::Result<()> {
+        execute!(self.writer, terminal::EnterAlternateScreen)
+    }
+
+    fn leave_alternate_screen(&mut self) -> io::Result<()> {
+        execute!(self.writer, terminal::LeaveAlternateScreen)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        execute!(self.writer, cursor::Hide)
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        execute!(self.writer, cursor::Show)
+    }
+
+    fn size(&self) -> io::Result<(usize, usize)> {
+        let (columns, rows) = terminal::size()?;
+        Ok((columns as usize, rows as usize))
+    }
+
+    fn move_cursor(&mut self, column: usize, row: usize) -> io::Result<()> {
+        queue!(self.writer, cursor::MoveTo(column as u16, row as u16))
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(usize, usize)> {
+        let (column, row) = cursor::position()?;
+        Ok((column as usize, row as usize))
+    }
+
+    fn set_title(&mut self, title: &str) -> io::Result<()> {
+        queue!(self.writer, terminal::SetTitle(title))
+    }
+
+    #[cfg(unix)]
+    fn query(&mut self, query: &[u8], timeout: Duration) -> io::Result<Vec<u8>> {
+        // The answers arrive as input. They are read from the terminal device without blocking,
+        // so no reader is left waiting to take input meant for the event loop.
+        let mut tty = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/tty")?;
+        self.writer.write_all(query)?;
+        self.writer.flush()?;
+        let deadline = Instant::now() + timeout;
+        let mut answer = Vec::new();
+        let mut buffer = [0; 256];
+        while !is_complete_response(&answer) && Instant::now() < deadline {
+            match tty.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => answer.extend_from_slice(&buffer[..count]),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(2));
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(answer)
+    }
+
+    fn supports_ansi(&self) -> bool {
+        !self.legacy
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        execute!(
+            self.writer,
+            style::ResetColor,
+            terminal::Clear(terminal::ClearType::All)
+        )
+    }
+
+    fn draw_frame(&mut self, frame: &Frame, previous: Option<&Frame>) -> io::Result<()> {
+        if !self.legacy {
+            return self.write(frame.ansi_since(previous).as_bytes());
+        }
+        let previous = previous.filter(|previous| {
+            (previous.width(), previous.height()) == (frame.width(), frame.height())
+        });
+        for row in 0..frame.height() {
+            for column in 0..frame.width() {
+                let (cell, glyph) = (frame.cell(column, row), frame.glyph(column, row));
+                if previous.is_some_and(|previous| {
+                    previous.cell(column, row) == cell && previous.glyph(column, row) == glyph
+                }) {
+                    continue;
+                }
+                let Some(cell) = cell else { continue };
+                let (symbol, foreground, background) = match glyph {
+                    Some((ch, color)) => (ch, color, cell.top_color),
+                    None => ('▄', cell.bottom_color, cell.top_color),
+                };
+                // Legacy consoles only show 16 colors, and crossterm turns RGB colors to black
+                // on them.
+                queue!(
+                    self.writer,
+                    cursor::MoveTo(column as u16, row as u16),
+                    style::SetForegroundColor(nearest_console_color(foreground)),
+                    style::SetBackgroundColor(nearest_console_color(background)),
+                    style::Print(symbol)
+                )?;
+            }
+        }
+        queue!(self.writer, style::ResetColor)
+    }
+
+    fn bytes_written(&self) -> Option<u64> {
+        Some(self.writer.bytes)
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if event::poll(timeout)? {
+            event::read().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn write(&mut self, output: &[u8]) -> io::Result<()> {
+        self.writer.write_all(output)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Returns the console color closest to `color`.
+fn nearest_console_color(color: Color) -> ConsoleColor {
+    let distance = |[r, g, b]: [u8; 3]| {
+        let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        channel(color.r, r) + channel(color.g, g) + channel(color.b, b)
+    };
+    CONSOLE_PALETTE
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(console, _)| *console)
+        .unwrap_or(ConsoleColor::Black)
+}
@@ -0,0 +1,135 @@
+//! Brushes: footprints stamped onto a canvas, for drawing tools, thick lines and trails.
+
+use crate::{Canvas, Color};
+
+/// A footprint of pixels stamped onto a canvas with [`Canvas::stamp`], such as the tip of a
+/// drawing tool or a particle of a trail.
+///
+/// The footprint is worked out once, as the runs of pixels of its rows, so stamping the same
+/// brush many times costs only the writing of its pixels.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Brush, Canvas, Color};
+///
+/// let orange = Color { r: 255, g: 140, b: 0 };
+/// let mut canvas = Canvas::new(40, 10, Color { r: 0, g: 0, b: 0 });
+/// let brush = Brush::circle(5);
+/// // A trail of puffs behind a rocket.
+/// for x in (4..36).step_by(6) {
+///     canvas.stamp(&brush, x, 10, 0, orange);
+/// }
+/// assert_eq!(canvas.composited_cell(16, 5).top_color, orange);
+/// assert_eq!(canvas.composited_cell(16, 3).top_color, Color { r: 0, g: 0, b: 0 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Brush {
+    width: usize,
+    height: usize,
+    /// The runs of pixels, as the vertical offset from the center, the horizontal offset of
+    /// their first pixel and their number of pixels.
+    spans: Vec<(isize, isize, usize)>,
+}
+
+impl Brush {
+    /// Creates a brush of the pixels whose centers lie within a disc `diameter` pixels across.
+    pub fn circle(diameter: usize) -> Self {
+        let radius = diameter as f64 / 2.0;
+        let center = (diameter as f64 - 1.0) / 2.0;
+        Self::from_rows(diameter, diameter, |row| {
+            let dy = row as f64 - center;
+            let half = (radius * radius - dy * dy).max(0.0).sqrt();
+            let first = (center - half).ceil() as usize;
+            let last = (center + half).floor() as usize;
+            vec![(first, (last + 1).saturating_sub(first))]
+        })
+    }
+
+    /// Creates a brush of a square of `size` by `size` pixels.
+    pub fn square(size: usize) -> Self {
+        Self::from_rows(size, size, |_| vec![(0, size)])
+    }
+
+    /// Creates a brush from the row-major `mask` of a `width` by `height` footprint, stamping
+    /// the pixels set to `true`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` does not hold exactly `width * height` values.
+    pub fn from_mask(width: usize, height: usize, mask: &[bool]) -> Self {
+        assert_eq!(
+            mask.len(),
+            width * height,
+            "a {width}x{height} brush needs {} mask values",
+            width * height
+        );
+        Self::from_rows(width, height, |row| {
+            let row = &mask[row * width..(row + 1) * width];
+            let mut runs = Vec::new();
+            let mut column = 0;
+            while column < width {
+                let length = row[column..].iter().take_while(|&&set| set).count();
+                if length > 0 {
+                    runs.push((column, length));
+                }
+                column += length + 1;
+            }
+            runs
+        })
+    }
+
+    /// Creates a `width` by `height` brush from the runs of every row, as their first column
+    /// and number of pixels.
+    fn from_rows(width: usize, height: usize, runs: impl Fn(usize) -> Vec<(usize, usize)>) -> Self {
+        // Even sizes reach one pixel further right and down than left and up.
+        let (offset_x, offset_y) = ((width as isize - 1) / 2, (height as isize - 1) / 2);
+        let spans = (0..height)
+            .flat_map(|row| {
+                runs(row).into_iter().filter(|&(_, length)| length > 0).map(
+                    move |(first, length)| {
+                        (row as isize - offset_y, first as isize - offset_x, length)
+                    },
+                )
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            spans,
+        }
+    }
+
+    /// Returns the width of the footprint in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the footprint in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns whether the brush stamps no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+impl Canvas {
+    /// Stamps `brush` centered on the pixel at (`x`, `y`) on layer `z`, in `color`. Brushes of
+    /// even sizes reach one pixel further right and down than left and up. The position goes
+    /// through the transform, but the footprint is not turned or resized. Parts outside the
+    /// canvas are clipped.
+    pub fn stamp(&mut self, brush: &Brush, x: isize, y: isize, z: usize, color: Color) {
+        let (x, y) = self.transformed_pixel(x, y);
+        self.stamp_at(brush, x, y, z, color);
+    }
+
+    /// Stamps `brush` centered on the pixel at (`x`, `y`) on layer `z`, ignoring the transform.
+    pub(crate) fn stamp_at(&mut self, brush: &Brush, x: isize, y: isize, z: usize, color: Color) {
+        for &(dy, dx, length) in &brush.spans {
+            self.fill_area(x + dx, y + dy, length, 1, z, color);
+        }
+    }
+}
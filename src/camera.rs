@@ -0,0 +1,38 @@
+//! A camera mapping world coordinates onto the canvas.
+
+/// A view into a world larger than the canvas.
+///
+/// The camera position is the world coordinate, in half-block pixels, shown at the top-left
+/// corner of the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Camera {
+    /// The world x coordinate shown at the left edge of the canvas.
+    pub x: isize,
+    /// The world y coordinate shown at the top edge of the canvas.
+    pub y: isize,
+}
+
+impl Camera {
+    /// Creates a camera showing the world starting at (`x`, `y`).
+    pub fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// Moves the camera so the world point (`x`, `y`) sits at the center of a viewport of
+    /// `viewport_width` by `viewport_height` pixels.
+    pub fn center_on(&mut self, x: isize, y: isize, viewport_width: usize, viewport_height: usize) {
+        self.x = x - (viewport_width / 2) as isize;
+        self.y = y - (viewport_height / 2) as isize;
+    }
+
+    /// Converts a world position to a position on the canvas.
+    pub fn world_to_screen(&self, x: isize, y: isize) -> (isize, isize) {
+        (x - self.x, y - self.y)
+    }
+
+    /// Converts a position on the canvas to a world position.
+    pub fn screen_to_world(&self, x: isize, y: isize) -> (isize, isize) {
+        (x + self.x, y + self.y)
+    }
+}
@@ -0,0 +1,389 @@
+//! Detecting what the terminal supports, from the environment, terminfo and terminal queries.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::ColorMode;
+
+/// The escape codes asking the terminal about its capabilities: DECRQM requests for
+/// synchronized output (mode 2026) and mouse reporting (mode 1000), a 24-bit background color
+/// read back with a DECRQSS request for the current colors, then a primary device attributes
+/// (DA1) request, which every terminal answers, marking the end of the answers.
+pub const CAPABILITY_QUERY: &str =
+    "\u{1b}[?2026$p\u{1b}[?1000$p\u{1b}[48;2;1;2;3m\u{1b}P$qm\u{1b}\\\u{1b}[m\u{1b}[c";
+
+/// The index of the `colors` number in compiled terminfo entries.
+const TERMINFO_COLORS: usize = 13;
+/// The index of the `kmous` string, the mouse event prefix, in compiled terminfo entries.
+const TERMINFO_KEY_MOUSE: usize = 355;
+
+/// What a terminal supports, used to choose how to draw.
+///
+/// [`Capabilities::default`] is what Rael assumes without detection: a modern terminal with
+/// 24-bit color. [`Capabilities::from_env`] looks at the environment and the terminfo entry
+/// of the terminal, and [`Capabilities::apply_response`] refines the result with the answers
+/// to [`CAPABILITY_QUERY`]. [`Terminal::detect_capabilities`] does both.
+///
+/// [`Terminal::detect_capabilities`]: crate::Terminal::detect_capabilities
+///
+/// # Example
+///
+/// ```
+/// use rael::Capabilities;
+///
+/// let mut capabilities = Capabilities::from_env();
+/// // The answers of a terminal supporting synchronized output.
+/// let complete = capabilities.apply_response(b"\x1b[?2026;2$y\x1b[?1000;2$y\x1b[?62;22c");
+/// assert!(complete);
+/// assert!(capabilities.synchronized_output);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Whether the terminal shows 24-bit colors.
+    pub truecolor: bool,
+    /// How many palette colors the terminal has, such as 8, 16 or 256, or 0 for none.
+    pub colors: u32,
+    /// Whether the terminal can hold updates until a frame is complete, so frames never show
+    /// half drawn.
+    pub synchronized_output: bool,
+    /// Whether the terminal reports mouse events.
+    pub mouse: bool,
+    /// Whether the terminal shows Unicode characters, such as the half blocks Rael draws with.
+    pub unicode: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            truecolor: true,
+            colors: 256,
+            synchronized_output: false,
+            mouse: true,
+            unicode: true,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Detects capabilities from the `TERM`, `COLORTERM` and locale environment variables and
+    /// from the terminfo entry of `TERM`, if one is installed.
+    pub fn from_env() -> Self {
+        let var = |name| {
+            env::var(name)
+                .ok()
+                .filter(|value: &String| !value.is_empty())
+        };
+        let term = var("TERM").unwrap_or_default();
+        let mut capabilities = Self {
+            truecolor: false,
+            colors: 0,
+            synchronized_output: false,
+            mouse: false,
+            unicode: cfg!(windows),
+        };
+
+        if let Some(terminfo) = Terminfo::load(&term) {
+            capabilities.colors = terminfo.colors;
+            capabilities.truecolor = terminfo.truecolor || terminfo.colors >= 1 << 24;
+            capabilities.mouse = terminfo.mouse;
+            capabilities.synchronized_output = terminfo.synchronized_output;
+        } else if !term.is_empty() && term != "dumb" {
+            capabilities.colors = if term.contains("256") { 256 } else { 16 };
+            capabilities.truecolor = term.contains("direct");
+            capabilities.mouse = term.starts_with("xterm") || term.starts_with("screen");
+        }
+        if var("COLORTERM").is_some_and(|value| value == "truecolor" || value == "24bit")
+            || var("WT_SESSION").is_some()
+        {
+            capabilities.truecolor = true;
+            capabilities.colors = capabilities.colors.max(256);
+        }
+        if let Some(locale) = var("LC_ALL")
+            .or_else(|| var("LC_CTYPE"))
+            .or_else(|| var("LANG"))
+        {
+            let locale = locale.to_ascii_lowercase();
+            capabilities.unicode = locale.contains("utf-8") || locale.contains("utf8");
+        }
+        capabilities
+    }
+
+    /// Returns the best way to send colors to the terminal, for [`Canvas::set_color_mode`]:
+    /// 24-bit colors if the terminal shows them, the 16 ANSI colors if it has fewer than 256
+    /// palette colors, or else the xterm 256-color palette. Terminals with no palette known are
+    /// assumed to have the 256 colors most terminals have.
+    ///
+    /// [`Canvas::set_color_mode`]: crate::Canvas::set_color_mode
+    pub fn color_mode(&self) -> ColorMode {
+        if self.truecolor {
+            ColorMode::TrueColor
+        } else if (1..256).contains(&self.colors) {
+            ColorMode::Ansi16
+        } else {
+            ColorMode::Xterm256
+        }
+    }
+
+    /// Updates the capabilities from the answers of the terminal to [`CAPABILITY_QUERY`],
+    /// returning whether all answers arrived, which is when the device attributes answer is
+    /// found.
+    pub fn apply_response(&mut self, response: &[u8]) -> bool {
+        for (parameters, terminator) in replies(response) {
+            if terminator != b'y' {
+                continue;
+            }
+            let mut fields = parameters
+                .strip_suffix(b"$")
+                .unwrap_or(parameters)
+                .split(|byte| *byte == b';');
+            let mode = fields.next().unwrap_or_default();
+            // 1 and 2 are set and reset, 3 is permanently set; 0 and 4 mean the mode is unknown
+            // or permanently off.
+            let supported = matches!(fields.next(), Some(b"1" | b"2" | b"3"));
+            match mode {
+                b"2026" => self.synchronized_output = supported,
+                b"1000" => self.mouse = supported,
+                _ => {}
+            }
+        }
+        if let Some(colors) = color_report(response) {
+            // Terminals without 24-bit colors report the palette color they picked instead.
+            self.truecolor = [b"48;2;1;2;3".as_slice(), b"48:2:1:2:3", b"48:2::1:2:3"]
+                .iter()
+                .any(|color| colors.windows(color.len()).any(|window| window == *color));
+            if self.truecolor {
+                self.colors = self.colors.max(256);
+            }
+        }
+        is_complete_response(response)
+    }
+}
+
+/// Returns the best way to send colors to the terminal, from the environment and the terminfo
+/// entry of the terminal, as found by [`Capabilities::from_env`]: 24-bit colors, the xterm
+/// 256-color palette or the 16 ANSI colors. Pass it to [`Canvas::set_color_mode`].
+///
+/// [`Terminal::detect_capabilities`] also asks the terminal itself, and draws canvases in no
+/// more colors than it finds.
+///
+/// [`Canvas::set_color_mode`]: crate::Canvas::set_color_mode
+/// [`Terminal::detect_capabilities`]: crate::Terminal::detect_capabilities
+///
+/// # Example
+///
+/// ```
+/// use rael::{detect_color_support, Canvas, Color};
+///
+/// let mut canvas = Canvas::new(80, 24, Color::BLACK);
+/// canvas.set_color_mode(detect_color_support());
+/// ```
+pub fn detect_color_support() -> ColorMode {
+    Capabilities::from_env().color_mode()
+}
+
+/// Returns the colors reported in the answer to the DECRQSS request of [`CAPABILITY_QUERY`],
+/// such as `0;48;2;1;2;3`, if the answer is in `response`.
+fn color_report(response: &[u8]) -> Option<&[u8]> {
+    let start = response
+        .windows(5)
+        .position(|window| window == b"\x1bP1$r")?;
+    let report = &response[start + 5..];
+    let end = report
+        .iter()
+        .position(|byte| *byte == b'm' || *byte == 0x1b)?;
+    Some(&report[..end])
+}
+
+/// Returns whether `response` holds the device attributes answer ending the answers to
+/// [`CAPABILITY_QUERY`].
+pub(crate) fn is_complete_response(response: &[u8]) -> bool {
+    replies(response).any(|(_, terminator)| terminator == b'c')
+}
+
+/// Returns the parameters and final byte of the private mode replies, starting with `ESC [ ?`,
+/// in `response`.
+fn replies(mut response: &[u8]) -> impl Iterator<Item = (&[u8], u8)> {
+    std::iter::from_fn(move || {
+        let start = response.windows(3).position(|window| window == b"\x1b[?")?;
+        let reply = &response[start + 3..];
+        let end = reply
+            .iter()
+            .position(|byte| !(byte.is_ascii_digit() || *byte == b';' || *byte == b'$'))?;
+        response = &reply[end + 1..];
+        Some((&reply[..end], reply[end]))
+    })
+}
+
+/// The capabilities read from a compiled terminfo entry.
+#[derive(Debug, Default)]
+struct Terminfo {
+    colors: u32,
+    truecolor: bool,
+    mouse: bool,
+    synchronized_output: bool,
+}
+
+impl Terminfo {
+    /// Finds and reads the compiled terminfo entry of `term` in the usual directories.
+    fn load(term: &str) -> Option<Self> {
+        let first = term.chars().next()?;
+        if term.contains(['/', '\\']) {
+            return None;
+        }
+        let mut directories = Vec::new();
+        if let Some(directory) = env::var_os("TERMINFO") {
+            directories.push(PathBuf::from(directory));
+        }
+        if let Some(home) = env::var_os("HOME") {
+            directories.push(PathBuf::from(home).join(".terminfo"));
+        }
+        if let Some(list) = env::var_os("TERMINFO_DIRS") {
+            directories.extend(env::split_paths(&list).filter(|path| !path.as_os_str().is_empty()));
+        }
+        directories.extend(
+            [
+                "/etc/terminfo",
+                "/lib/terminfo",
+                "/usr/share/terminfo",
+                "/usr/lib/terminfo",
+            ]
+            .map(PathBuf::from),
+        );
+        directories.into_iter().find_map(|directory| {
+            // Entries are filed under their first letter, or its hex code on macOS.
+            [first.to_string(), format!("{:x}", first as u32)]
+                .iter()
+                .find_map(|subdirectory| fs::read(directory.join(subdirectory).join(term)).ok())
+                .and_then(|bytes| Self::parse(&bytes))
+        })
+    }
+
+    /// Reads a compiled terminfo entry, in the legacy or the 32-bit number format, with its
+    /// extended capabilities.
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes, position: 0 };
+        let number_size = match reader.short()? {
+            0o432 => 2,
+            0o1036 => 4,
+            _ => return None,
+        };
+        let [names_size, bool_count, number_count, string_count, table_size] =
+            [(); 5].map(|()| reader.short().map(|value| value.max(0) as usize));
+        reader.skip(names_size? + bool_count?)?;
+        reader.align();
+        let numbers = reader.numbers(number_count?, number_size)?;
+        let offsets = reader.shorts(string_count?)?;
+        let table = reader.take(table_size?)?;
+
+        let mut terminfo = Terminfo {
+            colors: numbers
+                .get(TERMINFO_COLORS)
+                .map_or(0, |colors| (*colors).max(0) as u32),
+            mouse: offsets
+                .get(TERMINFO_KEY_MOUSE)
+                .is_some_and(|offset| *offset >= 0),
+            ..Terminfo::default()
+        };
+
+        // The extended capabilities, such as `Tc`, `RGB` and `Sync`, follow, if any.
+        reader.align();
+        let Some(header) = [(); 5]
+            .map(|()| reader.short())
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Some(terminfo);
+        };
+        let [bools, numbers, strings, _, table_size] =
+            [0, 1, 2, 3, 4].map(|index| header[index].max(0) as usize);
+        let flags = reader.take(bools)?;
+        reader.align();
+        let extended_numbers = reader.numbers(numbers, number_size)?;
+        let values = reader.shorts(strings)?;
+        let names = reader.shorts(bools + numbers + strings)?;
+        let table = reader.take(table_size).unwrap_or(table);
+        // Names follow the string values in the table.
+        let names_start = values
+            .iter()
+            .filter(|offset| **offset >= 0)
+            .filter_map(|offset| {
+                let start = *offset as usize;
+                let length = table.get(start..)?.iter().position(|byte| *byte == 0)?;
+                Some(start + length + 1)
+            })
+            .max()
+            .unwrap_or(0);
+        for (index, offset) in names.iter().enumerate() {
+            let Some(name) = table
+                .get(names_start + (*offset).max(0) as usize..)
+                .and_then(|name| name.split(|byte| *byte == 0).next())
+            else {
+                continue;
+            };
+            let present = if index < bools {
+                flags[index] == 1
+            } else if index < bools + numbers {
+                extended_numbers[index - bools] >= 0
+            } else {
+                values[index - bools - numbers] >= 0
+            };
+            match name {
+                b"Tc" | b"RGB" => terminfo.truecolor |= present,
+                b"XM" | b"xm" => terminfo.mouse |= present,
+                b"Sync" => terminfo.synchronized_output |= present,
+                _ => {}
+            }
+        }
+        Some(terminfo)
+    }
+}
+
+/// Reads little-endian values from a compiled terminfo entry.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position.checked_add(count)?)?;
+        self.position += count;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Option<()> {
+        self.take(count).map(|_| ())
+    }
+
+    /// Skips a padding byte so the next value starts at an even offset.
+    fn align(&mut self) {
+        if self.position % 2 == 1 {
+            self.position += 1;
+        }
+    }
+
+    fn short(&mut self) -> Option<i16> {
+        self.take(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn shorts(&mut self, count: usize) -> Option<Vec<i16>> {
+        (0..count).map(|_| self.short()).collect()
+    }
+
+    fn numbers(&mut self, count: usize, size: usize) -> Option<Vec<i32>> {
+        (0..count)
+            .map(|_| {
+                let bytes = self.take(size)?;
+                Some(if size == 4 {
+                    i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+                } else {
+                    i16::from_le_bytes([bytes[0], bytes[1]]) as i32
+                })
+            })
+            .collect()
+    }
+}
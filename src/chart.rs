@@ -0,0 +1,454 @@
+//! Chart widgets that draw into a [`Canvas`].
+//!
+//! Every chart is drawn inside a [`Rect`] given in half-block pixel coordinates, so bars, lines
+//! and axes are placed with twice the vertical precision of a terminal cell.
+
+mod bar;
+mod heatmap;
+mod histogram;
+mod line;
+mod pie;
+mod ramp;
+mod scatter;
+
+pub use bar::{BarChart, BarMode, BarSeries};
+pub use heatmap::Heatmap;
+pub use histogram::Histogram;
+pub use line::{LineChart, LineSeries};
+pub use pie::{PieChart, PieSlice};
+pub use ramp::ColorRamp;
+pub use scatter::{Marker, ScatterPlot, ScatterSeries};
+
+use crate::{font, Canvas, CellGlyph, Color, Rect};
+
+/// The default color used for axis lines and tick marks.
+const DEFAULT_AXIS_COLOR: Color = Color {
+    r: 128,
+    g: 128,
+    b: 128,
+};
+
+/// The default color used for tick and category labels.
+const DEFAULT_LABEL_COLOR: Color = Color {
+    r: 200,
+    g: 200,
+    b: 200,
+};
+
+/// A numeric axis spanning `min..=max` with evenly spaced tick values.
+///
+/// A logarithmic axis spaces values by their base 10 logarithm and puts its ticks on powers of
+/// ten.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Axis {
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+    pub ticks: Vec<f32>,
+    pub log: bool,
+}
+
+impl Axis {
+    /// Creates an axis covering `min..=max`, extended outwards to the nearest "nice" tick values
+    /// (multiples of 1, 2 or 5 times a power of ten).
+    pub fn nice(min: f32, max: f32, max_ticks: usize) -> Self {
+        let (mut min, mut max) = if min.is_finite() && max.is_finite() {
+            (min.min(max), min.max(max))
+        } else {
+            (0.0, 1.0)
+        };
+        if max - min <= f32::EPSILON {
+            min -= 0.5;
+            max += 0.5;
+        }
+
+        let span = nice_number(max - min, false);
+        let step = nice_number(span / (max_ticks.max(2) - 1) as f32, true);
+        let start = (min / step).floor() * step;
+        let end = (max / step).ceil() * step;
+        let count = ((end - start) / step).round() as usize;
+        let ticks = (0..=count).map(|i| start + i as f32 * step).collect();
+
+        Self {
+            min: start,
+            max: end,
+            step,
+            ticks,
+            log: false,
+        }
+    }
+
+    /// Creates an axis covering exactly `min..=max` with nice tick values inside that range.
+    pub fn exact(min: f32, max: f32, max_ticks: usize) -> Self {
+        let nice = Self::nice(min, max, max_ticks);
+        let (min, max) = if max - min > f32::EPSILON {
+            (min, max)
+        } else {
+            (nice.min, nice.max)
+        };
+        let ticks = nice
+            .ticks
+            .into_iter()
+            .filter(|tick| *tick >= min - nice.step * 1e-3 && *tick <= max + nice.step * 1e-3)
+            .collect();
+        Self {
+            min,
+            max,
+            step: nice.step,
+            ticks,
+            log: false,
+        }
+    }
+
+    /// Creates a logarithmic axis from `0.5` up to the power of ten at or above `max`, so values
+    /// of `1.0` and up are always visible.
+    pub fn log10(max: f32, max_ticks: usize) -> Self {
+        let top = if max.is_finite() && max > 1.0 {
+            max.log10().ceil() as usize
+        } else {
+            1
+        };
+        let every = top.div_ceil(max_ticks.max(2) - 1).max(1);
+        let ticks = (0..=top)
+            .step_by(every)
+            .map(|exponent| 10f32.powi(exponent as i32))
+            .collect();
+        Self {
+            min: 0.5,
+            max: 10f32.powi(top as i32),
+            step: 1.0,
+            ticks,
+            log: true,
+        }
+    }
+
+    /// Maps `value` to a fraction of the axis, where `0.0` is `min` and `1.0` is `max`.
+    /// On a logarithmic axis, values that are not positive map to `min`.
+    pub fn fraction(&self, value: f32) -> f32 {
+        if self.log {
+            let value = value.max(self.min);
+            return (value.log10() - self.min.log10()) / (self.max.log10() - self.min.log10());
+        }
+        (value - self.min) / (self.max - self.min)
+    }
+
+    /// Maps `value` to a (fractional) pixel row inside `plot`, with `min` at the bottom.
+    pub fn to_row(&self, value: f32, plot: Rect) -> f32 {
+        let bottom = (plot.y + plot.height.saturating_sub(1)) as f32;
+        bottom - self.fraction(value) * plot.height.saturating_sub(1) as f32
+    }
+
+    /// Maps `value` to a (fractional) pixel column inside `plot`, with `min` on the left.
+    pub fn to_column(&self, value: f32, plot: Rect) -> f32 {
+        plot.x as f32 + self.fraction(value) * plot.width.saturating_sub(1) as f32
+    }
+
+    /// Returns the formatted label for every tick.
+    pub fn labels(&self) -> Vec<String> {
+        self.ticks
+            .iter()
+            .map(|tick| {
+                if self.log {
+                    format_value(*tick, *tick)
+                } else {
+                    format_value(*tick, self.step)
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the width in pixels of the widest tick label.
+    pub fn label_width(&self) -> usize {
+        self.labels()
+            .iter()
+            .map(|label| font::text_width(label))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Draws the axis as a vertical line just left of `plot`, with tick marks and labels to its
+    /// left.
+    pub fn draw_vertical(&self, canvas: &mut Canvas, plot: Rect, z: usize, style: &AxisStyle) {
+        let Some(axis_x) = plot.x.checked_sub(1) else {
+            return;
+        };
+        vertical_line(canvas, axis_x, plot.y, plot.height, z, style.axis_color);
+
+        for (tick, label) in self.ticks.iter().zip(self.labels()) {
+            let row = self.to_row(*tick, plot).round() as isize;
+            if axis_x >= 1 {
+                set_pixel(canvas, axis_x as isize - 1, row, z, style.axis_color);
+            }
+            let label_x = axis_x as isize - 2 - font::text_width(&label) as isize;
+            let label_y = row - (font::GLYPH_HEIGHT / 2) as isize;
+            font::draw_text(canvas, label_x, label_y, z, style.label_color, &label);
+        }
+    }
+
+    /// Draws the axis as a horizontal line just below `plot`, with tick marks and labels below it.
+    /// Labels that would overlap their left neighbour are skipped.
+    pub fn draw_horizontal(&self, canvas: &mut Canvas, plot: Rect, z: usize, style: &AxisStyle) {
+        let axis_y = plot.y + plot.height;
+        let start = plot.x.saturating_sub(1);
+        horizontal_line(
+            canvas,
+            start,
+            axis_y,
+            plot.x + plot.width - start,
+            z,
+            style.axis_color,
+        );
+
+        let mut last_label_end = isize::MIN;
+        for (tick, label) in self.ticks.iter().zip(self.labels()) {
+            let column = self.to_column(*tick, plot).round() as isize;
+            set_pixel(canvas, column, axis_y as isize + 1, z, style.axis_color);
+
+            let width = font::text_width(&label) as isize;
+            let label_x = column - width / 2;
+            if label_x <= last_label_end + 1 {
+                continue;
+            }
+            let label_y = axis_y as isize + 2;
+            font::draw_text(canvas, label_x, label_y, z, style.label_color, &label);
+            last_label_end = label_x + width;
+        }
+    }
+}
+
+/// The resolution at which a chart draws its marks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    /// Marks are drawn as half-block pixels on the chart's z-layer: one column by two rows per
+    /// terminal cell.
+    #[default]
+    HalfBlock,
+    /// Marks are drawn as braille dots: two columns by four rows per terminal cell.
+    ///
+    /// Braille characters are drawn over every z-layer. Each cell takes a single color, so where
+    /// marks of different colors meet in a cell the last one drawn wins.
+    Braille,
+}
+
+/// Unicode braille dot bits, indexed by `[row][column]` inside a 2x4 cell.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// The first braille character, with no dots raised.
+const BRAILLE_BASE: u32 = 0x2800;
+
+/// A grid of dots covering a plot area, addressed with (0, 0) at the top-left dot.
+///
+/// Depending on the [`Resolution`], a dot is either a half-block pixel or a braille dot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DotSurface {
+    plot: Rect,
+    resolution: Resolution,
+}
+
+impl DotSurface {
+    /// Creates a surface over `plot`. Braille surfaces shrink `plot` to whole terminal cells.
+    pub fn new(plot: Rect, resolution: Resolution) -> Self {
+        let plot = match resolution {
+            Resolution::HalfBlock => plot,
+            Resolution::Braille => {
+                let top = plot.y.div_ceil(2) * 2;
+                let bottom = (plot.y + plot.height) / 2 * 2;
+                Rect::new(plot.x, top, plot.width, bottom.saturating_sub(top))
+            }
+        };
+        Self { plot, resolution }
+    }
+
+    /// Returns the plot area actually covered by the dots, in half-block pixel coordinates.
+    pub fn plot(&self) -> Rect {
+        self.plot
+    }
+
+    /// Returns the number of dots per row.
+    pub fn width(&self) -> usize {
+        match self.resolution {
+            Resolution::HalfBlock => self.plot.width,
+            Resolution::Braille => self.plot.width * 2,
+        }
+    }
+
+    /// Returns the number of dots per column.
+    pub fn height(&self) -> usize {
+        match self.resolution {
+            Resolution::HalfBlock => self.plot.height,
+            Resolution::Braille => self.plot.height * 2,
+        }
+    }
+
+    /// Sets the dot at (`x`, `y`). Dots outside the surface are ignored.
+    pub fn set(&self, canvas: &mut Canvas, x: isize, y: isize, z: usize, color: Color) {
+        if x < 0 || y < 0 || x as usize >= self.width() || y as usize >= self.height() {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        match self.resolution {
+            Resolution::HalfBlock => canvas.set_pixel(self.plot.x + x, self.plot.y + y, z, color),
+            Resolution::Braille => {
+                let column = self.plot.x + x / 2;
+                let row = self.plot.y / 2 + y / 4;
+                let dots = match canvas.glyph(column, row) {
+                    Some(glyph)
+                        if (BRAILLE_BASE..BRAILLE_BASE + 0x100).contains(&(glyph.ch as u32)) =>
+                    {
+                        glyph.ch as u32 - BRAILLE_BASE
+                    }
+                    _ => 0,
+                };
+                let dots = dots | BRAILLE_DOTS[y % 4][x % 2];
+                if let Some(ch) = char::from_u32(BRAILLE_BASE + dots) {
+                    canvas.set_glyph(column, row, CellGlyph::new(ch, color));
+                }
+            }
+        }
+    }
+
+    /// Draws a line of dots from `from` to `to` with Bresenham's algorithm.
+    pub fn line(
+        &self,
+        canvas: &mut Canvas,
+        from: (isize, isize),
+        to: (isize, isize),
+        z: usize,
+        color: Color,
+    ) {
+        let (mut x, mut y) = from;
+        let dx = (to.0 - x).abs();
+        let dy = -(to.1 - y).abs();
+        let step_x = if x < to.0 { 1 } else { -1 };
+        let step_y = if y < to.1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.set(canvas, x, y, z, color);
+            if (x, y) == to {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+}
+
+/// Colors used for the axes of a chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisStyle {
+    /// The color of axis lines and tick marks.
+    pub axis_color: Color,
+    /// The color of tick and category labels.
+    pub label_color: Color,
+}
+
+impl Default for AxisStyle {
+    fn default() -> Self {
+        Self {
+            axis_color: DEFAULT_AXIS_COLOR,
+            label_color: DEFAULT_LABEL_COLOR,
+        }
+    }
+}
+
+/// Rounds `x` to a "nice" number: 1, 2, 5 or 10 times a power of ten.
+fn nice_number(x: f32, round: bool) -> f32 {
+    if x <= 0.0 || !x.is_finite() {
+        return 1.0;
+    }
+    let exponent = x.log10().floor();
+    let power = 10f32.powf(exponent);
+    let fraction = x / power;
+    let nice = if round {
+        if fraction < 1.5 {
+            1.0
+        } else if fraction < 3.0 {
+            2.0
+        } else if fraction < 7.0 {
+            5.0
+        } else {
+            10.0
+        }
+    } else if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * power
+}
+
+/// Formats a tick value with just enough decimals to distinguish ticks `step` apart, using `K`
+/// and `M` suffixes for large values.
+pub(crate) fn format_value(value: f32, step: f32) -> String {
+    let value = if value.abs() < step.abs() * 1e-3 {
+        0.0
+    } else {
+        value
+    };
+    let abs_step = step.abs();
+    if abs_step >= 1_000_000.0 {
+        return format!(
+            "{}M",
+            trim_decimals(value / 1_000_000.0, abs_step / 1_000_000.0)
+        );
+    }
+    if abs_step >= 1_000.0 {
+        return format!("{}K", trim_decimals(value / 1_000.0, abs_step / 1_000.0));
+    }
+    trim_decimals(value, abs_step)
+}
+
+fn trim_decimals(value: f32, step: f32) -> String {
+    let decimals = if step > 0.0 && step < 1.0 {
+        (-step.log10().floor()) as usize
+    } else {
+        0
+    };
+    format!("{:.*}", decimals, value)
+}
+
+/// Sets a pixel from signed coordinates, ignoring pixels left of or above the canvas.
+pub(crate) fn set_pixel(canvas: &mut Canvas, x: isize, y: isize, z: usize, color: Color) {
+    if x >= 0 && y >= 0 {
+        canvas.set_pixel(x as usize, y as usize, z, color);
+    }
+}
+
+/// Draws a horizontal run of `length` pixels starting at (`x`, `y`).
+pub(crate) fn horizontal_line(
+    canvas: &mut Canvas,
+    x: usize,
+    y: usize,
+    length: usize,
+    z: usize,
+    color: Color,
+) {
+    for px in x..x + length {
+        canvas.set_pixel(px, y, z, color);
+    }
+}
+
+/// Draws a vertical run of `length` pixels starting at (`x`, `y`).
+pub(crate) fn vertical_line(
+    canvas: &mut Canvas,
+    x: usize,
+    y: usize,
+    length: usize,
+    z: usize,
+    color: Color,
+) {
+    for py in y..y + length {
+        canvas.set_pixel(x, py, z, color);
+    }
+}
@@ -0,0 +1,294 @@
+//! Bar charts with grouped and stacked modes.
+
+use super::{horizontal_line, Axis, AxisStyle};
+use crate::{font, Canvas, Color, Rect};
+
+/// How multiple series are arranged within a category of a [`BarChart`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BarMode {
+    /// Each series gets its own bar, drawn side by side within the category.
+    #[default]
+    Grouped,
+    /// The series share one bar per category. Positive values stack upwards from zero and
+    /// negative values stack downwards.
+    Stacked,
+}
+
+/// A named series of values, one per category, drawn in a single color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarSeries {
+    /// The name of the series.
+    pub name: String,
+    /// The value for each category. Missing values are treated as zero.
+    pub values: Vec<f32>,
+    /// The color of the bars of this series.
+    pub color: Color,
+}
+
+/// A bar chart with a labeled value axis and category labels.
+///
+/// Bar heights are resolved to half-block pixels, so a bar can end halfway through a terminal
+/// cell. Negative values are drawn below the zero baseline.
+///
+/// # Example
+///
+/// ```
+/// use rael::chart::{BarChart, BarMode};
+/// use rael::{Canvas, Color, Rect};
+///
+/// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+/// BarChart::new(["Q1", "Q2", "Q3"])
+///     .series("2024", vec![3.0, -1.5, 4.0], Color { r: 255, g: 80, b: 80 })
+///     .series("2025", vec![2.0, 2.5, 5.0], Color { r: 80, g: 80, b: 255 })
+///     .mode(BarMode::Grouped)
+///     .draw(&mut canvas, Rect::new(0, 0, 40, 40), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BarChart {
+    categories: Vec<String>,
+    series: Vec<BarSeries>,
+    mode: BarMode,
+    range: Option<(f32, f32)>,
+    style: AxisStyle,
+}
+
+impl BarChart {
+    /// Creates an empty bar chart with the given category labels.
+    pub fn new<S: Into<String>>(categories: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            categories: categories.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a series of values, one per category.
+    pub fn series(
+        mut self,
+        name: impl Into<String>,
+        values: impl Into<Vec<f32>>,
+        color: Color,
+    ) -> Self {
+        self.series.push(BarSeries {
+            name: name.into(),
+            values: values.into(),
+            color,
+        });
+        self
+    }
+
+    /// Sets how multiple series are arranged within a category.
+    pub fn mode(mut self, mode: BarMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Fixes the value axis to `min..=max` instead of fitting it to the data.
+    /// Bars extending past the range are cut off at the edge of the plot.
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Sets the colors of the axis and its labels.
+    pub fn axis_style(mut self, style: AxisStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn category_count(&self) -> usize {
+        self.series
+            .iter()
+            .map(|series| series.values.len())
+            .max()
+            .unwrap_or(0)
+            .max(self.categories.len())
+    }
+
+    fn value(&self, series: usize, category: usize) -> f32 {
+        let value = self.series[series]
+            .values
+            .get(category)
+            .copied()
+            .unwrap_or(0.0);
+        if value.is_finite() {
+            value
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the smallest and largest value the bars reach, always including zero.
+    fn data_range(&self) -> (f32, f32) {
+        let mut min = 0.0f32;
+        let mut max = 0.0f32;
+        for category in 0..self.category_count() {
+            let mut positive = 0.0;
+            let mut negative = 0.0;
+            for series in 0..self.series.len() {
+                let value = self.value(series, category);
+                match self.mode {
+                    BarMode::Grouped => {
+                        min = min.min(value);
+                        max = max.max(value);
+                    }
+                    BarMode::Stacked if value >= 0.0 => positive += value,
+                    BarMode::Stacked => negative += value,
+                }
+            }
+            min = min.min(negative);
+            max = max.max(positive);
+        }
+        (min, max)
+    }
+
+    /// Draws the chart into `area` on layer `z`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas to draw into.
+    /// * `area` - The area of the chart in half-block pixel coordinates, including its labels.
+    /// * `z` - The z-layer to draw on.
+    pub fn draw(&self, canvas: &mut Canvas, area: Rect, z: usize) {
+        let max_ticks = (area.height / 8).max(2);
+        let axis = match self.range {
+            Some((min, max)) => Axis::exact(min, max, max_ticks),
+            None => {
+                let (min, max) = self.data_range();
+                Axis::nice(min, max, max_ticks)
+            }
+        };
+
+        let left = axis.label_width() + 3;
+        let top = font::GLYPH_HEIGHT / 2;
+        let bottom = if self.categories.is_empty() {
+            0
+        } else {
+            font::GLYPH_HEIGHT + 1
+        };
+        if area.width <= left || area.height <= top + bottom + 1 {
+            return;
+        }
+        let plot = Rect::new(
+            area.x + left,
+            area.y + top,
+            area.width - left,
+            area.height - top - bottom,
+        );
+
+        axis.draw_vertical(canvas, plot, z, &self.style);
+
+        let categories = self.category_count();
+        if categories == 0 {
+            return;
+        }
+        let baseline = 0.0f32.clamp(axis.min, axis.max);
+        let slot_width = plot.width as f32 / categories as f32;
+
+        for category in 0..categories {
+            let slot_start = plot.x + (category as f32 * slot_width).round() as usize;
+            let slot_end = plot.x + ((category + 1) as f32 * slot_width).round() as usize;
+            let slot = slot_end - slot_start;
+            let gap = if slot > 3 { (slot / 6).max(1) } else { 0 };
+            let inner_start = slot_start + gap;
+            let inner_width = slot - 2 * gap;
+
+            match self.mode {
+                BarMode::Grouped => {
+                    let count = self.series.len().max(1);
+                    for series in 0..self.series.len() {
+                        let x0 = inner_start + inner_width * series / count;
+                        let x1 = inner_start + inner_width * (series + 1) / count;
+                        let value = self.value(series, category);
+                        let color = self.series[series].color;
+                        fill_bar(
+                            canvas,
+                            &axis,
+                            plot,
+                            x0,
+                            x1.max(x0 + 1),
+                            baseline,
+                            value,
+                            z,
+                            color,
+                        );
+                    }
+                }
+                BarMode::Stacked => {
+                    let mut positive = 0.0;
+                    let mut negative = 0.0;
+                    let x1 = inner_start + inner_width.max(1);
+                    for series in 0..self.series.len() {
+                        let value = self.value(series, category);
+                        let color = self.series[series].color;
+                        let (from, to) = if value >= 0.0 {
+                            positive += value;
+                            (positive - value, positive)
+                        } else {
+                            negative += value;
+                            (negative - value, negative)
+                        };
+                        fill_bar(canvas, &axis, plot, inner_start, x1, from, to, z, color);
+                    }
+                }
+            }
+
+            if let Some(label) = self.categories.get(category) {
+                let max_chars = (slot + 1) / font::ADVANCE;
+                let label: String = label.chars().take(max_chars).collect();
+                let width = font::text_width(&label);
+                let label_x = slot_start + slot.saturating_sub(width) / 2;
+                let label_y = plot.y + plot.height + 1;
+                font::draw_text(
+                    canvas,
+                    label_x as isize,
+                    label_y as isize,
+                    z,
+                    self.style.label_color,
+                    &label,
+                );
+            }
+        }
+
+        let baseline_row = axis.to_row(baseline, plot).round() as usize;
+        horizontal_line(
+            canvas,
+            plot.x,
+            baseline_row,
+            plot.width,
+            z,
+            self.style.axis_color,
+        );
+    }
+}
+
+/// Fills the columns `x0..x1` between the rows of the values `from` and `to`.
+#[allow(clippy::too_many_arguments)]
+fn fill_bar(
+    canvas: &mut Canvas,
+    axis: &Axis,
+    plot: Rect,
+    x0: usize,
+    x1: usize,
+    from: f32,
+    to: f32,
+    z: usize,
+    color: Color,
+) {
+    let from = from.clamp(axis.min, axis.max);
+    let to = to.clamp(axis.min, axis.max);
+    let from_row = axis.to_row(from, plot).round() as usize;
+    let to_row = axis.to_row(to, plot).round() as usize;
+    if from_row == to_row {
+        return;
+    }
+    let (top, bottom) = if to_row < from_row {
+        (to_row, from_row - 1)
+    } else {
+        (from_row + 1, to_row)
+    };
+    for y in top..=bottom {
+        for x in x0..x1 {
+            canvas.set_pixel(x, y, z, color);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Heatmaps mapping a grid of values to colors.
+
+use super::{format_value, AxisStyle, ColorRamp};
+use crate::{font, Canvas, Rect};
+
+/// The width in pixels of the color bar drawn by [`Heatmap::legend`].
+const LEGEND_BAR_WIDTH: usize = 2;
+
+/// A heatmap drawing a row-major grid of values as colored blocks.
+///
+/// Values are normalized to the range of the data (or a fixed range) and looked up in a
+/// [`ColorRamp`]. Each value is scaled to cover its share of the drawing area, down to single
+/// half-block pixels. Non-finite values are left undrawn.
+///
+/// # Example
+///
+/// ```
+/// use rael::chart::{ColorRamp, Heatmap};
+/// use rael::{Canvas, Color, Rect};
+///
+/// let values: Vec<f32> = (0..64).map(|i| ((i % 8) * (i / 8)) as f32).collect();
+/// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+/// Heatmap::new(8, values)
+///     .ramp(ColorRamp::magma())
+///     .legend(true)
+///     .draw(&mut canvas, Rect::new(0, 0, 40, 40), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Heatmap {
+    columns: usize,
+    values: Vec<f32>,
+    ramp: ColorRamp,
+    range: Option<(f32, f32)>,
+    legend: bool,
+    style: AxisStyle,
+}
+
+impl Heatmap {
+    /// Creates a heatmap of `values` laid out in rows of `columns` values each.
+    /// A trailing partial row is ignored.
+    pub fn new(columns: usize, values: impl Into<Vec<f32>>) -> Self {
+        Self {
+            columns,
+            values: values.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the color ramp values are mapped to. Defaults to [`ColorRamp::viridis`].
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    /// Fixes the value range mapped onto the ramp instead of using the range of the data.
+    /// Values outside the range take the color of the nearest end of the ramp.
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Shows a color bar with the minimum and maximum value on the right of the heatmap.
+    pub fn legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Sets the color of the legend labels.
+    pub fn axis_style(mut self, style: AxisStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns the number of complete rows of values.
+    pub fn rows(&self) -> usize {
+        self.values.len().checked_div(self.columns).unwrap_or(0)
+    }
+
+    /// Returns the value at (`column`, `row`).
+    pub fn value(&self, column: usize, row: usize) -> Option<f32> {
+        if column >= self.columns || row >= self.rows() {
+            return None;
+        }
+        Some(self.values[row * self.columns + column])
+    }
+
+    fn value_range(&self) -> (f32, f32) {
+        if let Some(range) = self.range {
+            return range;
+        }
+        self.values
+            .iter()
+            .filter(|value| value.is_finite())
+            .fold(None, |range, &value| match range {
+                None => Some((value, value)),
+                Some((min, max)) => Some((f32::min(min, value), f32::max(max, value))),
+            })
+            .unwrap_or((0.0, 1.0))
+    }
+
+    /// Returns the area covered by the grid itself once room has been made for the legend.
+    fn grid_area(&self, area: Rect) -> Rect {
+        if !self.legend {
+            return area;
+        }
+        let (min, max) = self.value_range();
+        let step = (max - min).abs() / 10.0;
+        let label_width = font::text_width(&format_value(min, step))
+            .max(font::text_width(&format_value(max, step)));
+        let legend_width = LEGEND_BAR_WIDTH + 2 + label_width + 1;
+        Rect::new(
+            area.x,
+            area.y,
+            area.width.saturating_sub(legend_width),
+            area.height,
+        )
+    }
+
+    /// Returns the (`column`, `row`) of the value drawn at pixel (`x`, `y`) when the heatmap is
+    /// drawn into `area`, for example to inspect the value under the mouse.
+    pub fn cell_at(&self, area: Rect, x: usize, y: usize) -> Option<(usize, usize)> {
+        let grid = self.grid_area(area);
+        if x < grid.x || y < grid.y || x >= grid.x + grid.width || y >= grid.y + grid.height {
+            return None;
+        }
+        let column = (x - grid.x) * self.columns / grid.width;
+        let row = (y - grid.y) * self.rows() / grid.height;
+        Some((column, row))
+    }
+
+    /// Draws the heatmap into `area` on layer `z`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas to draw into.
+    /// * `area` - The area of the heatmap in half-block pixel coordinates, including its legend.
+    /// * `z` - The z-layer to draw on.
+    pub fn draw(&self, canvas: &mut Canvas, area: Rect, z: usize) {
+        let rows = self.rows();
+        let grid = self.grid_area(area);
+        let (min, max) = self.value_range();
+        let span = if max - min > f32::EPSILON {
+            max - min
+        } else {
+            1.0
+        };
+
+        if rows > 0 && grid.width > 0 && grid.height > 0 {
+            for py in 0..grid.height {
+                let row = py * rows / grid.height;
+                for px in 0..grid.width {
+                    let column = px * self.columns / grid.width;
+                    let value = self.values[row * self.columns + column];
+                    if !value.is_finite() {
+                        continue;
+                    }
+                    let color = self.ramp.sample((value - min) / span);
+                    canvas.set_pixel(grid.x + px, grid.y + py, z, color);
+                }
+            }
+        }
+
+        if self.legend {
+            self.draw_legend(canvas, area, grid, min, max, z);
+        }
+    }
+
+    fn draw_legend(
+        &self,
+        canvas: &mut Canvas,
+        area: Rect,
+        grid: Rect,
+        min: f32,
+        max: f32,
+        z: usize,
+    ) {
+        let bar_x = grid.x + grid.width + 1;
+        let label_x = (bar_x + LEGEND_BAR_WIDTH + 1) as isize;
+        if bar_x + LEGEND_BAR_WIDTH > area.x + area.width || area.height == 0 {
+            return;
+        }
+        for py in 0..area.height {
+            let t = 1.0 - py as f32 / area.height.saturating_sub(1).max(1) as f32;
+            let color = self.ramp.sample(t);
+            for px in 0..LEGEND_BAR_WIDTH {
+                canvas.set_pixel(bar_x + px, area.y + py, z, color);
+            }
+        }
+
+        let step = (max - min).abs() / 10.0;
+        let top = area.y as isize;
+        let bottom = (area.y + area.height) as isize - font::GLYPH_HEIGHT as isize;
+        let color = self.style.label_color;
+        font::draw_text(canvas, label_x, top, z, color, &format_value(max, step));
+        font::draw_text(canvas, label_x, bottom, z, color, &format_value(min, step));
+    }
+}
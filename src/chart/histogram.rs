@@ -0,0 +1,186 @@
+//! Histograms showing the distribution of a set of samples.
+
+use super::{Axis, AxisStyle};
+use crate::{font, Canvas, Color, Rect};
+
+/// The default color of histogram bars.
+const DEFAULT_BAR_COLOR: Color = Color {
+    r: 90,
+    g: 150,
+    b: 255,
+};
+
+/// A histogram that sorts samples into equally wide bins and draws the count of each bin as a bar.
+///
+/// # Example
+///
+/// ```
+/// use rael::chart::Histogram;
+/// use rael::{Canvas, Color, Rect};
+///
+/// let samples: Vec<f32> = (0..1000).map(|i| ((i * 7919) % 1000) as f32 / 10.0).collect();
+/// let mut canvas = Canvas::new(60, 20, Color { r: 0, g: 0, b: 0 });
+/// Histogram::new(&samples)
+///     .bins(20)
+///     .log_scale(true)
+///     .draw(&mut canvas, Rect::new(0, 0, 60, 40), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    samples: Vec<f32>,
+    bins: Option<usize>,
+    range: Option<(f32, f32)>,
+    log_scale: bool,
+    color: Color,
+    style: AxisStyle,
+}
+
+impl Histogram {
+    /// Creates a histogram of `samples`. Non-finite samples are ignored.
+    pub fn new(samples: &[f32]) -> Self {
+        Self {
+            samples: samples.iter().copied().filter(|s| s.is_finite()).collect(),
+            bins: None,
+            range: None,
+            log_scale: false,
+            color: DEFAULT_BAR_COLOR,
+            style: AxisStyle::default(),
+        }
+    }
+
+    /// Sets the number of bins. By default it is picked with Sturges' rule from the number of
+    /// samples.
+    pub fn bins(mut self, bins: usize) -> Self {
+        self.bins = Some(bins.max(1));
+        self
+    }
+
+    /// Only bins samples in `min..=max` instead of the range of the samples.
+    /// Samples outside the range are not counted.
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Uses a logarithmic count axis, so small bins stay visible next to very large ones.
+    pub fn log_scale(mut self, log_scale: bool) -> Self {
+        self.log_scale = log_scale;
+        self
+    }
+
+    /// Sets the color of the bars.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the colors of the axes and their labels.
+    pub fn axis_style(mut self, style: AxisStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns the number of bins used.
+    pub fn bin_count(&self) -> usize {
+        self.bins.unwrap_or_else(|| {
+            // Sturges' rule.
+            (self.samples.len().max(1) as f32).log2().ceil() as usize + 1
+        })
+    }
+
+    /// Returns the range covered by the bins.
+    pub fn bin_range(&self) -> (f32, f32) {
+        let (min, max) = self.range.unwrap_or_else(|| {
+            self.samples
+                .iter()
+                .fold(None, |range, &sample| match range {
+                    None => Some((sample, sample)),
+                    Some((min, max)) => Some((f32::min(min, sample), f32::max(max, sample))),
+                })
+                .unwrap_or((0.0, 1.0))
+        });
+        if max - min > f32::EPSILON {
+            (min, max)
+        } else {
+            (min - 0.5, max + 0.5)
+        }
+    }
+
+    /// Returns the number of samples in each bin, from the lowest bin to the highest.
+    /// The highest bin includes samples equal to the upper end of the range.
+    pub fn counts(&self) -> Vec<usize> {
+        let bins = self.bin_count();
+        let (min, max) = self.bin_range();
+        let mut counts = vec![0; bins];
+        for &sample in &self.samples {
+            if sample < min || sample > max {
+                continue;
+            }
+            let bin = (((sample - min) / (max - min)) * bins as f32) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+        counts
+    }
+
+    /// Draws the histogram into `area` on layer `z`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas to draw into.
+    /// * `area` - The area of the histogram in half-block pixel coordinates, including its labels.
+    /// * `z` - The z-layer to draw on.
+    pub fn draw(&self, canvas: &mut Canvas, area: Rect, z: usize) {
+        let counts = self.counts();
+        let (min, max) = self.bin_range();
+        let highest = counts.iter().copied().max().unwrap_or(0) as f32;
+
+        let y_ticks = (area.height / 8).max(2);
+        let y_axis = if self.log_scale {
+            Axis::log10(highest, y_ticks)
+        } else {
+            Axis::nice(0.0, highest.max(1.0), y_ticks)
+        };
+        let x_axis = Axis::exact(min, max, (area.width / 12).max(2));
+
+        let left = y_axis.label_width() + 3;
+        let right = x_axis
+            .labels()
+            .last()
+            .map_or(0, |label| font::text_width(label) / 2);
+        let top = font::GLYPH_HEIGHT / 2;
+        let bottom = font::GLYPH_HEIGHT + 2;
+        if area.width <= left + right + 1 || area.height <= top + bottom + 1 {
+            return;
+        }
+        let plot = Rect::new(
+            area.x + left,
+            area.y + top,
+            area.width - left - right,
+            area.height - top - bottom,
+        );
+
+        y_axis.draw_vertical(canvas, plot, z, &self.style);
+        x_axis.draw_horizontal(canvas, plot, z, &self.style);
+
+        let bins = counts.len();
+        let bin_width = (max - min) / bins as f32;
+        let bottom_row = plot.y + plot.height - 1;
+        for (bin, count) in counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let x0 = x_axis.to_column(min + bin as f32 * bin_width, plot).round() as usize;
+            let x1 = x_axis
+                .to_column(min + (bin + 1) as f32 * bin_width, plot)
+                .round() as usize;
+            // Leave a one pixel gap between bars that are wide enough to spare it.
+            let x1 = if x1 - x0 >= 3 { x1 - 1 } else { x1.max(x0 + 1) };
+            let top_row = y_axis.to_row(*count as f32, plot).round() as usize;
+            for y in top_row..=bottom_row {
+                for x in x0..x1 {
+                    canvas.set_pixel(x, y, z, self.color);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Line charts for one or more series of points.
+
+use super::{Axis, AxisStyle, DotSurface, Resolution};
+use crate::{font, Canvas, Color, Rect};
+
+/// A named series of `(x, y)` points connected by straight lines.
+///
+/// Points with a non-finite coordinate (such as `f32::NAN`) break the line, which is handy for
+/// marking gaps in a metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineSeries {
+    /// The name of the series.
+    pub name: String,
+    /// The points of the series, in drawing order.
+    pub points: Vec<(f32, f32)>,
+    /// The color of the line.
+    pub color: Color,
+    max_points: Option<usize>,
+}
+
+impl LineSeries {
+    /// Creates an empty series.
+    pub fn new(name: impl Into<String>, color: Color) -> Self {
+        Self {
+            name: name.into(),
+            points: Vec::new(),
+            color,
+            max_points: None,
+        }
+    }
+
+    /// Creates a series from evenly spaced values, using each value's index as its x coordinate.
+    pub fn from_values(name: impl Into<String>, values: &[f32], color: Color) -> Self {
+        Self::new(name, color).with_points(
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (index as f32, *value)),
+        )
+    }
+
+    /// Replaces the points of the series.
+    pub fn with_points(mut self, points: impl IntoIterator<Item = (f32, f32)>) -> Self {
+        self.points = points.into_iter().collect();
+        self.trim();
+        self
+    }
+
+    /// Limits the series to its `max_points` most recent points. Older points are dropped as new
+    /// ones are pushed, turning the series into a rolling window for live metrics.
+    pub fn max_points(mut self, max_points: usize) -> Self {
+        self.max_points = Some(max_points);
+        self.trim();
+        self
+    }
+
+    /// Appends a point to the series, dropping the oldest point if the series is full.
+    pub fn push(&mut self, x: f32, y: f32) {
+        self.points.push((x, y));
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        if let Some(max_points) = self.max_points {
+            if self.points.len() > max_points {
+                let excess = self.points.len() - max_points;
+                self.points.drain(..excess);
+            }
+        }
+    }
+}
+
+/// A line chart with labeled x and y axes.
+///
+/// Both axes fit themselves to the data unless a fixed range is set. The y axis is extended to
+/// round tick values while the x axis spans the data exactly, so a rolling series always fills
+/// the plot horizontally.
+///
+/// # Example
+///
+/// ```
+/// use rael::chart::{LineChart, LineSeries, Resolution};
+/// use rael::{Canvas, Color, Rect};
+///
+/// let mut cpu = LineSeries::new("cpu", Color { r: 0, g: 255, b: 0 }).max_points(100);
+/// for tick in 0..250 {
+///     cpu.push(tick as f32, (tick as f32 * 0.1).sin() * 50.0 + 50.0);
+/// }
+///
+/// let mut canvas = Canvas::new(60, 20, Color { r: 0, g: 0, b: 0 });
+/// LineChart::new()
+///     .series(cpu)
+///     .y_range(0.0, 100.0)
+///     .resolution(Resolution::Braille)
+///     .draw(&mut canvas, Rect::new(0, 0, 60, 40), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LineChart {
+    series: Vec<LineSeries>,
+    x_range: Option<(f32, f32)>,
+    y_range: Option<(f32, f32)>,
+    resolution: Resolution,
+    style: AxisStyle,
+}
+
+impl LineChart {
+    /// Creates an empty line chart.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a series to the chart.
+    pub fn series(mut self, series: LineSeries) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    /// Returns a mutable reference to the series at `index`, for pushing live values.
+    pub fn series_mut(&mut self, index: usize) -> Option<&mut LineSeries> {
+        self.series.get_mut(index)
+    }
+
+    /// Fixes the x axis to `min..=max` instead of fitting it to the data.
+    pub fn x_range(mut self, min: f32, max: f32) -> Self {
+        self.x_range = Some((min, max));
+        self
+    }
+
+    /// Fixes the y axis to `min..=max` instead of fitting it to the data.
+    pub fn y_range(mut self, min: f32, max: f32) -> Self {
+        self.y_range = Some((min, max));
+        self
+    }
+
+    /// Sets the resolution at which the lines are drawn.
+    pub fn resolution(mut self, resolution: Resolution) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Sets the colors of the axes and their labels.
+    pub fn axis_style(mut self, style: AxisStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns the bounds of all finite points as `(x_min, x_max, y_min, y_max)`.
+    fn data_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        self.series
+            .iter()
+            .flat_map(|series| series.points.iter())
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .fold(None, |bounds, &(x, y)| match bounds {
+                None => Some((x, x, y, y)),
+                Some((x_min, x_max, y_min, y_max)) => {
+                    Some((x_min.min(x), x_max.max(x), y_min.min(y), y_max.max(y)))
+                }
+            })
+    }
+
+    /// Draws the chart into `area` on layer `z`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas to draw into.
+    /// * `area` - The area of the chart in half-block pixel coordinates, including its labels.
+    /// * `z` - The z-layer to draw the axes on, and the lines too in [`Resolution::HalfBlock`].
+    pub fn draw(&self, canvas: &mut Canvas, area: Rect, z: usize) {
+        let (x_min, x_max, y_min, y_max) = self.data_bounds().unwrap_or((0.0, 1.0, 0.0, 1.0));
+        let y_ticks = (area.height / 8).max(2);
+        let x_ticks = (area.width / 12).max(2);
+        let y_axis = match self.y_range {
+            Some((min, max)) => Axis::exact(min, max, y_ticks),
+            None => Axis::nice(y_min, y_max, y_ticks),
+        };
+        let (x_min, x_max) = self.x_range.unwrap_or((x_min, x_max));
+        let x_axis = Axis::exact(x_min, x_max, x_ticks);
+
+        let left = y_axis.label_width() + 3;
+        let right = x_axis
+            .labels()
+            .last()
+            .map_or(0, |label| font::text_width(label) / 2);
+        let top = font::GLYPH_HEIGHT / 2;
+        let bottom = font::GLYPH_HEIGHT + 2;
+        if area.width <= left + right || area.height <= top + bottom + 1 {
+            return;
+        }
+        let surface = DotSurface::new(
+            Rect::new(
+                area.x + left,
+                area.y + top,
+                area.width - left - right,
+                area.height - top - bottom,
+            ),
+            self.resolution,
+        );
+        let plot = surface.plot();
+        if plot.height == 0 {
+            return;
+        }
+
+        y_axis.draw_vertical(canvas, plot, z, &self.style);
+        x_axis.draw_horizontal(canvas, plot, z, &self.style);
+
+        let width = surface.width() as f32;
+        let height = surface.height() as f32;
+        let to_dot = |(x, y): (f32, f32)| -> Option<(isize, isize)> {
+            if !x.is_finite() || !y.is_finite() {
+                return None;
+            }
+            // Clamp far-away points so lines leaving the plot are clipped cheaply.
+            let dot_x = (x_axis.fraction(x) * (width - 1.0)).clamp(-width, 2.0 * width);
+            let dot_y = ((1.0 - y_axis.fraction(y)) * (height - 1.0)).clamp(-height, 2.0 * height);
+            Some((dot_x.round() as isize, dot_y.round() as isize))
+        };
+
+        for series in &self.series {
+            let mut previous = None;
+            for (index, point) in series.points.iter().enumerate() {
+                let current = to_dot(*point);
+                match (previous, current) {
+                    (Some(from), Some(to)) => surface.line(canvas, from, to, z, series.color),
+                    (None, Some(dot)) => {
+                        let next = series.points.get(index + 1).and_then(|next| to_dot(*next));
+                        if next.is_none() {
+                            surface.set(canvas, dot.0, dot.1, z, series.color);
+                        }
+                    }
+                    _ => {}
+                }
+                previous = current;
+            }
+        }
+    }
+}
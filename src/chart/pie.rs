@@ -0,0 +1,275 @@
+//! Pie and donut charts.
+
+use std::f32::consts::{PI, TAU};
+
+use super::{set_pixel, AxisStyle};
+use crate::{font, Canvas, Color, Rect};
+
+/// The gap in pixels between the pie and the start of its labels.
+const LABEL_GAP: usize = 3;
+
+/// The smallest radius the pie is shrunk to in order to make room for labels.
+const MIN_LABELED_RADIUS: f32 = 4.0;
+
+/// A single slice of a [`PieChart`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieSlice {
+    /// The label of the slice.
+    pub label: String,
+    /// The value of the slice. Its share of the pie is the value divided by the sum of all
+    /// values. Values that are not positive are left out.
+    pub value: f32,
+    /// The color of the slice.
+    pub color: Color,
+}
+
+/// A pie chart, or a donut chart when given an inner radius.
+///
+/// Slices start at the top and go clockwise. Each slice can be labeled with its name and its
+/// percentage of the total, placed outside the pie with a short leader line.
+///
+/// Pixels are assumed to be square by default, which holds for the usual terminal fonts whose
+/// cells are twice as tall as they are wide. Use [`PieChart::pixel_aspect`] for other fonts.
+///
+/// # Example
+///
+/// ```
+/// use rael::chart::PieChart;
+/// use rael::{Canvas, Color, Rect};
+///
+/// let mut canvas = Canvas::new(60, 20, Color { r: 0, g: 0, b: 0 });
+/// PieChart::new()
+///     .slice("Rust", 60.0, Color { r: 222, g: 165, b: 132 })
+///     .slice("C", 25.0, Color { r: 85, g: 85, b: 85 })
+///     .slice("Other", 15.0, Color { r: 100, g: 150, b: 255 })
+///     .donut(0.5)
+///     .draw(&mut canvas, Rect::new(0, 0, 60, 40), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieChart {
+    slices: Vec<PieSlice>,
+    inner_radius: f32,
+    labels: bool,
+    percentages: bool,
+    pixel_aspect: f32,
+    style: AxisStyle,
+}
+
+impl Default for PieChart {
+    fn default() -> Self {
+        Self {
+            slices: Vec::new(),
+            inner_radius: 0.0,
+            labels: true,
+            percentages: true,
+            pixel_aspect: 1.0,
+            style: AxisStyle::default(),
+        }
+    }
+}
+
+impl PieChart {
+    /// Creates an empty pie chart.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a slice to the chart.
+    pub fn slice(mut self, label: impl Into<String>, value: f32, color: Color) -> Self {
+        self.slices.push(PieSlice {
+            label: label.into(),
+            value,
+            color,
+        });
+        self
+    }
+
+    /// Turns the pie into a donut whose hole has `inner_radius` times the radius of the pie.
+    pub fn donut(mut self, inner_radius: f32) -> Self {
+        self.inner_radius = inner_radius.clamp(0.0, 0.95);
+        self
+    }
+
+    /// Shows or hides the slice labels. Labels are shown by default.
+    pub fn labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Shows or hides the percentage of each slice next to its label. Percentages are shown by
+    /// default.
+    pub fn percentages(mut self, percentages: bool) -> Self {
+        self.percentages = percentages;
+        self
+    }
+
+    /// Sets the height of a half-block pixel divided by its width, used to keep the pie round.
+    pub fn pixel_aspect(mut self, pixel_aspect: f32) -> Self {
+        if pixel_aspect.is_finite() && pixel_aspect > 0.0 {
+            self.pixel_aspect = pixel_aspect;
+        }
+        self
+    }
+
+    /// Sets the color of the labels and leader lines.
+    pub fn axis_style(mut self, style: AxisStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn total(&self) -> f32 {
+        self.slices
+            .iter()
+            .filter(|slice| slice.value.is_finite() && slice.value > 0.0)
+            .map(|slice| slice.value)
+            .sum()
+    }
+
+    fn callout(&self, slice: &PieSlice, total: f32) -> String {
+        let percent = format!("{:.0}%", slice.value / total * 100.0);
+        match (self.labels, self.percentages) {
+            (true, true) => format!("{} {}", slice.label, percent),
+            (true, false) => slice.label.clone(),
+            (false, true) => percent,
+            (false, false) => String::new(),
+        }
+    }
+
+    /// Returns the `(start, end)` angle of every visible slice, in radians clockwise from the top.
+    fn angles(&self, total: f32) -> Vec<(usize, f32, f32)> {
+        let mut start = 0.0;
+        let mut angles = Vec::new();
+        for (index, slice) in self.slices.iter().enumerate() {
+            if !slice.value.is_finite() || slice.value <= 0.0 {
+                continue;
+            }
+            let end = start + slice.value / total * TAU;
+            angles.push((index, start, end));
+            start = end;
+        }
+        // Close the pie exactly, whatever the rounding errors in the sum.
+        if let Some(last) = angles.last_mut() {
+            last.2 = TAU;
+        }
+        angles
+    }
+
+    /// Draws the chart into `area` on layer `z`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas to draw into.
+    /// * `area` - The area of the chart in half-block pixel coordinates, including its labels.
+    /// * `z` - The z-layer to draw on.
+    pub fn draw(&self, canvas: &mut Canvas, area: Rect, z: usize) {
+        let total = self.total();
+        if total <= 0.0 {
+            return;
+        }
+        let angles = self.angles(total);
+
+        // Room for the labels on the right (index 0) and left (index 1) of the pie.
+        let mut margins = [0usize; 2];
+        for (index, start, end) in &angles {
+            let width = font::text_width(&self.callout(&self.slices[*index], total));
+            if width > 0 {
+                let side = usize::from((start + end) / 2.0 > PI);
+                margins[side] = margins[side].max(width + LABEL_GAP + 1);
+            }
+        }
+        let aspect = self.pixel_aspect;
+        let fit = |margins: [usize; 2]| {
+            (area.width.saturating_sub(margins[0] + margins[1]) as f32 / 2.0)
+                .min(area.height as f32 * aspect / 2.0)
+        };
+        let mut radius = fit(margins);
+        // Give up on the labels rather than shrinking the pie to a few pixels.
+        if radius < MIN_LABELED_RADIUS {
+            margins = [0, 0];
+            radius = fit(margins);
+        }
+        if radius < 1.0 {
+            return;
+        }
+        let inner = radius * self.inner_radius;
+        let pie_width = area.width - margins[0] - margins[1];
+        let cx = (area.x + margins[1]) as f32 + pie_width as f32 / 2.0;
+        let cy = area.y as f32 + area.height as f32 / 2.0;
+
+        // Fill every slice as a filled arc of the ring between `inner` and `radius`.
+        let half_height = radius / aspect;
+        let top = (cy - half_height).floor().max(area.y as f32) as usize;
+        let bottom = ((cy + half_height).ceil() as usize).min(area.y + area.height);
+        let left = (cx - radius).floor().max(area.x as f32) as usize;
+        let right = ((cx + radius).ceil() as usize).min(area.x + area.width);
+        for py in top..bottom {
+            for px in left..right {
+                let dx = px as f32 + 0.5 - cx;
+                let dy = (py as f32 + 0.5 - cy) * aspect;
+                let distance = dx.hypot(dy);
+                if distance > radius || distance < inner {
+                    continue;
+                }
+                let angle = dx.atan2(-dy).rem_euclid(TAU);
+                if let Some((index, _, _)) = angles
+                    .iter()
+                    .find(|(_, start, end)| angle >= *start && angle < *end)
+                {
+                    canvas.set_pixel(px, py, z, self.slices[*index].color);
+                }
+            }
+        }
+
+        if margins != [0, 0] {
+            self.draw_callouts(canvas, &angles, total, (cx, cy), radius, z);
+        }
+    }
+
+    fn draw_callouts(
+        &self,
+        canvas: &mut Canvas,
+        angles: &[(usize, f32, f32)],
+        total: f32,
+        (cx, cy): (f32, f32),
+        radius: f32,
+        z: usize,
+    ) {
+        let aspect = self.pixel_aspect;
+        let color = self.style.label_color;
+        let point_at = |angle: f32, distance: f32| {
+            (
+                cx + angle.sin() * distance,
+                cy - angle.cos() * distance / aspect,
+            )
+        };
+
+        // Labels on the right side of the pie, then on the left side, each sorted top to bottom
+        // so they can be pushed apart where they would overlap.
+        let mut sides: [Vec<(f32, f32, String)>; 2] = [Vec::new(), Vec::new()];
+        for (index, start, end) in angles {
+            let middle = (start + end) / 2.0;
+            for distance in [radius + 1.0, radius + 2.0] {
+                let (x, y) = point_at(middle, distance);
+                set_pixel(canvas, x.floor() as isize, y.floor() as isize, z, color);
+            }
+            let (x, y) = point_at(middle, radius + LABEL_GAP as f32);
+            let side = usize::from(middle > PI);
+            sides[side].push((x, y, self.callout(&self.slices[*index], total)));
+        }
+
+        for (side, labels) in sides.iter_mut().enumerate() {
+            labels.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let mut next_free_row = f32::MIN;
+            for (x, y, text) in labels.iter() {
+                let row = (y - (font::GLYPH_HEIGHT / 2) as f32).max(next_free_row);
+                next_free_row = row + (font::GLYPH_HEIGHT + 1) as f32;
+                let text_x = if side == 0 {
+                    x.round() as isize
+                } else {
+                    x.round() as isize - font::text_width(text) as isize
+                };
+                font::draw_text(canvas, text_x, row.round() as isize, z, color, text);
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Color ramps mapping a value in `0.0..=1.0` to a color.
+
+use crate::Color;
+
+const fn hex(rgb: u32) -> Color {
+    Color {
+        r: (rgb >> 16) as u8,
+        g: (rgb >> 8) as u8,
+        b: rgb as u8,
+    }
+}
+
+const VIRIDIS: [Color; 9] = [
+    hex(0x440154),
+    hex(0x472c7a),
+    hex(0x3b518b),
+    hex(0x2c718e),
+    hex(0x21908d),
+    hex(0x27ad81),
+    hex(0x5cc863),
+    hex(0xaadc32),
+    hex(0xfde725),
+];
+
+const MAGMA: [Color; 9] = [
+    hex(0x000004),
+    hex(0x1c1044),
+    hex(0x4f127b),
+    hex(0x812581),
+    hex(0xb5367a),
+    hex(0xe55064),
+    hex(0xfb8761),
+    hex(0xfec287),
+    hex(0xfcfdbf),
+];
+
+const INFERNO: [Color; 9] = [
+    hex(0x000004),
+    hex(0x1f0c48),
+    hex(0x550f6d),
+    hex(0x88226a),
+    hex(0xba3655),
+    hex(0xe35933),
+    hex(0xf98e09),
+    hex(0xf9cb35),
+    hex(0xfcffa4),
+];
+
+/// A gradient of evenly spaced color stops, sampled with linear interpolation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorRamp {
+    stops: Vec<Color>,
+}
+
+impl ColorRamp {
+    /// Creates a ramp from evenly spaced color stops. The first stop is at `0.0` and the last
+    /// at `1.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stops` is empty.
+    pub fn new(stops: impl Into<Vec<Color>>) -> Self {
+        let stops = stops.into();
+        assert!(!stops.is_empty(), "a color ramp needs at least one stop");
+        Self { stops }
+    }
+
+    /// The perceptually uniform blue-green-yellow "viridis" ramp.
+    pub fn viridis() -> Self {
+        Self::new(VIRIDIS)
+    }
+
+    /// The perceptually uniform black-purple-orange-white "magma" ramp.
+    pub fn magma() -> Self {
+        Self::new(MAGMA)
+    }
+
+    /// The perceptually uniform black-purple-orange-yellow "inferno" ramp.
+    pub fn inferno() -> Self {
+        Self::new(INFERNO)
+    }
+
+    /// A ramp from black to white.
+    pub fn grayscale() -> Self {
+        Self::new([hex(0x000000), hex(0xffffff)])
+    }
+
+    /// Returns the color at `t`, clamped to `0.0..=1.0`. `NaN` samples the start of the ramp.
+    pub fn sample(&self, t: f32) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let last = self.stops.len() - 1;
+        let position = t * last as f32;
+        let index = (position.floor() as usize).min(last);
+        if index == last {
+            return self.stops[last];
+        }
+        let fraction = position - index as f32;
+        self.stops[index].lerp(self.stops[index + 1], fraction)
+    }
+}
+
+impl Default for ColorRamp {
+    fn default() -> Self {
+        Self::viridis()
+    }
+}
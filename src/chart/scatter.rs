@@ -0,0 +1,306 @@
+//! Scatter plots with mouse zoom and pan.
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+
+use super::{Axis, AxisStyle, DotSurface, Resolution};
+use crate::{font, Canvas, Color, Rect};
+
+/// How much one scroll step zooms in or out.
+const ZOOM_STEP: f32 = 0.8;
+
+/// The shape drawn for every point of a [`ScatterSeries`], measured in dots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Marker {
+    /// A single dot.
+    #[default]
+    Dot,
+    /// A 3x3 plus sign.
+    Plus,
+    /// A 3x3 diagonal cross.
+    Cross,
+    /// A filled 2x2 square.
+    Square,
+}
+
+impl Marker {
+    fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Marker::Dot => &[(0, 0)],
+            Marker::Plus => &[(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)],
+            Marker::Cross => &[(0, 0), (-1, -1), (1, -1), (-1, 1), (1, 1)],
+            Marker::Square => &[(0, 0), (1, 0), (0, 1), (1, 1)],
+        }
+    }
+}
+
+/// A named set of `(x, y)` points drawn with one marker and color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScatterSeries {
+    /// The name of the series.
+    pub name: String,
+    /// The points of the series. Points with a non-finite coordinate are skipped.
+    pub points: Vec<(f32, f32)>,
+    /// The color of the markers.
+    pub color: Color,
+    /// The marker drawn at every point.
+    pub marker: Marker,
+}
+
+impl ScatterSeries {
+    /// Creates a series of points drawn as single dots.
+    pub fn new(name: impl Into<String>, points: impl Into<Vec<(f32, f32)>>, color: Color) -> Self {
+        Self {
+            name: name.into(),
+            points: points.into(),
+            color,
+            marker: Marker::Dot,
+        }
+    }
+
+    /// Sets the marker drawn at every point.
+    pub fn marker(mut self, marker: Marker) -> Self {
+        self.marker = marker;
+        self
+    }
+}
+
+/// The visible data range of a [`ScatterPlot`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct View {
+    x: (f32, f32),
+    y: (f32, f32),
+}
+
+/// A scatter plot with labeled axes that can be zoomed and panned with the mouse.
+///
+/// Until the view is changed, the axes fit themselves to the data. Feed mouse events to
+/// [`ScatterPlot::handle_mouse`] to zoom with the scroll wheel and pan by dragging with the
+/// left button. Mouse capture must be enabled on the terminal for these events to arrive.
+///
+/// # Example
+///
+/// ```
+/// use rael::chart::{Marker, ScatterPlot, ScatterSeries};
+/// use rael::{Canvas, Color, Rect};
+///
+/// let points: Vec<(f32, f32)> = (0..100).map(|i| (i as f32, (i * i % 37) as f32)).collect();
+/// let mut canvas = Canvas::new(60, 20, Color { r: 0, g: 0, b: 0 });
+/// let plot = ScatterPlot::new()
+///     .series(ScatterSeries::new("samples", points, Color { r: 255, g: 200, b: 0 }).marker(Marker::Plus));
+/// plot.draw(&mut canvas, Rect::new(0, 0, 60, 40), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScatterPlot {
+    series: Vec<ScatterSeries>,
+    resolution: Resolution,
+    style: AxisStyle,
+    view: Option<View>,
+    drag_anchor: Option<(u16, u16)>,
+}
+
+impl ScatterPlot {
+    /// Creates an empty scatter plot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a series to the plot.
+    pub fn series(mut self, series: ScatterSeries) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    /// Returns a mutable reference to the series at `index`.
+    pub fn series_mut(&mut self, index: usize) -> Option<&mut ScatterSeries> {
+        self.series.get_mut(index)
+    }
+
+    /// Sets the resolution at which the markers are drawn.
+    pub fn resolution(mut self, resolution: Resolution) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Sets the colors of the axes and their labels.
+    pub fn axis_style(mut self, style: AxisStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Shows exactly the given data ranges, as if the user had zoomed to them.
+    pub fn set_view(&mut self, x: (f32, f32), y: (f32, f32)) {
+        self.view = Some(View { x, y });
+    }
+
+    /// Returns the visible `(x, y)` data ranges, or `None` while the axes fit the data.
+    pub fn view(&self) -> Option<((f32, f32), (f32, f32))> {
+        self.view.map(|view| (view.x, view.y))
+    }
+
+    /// Goes back to fitting the axes to the data.
+    pub fn reset_view(&mut self) {
+        self.view = None;
+        self.drag_anchor = None;
+    }
+
+    fn axes(&self, area: Rect) -> (Axis, Axis) {
+        let x_ticks = (area.width / 12).max(2);
+        let y_ticks = (area.height / 8).max(2);
+        if let Some(view) = self.view {
+            return (
+                Axis::exact(view.x.0, view.x.1, x_ticks),
+                Axis::exact(view.y.0, view.y.1, y_ticks),
+            );
+        }
+
+        let (x_min, x_max, y_min, y_max) = self
+            .series
+            .iter()
+            .flat_map(|series| series.points.iter())
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .fold(None, |bounds, &(x, y)| match bounds {
+                None => Some((x, x, y, y)),
+                Some((x_min, x_max, y_min, y_max)) => {
+                    Some((x_min.min(x), x_max.max(x), y_min.min(y), y_max.max(y)))
+                }
+            })
+            .unwrap_or((0.0, 1.0, 0.0, 1.0));
+        (
+            Axis::nice(x_min, x_max, x_ticks),
+            Axis::nice(y_min, y_max, y_ticks),
+        )
+    }
+
+    /// Computes the axes and the dot surface of the plot area inside `area`.
+    fn layout(&self, area: Rect) -> Option<(Axis, Axis, DotSurface)> {
+        let (x_axis, y_axis) = self.axes(area);
+        let left = y_axis.label_width() + 3;
+        let right = x_axis
+            .labels()
+            .last()
+            .map_or(0, |label| font::text_width(label) / 2);
+        let top = font::GLYPH_HEIGHT / 2;
+        let bottom = font::GLYPH_HEIGHT + 2;
+        if area.width <= left + right + 1 || area.height <= top + bottom + 1 {
+            return None;
+        }
+        let surface = DotSurface::new(
+            Rect::new(
+                area.x + left,
+                area.y + top,
+                area.width - left - right,
+                area.height - top - bottom,
+            ),
+            self.resolution,
+        );
+        if surface.plot().height < 2 {
+            return None;
+        }
+        Some((x_axis, y_axis, surface))
+    }
+
+    /// Draws the plot into `area` on layer `z`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas to draw into.
+    /// * `area` - The area of the plot in half-block pixel coordinates, including its labels.
+    /// * `z` - The z-layer to draw the axes on, and the markers too in [`Resolution::HalfBlock`].
+    pub fn draw(&self, canvas: &mut Canvas, area: Rect, z: usize) {
+        let Some((x_axis, y_axis, surface)) = self.layout(area) else {
+            return;
+        };
+        let plot = surface.plot();
+        y_axis.draw_vertical(canvas, plot, z, &self.style);
+        x_axis.draw_horizontal(canvas, plot, z, &self.style);
+
+        let width = surface.width() as f32;
+        let height = surface.height() as f32;
+        for series in &self.series {
+            for &(x, y) in &series.points {
+                if !x.is_finite() || !y.is_finite() {
+                    continue;
+                }
+                let fx = x_axis.fraction(x);
+                let fy = y_axis.fraction(y);
+                if !(0.0..=1.0).contains(&fx) || !(0.0..=1.0).contains(&fy) {
+                    continue;
+                }
+                let dot_x = (fx * (width - 1.0)).round() as isize;
+                let dot_y = ((1.0 - fy) * (height - 1.0)).round() as isize;
+                for (dx, dy) in series.marker.offsets() {
+                    surface.set(canvas, dot_x + dx, dot_y + dy, z, series.color);
+                }
+            }
+        }
+    }
+
+    /// Zooms or pans the plot in response to a mouse event, given the `area` the plot is drawn
+    /// into. Scrolling zooms around the point under the mouse and dragging with the left button
+    /// pans.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the event was handled and the view changed.
+    pub fn handle_mouse(&mut self, event: &MouseEvent, area: Rect) -> bool {
+        let Some((x_axis, y_axis, surface)) = self.layout(area) else {
+            return false;
+        };
+        let plot = surface.plot();
+        // Terminal cells are one pixel wide and two half-block pixels tall.
+        let px = event.column as f32;
+        let py = event.row as f32 * 2.0 + 0.5;
+        let inside = px >= plot.x as f32
+            && px < (plot.x + plot.width) as f32
+            && py >= plot.y as f32
+            && py < (plot.y + plot.height) as f32;
+        let units_per_column =
+            (x_axis.max - x_axis.min) / plot.width.saturating_sub(1).max(1) as f32;
+        let units_per_row = (y_axis.max - y_axis.min) / plot.height.saturating_sub(1).max(1) as f32;
+
+        match event.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown if inside => {
+                let factor = if event.kind == MouseEventKind::ScrollUp {
+                    ZOOM_STEP
+                } else {
+                    1.0 / ZOOM_STEP
+                };
+                let focus_x = x_axis.min + (px - plot.x as f32) * units_per_column;
+                let focus_y = y_axis.max - (py - plot.y as f32) * units_per_row;
+                let zoom = |(min, max): (f32, f32), focus: f32| {
+                    (
+                        focus - (focus - min) * factor,
+                        focus + (max - focus) * factor,
+                    )
+                };
+                self.view = Some(View {
+                    x: zoom((x_axis.min, x_axis.max), focus_x),
+                    y: zoom((y_axis.min, y_axis.max), focus_y),
+                });
+                true
+            }
+            MouseEventKind::Down(MouseButton::Left) if inside => {
+                self.drag_anchor = Some((event.column, event.row));
+                false
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let Some((anchor_column, anchor_row)) = self.drag_anchor else {
+                    return false;
+                };
+                let dx = (event.column as f32 - anchor_column as f32) * units_per_column;
+                let dy = (event.row as f32 - anchor_row as f32) * 2.0 * units_per_row;
+                self.drag_anchor = Some((event.column, event.row));
+                self.view = Some(View {
+                    x: (x_axis.min - dx, x_axis.max - dx),
+                    y: (y_axis.min + dy, y_axis.max + dy),
+                });
+                dx != 0.0 || dy != 0.0
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.drag_anchor = None;
+                false
+            }
+            _ => false,
+        }
+    }
+}
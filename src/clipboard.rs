@@ -0,0 +1,39 @@
+//! Copying text to the clipboard of the terminal's user with OSC 52 escape codes.
+
+/// Returns the OSC 52 escape code copying `text` to the clipboard of the terminal it is written
+/// to.
+///
+/// This works over SSH and from [`Server`](crate::Server) clients, since the terminal, not the
+/// program, sets the clipboard. Most modern terminals support it, some only after enabling it
+/// in their settings; others ignore the code. [`Terminal::copy_to_clipboard`] writes this code
+/// to the local terminal.
+///
+/// [`Terminal::copy_to_clipboard`]: crate::Terminal::copy_to_clipboard
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(rael::clipboard_escape("hi"), "\u{1b}]52;c;aGk=\u{7}");
+/// ```
+pub fn clipboard_escape(text: &str) -> String {
+    format!("\u{1b}]52;c;{}\u{7}", base64(text.as_bytes()))
+}
+
+/// Encodes `bytes` in standard base64, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| {
+            group | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * index) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
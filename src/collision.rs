@@ -0,0 +1,148 @@
+//! Pixel-perfect collision testing between sprites.
+
+use crate::Sprite;
+
+/// The solid pixels of a sprite, packed into bits for fast overlap tests.
+///
+/// Masks are meant to be built once per sprite and reused every frame. Overlap tests compare
+/// 64 pixels at a time, so even large sprites are cheap to test.
+///
+/// # Example
+///
+/// ```
+/// use rael::{CollisionMask, Color, Sprite};
+///
+/// let red = Color { r: 255, g: 0, b: 0 };
+/// let mut ship = Sprite::new(3, 3);
+/// ship.set(1, 0, Some(red));
+/// ship.set(0, 2, Some(red));
+/// ship.set(2, 2, Some(red));
+/// let bullet = Sprite::filled(1, 1, red);
+///
+/// let ship_mask = ship.collision_mask();
+/// let bullet_mask = bullet.collision_mask();
+/// // The bounding boxes overlap in both cases, but only the first one hits a solid pixel.
+/// assert!(ship_mask.overlaps(10, 10, &bullet_mask, 11, 10));
+/// assert!(!ship_mask.overlaps(10, 10, &bullet_mask, 10, 10));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionMask {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl CollisionMask {
+    /// Creates a mask with no solid pixels.
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            bits: vec![0; words_per_row * height],
+        }
+    }
+
+    /// Creates a mask whose solid pixels are the opaque pixels of `sprite`.
+    pub fn from_sprite(sprite: &Sprite) -> Self {
+        let mut mask = Self::new(sprite.width(), sprite.height());
+        for (index, pixel) in sprite.pixels().iter().enumerate() {
+            if pixel.is_some() {
+                mask.set(index % sprite.width(), index / sprite.width(), true);
+            }
+        }
+        mask
+    }
+
+    /// Returns the width of the mask in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the mask in half-block pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns `true` if the pixel at (`x`, `y`) is solid. Out of bounds pixels are not.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width
+            && y < self.height
+            && self.bits[y * self.words_per_row + x / 64] & (1 << (x % 64)) != 0
+    }
+
+    /// Marks the pixel at (`x`, `y`) as solid or empty. Out of bounds pixels are ignored.
+    pub fn set(&mut self, x: usize, y: usize, solid: bool) {
+        if x < self.width && y < self.height {
+            let word = &mut self.bits[y * self.words_per_row + x / 64];
+            if solid {
+                *word |= 1 << (x % 64);
+            } else {
+                *word &= !(1 << (x % 64));
+            }
+        }
+    }
+
+    /// Returns the bits of `row` for the `len` columns starting at `start`, with `len` at most 64.
+    fn row_bits(&self, row: usize, start: usize, len: usize) -> u64 {
+        let words = &self.bits[row * self.words_per_row..(row + 1) * self.words_per_row];
+        let (word, shift) = (start / 64, start % 64);
+        let mut bits = words[word] >> shift;
+        if shift > 0 && word + 1 < words.len() {
+            bits |= words[word + 1] << (64 - shift);
+        }
+        if len < 64 {
+            bits &= (1 << len) - 1;
+        }
+        bits
+    }
+
+    /// Returns `true` if this mask placed at (`x`, `y`) and `other` placed at
+    /// (`other_x`, `other_y`) have a solid pixel in common.
+    pub fn overlaps(
+        &self,
+        x: isize,
+        y: isize,
+        other: &CollisionMask,
+        other_x: isize,
+        other_y: isize,
+    ) -> bool {
+        let left = x.max(other_x);
+        let top = y.max(other_y);
+        let right = (x + self.width as isize).min(other_x + other.width as isize);
+        let bottom = (y + self.height as isize).min(other_y + other.height as isize);
+        if left >= right || top >= bottom {
+            return false;
+        }
+
+        for row in top..bottom {
+            let (row_a, row_b) = ((row - y) as usize, (row - other_y) as usize);
+            let mut column = left;
+            while column < right {
+                let len = ((right - column) as usize).min(64);
+                let a = self.row_bits(row_a, (column - x) as usize, len);
+                let b = other.row_bits(row_b, (column - other_x) as usize, len);
+                if a & b != 0 {
+                    return true;
+                }
+                column += 64;
+            }
+        }
+        false
+    }
+
+    /// Returns `true` if this mask placed at (`x`, `y`) has a solid pixel at the world position
+    /// (`point_x`, `point_y`).
+    pub fn contains(&self, x: isize, y: isize, point_x: isize, point_y: isize) -> bool {
+        point_x >= x && point_y >= y && self.get((point_x - x) as usize, (point_y - y) as usize)
+    }
+}
+
+impl Sprite {
+    /// Builds the collision mask of this sprite, where every opaque pixel is solid.
+    pub fn collision_mask(&self) -> CollisionMask {
+        CollisionMask::from_sprite(self)
+    }
+}
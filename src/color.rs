@@ -0,0 +1,370 @@
+//! Constructors and conversions of colors.
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use core::str::FromStr;
+
+use crate::Color;
+
+/// An error raised when parsing a string that is not a hex color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid hex color, expected `#rrggbb` or `#rgb`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseColorError {}
+
+impl Color {
+    /// Creates a color from its `0xRRGGBB` hex code, as given by designers and color pickers.
+    /// Bits above the lowest 24 are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::Color;
+    ///
+    /// const ORANGE: Color = Color::from_hex(0xFF8800);
+    /// assert_eq!(ORANGE, Color { r: 255, g: 136, b: 0 });
+    /// assert_eq!(ORANGE.to_hex(), 0xFF8800);
+    /// assert_eq!(Color::from_hex_str("#ff8800"), Ok(ORANGE));
+    /// assert_eq!("#f80".parse(), Ok(ORANGE));
+    /// ```
+    pub const fn from_hex(hex: u32) -> Self {
+        Self {
+            r: (hex >> 16) as u8,
+            g: (hex >> 8) as u8,
+            b: hex as u8,
+        }
+    }
+
+    /// Parses a color from a hex string, `#rrggbb` or the shorthand `#rgb`, with or without the
+    /// `#`, in either case.
+    pub const fn from_hex_str(hex: &str) -> Result<Self, ParseColorError> {
+        let bytes = hex.as_bytes();
+        let digits = match bytes {
+            [b'#', digits @ ..] => digits,
+            digits => digits,
+        };
+        let mut value = 0;
+        let mut index = 0;
+        while index < digits.len() {
+            let digit = match digits[index] {
+                digit @ b'0'..=b'9' => digit - b'0',
+                digit @ b'a'..=b'f' => digit - b'a' + 10,
+                digit @ b'A'..=b'F' => digit - b'A' + 10,
+                _ => return Err(ParseColorError),
+            };
+            value = value << 4 | digit as u32;
+            index += 1;
+        }
+        match digits.len() {
+            6 => Ok(Self::from_hex(value)),
+            // Every digit of the shorthand stands for two.
+            3 => Ok(Self {
+                r: (value >> 8) as u8 * 0x11,
+                g: (value >> 4 & 0xF) as u8 * 0x11,
+                b: (value & 0xF) as u8 * 0x11,
+            }),
+            _ => Err(ParseColorError),
+        }
+    }
+
+    /// Returns the color `t` of the way from this color to `other`, channel by channel, with
+    /// `t` clamped to `0.0` (this color) to `1.0` (`other`). Useful for gradients and fades.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::Color;
+    ///
+    /// let (black, white) = (Color::from_hex(0x000000), Color::from_hex(0xFFFFFF));
+    /// assert_eq!(Color::lerp(black, white, 0.5), Color::from_hex(0x808080));
+    /// assert_eq!(white.blend(black, 64), Color::from_hex(0x404040));
+    /// assert_eq!(Color::from_hex(0xFF8800).darken(0.5), Color::from_hex(0x804400));
+    /// ```
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let channel = |from: u8, to: u8| {
+            let value = from as f32 + (to as f32 - from as f32) * t;
+            // Round half up; the value is within 0 to 255.
+            (value + 0.5) as u8
+        };
+        Color {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+        }
+    }
+
+    /// Returns the color `t` of the way from this color to `other` like [`Color::lerp`], but
+    /// interpolated in the perceptual OKLab color space, so the colors in between keep their
+    /// brightness and saturation instead of going dull and dark as in RGB. Suited to sky
+    /// gradients and health bar ramps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::Color;
+    ///
+    /// let (red, green) = (Color::RED, Color::GREEN);
+    /// assert_eq!(red.lerp_oklab(green, 0.0), red);
+    /// assert_eq!(red.lerp_oklab(green, 1.0), green);
+    /// // Halfway is a bright yellow-orange, not the dark olive of the RGB mix.
+    /// let (perceptual, rgb) = (red.lerp_oklab(green, 0.5), red.lerp(green, 0.5));
+    /// assert!(perceptual.r > rgb.r && perceptual.g > rgb.g);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn lerp_oklab(self, other: Color, t: f32) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let (from, to) = (Oklab::from(self), Oklab::from(other));
+        Oklab {
+            l: from.l + (to.l - from.l) * t,
+            a: from.a + (to.a - from.a) * t,
+            b: from.b + (to.b - from.b) * t,
+        }
+        .into()
+    }
+
+    /// Returns the color `t` of the way from this color to `other` like
+    /// [`Color::lerp_oklab`], but interpolating the hue around the color wheel, the shorter
+    /// way, with the lightness and chroma of OKLCH, the polar form of OKLab. The colors in
+    /// between stay as saturated as the ends, such as red through orange and yellow to green.
+    #[cfg(feature = "std")]
+    pub fn lerp_oklch(self, other: Color, t: f32) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let (from, to) = (Oklab::from(self), Oklab::from(other));
+        let (from_chroma, to_chroma) = (from.a.hypot(from.b), to.a.hypot(to.b));
+        let (mut from_hue, mut to_hue) = (from.b.atan2(from.a), to.b.atan2(to.a));
+        // Grays have no hue of their own, so take the hue of the other end.
+        const GRAY: f32 = 1e-4;
+        if from_chroma < GRAY {
+            from_hue = to_hue;
+        } else if to_chroma < GRAY {
+            to_hue = from_hue;
+        }
+        let mut turn = to_hue - from_hue;
+        if turn > core::f32::consts::PI {
+            turn -= core::f32::consts::TAU;
+        } else if turn < -core::f32::consts::PI {
+            turn += core::f32::consts::TAU;
+        }
+        let (chroma, hue) = (
+            from_chroma + (to_chroma - from_chroma) * t,
+            from_hue + turn * t,
+        );
+        Oklab {
+            l: from.l + (to.l - from.l) * t,
+            a: chroma * hue.cos(),
+            b: chroma * hue.sin(),
+        }
+        .into()
+    }
+
+    /// Returns this color drawn over `dst` with `alpha`, from `0` (only `dst`) to `255` (only
+    /// this color), rounded to the nearest color.
+    pub const fn blend(self, dst: Color, alpha: u8) -> Color {
+        Color {
+            r: blend_channel(self.r, dst.r, alpha),
+            g: blend_channel(self.g, dst.g, alpha),
+            b: blend_channel(self.b, dst.b, alpha),
+        }
+    }
+
+    /// Returns the color `amount` of the way to black, from `0.0` (unchanged) to `1.0` (black).
+    pub fn darken(self, amount: f32) -> Color {
+        self.lerp(Color::BLACK, amount)
+    }
+
+    /// Returns the color `amount` of the way to white, from `0.0` (unchanged) to `1.0` (white).
+    pub fn lighten(self, amount: f32) -> Color {
+        self.lerp(Color::WHITE, amount)
+    }
+
+    /// Returns the color with every channel multiplied by `brightness`, rounded and clamped:
+    /// `1.0` keeps it unchanged, `0.0` makes it black and values above `1.0` brighten it, such
+    /// as for the light level falling on a texture. `color * brightness` does the same.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::Color;
+    ///
+    /// let brick = Color::from_hex(0xA04632);
+    /// assert_eq!(brick.scale(0.5), Color::from_hex(0x502319));
+    /// assert_eq!(brick * 2.0, Color::from_hex(0xFF8C64));
+    /// // Colors add and subtract channel by channel, stopping at 0 and 255.
+    /// assert_eq!(brick + Color::from_hex(0x808080), Color::from_hex(0xFFC6B2));
+    /// assert_eq!(brick - Color::from_hex(0x808080), Color::from_hex(0x200000));
+    /// // Multiplying two colors tints one by the other, like a colored light.
+    /// assert_eq!(Color::WHITE * brick, brick);
+    /// ```
+    pub fn scale(self, brightness: f32) -> Color {
+        // Round half up; `as` clamps to 0 to 255, with NaN as 0.
+        let channel = |value: u8| (value as f32 * brightness + 0.5) as u8;
+        Color {
+            r: channel(self.r),
+            g: channel(self.g),
+            b: channel(self.b),
+        }
+    }
+
+    /// Returns the sum of the two colors, channel by channel, stopping at 255.
+    pub const fn saturating_add(self, other: Color) -> Color {
+        Color {
+            r: self.r.saturating_add(other.r),
+            g: self.g.saturating_add(other.g),
+            b: self.b.saturating_add(other.b),
+        }
+    }
+
+    /// Returns the difference of the two colors, channel by channel, stopping at 0.
+    pub const fn saturating_sub(self, other: Color) -> Color {
+        Color {
+            r: self.r.saturating_sub(other.r),
+            g: self.g.saturating_sub(other.g),
+            b: self.b.saturating_sub(other.b),
+        }
+    }
+
+    /// Returns the `0xRRGGBB` hex code of the color. Format it with `{:06x}` for a hex string.
+    pub const fn to_hex(self) -> u32 {
+        (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32
+    }
+}
+
+/// Returns the channel `src` drawn over `dst` with `alpha`, rounded to the nearest value.
+const fn blend_channel(src: u8, dst: u8, alpha: u8) -> u8 {
+    let (src, dst, alpha) = (src as u16, dst as u16, alpha as u16);
+    ((src * alpha + dst * (255 - alpha) + 127) / 255) as u8
+}
+
+impl Add for Color {
+    type Output = Color;
+
+    /// Adds the colors channel by channel, stopping at 255, like [`Color::saturating_add`].
+    fn add(self, other: Color) -> Color {
+        self.saturating_add(other)
+    }
+}
+
+impl AddAssign for Color {
+    fn add_assign(&mut self, other: Color) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Color {
+    type Output = Color;
+
+    /// Subtracts the colors channel by channel, stopping at 0, like [`Color::saturating_sub`].
+    fn sub(self, other: Color) -> Color {
+        self.saturating_sub(other)
+    }
+}
+
+impl SubAssign for Color {
+    fn sub_assign(&mut self, other: Color) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<f32> for Color {
+    type Output = Color;
+
+    /// Scales every channel by `brightness`, like [`Color::scale`].
+    fn mul(self, brightness: f32) -> Color {
+        self.scale(brightness)
+    }
+}
+
+impl MulAssign<f32> for Color {
+    fn mul_assign(&mut self, brightness: f32) {
+        *self = *self * brightness;
+    }
+}
+
+impl Mul for Color {
+    type Output = Color;
+
+    /// Multiplies the colors channel by channel, as fractions of 255, so white keeps the other
+    /// color and black gives black.
+    fn mul(self, other: Color) -> Color {
+        let channel = |a: u8, b: u8| ((a as u16 * b as u16 + 127) / 255) as u8;
+        Color {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        Self::from_hex_str(hex)
+    }
+}
+
+/// A color in the OKLab color space: its perceived lightness `l`, from `0.0` to `1.0`, and
+/// how green or red (`a`) and blue or yellow (`b`) it is.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+struct Oklab {
+    l: f32,
+    a: f32,
+    b: f32,
+}
+
+#[cfg(feature = "std")]
+impl From<Color> for Oklab {
+    fn from(color: Color) -> Self {
+        // From sRGB to linear light.
+        let linear = |channel: u8| {
+            let value = channel as f32 / 255.0;
+            if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let (r, g, b) = (linear(color.r), linear(color.g), linear(color.b));
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+        Oklab {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Oklab> for Color {
+    fn from(lab: Oklab) -> Self {
+        let l = (lab.l + 0.396_337_78 * lab.a + 0.215_803_76 * lab.b).powi(3);
+        let m = (lab.l - 0.105_561_346 * lab.a - 0.063_854_17 * lab.b).powi(3);
+        let s = (lab.l - 0.089_484_18 * lab.a - 1.291_485_5 * lab.b).powi(3);
+        // From linear light back to sRGB, with colors outside of it clamped.
+        let channel = |value: f32| {
+            let value = value.clamp(0.0, 1.0);
+            let value = if value <= 0.003_130_8 {
+                value * 12.92
+            } else {
+                1.055 * value.powf(1.0 / 2.4) - 0.055
+            };
+            (value * 255.0 + 0.5) as u8
+        };
+        Color {
+            r: channel(4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s),
+            g: channel(-1.268_438 * l + 2.609_757_4 * m - 0.341_319_4 * s),
+            b: channel(-0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Operations combining two canvases into a new one: differences, overlays and masks.
+
+use crate::{shade_color, Canvas, Color};
+
+impl Canvas {
+    /// Returns a canvas showing where `other` differs from this canvas: the pixels that changed,
+    /// and both pixels of terminal cells whose characters changed, in `highlight`, over `other`
+    /// darkened to a quarter of its brightness. Useful for visual diff tools and for finding
+    /// what a frame redraws.
+    ///
+    /// Like every operation combining canvases, the result covers the area both canvases cover,
+    /// with this canvas's default color, and holds the result on layer `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let black = Color { r: 0, g: 0, b: 0 };
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let before = Canvas::new(10, 5, black);
+    /// let mut after = Canvas::new(10, 5, black);
+    /// after.set_pixel(3, 4, 0, Color { r: 0, g: 0, b: 200 });
+    ///
+    /// let difference = before.difference(&after, red);
+    /// assert_eq!(difference.composited_cell(3, 2).top_color, red);
+    /// assert_eq!(difference.composited_cell(4, 2).top_color, black);
+    /// ```
+    pub fn difference(&self, other: &Canvas, highlight: Color) -> Canvas {
+        let mut result = self.combined_with(other);
+        for row in 0..result.height {
+            for column in 0..result.width {
+                let glyph_changed =
+                    self.composited_glyph(column, row) != other.composited_glyph(column, row);
+                let (before, after) = (
+                    self.composited_cell(column, row),
+                    other.composited_cell(column, row),
+                );
+                for (y, before, after) in [
+                    (row * 2, before.top_color, after.top_color),
+                    (row * 2 + 1, before.bottom_color, after.bottom_color),
+                ] {
+                    let color = if glyph_changed || before != after {
+                        highlight
+                    } else {
+                        shade_color(after, 64)
+                    };
+                    result.set_pixel(column, y, 0, color);
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns this canvas with the visible pixels and characters of `top` drawn over it,
+    /// blending the pixels by `opacity`, from `0` (invisible) to `255` (opaque). Pixels of `top`
+    /// with nothing drawn on any layer are transparent. Useful for effects computed from two
+    /// frames, such as a damage flash drawn over the frame before the hit.
+    pub fn overlay(&self, top: &Canvas, opacity: u8) -> Canvas {
+        let mut result = self.combined_with(top);
+        for row in 0..result.height {
+            for column in 0..result.width {
+                let (below, above) = (
+                    self.composited_cell(column, row),
+                    top.composited_cell(column, row),
+                );
+                for (y, below, above) in [
+                    (row * 2, below.top_color, above.top_color),
+                    (row * 2 + 1, below.bottom_color, above.bottom_color),
+                ] {
+                    let color = if top.is_half_block_empty(column, y) {
+                        below
+                    } else {
+                        above.blend(below, opacity)
+                    };
+                    result.set_pixel(column, y, 0, color);
+                }
+                let glyph = top
+                    .composited_glyph(column, row)
+                    .or_else(|| self.composited_glyph(column, row));
+                if let Some(glyph) = glyph {
+                    result.set_glyph(column, row, glyph);
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the pixels of this canvas where `mask` has a pixel drawn on any layer, with the
+    /// rest in this canvas's default color. Characters are kept where
+    /// the top pixel of their cell is kept.
+    pub fn masked(&self, mask: &Canvas) -> Canvas {
+        let mut result = self.combined_with(mask);
+        for row in 0..result.height {
+            for column in 0..result.width {
+                let cell = self.composited_cell(column, row);
+                for (y, color) in [(row * 2, cell.top_color), (row * 2 + 1, cell.bottom_color)] {
+                    if !mask.is_half_block_empty(column, y) {
+                        result.set_pixel(column, y, 0, color);
+                    }
+                }
+                if !mask.is_half_block_empty(column, row * 2) {
+                    if let Some(glyph) = self.composited_glyph(column, row) {
+                        result.set_glyph(column, row, glyph);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns an empty canvas covering the area covered by both this canvas and `other`.
+    fn combined_with(&self, other: &Canvas) -> Canvas {
+        Canvas::new(
+            self.width.min(other.width),
+            self.height.min(other.height),
+            self.default_color,
+        )
+    }
+}
@@ -0,0 +1,171 @@
+//! Writing the last rendered frame to a file when the program panics.
+
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::{testing, Canvas, Frame};
+
+/// How many PNG pixels wide and tall every half-block pixel is in PNG dumps.
+const PNG_SCALE: usize = 4;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static LAST_FRAME: Mutex<Option<Frame>> = Mutex::new(None);
+
+/// Makes every [`Canvas::render`], [`Terminal::draw`](crate::Terminal::draw) and
+/// [`MultiSink::present`](crate::MultiSink::present) keep a copy of the frame shown, and
+/// installs a panic hook writing the last one to `path` when the program panics, so bug reports
+/// can show what was on screen.
+///
+/// Paths ending in `.png` get an image where every half-block pixel is a 4x4 square, without
+/// the characters drawn over cells. Other paths get the text format of
+/// [`testing::to_golden`], which keeps the colors and the characters with their marks and
+/// styles. The previous panic hook still runs after the dump is written.
+///
+/// # Example
+///
+/// ```no_run
+/// rael::install_crash_dump("crash-frame.png");
+/// ```
+pub fn install_crash_dump(path: impl Into<PathBuf>) {
+    let path = path.into();
+    ENABLED.store(true, Ordering::Relaxed);
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // A panic while the frame was being stored leaves the lock poisoned, but the last
+        // complete frame is still there.
+        let frame = LAST_FRAME
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if let Some(frame) = frame {
+            match write_dump(&frame, &path) {
+                Ok(()) => eprintln!("the last frame was written to {}", path.display()),
+                Err(error) => eprintln!("could not write the last frame: {error}"),
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Returns the last frame rendered since [`install_crash_dump`] was called.
+pub fn last_frame() -> Option<Frame> {
+    LAST_FRAME
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Keeps a copy of `frame`, the last frame shown, if crash dumps are installed.
+pub(crate) fn remember_frame(frame: &Frame) {
+    if ENABLED.load(Ordering::Relaxed) {
+        *LAST_FRAME
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(frame.clone());
+    }
+}
+
+/// Keeps a copy of what `canvas` shows if crash dumps are installed, compositing it. Callers
+/// holding the frame they just showed pass it to [`remember_frame`] instead.
+pub(crate) fn remember_canvas(canvas: &Canvas) {
+    if ENABLED.load(Ordering::Relaxed) {
+        remember_frame(&Frame::capture(canvas));
+    }
+}
+
+/// Keeps a copy of what `canvas` showed when it was just rendered if crash dumps are installed,
+/// from the cells the render composited.
+pub(crate) fn remember_rendered(canvas: &Canvas) {
+    if ENABLED.load(Ordering::Relaxed) {
+        remember_frame(&Frame::rendered(canvas));
+    }
+}
+
+fn write_dump(frame: &Frame, path: &Path) -> io::Result<()> {
+    let is_png = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+    if is_png {
+        fs::write(path, encode_png(frame))
+    } else {
+        fs::write(path, testing::to_golden(frame))
+    }
+}
+
+/// Encodes a frame as an RGB PNG, with uncompressed deflate blocks.
+fn encode_png(frame: &Frame) -> Vec<u8> {
+    let width = frame.width() * PNG_SCALE;
+    let height = frame.height() * 2 * PNG_SCALE;
+    let mut raw = Vec::with_capacity((width * 3 + 1) * height);
+    for y in 0..height {
+        // Each scanline starts with its filter type, none here.
+        raw.push(0);
+        for x in 0..width {
+            let color = frame.pixel(x / PNG_SCALE, y / PNG_SCALE).unwrap();
+            raw.extend([color.r, color.g, color.b]);
+        }
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        zlib.push(last as u8);
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bits per channel, RGB, default compression, filtering and no interlacing.
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    push_chunk(&mut png, b"IHDR", &header);
+    push_chunk(&mut png, b"IDAT", &zlib);
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
@@ -0,0 +1,225 @@
+//! A toggleable overlay of gridlines, rulers and a crosshair for debugging layouts.
+
+use crossterm::event::{Event, MouseEventKind};
+
+use crate::{Canvas, CellGlyph, Color};
+
+/// Gridlines, coordinate rulers and a crosshair drawn over a canvas, for finding where things
+/// are and catching pixels placed one off.
+///
+/// The overlay is drawn last, over everything else, while it is enabled; toggle it from a key
+/// binding with [`DebugOverlay::toggle`]. Rulers number the pixel columns along the top edge
+/// and the pixel rows along the left edge. The crosshair follows the mouse when events are
+/// passed to [`DebugOverlay::track_mouse`], with mouse reporting turned on through
+/// [`Terminal::set_mouse_capture`], and shows the coordinates of the pixel under it.
+///
+/// [`Terminal::set_mouse_capture`]: crate::Terminal::set_mouse_capture
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, DebugOverlay};
+///
+/// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+/// let mut overlay = DebugOverlay::new().grid(Some((8, 8)));
+/// overlay.toggle();
+/// overlay.set_crosshair(Some((12, 7)));
+/// overlay.draw(&mut canvas, 9);
+/// assert_eq!(canvas.composited_cell(12, 0).top_color, Color { r: 255, g: 0, b: 255 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugOverlay {
+    enabled: bool,
+    grid: Option<(usize, usize)>,
+    checkerboard: bool,
+    rulers: bool,
+    crosshair: Option<(usize, usize)>,
+    grid_color: Color,
+    crosshair_color: Color,
+    text_color: Color,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            grid: Some((8, 8)),
+            checkerboard: false,
+            rulers: true,
+            crosshair: None,
+            grid_color: Color {
+                r: 60,
+                g: 60,
+                b: 90,
+            },
+            crosshair_color: Color {
+                r: 255,
+                g: 0,
+                b: 255,
+            },
+            text_color: Color {
+                r: 255,
+                g: 255,
+                b: 0,
+            },
+        }
+    }
+}
+
+impl DebugOverlay {
+    /// Creates a disabled overlay with gridlines every 8 pixels and rulers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the spacing of the gridlines in pixels, across and down, or turns them off with
+    /// `None`. `Some((1, 2))` outlines every terminal cell. Defaults to `Some((8, 8))`.
+    pub fn grid(mut self, spacing: Option<(usize, usize)>) -> Self {
+        self.grid = spacing.filter(|(x, y)| *x > 0 && *y > 0);
+        self
+    }
+
+    /// Sets whether every other pixel is darkened, showing where each pixel is. Defaults to
+    /// `false`.
+    pub fn checkerboard(mut self, enabled: bool) -> Self {
+        self.checkerboard = enabled;
+        self
+    }
+
+    /// Sets whether coordinate rulers are drawn along the top and left edges. Defaults to
+    /// `true`.
+    pub fn rulers(mut self, enabled: bool) -> Self {
+        self.rulers = enabled;
+        self
+    }
+
+    /// Sets the color of the gridlines. Defaults to a dark blue-grey.
+    pub fn grid_color(mut self, color: Color) -> Self {
+        self.grid_color = color;
+        self
+    }
+
+    /// Sets the color of the crosshair. Defaults to magenta.
+    pub fn crosshair_color(mut self, color: Color) -> Self {
+        self.crosshair_color = color;
+        self
+    }
+
+    /// Sets the color of the ruler numbers and the crosshair coordinates. Defaults to yellow.
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    /// Shows the overlay if it is hidden, or hides it. The overlay starts hidden.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Shows or hides the overlay.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns whether the overlay is shown.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Puts the crosshair on pixel (`x`, `y`), or removes it with `None`.
+    pub fn set_crosshair(&mut self, position: Option<(usize, usize)>) {
+        self.crosshair = position;
+    }
+
+    /// Returns the pixel under the crosshair, if any.
+    pub fn crosshair(&self) -> Option<(usize, usize)> {
+        self.crosshair
+    }
+
+    /// Moves the crosshair to the mouse if `event` is a mouse event, on the top pixel of the
+    /// cell under the pointer.
+    pub fn track_mouse(&mut self, event: &Event) {
+        if let Event::Mouse(mouse) = event {
+            if !matches!(
+                mouse.kind,
+                MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+            ) {
+                self.crosshair = Some((mouse.column as usize, mouse.row as usize * 2));
+            }
+        }
+    }
+
+    /// Draws the overlay on layer `z` of `canvas`, if it is shown. Use the highest layer, so the
+    /// overlay covers everything else.
+    pub fn draw(&self, canvas: &mut Canvas, z: usize) {
+        if !self.enabled {
+            return;
+        }
+        let (width, height) = (canvas.final_width, canvas.final_height);
+        if self.checkerboard {
+            for y in 0..height {
+                for x in (y % 2..width).step_by(2) {
+                    let shade = canvas.shade(x, y);
+                    canvas.set_shade(x, y, (shade as u16 * 3 / 4) as u8);
+                }
+            }
+        }
+        if let Some((spacing_x, spacing_y)) = self.grid {
+            for y in 0..height {
+                for x in 0..width {
+                    if x % spacing_x == 0 || y % spacing_y == 0 {
+                        canvas.set_pixel(x, y, z, self.grid_color);
+                    }
+                }
+            }
+        }
+        if let Some((cross_x, cross_y)) = self.crosshair {
+            if cross_x < width && cross_y < height {
+                for x in 0..width {
+                    canvas.set_pixel(x, cross_y, z, self.crosshair_color);
+                }
+                for y in 0..height {
+                    canvas.set_pixel(cross_x, y, z, self.crosshair_color);
+                }
+                let label = format!("{cross_x},{cross_y}");
+                // Below the crosshair, or above it on the bottom row.
+                let row = if cross_y / 2 + 1 < canvas.height {
+                    cross_y / 2 + 1
+                } else {
+                    (cross_y / 2).saturating_sub(1)
+                };
+                let column = if cross_x + 2 + label.len() <= canvas.width {
+                    cross_x + 2
+                } else {
+                    cross_x.saturating_sub(label.len() + 1)
+                };
+                self.write(canvas, column, row, &label);
+            }
+        }
+        if self.rulers {
+            for column in 0..canvas.width {
+                let tick = if column % 5 == 0 { '|' } else { '.' };
+                self.write(canvas, column, 0, &tick.to_string());
+            }
+            for column in (0..canvas.width).step_by(10) {
+                self.write(canvas, column, 0, &column.to_string());
+            }
+            for row in 1..canvas.height {
+                let tick = if row % 5 == 0 { '-' } else { '.' };
+                self.write(canvas, 0, row, &tick.to_string());
+            }
+            for row in (5..canvas.height).step_by(5) {
+                // Rows are numbered by their top pixel.
+                self.write(canvas, 0, row, &(row * 2).to_string());
+            }
+        }
+    }
+
+    /// Writes `text` as characters starting at the terminal cell (`column`, `row`).
+    fn write(&self, canvas: &mut Canvas, column: usize, row: usize, text: &str) {
+        for (offset, ch) in text.chars().enumerate() {
+            let glyph = CellGlyph::new(ch, self.text_color);
+            canvas.set_glyph(column + offset, row, glyph);
+        }
+    }
+}
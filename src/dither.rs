@@ -0,0 +1,204 @@
+//! Dithering of composited frames to the colors of a palette color mode, so gradients and
+//! images keep their shades instead of banding.
+
+use alloc::vec;
+
+use crate::palette::ansi;
+use crate::{xterm, Canvas, Color, ColorMode, CompositedCell};
+
+/// The 4x4 Bayer matrix used for ordered dithering.
+pub(crate) const BAYER: [[u8; 4]; 4] =
+    [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How colors falling between the colors of the palette are drawn when a canvas sends palette
+/// colors, with [`ColorMode::Xterm256`] or [`ColorMode::Ansi16`]. Colors sent as 24-bit colors
+/// are never dithered.
+///
+/// Dithering runs on the final composited cells, after the render passes, on the grid of
+/// half-block pixels. Cells shown in the terminal's default background are left alone.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, ColorMode, DitherMode};
+///
+/// let mut canvas = Canvas::new(32, 4, Color::BLACK);
+/// canvas.fill_with(0, |x, _| Color::from_hex(0x080808 * x as u32));
+/// canvas.set_color_mode(ColorMode::Ansi16);
+/// canvas.set_dither_mode(DitherMode::Ordered);
+/// let output = canvas.render();
+/// // The grays between black and white mix the four ANSI grays in a pattern.
+/// assert!(output.contains("\x1b[90m") && output.contains("\x1b[37m"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DitherMode {
+    /// Every color is drawn in the palette color closest to it.
+    #[default]
+    None,
+    /// The closest palette colors are mixed in a 4x4 Bayer pattern, which stays still from
+    /// frame to frame, so animations do not shimmer.
+    Ordered,
+    /// The error of every pixel is spread to the pixels right of it and below it with
+    /// Floyd-Steinberg error diffusion, which keeps the most detail in images but shimmers in
+    /// moving areas.
+    FloydSteinberg,
+}
+
+impl Canvas {
+    /// Sets how colors between the colors of the palette are drawn when the color mode sends
+    /// palette colors. Defaults to [`DitherMode::None`].
+    pub fn set_dither_mode(&mut self, mode: DitherMode) {
+        self.dither_mode = mode;
+    }
+
+    /// Returns how colors between the colors of the palette are drawn.
+    pub fn dither_mode(&self) -> DitherMode {
+        self.dither_mode
+    }
+}
+
+/// Replaces the colors of `cells`, rows of `width` cells, by palette colors of `color_mode`
+/// mixed by `dither_mode`, leaving the `terminal_default` color, if any, alone.
+pub(crate) fn dither_to_mode(
+    cells: &mut [CompositedCell],
+    width: usize,
+    color_mode: ColorMode,
+    dither_mode: DitherMode,
+    terminal_default: Option<Color>,
+) {
+    let spread = match color_mode {
+        ColorMode::TrueColor => return,
+        // About the distance between the levels of the color cube.
+        ColorMode::Xterm256 => 40.0,
+        ColorMode::Ansi16 => 128.0,
+    };
+    let quantize = |color| match color_mode {
+        ColorMode::Xterm256 => xterm::color(xterm::nearest(color)),
+        _ => ansi::COLORS[ansi::nearest(color) as usize].1,
+    };
+    dither(
+        cells,
+        width,
+        quantize,
+        spread,
+        dither_mode,
+        terminal_default,
+    );
+}
+
+/// The items of a grid of pixels to dither, each a column of one or more pixels.
+pub(crate) trait Pixels {
+    /// The number of pixels of each item, one above the other.
+    const HEIGHT: usize;
+
+    /// Returns the color of pixel `row` of the item, counted from the top.
+    fn color(&mut self, row: usize) -> &mut Color;
+}
+
+/// A cell holds two half-block pixels.
+impl Pixels for CompositedCell {
+    const HEIGHT: usize = 2;
+
+    fn color(&mut self, row: usize) -> &mut Color {
+        if row == 0 {
+            &mut self.top_color
+        } else {
+            &mut self.bottom_color
+        }
+    }
+}
+
+/// A color is a pixel of its own, such as in the frames of a video.
+impl Pixels for Color {
+    const HEIGHT: usize = 1;
+
+    fn color(&mut self, _: usize) -> &mut Color {
+        self
+    }
+}
+
+/// Replaces the colors of `pixels`, rows of `width` items, by the colors `quantize` snaps them
+/// to, mixed by `dither_mode` with colors up to `spread` apart, leaving the `terminal_default`
+/// color, if any, alone.
+pub(crate) fn dither<P: Pixels>(
+    pixels: &mut [P],
+    width: usize,
+    quantize: impl Fn(Color) -> Color,
+    spread: f32,
+    dither_mode: DitherMode,
+    terminal_default: Option<Color>,
+) {
+    if width == 0 {
+        return;
+    }
+    let height = pixels.len() / width * P::HEIGHT;
+    match dither_mode {
+        DitherMode::None => {
+            for item in pixels.iter_mut() {
+                for row in 0..P::HEIGHT {
+                    let color = item.color(row);
+                    if terminal_default != Some(*color) {
+                        *color = quantize(*color);
+                    }
+                }
+            }
+        }
+        DitherMode::Ordered => {
+            for y in 0..height {
+                for x in 0..width {
+                    let color = pixel(pixels, width, x, y);
+                    if terminal_default == Some(*color) {
+                        continue;
+                    }
+                    let offset = ((BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5) * spread;
+                    let channel = |value: u8| (value as f32 + offset).clamp(0.0, 255.0) as u8;
+                    *color = quantize(Color {
+                        r: channel(color.r),
+                        g: channel(color.g),
+                        b: channel(color.b),
+                    });
+                }
+            }
+        }
+        DitherMode::FloydSteinberg => {
+            // The errors carried to the pixels of this row and the next, with a pixel of margin
+            // on both sides.
+            let mut errors = vec![[0f32; 3]; width + 2];
+            let mut next = vec![[0f32; 3]; width + 2];
+            for y in 0..height {
+                for x in 0..width {
+                    let color = pixel(pixels, width, x, y);
+                    if terminal_default == Some(*color) {
+                        continue;
+                    }
+                    let error = errors[x + 1];
+                    let wanted = [
+                        color.r as f32 + error[0],
+                        color.g as f32 + error[1],
+                        color.b as f32 + error[2],
+                    ];
+                    let channel = |value: f32| value.clamp(0.0, 255.0) as u8;
+                    let shown = quantize(Color {
+                        r: channel(wanted[0]),
+                        g: channel(wanted[1]),
+                        b: channel(wanted[2]),
+                    });
+                    *color = shown;
+                    for (index, value) in [shown.r, shown.g, shown.b].into_iter().enumerate() {
+                        let error = wanted[index] - value as f32;
+                        errors[x + 2][index] += error * 7.0 / 16.0;
+                        next[x][index] += error * 3.0 / 16.0;
+                        next[x + 1][index] += error * 5.0 / 16.0;
+                        next[x + 2][index] += error / 16.0;
+                    }
+                }
+                errors = core::mem::replace(&mut next, vec![[0f32; 3]; width + 2]);
+            }
+        }
+    }
+}
+
+/// Returns the color of the pixel at (`x`, `y`) of `pixels`, rows of `width` items.
+fn pixel<P: Pixels>(pixels: &mut [P], width: usize, x: usize, y: usize) -> &mut Color {
+    pixels[y / P::HEIGHT * width + x].color(y % P::HEIGHT)
+}
@@ -0,0 +1,1412 @@
+//! Drawing primitives: lines and shapes rasterized into the half-block pixels of a canvas.
+
+use std::f64::consts::TAU;
+
+use crate::{Brush, Canvas, Color, Path, Rect, TerminalPixel};
+
+/// The shape of the ends of thick lines, which is also the shape stamped along them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineCap {
+    /// A disc, giving rounded ends and an even width at every angle.
+    #[default]
+    Round,
+    /// A square, giving square ends; diagonal lines come out wider.
+    Square,
+}
+
+/// A repeating pattern of drawn and skipped pixels along a line, for dashed and dotted lines.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, LineStyle};
+///
+/// let white = Color { r: 255, g: 255, b: 255 };
+/// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+/// // A selection rectangle with dashes of 3 pixels; increase the offset every frame to make
+/// // the dashes march around it.
+/// let style = LineStyle::dashed(3, 2).offset(1);
+/// canvas.draw_rect_styled(2, 2, 10, 6, &style, 0, white);
+/// assert_eq!(canvas.composited_cell(2, 1).top_color, white);
+/// assert_eq!(canvas.composited_cell(5, 1).top_color, Color { r: 0, g: 0, b: 0 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineStyle {
+    pattern: Vec<bool>,
+    offset: usize,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self::solid()
+    }
+}
+
+impl LineStyle {
+    /// Creates a style drawing every pixel.
+    pub fn solid() -> Self {
+        Self::from_pattern(&[true])
+    }
+
+    /// Creates a style of dashes `on` pixels long with gaps of `off` pixels.
+    pub fn dashed(on: usize, off: usize) -> Self {
+        let mut pattern = vec![true; on];
+        pattern.resize(on + off, false);
+        Self::from_pattern(&pattern)
+    }
+
+    /// Creates a style drawing every other pixel.
+    pub fn dotted() -> Self {
+        Self::dashed(1, 1)
+    }
+
+    /// Creates a style from the pattern of drawn (`true`) and skipped (`false`) pixels repeated
+    /// along the line. An empty pattern draws every pixel.
+    pub fn from_pattern(pattern: &[bool]) -> Self {
+        Self {
+            pattern: if pattern.is_empty() {
+                vec![true]
+            } else {
+                pattern.to_vec()
+            },
+            offset: 0,
+        }
+    }
+
+    /// Sets how many pixels into the pattern lines start, for moving the pattern along them.
+    /// Defaults to `0`.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns whether the pixel `index` pixels along a line is drawn.
+    pub fn is_drawn(&self, index: usize) -> bool {
+        self.pattern[(index + self.offset) % self.pattern.len()]
+    }
+}
+
+impl Canvas {
+    /// Draws a line from (`x0`, `y0`) to (`x1`, `y1`), both ends included, on layer `z`, with
+    /// Bresenham's algorithm.
+    ///
+    /// Coordinates are in pixels and may lie outside the canvas, even be negative; the parts of
+    /// the line outside the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let mut canvas = Canvas::new(10, 5, Color { r: 0, g: 0, b: 0 });
+    /// canvas.draw_line(-5, -5, 20, 20, 0, red);
+    /// assert_eq!(canvas.composited_cell(3, 1).bottom_color, red);
+    /// ```
+    pub fn draw_line(
+        &mut self,
+        x0: isize,
+        y0: isize,
+        x1: isize,
+        y1: isize,
+        z: usize,
+        color: Color,
+    ) {
+        let ((x0, y0), (x1, y1)) = (
+            self.transformed_pixel(x0, y0),
+            self.transformed_pixel(x1, y1),
+        );
+        self.line(x0, y0, x1, y1, z, color);
+    }
+
+    /// Draws a line from the pixel at (`x0`, `y0`) to the pixel at (`x1`, `y1`) on layer `z`,
+    /// ignoring the transform.
+    pub(crate) fn line(
+        &mut self,
+        x0: isize,
+        y0: isize,
+        x1: isize,
+        y1: isize,
+        z: usize,
+        color: Color,
+    ) {
+        let edges = self.drawable_edges();
+        line_points((x0, y0), (x1, y1), edges, |_, x, y| {
+            self.plot(x, y, z, color)
+        });
+    }
+
+    /// Draws lines joining `points`, in order, on layer `z`, as one connected line: every joint
+    /// is drawn once, so joints have no seams and drawing many points costs no more than
+    /// their lines. Parts outside the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let green = Color { r: 0, g: 255, b: 0 };
+    /// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+    /// // A line chart of five samples.
+    /// let samples = [3, 7, 5, 12, 9];
+    /// let points: Vec<(isize, isize)> = samples
+    ///     .iter()
+    ///     .enumerate()
+    ///     .map(|(index, sample)| (index as isize * 4, 19 - sample))
+    ///     .collect();
+    /// canvas.draw_polyline(&points, 0, green);
+    /// assert_eq!(canvas.composited_cell(12, 3).bottom_color, green);
+    /// ```
+    pub fn draw_polyline(&mut self, points: &[(isize, isize)], z: usize, color: Color) {
+        let points: Vec<(isize, isize)> = points
+            .iter()
+            .map(|&(x, y)| self.transformed_pixel(x, y))
+            .collect();
+        self.polyline(&points, z, color);
+    }
+
+    /// Draws lines joining the pixels `points` on layer `z`, every joint once, ignoring the
+    /// transform.
+    pub(crate) fn polyline(&mut self, points: &[(isize, isize)], z: usize, color: Color) {
+        if let [(x, y)] = *points {
+            self.plot(x, y, z, color);
+        }
+        let edges = self.drawable_edges();
+        for (index, pair) in points.windows(2).enumerate() {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            line_points((x0, y0), (x1, y1), edges, |_, x, y| {
+                // The start of every line after the first is the end of the one before.
+                if index == 0 || (x, y) != (x0, y0) {
+                    self.plot(x, y, z, color);
+                }
+            });
+        }
+    }
+
+    /// Draws a line from (`x0`, `y0`) to (`x1`, `y1`) on layer `z` like [`Canvas::draw_line`],
+    /// drawing only the pixels `style` draws, counted from (`x0`, `y0`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_line_styled(
+        &mut self,
+        x0: isize,
+        y0: isize,
+        x1: isize,
+        y1: isize,
+        style: &LineStyle,
+        z: usize,
+        color: Color,
+    ) {
+        let ((x0, y0), (x1, y1)) = (
+            self.transformed_pixel(x0, y0),
+            self.transformed_pixel(x1, y1),
+        );
+        let edges = self.drawable_edges();
+        line_points((x0, y0), (x1, y1), edges, |index, x, y| {
+            if style.is_drawn(index) {
+                self.plot(x, y, z, color);
+            }
+        });
+    }
+
+    /// Draws a line `width` pixels thick from `from` to `to`, stamping a disc or a square of
+    /// that width, as chosen by `cap`, along it. A width of `1` draws the same line as
+    /// [`Canvas::draw_line`]. The width is scaled with the transform. Parts outside the canvas
+    /// are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color, LineCap};
+    ///
+    /// let gray = Color { r: 128, g: 128, b: 128 };
+    /// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+    /// canvas.draw_thick_line((5, 5), (30, 30), 5, LineCap::Round, 0, gray);
+    /// // Two pixels to either side of the middle of the line.
+    /// assert_eq!(canvas.composited_cell(19, 8).top_color, gray);
+    /// assert_eq!(canvas.composited_cell(15, 8).top_color, gray);
+    /// ```
+    pub fn draw_thick_line(
+        &mut self,
+        from: (isize, isize),
+        to: (isize, isize),
+        width: usize,
+        cap: LineCap,
+        z: usize,
+        color: Color,
+    ) {
+        let width = (width as f64 * self.length_scale()).round() as usize;
+        if width == 0 {
+            return;
+        }
+        let reach = width as isize / 2 + 1;
+        let ((x0, y0), (x1, y1)) = (
+            self.transformed_pixel(from.0, from.1),
+            self.transformed_pixel(to.0, to.1),
+        );
+        let brush = match cap {
+            LineCap::Round => Brush::circle(width),
+            LineCap::Square => Brush::square(width),
+        };
+        // Points just outside the drawable area still stamp part of the brush inside it.
+        let (left, top, right, bottom) = self.drawable_edges();
+        let edges = (left - reach, top - reach, right + reach, bottom + reach);
+        line_points((x0, y0), (x1, y1), edges, |_, x, y| {
+            self.stamp_at(&brush, x, y, z, color)
+        });
+    }
+
+    /// Draws an anti-aliased line from (`x0`, `y0`) to (`x1`, `y1`) on layer `z`, with Wu's
+    /// algorithm.
+    ///
+    /// Every pixel along the line is blended into the pixel already on the layer by how much
+    /// the line covers it, or drawn that much opaque where the layer is transparent, so the
+    /// line looks smooth over every layer below and can start and end between pixels. Parts
+    /// outside the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+    /// canvas.draw_line_aa(0.0, 0.0, 19.0, 5.5, 0, white);
+    /// let cell = canvas.composited_cell(10, 1);
+    /// // The line passes between the two pixels of the cell, lighting both partly.
+    /// assert!(cell.top_color.r > 0 && cell.top_color.r < 255);
+    /// assert!(cell.bottom_color.r > 0 && cell.bottom_color.r < 255);
+    /// ```
+    pub fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, z: usize, color: Color) {
+        let ((x0, y0), (x1, y1)) = (
+            self.transformed((x0 as f64, y0 as f64)),
+            self.transformed((x1 as f64, y1 as f64)),
+        );
+        let (x0, y0, x1, y1) = (x0 as f32, y0 as f32, x1 as f32, y1 as f32);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        // Walk along the major axis, from left to right once swapped.
+        let (mut x0, mut y0, mut x1, mut y1) = if steep {
+            (y0, x0, y1, x1)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        if x0 > x1 {
+            (x0, x1) = (x1, x0);
+            (y0, y1) = (y1, y0);
+        }
+        let gradient = if x1 - x0 == 0.0 {
+            1.0
+        } else {
+            (y1 - y0) / (x1 - x0)
+        };
+        let plot = |canvas: &mut Canvas, major: isize, minor: isize, coverage: f32| {
+            let (x, y) = if steep {
+                (minor, major)
+            } else {
+                (major, minor)
+            };
+            canvas.blend_pixel(x, y, z, color, coverage);
+        };
+        // The ends cover their pixels by how far the line reaches into them.
+        let end = |canvas: &mut Canvas, x: f32, y: f32, gap: f32| {
+            let major = x.round();
+            let minor = y + gradient * (major - x);
+            let row = minor.floor();
+            plot(
+                canvas,
+                major as isize,
+                row as isize,
+                (1.0 - (minor - row)) * gap,
+            );
+            plot(
+                canvas,
+                major as isize,
+                row as isize + 1,
+                (minor - row) * gap,
+            );
+            major as isize
+        };
+        let first = end(self, x0, y0, 1.0 - (x0 + 0.5).fract());
+        let last = end(self, x1, y1, (x1 + 0.5).fract());
+        let extent = if steep {
+            self.final_height
+        } else {
+            self.final_width
+        } as isize;
+        for major in (first + 1).max(0)..last.min(extent) {
+            let minor = y0 + gradient * (major as f32 - x0);
+            let row = minor.floor();
+            plot(self, major, row as isize, 1.0 - (minor - row));
+            plot(self, major, row as isize + 1, minor - row);
+        }
+    }
+
+    /// Blends `color` into the pixel at (`x`, `y`) on layer `z` by `coverage`, from `0.0` to
+    /// `1.0`, if it is on the canvas.
+    fn blend_pixel(&mut self, x: isize, y: isize, z: usize, color: Color, coverage: f32) {
+        if x < 0 || y < 0 {
+            return;
+        }
+        if self.is_clipped(x as usize, y as usize) {
+            return;
+        }
+        if let Some(index) = self.get_index(x as usize, y as usize, z) {
+            let alpha = (coverage.clamp(0.0, 1.0) * self.opacity as f32).round() as u8;
+            self.blend_into(index, color, alpha);
+        }
+    }
+
+    /// Draws `color` with the opacity `alpha` over the pixel at `index`, keeping what it covers
+    /// showing through.
+    fn blend_into(&mut self, index: usize, color: Color, alpha: u8) {
+        self.pixels[index] = TerminalPixel { color, alpha }.over(self.pixels[index]);
+    }
+
+    /// Draws the outline of the rectangle of `width` by `height` pixels with its top-left corner
+    /// at (`x`, `y`) on layer `z`. Parts outside the canvas are clipped.
+    pub fn draw_rect(
+        &mut self,
+        x: isize,
+        y: isize,
+        width: usize,
+        height: usize,
+        z: usize,
+        color: Color,
+    ) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (right, bottom) = (x + width as isize - 1, y + height as isize - 1);
+        let Some((dx, dy)) = self.pixel_offset() else {
+            let corners = self.transformed_corners(x, y, right, bottom);
+            for (index, &(x0, y0)) in corners.iter().enumerate() {
+                let (x1, y1) = corners[(index + 1) % corners.len()];
+                self.line(x0, y0, x1, y1, z, color);
+            }
+            return;
+        };
+        let (x, y, right, bottom) = (x + dx, y + dy, right + dx, bottom + dy);
+        self.fill_area(x, y, width, 1, z, color);
+        self.fill_area(x, bottom, width, 1, z, color);
+        self.fill_area(x, y, 1, height, z, color);
+        self.fill_area(right, y, 1, height, z, color);
+    }
+
+    /// Draws the outline of the rectangle of `width` by `height` pixels with its top-left corner
+    /// at (`x`, `y`) on layer `z` like [`Canvas::draw_rect`], drawing only the pixels `style`
+    /// draws, counted clockwise from the top-left corner.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_rect_styled(
+        &mut self,
+        x: isize,
+        y: isize,
+        width: usize,
+        height: usize,
+        style: &LineStyle,
+        z: usize,
+        color: Color,
+    ) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (right, bottom) = (x + width as isize - 1, y + height as isize - 1);
+        let Some((dx, dy)) = self.pixel_offset() else {
+            // Every side without its last pixel, which starts the next side.
+            let corners = self.transformed_corners(x, y, right, bottom);
+            let edges = self.drawable_edges();
+            let mut start = 0;
+            for (side, &(x0, y0)) in corners.iter().enumerate() {
+                let (x1, y1) = corners[(side + 1) % corners.len()];
+                let length = x0.abs_diff(x1).max(y0.abs_diff(y1));
+                line_points((x0, y0), (x1, y1), edges, |index, column, row| {
+                    if index < length && style.is_drawn(start + index) {
+                        self.plot(column, row, z, color);
+                    }
+                });
+                start += length;
+            }
+            return;
+        };
+        let (x, y, right, bottom) = (x + dx, y + dy, right + dx, bottom + dy);
+        let top_edge = (x..=right).map(|column| (column, y));
+        let right_edge = (y + 1..=bottom).map(|row| (right, row));
+        let bottom_edge = (x..right)
+            .rev()
+            .map(|column| (column, bottom))
+            .filter(|_| bottom > y);
+        let left_edge = (y + 1..bottom)
+            .rev()
+            .map(|row| (x, row))
+            .filter(|_| right > x);
+        let outline = top_edge
+            .chain(right_edge)
+            .chain(bottom_edge)
+            .chain(left_edge);
+        for (index, (column, row)) in outline.enumerate() {
+            if style.is_drawn(index) {
+                self.plot(column, row, z, color);
+            }
+        }
+    }
+
+    /// Fills the rectangle of `width` by `height` pixels with its top-left corner at (`x`, `y`)
+    /// on layer `z`, writing whole rows of the layer at once. Parts outside the canvas are
+    /// clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let blue = Color { r: 0, g: 0, b: 255 };
+    /// let mut canvas = Canvas::new(10, 5, Color { r: 0, g: 0, b: 0 });
+    /// canvas.fill_rect(-2, 1, 5, 2, 0, blue);
+    /// assert_eq!(canvas.composited_cell(2, 0).bottom_color, blue);
+    /// assert_eq!(canvas.composited_cell(3, 0).bottom_color, Color { r: 0, g: 0, b: 0 });
+    /// ```
+    pub fn fill_rect(
+        &mut self,
+        x: isize,
+        y: isize,
+        width: usize,
+        height: usize,
+        z: usize,
+        color: Color,
+    ) {
+        let Some((dx, dy)) = self.pixel_offset() else {
+            let (right, bottom) = ((x + width as isize) as f64, (y + height as isize) as f64);
+            let (x, y) = (x as f64, y as f64);
+            let corners = [(x, y), (right, y), (right, bottom), (x, bottom)];
+            let ring = corners.map(|corner| self.transformed(corner)).to_vec();
+            self.fill_rings(&[ring], z, color);
+            return;
+        };
+        self.fill_area(x + dx, y + dy, width, height, z, color);
+    }
+
+    /// Fills the rectangle of `width` by `height` pixels with its top-left corner at (`x`, `y`)
+    /// on layer `z`, ignoring the transform.
+    pub(crate) fn fill_area(
+        &mut self,
+        x: isize,
+        y: isize,
+        width: usize,
+        height: usize,
+        z: usize,
+        color: Color,
+    ) {
+        let (min_x, min_y, max_x, max_y) = self.drawable_edges();
+        let left = x.clamp(min_x, max_x) as usize;
+        let top = y.clamp(min_y, max_y) as usize;
+        let right = (x + width as isize).clamp(min_x, max_x) as usize;
+        let bottom = (y + height as isize).clamp(min_y, max_y) as usize;
+        if left >= right {
+            return;
+        }
+        for row in top..bottom {
+            let Some(start) = self.get_index(left, row, z) else {
+                return;
+            };
+            self.pixels[start..start + right - left].fill(TerminalPixel {
+                color,
+                alpha: self.opacity,
+            });
+        }
+    }
+
+    /// Draws the outline of the circle of `radius` pixels centered on (`x`, `y`) on layer `z`.
+    ///
+    /// The circle is stretched vertically by [`Canvas::pixel_aspect`], so it looks round on
+    /// screen. Parts outside the canvas are clipped, and only the part of the outline over the
+    /// canvas is worked out, so huge circles cost no more than small ones. Circles with a
+    /// radius over 2<sup>30</sup> pixels are not drawn.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+    /// // The left edge of a circle centered far right of the canvas crosses it.
+    /// canvas.draw_circle(100_005, 10, 100_000, 0, white);
+    /// assert_eq!(canvas.composited_cell(5, 5).top_color, white);
+    /// assert_eq!(canvas.composited_cell(6, 5).top_color, Color { r: 0, g: 0, b: 0 });
+    /// ```
+    pub fn draw_circle(&mut self, x: isize, y: isize, radius: usize, z: usize, color: Color) {
+        let vertical_radius = self.corrected_radius(radius);
+        self.draw_ellipse(x, y, radius, vertical_radius, z, color);
+    }
+
+    /// Fills the circle of `radius` pixels centered on (`x`, `y`) on layer `z`.
+    ///
+    /// The circle is stretched vertically by [`Canvas::pixel_aspect`], so it looks round on
+    /// screen. Parts outside the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let yellow = Color { r: 255, g: 255, b: 0 };
+    /// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+    /// canvas.fill_circle(10, 10, 6, 0, yellow);
+    /// assert_eq!(canvas.composited_cell(10, 5).top_color, yellow);
+    /// assert_eq!(canvas.composited_cell(4, 5).top_color, yellow);
+    /// assert_eq!(canvas.composited_cell(5, 2).top_color, Color { r: 0, g: 0, b: 0 });
+    /// ```
+    pub fn fill_circle(&mut self, x: isize, y: isize, radius: usize, z: usize, color: Color) {
+        let vertical_radius = self.corrected_radius(radius);
+        self.fill_ellipse(x, y, radius, vertical_radius, z, color);
+    }
+
+    /// Draws the outline of the ellipse centered on (`x`, `y`) on layer `z`, reaching
+    /// `radius_x` pixels left and right and `radius_y` pixels up and down, with the midpoint
+    /// ellipse algorithm. Parts outside the canvas are clipped; ellipses with a radius over
+    /// 2<sup>30</sup> pixels are not drawn.
+    pub fn draw_ellipse(
+        &mut self,
+        x: isize,
+        y: isize,
+        radius_x: usize,
+        radius_y: usize,
+        z: usize,
+        color: Color,
+    ) {
+        let Some((offset_x, offset_y)) = self.pixel_offset() else {
+            let outline = ellipse_path((x as f32, y as f32), radius_x as f32, radius_y as f32);
+            self.stroke_path(&outline, z, color);
+            return;
+        };
+        if radius_x.max(radius_y) > MAX_RADIUS {
+            return;
+        }
+        let (x, y) = (x + offset_x, y + offset_y);
+        let (left, top, right, bottom) = self.drawable_edges();
+        let (Some(columns), Some(rows)) = (
+            mirrored_offsets(x, left, right, radius_x),
+            mirrored_offsets(y, top, bottom, radius_y),
+        ) else {
+            return;
+        };
+        Quadrant::new(radius_x, radius_y).outline(columns, rows, |dx, dy| {
+            self.plot(x + dx, y + dy, z, color);
+            self.plot(x - dx, y + dy, z, color);
+            self.plot(x + dx, y - dy, z, color);
+            self.plot(x - dx, y - dy, z, color);
+        });
+    }
+
+    /// Fills the ellipse centered on (`x`, `y`) on layer `z`, reaching `radius_x` pixels left
+    /// and right and `radius_y` pixels up and down, with the midpoint ellipse algorithm. Parts
+    /// outside the canvas are clipped; ellipses with a radius over 2<sup>30</sup> pixels are
+    /// not drawn.
+    pub fn fill_ellipse(
+        &mut self,
+        x: isize,
+        y: isize,
+        radius_x: usize,
+        radius_y: usize,
+        z: usize,
+        color: Color,
+    ) {
+        let Some((offset_x, offset_y)) = self.pixel_offset() else {
+            // The pixels are filled out to their far edges.
+            let center = (x as f32 + 0.5, y as f32 + 0.5);
+            let area = ellipse_path(center, radius_x as f32 + 0.5, radius_y as f32 + 0.5);
+            self.fill_path(&area, z, color);
+            return;
+        };
+        if radius_x.max(radius_y) > MAX_RADIUS {
+            return;
+        }
+        let (x, y) = (x + offset_x, y + offset_y);
+        let (_, top, _, bottom) = self.drawable_edges();
+        let Some((first_row, last_row)) = mirrored_offsets(y, top, bottom, radius_y) else {
+            return;
+        };
+        let quadrant = Quadrant::new(radius_x, radius_y);
+        for dy in first_row..=last_row {
+            let (dx, dy) = (quadrant.width_at(dy) as isize, dy as isize);
+            let span = 2 * dx as usize + 1;
+            self.fill_area(x - dx, y + dy, span, 1, z, color);
+            self.fill_area(x - dx, y - dy, span, 1, z, color);
+        }
+    }
+
+    /// Draws the arc of the circle of `radius` pixels centered on (`x`, `y`) from `start_angle`
+    /// to `end_angle` on layer `z`, as one connected line, for gauges, pie charts and loading
+    /// spinners. Angles are in radians, clockwise on screen from the right; an `end_angle`
+    /// below `start_angle` draws the arc counterclockwise, and arcs are no longer than a full
+    /// circle.
+    ///
+    /// Like [`Canvas::draw_circle`], the arc is stretched vertically by
+    /// [`Canvas::pixel_aspect`]. Parts outside the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    /// use std::f32::consts::PI;
+    ///
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let mut canvas = Canvas::new(30, 15, Color { r: 0, g: 0, b: 0 });
+    /// // A gauge three quarters full, from the left through the top.
+    /// canvas.draw_arc(15, 15, 10, PI, PI * 2.0, 0, red);
+    /// assert_eq!(canvas.composited_cell(15, 2).bottom_color, red);
+    /// assert_eq!(canvas.composited_cell(15, 12).bottom_color, Color { r: 0, g: 0, b: 0 });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_arc(
+        &mut self,
+        x: isize,
+        y: isize,
+        radius: usize,
+        start_angle: f32,
+        end_angle: f32,
+        z: usize,
+        color: Color,
+    ) {
+        let sweep = ((end_angle - start_angle) as f64).clamp(-TAU, TAU);
+        let (radius_x, radius_y) = (radius as f64, self.corrected_radius(radius) as f64);
+        // Enough points to keep every chord within a quarter of a pixel of the arc.
+        let reach = radius_x.max(radius_y) * self.length_scale();
+        let step = if reach > 0.25 {
+            2.0 * (1.0 - 0.25 / reach).acos()
+        } else {
+            TAU
+        };
+        let count = (sweep.abs() / step).ceil().max(1.0) as usize;
+        let points: Vec<(isize, isize)> = (0..=count)
+            .map(|index| {
+                let angle = start_angle as f64 + sweep * index as f64 / count as f64;
+                let point = (
+                    x as f64 + radius_x * angle.cos(),
+                    y as f64 + radius_y * angle.sin(),
+                );
+                let (x, y) = self.transformed(point);
+                (x.round() as isize, y.round() as isize)
+            })
+            .collect();
+        self.polyline(&points, z, color);
+    }
+
+    /// Fills the polygon with corners `points`, in order, on layer `z`, with a scanline
+    /// algorithm. The last corner joins the first.
+    ///
+    /// Polygons may be concave or cross themselves; where they do, the even-odd rule decides
+    /// which parts are inside. Pixels are filled when their center is inside the polygon, and
+    /// parts outside the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let green = Color { r: 0, g: 200, b: 0 };
+    /// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+    /// // An arrow pointing right.
+    /// let arrow = [(0, 6), (10, 6), (10, 0), (19, 10), (10, 20), (10, 14), (0, 14)];
+    /// canvas.fill_polygon(&arrow, 0, green);
+    /// assert_eq!(canvas.composited_cell(2, 5).top_color, green);
+    /// assert_eq!(canvas.composited_cell(2, 1).top_color, Color { r: 0, g: 0, b: 0 });
+    /// ```
+    pub fn fill_polygon(&mut self, points: &[(isize, isize)], z: usize, color: Color) {
+        let ring: Vec<(f64, f64)> = points
+            .iter()
+            .map(|&corner| self.transformed(point(corner)))
+            .collect();
+        self.fill_rings(&[ring], z, color);
+    }
+
+    /// Fills the polygons with corners `rings` on layer `z` together, by the even-odd rule, so
+    /// polygons inside others cut holes into them, ignoring the transform.
+    pub(crate) fn fill_rings(&mut self, rings: &[Vec<(f64, f64)>], z: usize, color: Color) {
+        let rings: Vec<&Vec<(f64, f64)>> = rings.iter().filter(|ring| ring.len() >= 3).collect();
+        let ys = rings.iter().flat_map(|ring| ring.iter().map(|(_, y)| *y));
+        let (top, bottom) = ys.fold((f64::INFINITY, f64::NEG_INFINITY), |(top, bottom), y| {
+            (top.min(y), bottom.max(y))
+        });
+        if top > bottom {
+            return;
+        }
+        let top = (top.floor() as isize).max(0);
+        let bottom = (bottom.ceil() as isize).min(self.final_height as isize - 1);
+        let mut crossings = Vec::new();
+        for y in top..=bottom {
+            let center = y as f64 + 0.5;
+            crossings.clear();
+            for ring in &rings {
+                for (index, &(x0, y0)) in ring.iter().enumerate() {
+                    let (x1, y1) = ring[(index + 1) % ring.len()];
+                    if (y0 <= center) != (y1 <= center) {
+                        let along = (center - y0) / (y1 - y0);
+                        crossings.push(x0 + along * (x1 - x0));
+                    }
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                // The pixels whose centers lie between the two crossings.
+                let left = (span[0] - 0.5).ceil() as isize;
+                let right = (span[1] - 0.5).ceil() as isize;
+                if right > left {
+                    self.fill_area(left, y, (right - left) as usize, 1, z, color);
+                }
+            }
+        }
+    }
+
+    /// Fills the triangle with corners `vertices`, each a position and a color, on layer `z`,
+    /// shading every pixel with the colors of the corners mixed by how close its center is to
+    /// them, in barycentric coordinates. Corners may be given in either order and between
+    /// pixels; pixels are filled when their center is inside the triangle, and parts outside
+    /// the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let green = Color { r: 0, g: 255, b: 0 };
+    /// let blue = Color { r: 0, g: 0, b: 255 };
+    /// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+    /// canvas.fill_triangle([(0.0, 0.0, red), (40.0, 0.0, green), (0.0, 40.0, blue)], 0);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color { r: 249, g: 3, b: 3 });
+    /// assert_eq!(canvas.composited_cell(20, 0).top_color, Color { r: 121, g: 131, b: 3 });
+    /// ```
+    pub fn fill_triangle(&mut self, vertices: [(f32, f32, Color); 3], z: usize) {
+        let [a, b, c] = vertices.map(|(x, y, color)| {
+            let (x, y) = self.transformed((x as f64, y as f64));
+            (x, y, color)
+        });
+        let edge = |p: (f64, f64, Color), q: (f64, f64, Color), x: f64, y: f64| {
+            (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0)
+        };
+        let area = edge(a, b, c.0, c.1);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        let (min_x, min_y, max_x, max_y) = self.drawable_edges();
+        let left = (a.0.min(b.0).min(c.0).floor() as isize).max(min_x);
+        let top = (a.1.min(b.1).min(c.1).floor() as isize).max(min_y);
+        let right = (a.0.max(b.0).max(c.0).ceil() as isize).min(max_x);
+        let bottom = (a.1.max(b.1).max(c.1).ceil() as isize).min(max_y);
+        let mix = |weights: [f64; 3], channel: fn(Color) -> u8| {
+            let value = weights[0] * channel(a.2) as f64
+                + weights[1] * channel(b.2) as f64
+                + weights[2] * channel(c.2) as f64;
+            value.round().clamp(0.0, 255.0) as u8
+        };
+        for y in top..bottom {
+            for x in left..right {
+                let (center_x, center_y) = (x as f64 + 0.5, y as f64 + 0.5);
+                let weight_a = edge(b, c, center_x, center_y) / area;
+                let weight_b = edge(c, a, center_x, center_y) / area;
+                let weight_c = 1.0 - weight_a - weight_b;
+                if weight_a < 0.0 || weight_b < 0.0 || weight_c < 0.0 {
+                    continue;
+                }
+                let weights = [weight_a, weight_b, weight_c];
+                let color = Color {
+                    r: mix(weights, |color| color.r),
+                    g: mix(weights, |color| color.g),
+                    b: mix(weights, |color| color.b),
+                };
+                self.plot(x, y, z, color);
+            }
+        }
+    }
+
+    /// Draws the quadratic Bézier curve from `start` to `end`, pulled towards `control`, on
+    /// layer `z`. The curve is split until its pieces are straight to within a quarter of a
+    /// pixel and the pieces are drawn as lines, so it has no gaps. Parts outside the canvas are
+    /// clipped.
+    pub fn draw_bezier_quad(
+        &mut self,
+        start: (isize, isize),
+        control: (isize, isize),
+        end: (isize, isize),
+        z: usize,
+        color: Color,
+    ) {
+        // The same curve as a cubic one, with both controls two thirds of the way to `control`.
+        let towards = |from: (isize, isize)| {
+            (
+                from.0 as f64 + (control.0 - from.0) as f64 * 2.0 / 3.0,
+                from.1 as f64 + (control.1 - from.1) as f64 * 2.0 / 3.0,
+            )
+        };
+        let curve = [point(start), towards(start), towards(end), point(end)];
+        self.draw_bezier(curve, z, color);
+    }
+
+    /// Draws the cubic Bézier curve from `start` to `end`, leaving `start` towards `control1`
+    /// and arriving at `end` from `control2`, on layer `z`. The curve is split until its pieces
+    /// are straight to within a quarter of a pixel and the pieces are drawn as lines, so it has
+    /// no gaps. Parts outside the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let mut canvas = Canvas::new(40, 10, Color { r: 0, g: 0, b: 0 });
+    /// canvas.draw_bezier_cubic((0, 19), (10, -10), (30, 30), (39, 0), 0, white);
+    /// assert_eq!(canvas.composited_cell(0, 9).bottom_color, white);
+    /// assert_eq!(canvas.composited_cell(39, 0).top_color, white);
+    /// ```
+    pub fn draw_bezier_cubic(
+        &mut self,
+        start: (isize, isize),
+        control1: (isize, isize),
+        control2: (isize, isize),
+        end: (isize, isize),
+        z: usize,
+        color: Color,
+    ) {
+        let curve = [point(start), point(control1), point(control2), point(end)];
+        self.draw_bezier(curve, z, color);
+    }
+
+    /// Draws the cubic Bézier `curve`, given by its start, controls and end, as lines.
+    fn draw_bezier(&mut self, curve: [(f64, f64); 4], z: usize, color: Color) {
+        let curve = curve.map(|point| self.transformed(point));
+        let mut points = vec![curve[0]];
+        flatten(curve, 0, &mut points);
+        let round = |(x, y): (f64, f64)| (x.round() as isize, y.round() as isize);
+        let points: Vec<(isize, isize)> = points.into_iter().map(round).collect();
+        self.polyline(&points, z, color);
+    }
+
+    /// Fills the area of layer `z` around the pixel at (`x`, `y`) with `color`: the pixels of
+    /// the same color as it, reached through their left, right, top and bottom neighbors, up to
+    /// pixels of other colors on the layer or the edges of the canvas. Other layers are left
+    /// untouched.
+    ///
+    /// The fill runs along whole rows with a stack of its own, so filling large areas takes
+    /// little memory and never overflows the call stack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+    /// canvas.draw_rect(2, 2, 10, 10, 0, white);
+    /// canvas.flood_fill(5, 5, 0, red);
+    /// assert_eq!(canvas.composited_cell(5, 3).top_color, red);
+    /// assert_eq!(canvas.composited_cell(15, 3).top_color, Color { r: 0, g: 0, b: 0 });
+    /// ```
+    pub fn flood_fill(&mut self, x: usize, y: usize, z: usize, color: Color) {
+        let (x, y) = self.transformed_pixel(x as isize, y as isize);
+        if x < 0 || y < 0 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        if self.is_clipped(x, y) {
+            return;
+        }
+        let Some(start) = self.get_index(x, y, z) else {
+            return;
+        };
+        let target = self.pixels[start];
+        if same_pixel(
+            target,
+            TerminalPixel {
+                color,
+                alpha: self.opacity,
+            },
+        ) {
+            return;
+        }
+        let (width, height) = (self.final_width, self.final_height);
+        let matches = |canvas: &Canvas, x: usize, y: usize| {
+            !canvas.is_clipped(x, y)
+                && canvas
+                    .get_index(x, y, z)
+                    .is_some_and(|index| same_pixel(canvas.pixels[index], target))
+        };
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if !matches(self, x, y) {
+                continue;
+            }
+            let mut left = x;
+            while left > 0 && matches(self, left - 1, y) {
+                left -= 1;
+            }
+            let mut right = x;
+            while right + 1 < width && matches(self, right + 1, y) {
+                right += 1;
+            }
+            self.fill_area(left as isize, y as isize, right - left + 1, 1, z, color);
+            // Seed every run of matching pixels in the rows above and below.
+            for row in [y.checked_sub(1), (y + 1 < height).then_some(y + 1)]
+                .into_iter()
+                .flatten()
+            {
+                let mut in_run = false;
+                for column in left..=right {
+                    let inside = matches(self, column, row);
+                    if inside && !in_run {
+                        stack.push((column, row));
+                    }
+                    in_run = inside;
+                }
+            }
+        }
+    }
+
+    /// Sets every pixel of layer `z` to the color `shader` returns for its coordinates, like a
+    /// fragment shader, for plasma effects, procedural textures and gradients. Pixels outside
+    /// the clip rectangle are left untouched, and the transform is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(32, 8, Color { r: 0, g: 0, b: 0 });
+    /// // A horizontal gradient from black to red.
+    /// canvas.fill_with(0, |x, _| Color { r: (x * 8) as u8, g: 0, b: 0 });
+    /// assert_eq!(canvas.composited_cell(16, 3).top_color, Color { r: 128, g: 0, b: 0 });
+    /// ```
+    pub fn fill_with(&mut self, z: usize, shader: impl FnMut(usize, usize) -> Color) {
+        let area = Rect::new(0, 0, self.final_width, self.final_height);
+        self.fill_rect_with(area, z, shader);
+    }
+
+    /// Sets every pixel of `area` on layer `z` to the color `shader` returns for its
+    /// coordinates, like [`Canvas::fill_with`]. The coordinates are those of the canvas, not
+    /// relative to `area`.
+    pub fn fill_rect_with(
+        &mut self,
+        area: Rect,
+        z: usize,
+        mut shader: impl FnMut(usize, usize) -> Color,
+    ) {
+        let (min_x, min_y, max_x, max_y) = self.drawable_edges();
+        let left = area.x.clamp(min_x as usize, max_x as usize);
+        let top = area.y.clamp(min_y as usize, max_y as usize);
+        let right = area
+            .x
+            .saturating_add(area.width)
+            .clamp(left, max_x as usize);
+        let bottom = area
+            .y
+            .saturating_add(area.height)
+            .clamp(top, max_y as usize);
+        for y in top..bottom {
+            let Some(start) = self.get_index(left, y, z) else {
+                return;
+            };
+            let alpha = self.opacity;
+            for (x, pixel) in (left..right).zip(&mut self.pixels[start..start + right - left]) {
+                *pixel = TerminalPixel {
+                    color: shader(x, y),
+                    alpha,
+                };
+            }
+        }
+    }
+
+    /// Copies the pixels of `source` on layer `z` so its top-left corner lands on the pixel at
+    /// (`x`, `y`), such as to scroll a ticker or smear the screen. The source and destination
+    /// may overlap: the pixels are copied as they were before the copy. Parts of either outside
+    /// the canvas, and destination pixels outside the clip rectangle, are skipped; the
+    /// transform is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color, Rect};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let mut canvas = Canvas::new(20, 2, Color { r: 0, g: 0, b: 0 });
+    /// canvas.fill_rect(5, 0, 2, 4, 0, white);
+    /// // Scroll the ticker one pixel left.
+    /// canvas.copy_region(Rect::new(1, 0, 19, 4), 0, 0, 0);
+    /// assert_eq!(canvas.composited_cell(4, 0).top_color, white);
+    /// assert_eq!(canvas.composited_cell(6, 0).top_color, Color { r: 0, g: 0, b: 0 });
+    ///
+    /// // A source reaching past the canvas copies the part on it.
+    /// canvas.copy_region(Rect::new(4, 0, usize::MAX, usize::MAX), 2, 0, 0);
+    /// assert_eq!(canvas.composited_cell(2, 0).top_color, white);
+    /// ```
+    pub fn copy_region(&mut self, source: Rect, x: isize, y: isize, z: usize) {
+        if z >= self.max_z_layers || source.x >= self.final_width || source.y >= self.final_height {
+            return;
+        }
+        let (min_x, min_y, max_x, max_y) = self.drawable_edges();
+        let shift_x = x.saturating_sub(source.x as isize);
+        let shift_y = y.saturating_sub(source.y as isize);
+        let source_right = source.x.saturating_add(source.width).min(self.final_width) as isize;
+        let source_bottom = source
+            .y
+            .saturating_add(source.height)
+            .min(self.final_height) as isize;
+        let left = (source.x as isize).saturating_add(shift_x).max(min_x);
+        let top = (source.y as isize).saturating_add(shift_y).max(min_y);
+        let right = source_right.saturating_add(shift_x).min(max_x);
+        let bottom = source_bottom.saturating_add(shift_y).min(max_y);
+        if left >= right || top >= bottom {
+            return;
+        }
+        let length = (right - left) as usize;
+        for step in 0..bottom - top {
+            // Copy from the bottom up when moving down, so rows are read before they are
+            // overwritten.
+            let row = if shift_y > 0 {
+                bottom - 1 - step
+            } else {
+                top + step
+            };
+            let from = self.get_index((left - shift_x) as usize, (row - shift_y) as usize, z);
+            let to = self.get_index(left as usize, row as usize, z);
+            if let (Some(from), Some(to)) = (from, to) {
+                self.pixels.copy_within(from..from + length, to);
+            }
+        }
+    }
+
+    /// Returns the left, top, right and bottom edges of the pixels drawing may change: the
+    /// canvas, or the part of it inside the clip rectangle.
+    fn drawable_edges(&self) -> (isize, isize, isize, isize) {
+        let (width, height) = (self.final_width, self.final_height);
+        match self.clip_rect() {
+            Some(clip) => (
+                clip.x.min(width) as isize,
+                clip.y.min(height) as isize,
+                clip.x.saturating_add(clip.width).min(width) as isize,
+                clip.y.saturating_add(clip.height).min(height) as isize,
+            ),
+            None => (0, 0, width as isize, height as isize),
+        }
+    }
+
+    /// Returns the pixels the transform takes the corners of the rectangle from (`left`, `top`)
+    /// to (`right`, `bottom`) to, clockwise from the top-left one.
+    fn transformed_corners(
+        &self,
+        left: isize,
+        top: isize,
+        right: isize,
+        bottom: isize,
+    ) -> [(isize, isize); 4] {
+        [(left, top), (right, top), (right, bottom), (left, bottom)]
+            .map(|(x, y)| self.transformed_pixel(x, y))
+    }
+
+    /// Returns the vertical radius of a circle of `radius` pixels across, corrected for the
+    /// pixel aspect.
+    fn corrected_radius(&self, radius: usize) -> usize {
+        (radius as f32 * self.pixel_aspect + 0.5).max(0.0) as usize
+    }
+
+    /// Sets the pixel at (`x`, `y`) on layer `z`, if it is on the canvas.
+    fn plot(&mut self, x: isize, y: isize, z: usize, color: Color) {
+        if x >= 0 && y >= 0 {
+            self.set_pixel(x as usize, y as usize, z, color);
+        }
+    }
+}
+
+/// The largest radius, in pixels, of the ellipses drawn with the midpoint ellipse algorithm,
+/// past which its arithmetic would overflow. Larger ellipses are not drawn.
+const MAX_RADIUS: usize = 1 << 30;
+
+/// The quarter of the outline of an ellipse from its top going right and down, as the midpoint
+/// ellipse algorithm draws it, with the point of any column or row worked out directly, so only
+/// the visible ones are visited.
+///
+/// The outline has one point per column from the top while it is flatter than 45 degrees, up to
+/// its `corner`, then one point per row from the corner down to the right end.
+struct Quadrant {
+    radius_x: u128,
+    radius_y: u128,
+    corner: (u128, u128),
+}
+
+impl Quadrant {
+    /// Returns the quarter of the ellipse with radii `radius_x` and `radius_y`, at most
+    /// [`MAX_RADIUS`].
+    fn new(radius_x: usize, radius_y: usize) -> Self {
+        let (radius_x, radius_y) = (radius_x as u128, radius_y as u128);
+        let mut quadrant = Self {
+            radius_x,
+            radius_y,
+            corner: (radius_x, 0),
+        };
+        if radius_y == 0 {
+            return quadrant;
+        }
+        let (rx2, ry2) = (radius_x * radius_x, radius_y * radius_y);
+        let x = first_step(0, radius_x, |x| ry2 * x >= rx2 * quadrant.row_at(x));
+        let y = match x {
+            0 => radius_y,
+            _ => {
+                // The row of the corner is the one stepped to from the column before it.
+                let y = quadrant.row_at(x - 1);
+                let below = 4 * ry2 * x * x + rx2 * (2 * y).abs_diff(1).pow(2) >= 4 * rx2 * ry2;
+                y - u128::from(below)
+            }
+        };
+        quadrant.corner = (x, y);
+        quadrant
+    }
+
+    /// Returns the row of the point in column `x`, left of the corner: the lowest whose upper
+    /// neighbor lies outside the ellipse, halfway between the rows.
+    fn row_at(&self, x: u128) -> u128 {
+        let (rx2, ry2) = (self.radius_x * self.radius_x, self.radius_y * self.radius_y);
+        let room = 4 * ry2 * (rx2 - x * x);
+        if room == 0 {
+            return 0;
+        }
+        ((room - 1) / rx2).isqrt().div_ceil(2)
+    }
+
+    /// Returns the column of the point in row `y`, at or below the corner: the first, from the
+    /// corner on, whose right neighbor lies outside the ellipse, halfway between the columns.
+    fn column_at(&self, y: u128) -> u128 {
+        let (corner_x, corner_y) = self.corner;
+        if y == corner_y {
+            return corner_x;
+        }
+        let (rx2, ry2) = (self.radius_x * self.radius_x, self.radius_y * self.radius_y);
+        let across = (4 * rx2 * (ry2 - y * y) / ry2).isqrt();
+        corner_x.max(across.div_ceil(2))
+    }
+
+    /// Returns the column of the rightmost point in row `y`, for `y` up to the vertical radius.
+    fn width_at(&self, y: u128) -> u128 {
+        let (corner_x, corner_y) = self.corner;
+        if y <= corner_y {
+            return self.column_at(y);
+        }
+        first_step(0, corner_x, |x| self.row_at(x) < y) - 1
+    }
+
+    /// Calls `visit` with the offsets from the center of the points of the outline, skipping
+    /// only points outside the columns `columns` or the rows `rows`, inclusive ranges of
+    /// offsets.
+    fn outline(
+        &self,
+        (first_column, last_column): (u128, u128),
+        (first_row, last_row): (u128, u128),
+        mut visit: impl FnMut(isize, isize),
+    ) {
+        let (corner_x, corner_y) = self.corner;
+        for x in first_column..last_column.saturating_add(1).min(corner_x) {
+            visit(x as isize, self.row_at(x) as isize);
+        }
+        for y in (first_row..=last_row.min(corner_y)).rev() {
+            visit(self.column_at(y) as isize, y as isize);
+        }
+    }
+}
+
+/// Returns the first and last of the offsets `0` to `radius`, included, that put `center`,
+/// moved either way, from `low`, included, to `high`, excluded, if any.
+fn mirrored_offsets(center: isize, low: isize, high: isize, radius: usize) -> Option<(u128, u128)> {
+    let (center, low, high) = (center as i128, low as i128, high as i128 - 1);
+    let (first, last) = if center < low {
+        (low - center, high - center)
+    } else if center > high {
+        (center - high, center - low)
+    } else {
+        (0, (high - center).max(center - low))
+    };
+    let last = last.min(radius as i128);
+    (low <= high && first <= last).then_some((first as u128, last as u128))
+}
+
+/// Returns the ellipse centered on `center` with radii `radius_x` and `radius_y` as a path of
+/// four cubic Bézier curves, one per quarter, for drawing it through a transform.
+fn ellipse_path(center: (f32, f32), radius_x: f32, radius_y: f32) -> Path {
+    // How far along the tangents the controls of a quarter circle lie.
+    const KAPPA: f32 = 0.552_284_8;
+    let (x, y) = center;
+    let (kx, ky) = (radius_x * KAPPA, radius_y * KAPPA);
+    Path::new()
+        .move_to(x + radius_x, y)
+        .curve_to(x + radius_x, y + ky, x + kx, y + radius_y, x, y + radius_y)
+        .curve_to(x - kx, y + radius_y, x - radius_x, y + ky, x - radius_x, y)
+        .curve_to(x - radius_x, y - ky, x - kx, y - radius_y, x, y - radius_y)
+        .curve_to(x + kx, y - radius_y, x + radius_x, y - ky, x + radius_x, y)
+        .close()
+}
+
+/// Returns `point` with floating-point coordinates.
+fn point((x, y): (isize, isize)) -> (f64, f64) {
+    (x as f64, y as f64)
+}
+
+/// Appends the ends of the straight pieces of the cubic Bézier `curve` to `points`, after its
+/// start, splitting it in halves with de Casteljau's algorithm until its controls are within a
+/// quarter of a pixel of the line between its ends.
+pub(crate) fn flatten(curve: [(f64, f64); 4], depth: usize, points: &mut Vec<(f64, f64)>) {
+    const TOLERANCE: f64 = 0.25;
+    const MAX_DEPTH: usize = 16;
+    let [start, control1, control2, end] = curve;
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    let distance = |(x, y): (f64, f64)| {
+        if length < f64::EPSILON {
+            ((x - start.0).powi(2) + (y - start.1).powi(2)).sqrt()
+        } else {
+            ((x - start.0) * dy - (y - start.1) * dx).abs() / length
+        }
+    };
+    if depth >= MAX_DEPTH || distance(control1).max(distance(control2)) <= TOLERANCE {
+        points.push(end);
+        return;
+    }
+    let middle = |a: (f64, f64), b: (f64, f64)| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let (ab, bc, cd) = (
+        middle(start, control1),
+        middle(control1, control2),
+        middle(control2, end),
+    );
+    let (abc, bcd) = (middle(ab, bc), middle(bc, cd));
+    let center = middle(abc, bcd);
+    flatten([start, ab, abc, center], depth + 1, points);
+    flatten([center, bcd, cd, end], depth + 1, points);
+}
+
+/// Calls `visit` with the index along the line, counted from (`x0`, `y0`), and the position of
+/// every point of the line from (`x0`, `y0`) to (`x1`, `y1`), both ends included, that lies
+/// inside `edges`: the left, top, right and bottom edges of an area, right and bottom excluded.
+///
+/// The points are those of Bresenham's algorithm, which rounds the minor axis half up at every
+/// step of the major one. The line is clipped to `edges` before stepping, so points outside are
+/// skipped without being walked: a line reaching far past the canvas costs no more than its
+/// visible part.
+fn line_points(
+    (x0, y0): (isize, isize),
+    (x1, y1): (isize, isize),
+    edges: (isize, isize, isize, isize),
+    mut visit: impl FnMut(usize, isize, isize),
+) {
+    let (left, top, right, bottom) = (
+        edges.0 as i128,
+        edges.1 as i128,
+        edges.2 as i128,
+        edges.3 as i128,
+    );
+    let (x0, y0, x1, y1) = (x0 as i128, y0 as i128, x1 as i128, y1 as i128);
+    let (dx, dy) = ((x1 - x0).unsigned_abs(), (y1 - y0).unsigned_abs());
+    // Every step moves one pixel along the major axis, and the minor axis by the rounded share
+    // of `rise` over `steps`.
+    let (steps, rise, major, minor) = if dx >= dy {
+        (
+            dx,
+            dy,
+            (x0, (x1 - x0).signum(), left, right),
+            (y0, (y1 - y0).signum(), top, bottom),
+        )
+    } else {
+        (
+            dy,
+            dx,
+            (y0, (y1 - y0).signum(), top, bottom),
+            (x0, (x1 - x0).signum(), left, right),
+        )
+    };
+    let (Some((first, last)), Some((minor_first, minor_last))) =
+        (offsets_within(major, steps), offsets_within(minor, rise))
+    else {
+        return;
+    };
+    let minor_offset = |step: u128| match steps {
+        0 => 0,
+        _ => {
+            let share = step * rise;
+            share / steps + u128::from(2 * (share % steps) >= steps)
+        }
+    };
+    // The minor offset never decreases along the line, so the steps inside the area along the
+    // minor axis are found by bisection.
+    let first = first_step(first, last + 1, |step| minor_offset(step) >= minor_first);
+    let end = first_step(first, last + 1, |step| minor_offset(step) > minor_last);
+    let position = |start: i128, sign: i128, offset: u128| (start + sign * offset as i128) as isize;
+    let (mut share, mut remainder) = match steps {
+        0 => (0, 0),
+        _ => (first * rise / steps, first * rise % steps),
+    };
+    for step in first..end {
+        let offset = share + u128::from(steps > 0 && 2 * remainder >= steps);
+        let (along, across) = (
+            position(major.0, major.1, step),
+            position(minor.0, minor.1, offset),
+        );
+        let (x, y) = if dx >= dy {
+            (along, across)
+        } else {
+            (across, along)
+        };
+        visit(step as usize, x, y);
+        remainder += rise;
+        if remainder >= steps {
+            remainder -= steps;
+            share += 1;
+        }
+    }
+}
+
+/// Returns the first and last of the offsets `0` to `count`, included, that put `start`, moved
+/// by `sign` per offset, from `low`, included, to `high`, excluded, if any.
+fn offsets_within(
+    (start, sign, low, high): (i128, i128, i128, i128),
+    count: u128,
+) -> Option<(u128, u128)> {
+    let (first, last) = match sign {
+        0 if (low..high).contains(&start) => (0, count as i128),
+        0 => return None,
+        1 => (low - start, high - 1 - start),
+        _ => (start - (high - 1), start - low),
+    };
+    let (first, last) = (first.max(0), last.min(count as i128));
+    (first <= last).then_some((first as u128, last as u128))
+}
+
+/// Returns the first of the steps from `start` to `end`, excluded, for which `reached` holds,
+/// or `end` if none does, given that once it holds it holds for every later step.
+fn first_step(mut start: u128, mut end: u128, reached: impl Fn(u128) -> bool) -> u128 {
+    while start < end {
+        let middle = start + (end - start) / 2;
+        if reached(middle) {
+            end = middle;
+        } else {
+            start = middle + 1;
+        }
+    }
+    start
+}
+
+impl TerminalPixel {
+    /// Returns this pixel drawn over `below`, with the opacity of both.
+    fn over(self, below: TerminalPixel) -> TerminalPixel {
+        let (above, below_alpha) = (self.alpha as u32, below.alpha as u32);
+        // The opacity of the result, times 255.
+        let total = above * 255 + below_alpha * (255 - above);
+        if total == 0 {
+            return below;
+        }
+        TerminalPixel {
+            color: self
+                .color
+                .blend(below.color, ((above * 255 * 255 + total / 2) / total) as u8),
+            alpha: ((total + 127) / 255) as u8,
+        }
+    }
+}
+
+/// Returns whether the pixels `a` and `b` look the same, every transparent pixel alike.
+fn same_pixel(a: TerminalPixel, b: TerminalPixel) -> bool {
+    a == b || (a.is_transparent() && b.is_transparent())
+}
@@ -0,0 +1,328 @@
+//! A minimal entity-component store for organizing game state.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A handle to an entity of a [`World`].
+///
+/// Handles carry a generation, so a handle to a despawned entity never refers to a newer entity
+/// that reused its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    /// Returns the slot of the entity, which is reused after the entity is despawned.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns how many times the slot of the entity had been reused when it was spawned.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// The components of one type, indexed by entity slot.
+struct Column<T> {
+    values: Vec<Option<T>>,
+}
+
+/// A [`Column`] of any component type.
+trait Storage: Any {
+    fn remove_slot(&mut self, index: usize);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> Storage for Column<T> {
+    fn remove_slot(&mut self, index: usize) {
+        if let Some(value) = self.values.get_mut(index) {
+            *value = None;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A store of entities and their components.
+///
+/// Entities are plain handles, and components are values of any `'static` type attached to
+/// them, with at most one component of each type per entity. Queries iterate over the entities
+/// holding some component types, in slot order.
+///
+/// # Example
+///
+/// ```
+/// use rael::World;
+///
+/// struct Position(f32, f32);
+/// struct Velocity(f32, f32);
+///
+/// let mut world = World::new();
+/// let player = world.spawn();
+/// world.insert(player, Position(0.0, 0.0));
+/// world.insert(player, Velocity(1.0, 2.0));
+/// let rock = world.spawn();
+/// world.insert(rock, Position(5.0, 5.0));
+///
+/// for (_, position, velocity) in world.query2_mut::<Position, Velocity>() {
+///     position.0 += velocity.0;
+///     position.1 += velocity.1;
+/// }
+/// assert_eq!(world.get::<Position>(player).unwrap().1, 2.0);
+///
+/// world.despawn(rock);
+/// assert!(!world.is_alive(rock));
+/// ```
+#[derive(Default)]
+pub struct World {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    storages: HashMap<TypeId, Box<dyn Storage>>,
+}
+
+impl fmt::Debug for World {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("World")
+            .field("entities", &self.len())
+            .field("component_types", &self.storages.len())
+            .finish()
+    }
+}
+
+impl World {
+    /// Creates an empty world.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an entity with no components.
+    pub fn spawn(&mut self) -> Entity {
+        if let Some(index) = self.free.pop() {
+            self.alive[index as usize] = true;
+            return Entity {
+                index,
+                generation: self.generations[index as usize],
+            };
+        }
+        self.generations.push(0);
+        self.alive.push(true);
+        Entity {
+            index: (self.generations.len() - 1) as u32,
+            generation: 0,
+        }
+    }
+
+    /// Removes an entity and all its components. Returns `false` if it was already despawned.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        let index = entity.index as usize;
+        for storage in self.storages.values_mut() {
+            storage.remove_slot(index);
+        }
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.push(entity.index);
+        true
+    }
+
+    /// Returns `true` if the entity has been spawned and not despawned.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        index < self.alive.len()
+            && self.alive[index]
+            && self.generations[index] == entity.generation
+    }
+
+    /// Returns the number of living entities.
+    pub fn len(&self) -> usize {
+        self.alive.len() - self.free.len()
+    }
+
+    /// Returns `true` if there are no living entities.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the living entities.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        (0..self.alive.len())
+            .filter(|index| self.alive[*index])
+            .map(|index| self.entity_at(index))
+    }
+
+    /// Despawns every entity.
+    pub fn clear(&mut self) {
+        let entities: Vec<_> = self.entities().collect();
+        for entity in entities {
+            self.despawn(entity);
+        }
+    }
+
+    fn entity_at(&self, index: usize) -> Entity {
+        Entity {
+            index: index as u32,
+            generation: self.generations[index],
+        }
+    }
+
+    fn column<T: 'static>(&self) -> Option<&Column<T>> {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any().downcast_ref())
+    }
+
+    fn column_mut<T: 'static>(&mut self) -> Option<&mut Column<T>> {
+        self.storages
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any_mut().downcast_mut())
+    }
+
+    /// Attaches `component` to the entity, returning the component of the same type it replaces.
+    /// Does nothing and returns `None` if the entity is not alive.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        let column = self
+            .storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Column::<T> { values: Vec::new() }))
+            .as_any_mut()
+            .downcast_mut::<Column<T>>()
+            .expect("storages are keyed by their component type");
+        let index = entity.index as usize;
+        if column.values.len() <= index {
+            column.values.resize_with(index + 1, || None);
+        }
+        column.values[index].replace(component)
+    }
+
+    /// Detaches the component of type `T` from the entity and returns it.
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.column_mut::<T>()?
+            .values
+            .get_mut(entity.index as usize)?
+            .take()
+    }
+
+    /// Returns the component of type `T` of the entity.
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.column::<T>()?
+            .values
+            .get(entity.index as usize)?
+            .as_ref()
+    }
+
+    /// Returns the component of type `T` of the entity, for changing it.
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.column_mut::<T>()?
+            .values
+            .get_mut(entity.index as usize)?
+            .as_mut()
+    }
+
+    /// Returns `true` if the entity has a component of type `T`.
+    pub fn has<T: 'static>(&self, entity: Entity) -> bool {
+        self.get::<T>(entity).is_some()
+    }
+
+    /// Returns an iterator over the entities with a component of type `T`.
+    pub fn query<T: 'static>(&self) -> impl Iterator<Item = (Entity, &T)> + '_ {
+        let values = self.column::<T>().map(|column| column.values.as_slice());
+        values
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| Some((self.entity_at(index), value.as_ref()?)))
+    }
+
+    /// Returns an iterator over the entities with a component of type `T`, for changing it.
+    pub fn query_mut<T: 'static>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> + '_ {
+        let generations = &self.generations;
+        let values = self
+            .storages
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<Column<T>>())
+            .map(|column| column.values.as_mut_slice());
+        values
+            .unwrap_or_default()
+            .iter_mut()
+            .enumerate()
+            .filter_map(move |(index, value)| {
+                let entity = Entity {
+                    index: index as u32,
+                    generation: generations[index],
+                };
+                Some((entity, value.as_mut()?))
+            })
+    }
+
+    /// Returns an iterator over the entities with components of both types `A` and `B`.
+    pub fn query2<A: 'static, B: 'static>(&self) -> impl Iterator<Item = (Entity, &A, &B)> + '_ {
+        let b = self.column::<B>();
+        self.query::<A>().filter_map(move |(entity, a)| {
+            let b = b?.values.get(entity.index as usize)?.as_ref()?;
+            Some((entity, a, b))
+        })
+    }
+
+    /// Returns an iterator over the entities with components of both types `A` and `B`, for
+    /// changing the `A` component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` and `B` are the same type.
+    pub fn query2_mut<A: 'static, B: 'static>(
+        &mut self,
+    ) -> impl Iterator<Item = (Entity, &mut A, &B)> + '_ {
+        assert_ne!(
+            TypeId::of::<A>(),
+            TypeId::of::<B>(),
+            "query2_mut needs two different component types"
+        );
+        let generations = &self.generations;
+        let [a, b] = self
+            .storages
+            .get_disjoint_mut([&TypeId::of::<A>(), &TypeId::of::<B>()]);
+        let a = a
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<Column<A>>())
+            .map(|column| column.values.as_mut_slice());
+        let b = b
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<Column<B>>())
+            .map(|column| column.values.as_slice());
+        a.unwrap_or_default()
+            .iter_mut()
+            .zip(b.unwrap_or_default())
+            .enumerate()
+            .filter_map(move |(index, (a, b))| {
+                let entity = Entity {
+                    index: index as u32,
+                    generation: generations[index],
+                };
+                Some((entity, a.as_mut()?, b.as_ref()?))
+            })
+    }
+}
@@ -0,0 +1,412 @@
+//! Large banner text in [FIGlet](http://www.figlet.org) fonts, drawn into the pixel grid or
+//! placed on the text plane.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::Lines;
+
+use crate::{Canvas, Color};
+
+/// An error raised while loading a FIGlet font.
+#[derive(Debug)]
+pub enum FigletError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not a valid FIGlet font.
+    Invalid(String),
+}
+
+impl fmt::Display for FigletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FigletError::Io(error) => write!(f, "failed to read FIGlet font: {error}"),
+            FigletError::Invalid(message) => write!(f, "invalid FIGlet font: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for FigletError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FigletError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FigletError {
+    fn from(error: io::Error) -> Self {
+        FigletError::Io(error)
+    }
+}
+
+/// The characters every FIGlet font defines after the printable ASCII ones, in order.
+const DEUTSCH: [char; 7] = ['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'];
+
+/// How the characters of a banner are put next to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// Every character keeps its full width.
+    FullWidth,
+    /// Characters are moved together until they touch.
+    Fitted,
+    /// Characters are moved one column further, merging the touching characters by the rules,
+    /// a set of bits, or keeping the right one when there are no rules.
+    Smushed(u32),
+}
+
+/// The smushing rules, as bits of the layout of a font.
+const EQUAL: u32 = 1;
+const UNDERSCORE: u32 = 2;
+const HIERARCHY: u32 = 4;
+const OPPOSITE_PAIR: u32 = 8;
+const BIG_X: u32 = 16;
+const HARDBLANK: u32 = 32;
+
+/// A FIGlet font, parsed from a `.flf` file, for the large banner text of title screens, drawn
+/// with [`Canvas::draw_figlet_pixels`] or [`Canvas::put_figlet`].
+///
+/// Characters are put together as the font asks: at their full width, moved together until
+/// they touch, or smushed into each other by the rules of the font. Characters the font does
+/// not define are left out.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{Canvas, Color, FigletFont};
+///
+/// let font = FigletFont::load("assets/standard.flf")?;
+/// let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// canvas.put_figlet(&font, 4, 2, "Dungeon", Color { r: 255, g: 200, b: 0 }, None);
+/// # Ok::<(), rael::FigletError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FigletFont {
+    height: usize,
+    hardblank: char,
+    layout: Layout,
+    /// The rows of every character, all `height` long.
+    characters: HashMap<char, Vec<Vec<char>>>,
+}
+
+impl FigletFont {
+    /// Loads a FIGlet font from a `.flf` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FigletError> {
+        Self::from_flf(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a FIGlet font from the contents of a `.flf` file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::FigletFont;
+    ///
+    /// // A font of two lines with a single character, a box.
+    /// let flf = "flf2a$ 2 2 4 -1 0\n\
+    ///            \u{20}@\n\u{20}@@\n";
+    /// let mut font = String::from(flf);
+    /// for ch in 33..127u8 {
+    ///     let row = if ch == b'O' { "[]" } else { "" };
+    ///     font += &format!("{row}@\n{row}@@\n");
+    /// }
+    /// let font = FigletFont::from_flf(&font)?;
+    /// assert_eq!(font.render("OO"), ["[][]", "[][]"]);
+    /// # Ok::<(), rael::FigletError>(())
+    /// ```
+    pub fn from_flf(text: &str) -> Result<Self, FigletError> {
+        let invalid = |message: String| FigletError::Invalid(message);
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        let Some(signature) = header.strip_prefix("flf2a") else {
+            return Err(invalid("the file does not start with `flf2a`".into()));
+        };
+        let hardblank = signature
+            .chars()
+            .next()
+            .ok_or_else(|| invalid("the header has no hardblank".into()))?;
+        let fields: Vec<i64> = signature[hardblank.len_utf8()..]
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid(format!("invalid numbers in the header `{header}`")))?;
+        if fields.len() < 5 {
+            return Err(invalid(format!("missing numbers in the header `{header}`")));
+        }
+        let height = usize::try_from(fields[0])
+            .ok()
+            .filter(|&height| height > 0)
+            .ok_or_else(|| invalid(format!("invalid height {}", fields[0])))?;
+        let layout = match (fields.get(6), fields[3]) {
+            (Some(&full), _) if full & 128 != 0 => Layout::Smushed(full as u32 & 63),
+            (Some(&full), _) if full & 64 != 0 => Layout::Fitted,
+            (Some(_), _) | (None, -1) => Layout::FullWidth,
+            (None, 0) => Layout::Fitted,
+            (None, old) => Layout::Smushed(old as u32 & 63),
+        };
+        for _ in 0..fields[4].max(0) {
+            lines.next();
+        }
+
+        let mut characters = HashMap::new();
+        for ch in ' '..='~' {
+            let rows = read_character(&mut lines, height)?
+                .ok_or_else(|| invalid(format!("the font ends before `{ch}`")))?;
+            characters.insert(ch, rows);
+        }
+        for ch in DEUTSCH {
+            match read_character(&mut lines, height)? {
+                Some(rows) => characters.insert(ch, rows),
+                None => break,
+            };
+        }
+        // Then come characters tagged with their code, in decimal, octal or hexadecimal.
+        while let Some(tag) = lines.next() {
+            if tag.trim().is_empty() {
+                continue;
+            }
+            let code = tag.split_whitespace().next().unwrap_or_default();
+            let (digits, radix) = match code.trim_start_matches('-') {
+                hex if hex.starts_with("0x") || hex.starts_with("0X") => (&hex[2..], 16),
+                octal if octal.starts_with('0') && octal.len() > 1 => (&octal[1..], 8),
+                decimal => (decimal, 10),
+            };
+            let ch = u32::from_str_radix(digits, radix)
+                .map_err(|_| invalid(format!("invalid character code `{code}`")))?;
+            // Negative codes are for characters that no input produces.
+            let ch = (!code.starts_with('-'))
+                .then(|| char::from_u32(ch))
+                .flatten();
+            let Some(rows) = read_character(&mut lines, height)? else {
+                break;
+            };
+            if let Some(ch) = ch {
+                characters.insert(ch, rows);
+            }
+        }
+        Ok(FigletFont {
+            height,
+            hardblank,
+            layout,
+            characters,
+        })
+    }
+
+    /// Returns the height of a line of banner text in terminal rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the rows of `text` in the font, `'\n'` starting a new line of banner text below.
+    pub fn render(&self, text: &str) -> Vec<String> {
+        text.split('\n')
+            .flat_map(|line| self.render_line(line))
+            .map(|row| {
+                row.into_iter()
+                    .map(|ch| if ch == self.hardblank { ' ' } else { ch })
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Returns the rows of a line of banner text, with hardblanks left in.
+    fn render_line(&self, text: &str) -> Vec<Vec<char>> {
+        let mut rows = vec![Vec::new(); self.height];
+        let mut previous_width = 0;
+        for ch in text.chars() {
+            let Some(character) = self.characters.get(&ch) else {
+                continue;
+            };
+            let width = character[0].len();
+            let overlap = self.overlap(&rows, character, previous_width);
+            for (row, character_row) in rows.iter_mut().zip(character) {
+                let start = row.len() - overlap;
+                for (offset, &right) in character_row[..overlap].iter().enumerate() {
+                    let left = row[start + offset];
+                    row[start + offset] = self
+                        .smushed(left, right, previous_width, width)
+                        .unwrap_or(right);
+                }
+                row.extend_from_slice(&character_row[overlap..]);
+            }
+            previous_width = width;
+        }
+        rows
+    }
+
+    /// Returns how many columns `character` can be moved left over the end of `rows`.
+    fn overlap(&self, rows: &[Vec<char>], character: &[Vec<char>], previous_width: usize) -> usize {
+        if self.layout == Layout::FullWidth {
+            return 0;
+        }
+        let width = character[0].len();
+        let line_width = rows[0].len();
+        let mut overlap = width;
+        for (row, character_row) in rows.iter().zip(character) {
+            let first = character_row.iter().take_while(|&&ch| ch == ' ').count();
+            let last = row.iter().rposition(|&ch| ch != ' ');
+            let mut amount = first + line_width - last.map_or(0, |last| last + 1);
+            if let (Some(last), Some(&right)) = (last, character_row.get(first)) {
+                if self
+                    .smushed(row[last], right, previous_width, width)
+                    .is_some()
+                {
+                    amount += 1;
+                }
+            }
+            overlap = overlap.min(amount);
+        }
+        overlap.min(line_width)
+    }
+
+    /// Returns the character `left` and `right` merge into when they overlap, if they can.
+    fn smushed(&self, left: char, right: char, left_width: usize, width: usize) -> Option<char> {
+        if left == ' ' {
+            return Some(right);
+        }
+        if right == ' ' {
+            return Some(left);
+        }
+        let Layout::Smushed(rules) = self.layout else {
+            return None;
+        };
+        if left_width < 2 || width < 2 {
+            return None;
+        }
+        let hardblank = self.hardblank;
+        if rules == 0 {
+            return Some(if right == hardblank { left } else { right });
+        }
+        if left == hardblank || right == hardblank {
+            return (rules & HARDBLANK != 0 && left == right).then_some(left);
+        }
+        if rules & EQUAL != 0 && left == right {
+            return Some(left);
+        }
+        const BORDERS: &str = "|/\\[]{}()<>";
+        if rules & UNDERSCORE != 0 {
+            if left == '_' && BORDERS.contains(right) {
+                return Some(right);
+            }
+            if right == '_' && BORDERS.contains(left) {
+                return Some(left);
+            }
+        }
+        if rules & HIERARCHY != 0 {
+            const CLASSES: [&str; 6] = ["|", "/\\", "[]", "{}", "()", "<>"];
+            let class = |ch: char| CLASSES.iter().position(|class| class.contains(ch));
+            if let (Some(left_class), Some(right_class)) = (class(left), class(right)) {
+                if left_class != right_class {
+                    return Some(if left_class > right_class {
+                        left
+                    } else {
+                        right
+                    });
+                }
+            }
+        }
+        if rules & OPPOSITE_PAIR != 0
+            && matches!(
+                (left, right),
+                ('[', ']') | (']', '[') | ('{', '}') | ('}', '{') | ('(', ')') | (')', '(')
+            )
+        {
+            return Some('|');
+        }
+        if rules & BIG_X != 0 {
+            match (left, right) {
+                ('/', '\\') => return Some('|'),
+                ('\\', '/') => return Some('Y'),
+                ('>', '<') => return Some('X'),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Reads the `height` rows of the next character, padded with spaces to the same width, or
+/// returns `None` at the end of the font.
+fn read_character(
+    lines: &mut Lines<'_>,
+    height: usize,
+) -> Result<Option<Vec<Vec<char>>>, FigletError> {
+    let mut rows = Vec::with_capacity(height);
+    for _ in 0..height {
+        let Some(line) = lines.next() else {
+            return if rows.is_empty() {
+                Ok(None)
+            } else {
+                Err(FigletError::Invalid("a character ends early".into()))
+            };
+        };
+        // Rows end with one or more copies of an end mark.
+        let line = line.trim_end_matches(['\r', ' ']);
+        let row = match line.chars().last() {
+            Some(mark) => line.trim_end_matches(mark),
+            None => line,
+        };
+        rows.push(row.chars().collect::<Vec<char>>());
+    }
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for row in &mut rows {
+        row.resize(width, ' ');
+    }
+    Ok(Some(rows))
+}
+
+impl Canvas {
+    /// Draws `text` as a banner in `font` into the pixel grid, with its top-left corner at pixel
+    /// (`x`, `y`) on layer `z`: every character of the banner but spaces becomes a block of one
+    /// pixel across and two down, so the banner keeps the shape it has in a terminal while it
+    /// can be moved by single pixels. Blocks go through the transform, and parts outside the
+    /// canvas are clipped.
+    pub fn draw_figlet_pixels(
+        &mut self,
+        font: &FigletFont,
+        x: isize,
+        y: isize,
+        z: usize,
+        color: Color,
+        text: &str,
+    ) {
+        for (row, line) in font.render(text).iter().enumerate() {
+            for (column, ch) in line.chars().enumerate() {
+                if ch != ' ' {
+                    let (x, y) = (x + column as isize, y + row as isize * 2);
+                    self.fill_rect(x, y, 1, 2, z, color);
+                }
+            }
+        }
+    }
+
+    /// Places `text` as a banner in `font` on the text plane, with its top-left corner at the
+    /// terminal cell at (`column`, `row`), like [`Canvas::put_str`]. The spaces of the banner
+    /// are left out, so the graphics behind show between its strokes.
+    pub fn put_figlet(
+        &mut self,
+        font: &FigletFont,
+        column: usize,
+        row: usize,
+        text: &str,
+        foreground: Color,
+        background: Option<Color>,
+    ) {
+        for (offset, line) in font.render(text).iter().enumerate() {
+            let characters: Vec<char> = line.chars().collect();
+            let mut start = 0;
+            for run in characters.split(|&ch| ch == ' ') {
+                if !run.is_empty() {
+                    let run: String = run.iter().collect();
+                    self.put_str(column + start, row + offset, &run, foreground, background);
+                }
+                start += run.len() + 1;
+            }
+        }
+    }
+}
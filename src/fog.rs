@@ -0,0 +1,151 @@
+//! Fog of war: remembering which cells of a map have been seen.
+
+use crate::{Camera, Canvas, FieldOfView};
+
+/// What the player knows about a cell of a [`FogOfWar`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Visibility {
+    /// The cell has never been seen.
+    #[default]
+    Unseen,
+    /// The cell has been seen before but is not in view now.
+    Explored,
+    /// The cell is in view.
+    Visible,
+}
+
+/// Tracks which cells of a grid are unseen, explored or visible, and shades the canvas to match.
+///
+/// Unseen cells are drawn black and explored cells out of view are dimmed, using the darkening
+/// layer of the canvas (see [`Canvas::set_shade`]) so every z-layer below is affected.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Camera, Canvas, Color, FieldOfView, FogOfWar, FovAlgorithm, Visibility};
+///
+/// let mut fog = FogOfWar::new(40, 20);
+/// let fov = FieldOfView::compute(40, 20, (5, 5), Some(4), FovAlgorithm::Symmetric, |_, _| false);
+/// fog.update(&fov);
+/// assert_eq!(fog.get(5, 9), Visibility::Visible);
+/// assert_eq!(fog.get(20, 10), Visibility::Unseen);
+///
+/// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+/// // Draw the map, then cover it with the fog, with cells of 1x2 pixels.
+/// fog.draw(&mut canvas, &Camera::default(), 1, 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FogOfWar {
+    width: usize,
+    height: usize,
+    cells: Vec<Visibility>,
+    explored_brightness: u8,
+}
+
+impl FogOfWar {
+    /// Creates a fog of war covering a `width` by `height` grid, with every cell unseen.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Visibility::Unseen; width * height],
+            explored_brightness: 96,
+        }
+    }
+
+    /// Sets how bright explored cells out of view are drawn, from `0` (black) to `255` (not
+    /// dimmed). Defaults to `96`.
+    pub fn explored_brightness(mut self, brightness: u8) -> Self {
+        self.explored_brightness = brightness;
+        self
+    }
+
+    /// Returns the width of the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the visibility of the cell at (`x`, `y`). Cells outside the grid are unseen.
+    pub fn get(&self, x: usize, y: usize) -> Visibility {
+        if x >= self.width || y >= self.height {
+            return Visibility::Unseen;
+        }
+        self.cells[y * self.width + x]
+    }
+
+    /// Sets the visibility of the cell at (`x`, `y`). Out of bounds cells are ignored.
+    pub fn set(&mut self, x: usize, y: usize, visibility: Visibility) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = visibility;
+        }
+    }
+
+    /// Returns `true` if the cell at (`x`, `y`) has ever been seen.
+    pub fn is_explored(&self, x: usize, y: usize) -> bool {
+        self.get(x, y) != Visibility::Unseen
+    }
+
+    /// Returns `true` if the cell at (`x`, `y`) is in view.
+    pub fn is_visible(&self, x: usize, y: usize) -> bool {
+        self.get(x, y) == Visibility::Visible
+    }
+
+    /// Replaces the cells in view with those of `fov`. Cells that were visible and no longer
+    /// are become explored.
+    pub fn update(&mut self, fov: &FieldOfView) {
+        for cell in self.cells.iter_mut() {
+            if *cell == Visibility::Visible {
+                *cell = Visibility::Explored;
+            }
+        }
+        for (x, y) in fov.visible_cells() {
+            self.set(x, y, Visibility::Visible);
+        }
+    }
+
+    /// Marks every cell as explored, as with a magic map.
+    pub fn explore_all(&mut self) {
+        for cell in self.cells.iter_mut() {
+            if *cell == Visibility::Unseen {
+                *cell = Visibility::Explored;
+            }
+        }
+    }
+
+    /// Shades the canvas over the cells seen through `camera`, where every cell covers
+    /// `cell_width` by `cell_height` pixels. Unseen cells turn black and explored cells are
+    /// dimmed, while visible cells and the area outside the grid are left untouched.
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas,
+        camera: &Camera,
+        cell_width: usize,
+        cell_height: usize,
+    ) {
+        if cell_width == 0 || cell_height == 0 {
+            return;
+        }
+        for py in 0..canvas.final_height {
+            for px in 0..canvas.final_width {
+                let (x, y) = camera.screen_to_world(px as isize, py as isize);
+                if x < 0 || y < 0 {
+                    continue;
+                }
+                let (column, row) = (x as usize / cell_width, y as usize / cell_height);
+                if column >= self.width || row >= self.height {
+                    continue;
+                }
+                match self.cells[row * self.width + column] {
+                    Visibility::Unseen => canvas.set_shade(px, py, 0),
+                    Visibility::Explored => canvas.set_shade(px, py, self.explored_brightness),
+                    Visibility::Visible => {}
+                }
+            }
+        }
+    }
+}
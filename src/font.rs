@@ -0,0 +1,678 @@
+//! Bitmap fonts for text drawn into the half-block pixel grid: a tiny built-in 3x5 font, and
+//! fonts loaded from PSF and BDF files.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::text::wrap;
+use crate::{Canvas, Color, TextLayout};
+
+/// The width of a glyph in pixels.
+pub(crate) const GLYPH_WIDTH: usize = 3;
+/// The height of a glyph in half-block pixels.
+pub(crate) const GLYPH_HEIGHT: usize = 5;
+/// The horizontal distance between the origins of two consecutive glyphs.
+pub(crate) const ADVANCE: usize = GLYPH_WIDTH + 1;
+
+/// Returns the rows of the glyph for `ch`, top to bottom. Bit 2 of each row is the leftmost pixel.
+///
+/// Lowercase letters are drawn with their uppercase glyph and unknown characters fall back to `?`.
+pub(crate) fn glyph(ch: char) -> [u8; GLYPH_HEIGHT] {
+    match ch.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b010, 0b100, 0b100, 0b100, 0b010],
+        ')' => [0b010, 0b001, 0b001, 0b001, 0b010],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '"' => [0b101, 0b101, 0b000, 0b000, 0b000],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '*' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        '[' => [0b110, 0b100, 0b100, 0b100, 0b110],
+        ']' => [0b011, 0b001, 0b001, 0b001, 0b011],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010],
+    }
+}
+
+/// Returns the width in pixels of `text` when drawn with [`draw_text`].
+pub(crate) fn text_width(text: &str) -> usize {
+    let count = text.chars().count();
+    if count == 0 {
+        0
+    } else {
+        count * ADVANCE - 1
+    }
+}
+
+/// Draws `text` with its top-left corner at pixel (`x`, `y`). Pixels falling outside the canvas
+/// are skipped.
+pub(crate) fn draw_text(
+    canvas: &mut Canvas,
+    x: isize,
+    y: isize,
+    z: usize,
+    color: Color,
+    text: &str,
+) {
+    for (i, ch) in text.chars().enumerate() {
+        let origin_x = x + (i * ADVANCE) as isize;
+        for (row, bits) in glyph(ch).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                let px = origin_x + column as isize;
+                let py = y + row as isize;
+                if px >= 0 && py >= 0 {
+                    canvas.set_pixel(px as usize, py as usize, z, color);
+                }
+            }
+        }
+    }
+}
+
+/// An error raised while loading a font.
+#[derive(Debug)]
+pub enum FontError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not a valid PSF or BDF font.
+    Invalid(String),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(error) => write!(f, "failed to read font: {error}"),
+            FontError::Invalid(message) => write!(f, "invalid font file: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FontError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(error: io::Error) -> Self {
+        FontError::Io(error)
+    }
+}
+
+/// The magic bytes of version 1 PSF fonts.
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+/// The magic bytes of version 2 PSF fonts.
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+
+/// The outline and drop shadow drawn behind text by [`Canvas::draw_text_effects`].
+///
+/// The outline covers the pixels around the text, diagonals included, one pixel wide. The drop
+/// shadow is the text, with its outline, moved by an offset, and is drawn behind both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextEffects {
+    outline: Option<Color>,
+    shadow: Option<(isize, isize, Color)>,
+}
+
+impl TextEffects {
+    /// Creates effects drawing neither an outline nor a shadow.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the color of a one-pixel outline around the text.
+    pub fn outline(mut self, color: Color) -> Self {
+        self.outline = Some(color);
+        self
+    }
+
+    /// Sets a drop shadow in `color`, moved `dx` pixels right and `dy` pixels down from the
+    /// text.
+    pub fn shadow(mut self, dx: isize, dy: isize, color: Color) -> Self {
+        self.shadow = Some((dx, dy, color));
+        self
+    }
+}
+
+/// A bitmap font of fixed-size glyphs for drawing text into the pixel grid with
+/// [`Canvas::draw_text_font`], such as the 8x8 font of a retro game.
+///
+/// Fonts are loaded from PSF files, the format of the Linux console, versions 1 and 2, or from
+/// BDF files, the text format of X11 bitmap fonts. Characters the font has no glyph for are
+/// drawn with the glyph of their uppercase letter, or else of `?`, if the font has them.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{Canvas, Color, Font};
+///
+/// let font = Font::load("assets/retro8x8.psf")?;
+/// let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// canvas.draw_text_font(&font, 4, 4, 0, Color { r: 255, g: 255, b: 255 }, "GAME OVER");
+/// # Ok::<(), rael::FontError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    width: usize,
+    height: usize,
+    letter_spacing: usize,
+    line_spacing: usize,
+    /// The pixels of every glyph, row-major, `width` by `height`.
+    glyphs: HashMap<char, Vec<bool>>,
+}
+
+impl Font {
+    /// Returns the built-in 3x5 font used by [`Canvas::draw_text_pixels`], with digits,
+    /// uppercase letters and common punctuation, and a pixel between glyphs and between lines.
+    pub fn builtin() -> &'static Font {
+        static BUILTIN: OnceLock<Font> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let glyphs = (' '..='~')
+                .filter(|ch| !ch.is_ascii_lowercase())
+                .map(|ch| {
+                    let rows = glyph(ch);
+                    let pixels = (0..GLYPH_WIDTH * GLYPH_HEIGHT)
+                        .map(|index| {
+                            rows[index / GLYPH_WIDTH] & (0b100 >> (index % GLYPH_WIDTH)) != 0
+                        })
+                        .collect();
+                    (ch, pixels)
+                })
+                .collect();
+            Font {
+                width: GLYPH_WIDTH,
+                height: GLYPH_HEIGHT,
+                letter_spacing: 1,
+                line_spacing: 1,
+                glyphs,
+            }
+        })
+    }
+
+    /// Loads a PSF or BDF font from a file, telling the formats apart by their contents.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        let bytes = fs::read(path)?;
+        if bytes.starts_with(&PSF1_MAGIC) || bytes.starts_with(&PSF2_MAGIC) {
+            Self::from_psf(&bytes)
+        } else {
+            let text = String::from_utf8_lossy(&bytes);
+            Self::from_bdf(&text)
+        }
+    }
+
+    /// Parses a font in the PSF format, version 1 or 2. Glyphs are given the characters of the
+    /// Unicode table of the font, or else the characters whose code is their position.
+    pub fn from_psf(bytes: &[u8]) -> Result<Self, FontError> {
+        let invalid = |message: &str| FontError::Invalid(message.into());
+        let (width, height, count, glyph_size, glyphs_start, unicode) =
+            if bytes.starts_with(&PSF1_MAGIC) {
+                let (mode, height) = match bytes.get(2..4) {
+                    Some(&[mode, height]) => (mode, height as usize),
+                    _ => return Err(invalid("the header ends early")),
+                };
+                let count = if mode & 0x01 != 0 { 512 } else { 256 };
+                (8, height, count, height, 4, mode & 0x02 != 0)
+            } else if bytes.starts_with(&PSF2_MAGIC) {
+                let field = |index: usize| {
+                    let start = 4 + index * 4;
+                    bytes
+                        .get(start..start + 4)
+                        .map(|field| u32::from_le_bytes(field.try_into().unwrap()) as usize)
+                        .ok_or_else(|| invalid("the header ends early"))
+                };
+                let (header_size, flags, count) = (field(1)?, field(2)?, field(3)?);
+                let (glyph_size, height, width) = (field(4)?, field(5)?, field(6)?);
+                (
+                    width,
+                    height,
+                    count,
+                    glyph_size,
+                    header_size,
+                    flags & 0x01 != 0,
+                )
+            } else {
+                return Err(invalid("the file does not start with PSF magic bytes"));
+            };
+        let row_size = width.div_ceil(8);
+        if width == 0 || height == 0 || glyph_size < row_size * height {
+            return Err(invalid(
+                "the glyph size does not match the glyph dimensions",
+            ));
+        }
+        let glyphs_end = count
+            .checked_mul(glyph_size)
+            .and_then(|size| size.checked_add(glyphs_start))
+            .filter(|&end| end <= bytes.len())
+            .ok_or_else(|| invalid("the glyphs end early"))?;
+        let bitmaps: Vec<Vec<bool>> = bytes[glyphs_start..glyphs_end]
+            .chunks_exact(glyph_size)
+            .map(|bitmap| {
+                (0..width * height)
+                    .map(|index| {
+                        let (row, column) = (index / width, index % width);
+                        bitmap[row * row_size + column / 8] & (0x80 >> (column % 8)) != 0
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut glyphs = HashMap::new();
+        if unicode {
+            let table = &bytes[glyphs_end..];
+            let characters = if bytes.starts_with(&PSF1_MAGIC) {
+                psf1_characters(table, count)
+            } else {
+                psf2_characters(table, count)
+            };
+            for (index, characters) in characters.into_iter().enumerate() {
+                for ch in characters {
+                    glyphs.entry(ch).or_insert_with(|| bitmaps[index].clone());
+                }
+            }
+        } else {
+            for (index, bitmap) in bitmaps.into_iter().enumerate() {
+                if let Some(ch) = char::from_u32(index as u32) {
+                    glyphs.insert(ch, bitmap);
+                }
+            }
+        }
+        Ok(Font {
+            width,
+            height,
+            letter_spacing: 0,
+            line_spacing: 0,
+            glyphs,
+        })
+    }
+
+    /// Parses a font in the BDF format. Every glyph is placed in a cell the size of the
+    /// bounding box of the font, on a shared baseline.
+    pub fn from_bdf(text: &str) -> Result<Self, FontError> {
+        let invalid = |message: String| FontError::Invalid(message);
+        let numbers = |line: &str, keyword: &str, count: usize| -> Result<Vec<i64>, FontError> {
+            let values: Vec<i64> = line[keyword.len()..]
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid(format!("invalid numbers in `{line}`")))?;
+            if values.len() < count {
+                return Err(invalid(format!("missing numbers in `{line}`")));
+            }
+            Ok(values)
+        };
+        let mut bounds = None;
+        let mut glyphs = HashMap::new();
+        let mut lines = text.lines().map(str::trim);
+        while let Some(line) = lines.next() {
+            if line.starts_with("FONTBOUNDINGBOX") {
+                bounds = Some(numbers(line, "FONTBOUNDINGBOX", 4)?);
+            }
+            if !line.starts_with("STARTCHAR") {
+                continue;
+            }
+            let Some(font) = bounds.as_deref() else {
+                return Err(invalid("a glyph comes before FONTBOUNDINGBOX".into()));
+            };
+            let (width, height) = (font[0].max(0) as usize, font[1].max(0) as usize);
+            let (mut encoding, mut glyph_box) = (None, None);
+            let mut pixels = vec![false; width * height];
+            while let Some(line) = lines.next() {
+                if line.starts_with("ENCODING") {
+                    encoding = Some(numbers(line, "ENCODING", 1)?[0]);
+                } else if line.starts_with("BBX") {
+                    glyph_box = Some(numbers(line, "BBX", 4)?);
+                } else if line == "BITMAP" {
+                    let Some(glyph) = glyph_box.as_deref() else {
+                        return Err(invalid("a glyph has no BBX".into()));
+                    };
+                    // Rows are counted from the top of the cell, whose bottom is the bottom
+                    // of the bounding box of the font.
+                    let top = (font[1] + font[3]) - (glyph[1] + glyph[3]);
+                    let left = glyph[2] - font[2];
+                    for row in 0..glyph[1].max(0) {
+                        let Some(hex) = lines.next() else {
+                            return Err(invalid("a bitmap ends early".into()));
+                        };
+                        let bits = u128::from_str_radix(hex, 16)
+                            .map_err(|_| invalid(format!("invalid bitmap row `{hex}`")))?;
+                        let row_bits = hex.len() as i64 * 4;
+                        for column in 0..glyph[0].max(0).min(row_bits) {
+                            let (x, y) = (left + column, top + row);
+                            let set = bits >> (row_bits - 1 - column) & 1 == 1;
+                            if set
+                                && (0..width as i64).contains(&x)
+                                && (0..height as i64).contains(&y)
+                            {
+                                pixels[y as usize * width + x as usize] = true;
+                            }
+                        }
+                    }
+                } else if line == "ENDCHAR" {
+                    break;
+                }
+            }
+            let ch = encoding
+                .filter(|&code| code >= 0)
+                .and_then(|code| char::from_u32(code as u32));
+            if let Some(ch) = ch {
+                glyphs.insert(ch, pixels);
+            }
+        }
+        let Some(font) = bounds else {
+            return Err(invalid("there is no FONTBOUNDINGBOX".into()));
+        };
+        Ok(Font {
+            width: font[0].max(0) as usize,
+            height: font[1].max(0) as usize,
+            letter_spacing: 0,
+            line_spacing: 0,
+            glyphs,
+        })
+    }
+
+    /// Sets the pixels left between glyphs, `letter`, and between lines, `line`, for fonts
+    /// whose glyphs touch the edges of their cells.
+    pub fn spacing(mut self, letter: usize, line: usize) -> Self {
+        self.letter_spacing = letter;
+        self.line_spacing = line;
+        self
+    }
+
+    /// Returns the width of a glyph in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of a glyph in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the width and height in pixels of `text` drawn in the font, `'\n'` starting a new
+    /// line, for placing it before drawing it, such as centering a title.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::Font;
+    ///
+    /// // Glyphs of 3 by 5 pixels, with a pixel between glyphs and between lines.
+    /// assert_eq!(Font::builtin().measure_text("LEVEL 1\nREADY"), (27, 11));
+    /// ```
+    pub fn measure_text(&self, text: &str) -> (usize, usize) {
+        let lines = text.split('\n');
+        let width = lines
+            .clone()
+            .map(|line| self.line_width(line))
+            .max()
+            .unwrap_or(0);
+        let count = lines.count();
+        (
+            width,
+            count * (self.height + self.line_spacing) - self.line_spacing,
+        )
+    }
+
+    /// Returns the lines of `text` wrapped to `max_width` pixels in the font, like
+    /// [`wrap_text`](crate::wrap_text) does for the text plane.
+    pub fn wrap_text<'a>(&self, text: &'a str, max_width: usize) -> Vec<&'a str> {
+        wrap(text, max_width + self.letter_spacing, |_| {
+            self.width + self.letter_spacing
+        })
+    }
+
+    /// Returns the pixels of `text` drawn in the font, relative to its top-left corner.
+    fn text_pixels<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (isize, isize)> + 'a {
+        let advance = self.width + self.letter_spacing;
+        text.split('\n').enumerate().flat_map(move |(line, text)| {
+            let line_y = line * (self.height + self.line_spacing);
+            text.chars().enumerate().flat_map(move |(index, ch)| {
+                let pixels = self.glyph(ch).unwrap_or_default();
+                pixels
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &set)| set)
+                    .map(move |(pixel, _)| {
+                        let column = index * advance + pixel % self.width;
+                        (column as isize, (line_y + pixel / self.width) as isize)
+                    })
+            })
+        })
+    }
+
+    /// Returns the width in pixels of a line of text drawn in the font.
+    fn line_width(&self, line: &str) -> usize {
+        (line.chars().count() * (self.width + self.letter_spacing))
+            .saturating_sub(self.letter_spacing)
+    }
+
+    /// Returns whether the font has a glyph of its own for `ch`.
+    pub fn has_glyph(&self, ch: char) -> bool {
+        self.glyphs.contains_key(&ch)
+    }
+
+    /// Returns the pixels of the glyph drawn for `ch`, if any.
+    fn glyph(&self, ch: char) -> Option<&[bool]> {
+        self.glyphs
+            .get(&ch)
+            .or_else(|| self.glyphs.get(&ch.to_ascii_uppercase()))
+            .or_else(|| self.glyphs.get(&'?'))
+            .map(Vec::as_slice)
+    }
+}
+
+/// Returns the characters of each of the first `count` glyphs from the Unicode table of a
+/// version 1 PSF font: 16-bit codes, with sequences after `0xFFFE`, ending at `0xFFFF`.
+fn psf1_characters(table: &[u8], count: usize) -> Vec<Vec<char>> {
+    let mut codes = table
+        .chunks_exact(2)
+        .map(|code| u16::from_le_bytes([code[0], code[1]]));
+    (0..count)
+        .map(|_| {
+            let mut characters = Vec::new();
+            let mut in_sequence = false;
+            for code in codes.by_ref() {
+                match code {
+                    0xFFFF => break,
+                    0xFFFE => in_sequence = true,
+                    code if !in_sequence => characters.extend(char::from_u32(code as u32)),
+                    _ => {}
+                }
+            }
+            characters
+        })
+        .collect()
+}
+
+/// Returns the characters of each of the first `count` glyphs from the Unicode table of a
+/// version 2 PSF font: UTF-8 characters, with sequences after `0xFE`, ending at `0xFF`.
+fn psf2_characters(table: &[u8], count: usize) -> Vec<Vec<char>> {
+    let mut entries = table.split(|&byte| byte == 0xFF);
+    (0..count)
+        .map(|_| {
+            let entry = entries.next().unwrap_or_default();
+            let single = entry.split(|&byte| byte == 0xFE).next().unwrap_or_default();
+            String::from_utf8_lossy(single)
+                .chars()
+                .filter(|&ch| ch != char::REPLACEMENT_CHARACTER)
+                .collect()
+        })
+        .collect()
+}
+
+impl Canvas {
+    /// Draws `text` in the built-in 3x5 pixel font with its top-left corner at pixel (`x`, `y`)
+    /// on layer `z`, for scores, labels and debug text inside the pixel world.
+    ///
+    /// Every glyph is 3 pixels wide and 5 tall, with a pixel between glyphs and between lines,
+    /// which `'\n'` starts. The font has digits, letters, drawn in uppercase, and common
+    /// punctuation; other characters are drawn as `?`. Text goes through the transform, so it
+    /// can be turned and resized, and parts outside the canvas are clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let mut canvas = Canvas::new(40, 10, Color { r: 0, g: 0, b: 0 });
+    /// canvas.draw_text_pixels(1, 1, 0, white, "SCORE 120");
+    /// // The top bar of the S.
+    /// assert_eq!(canvas.composited_cell(2, 0).bottom_color, white);
+    /// ```
+    pub fn draw_text_pixels(&mut self, x: isize, y: isize, z: usize, color: Color, text: &str) {
+        self.draw_text_font(Font::builtin(), x, y, z, color, text);
+    }
+
+    /// Draws `text` in `font` with its top-left corner at pixel (`x`, `y`) on layer `z`, like
+    /// [`Canvas::draw_text_pixels`]. Lines start at `'\n'`.
+    pub fn draw_text_font(
+        &mut self,
+        font: &Font,
+        x: isize,
+        y: isize,
+        z: usize,
+        color: Color,
+        text: &str,
+    ) {
+        for (pixel_x, pixel_y) in font.text_pixels(text) {
+            self.fill_rect(x + pixel_x, y + pixel_y, 1, 1, z, color);
+        }
+    }
+
+    /// Draws `text` in `font` like [`Canvas::draw_text_font`], with the outline and drop shadow
+    /// of `effects` drawn behind it, keeping it readable over busy backgrounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color, Font, TextEffects};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let black = Color { r: 0, g: 0, b: 1 };
+    /// let mut canvas = Canvas::new(40, 10, Color { r: 0, g: 0, b: 0 });
+    /// canvas.fill_rect(0, 0, 40, 20, 0, Color { r: 40, g: 160, b: 40 });
+    /// let effects = TextEffects::new().outline(black);
+    /// canvas.draw_text_effects(Font::builtin(), 2, 2, 1, white, "GO", &effects);
+    /// // The pixel left of the G is outlined.
+    /// assert_eq!(canvas.composited_cell(1, 2).top_color, black);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text_effects(
+        &mut self,
+        font: &Font,
+        x: isize,
+        y: isize,
+        z: usize,
+        color: Color,
+        text: &str,
+        effects: &TextEffects,
+    ) {
+        let pixels: HashSet<(isize, isize)> = font.text_pixels(text).collect();
+        let mut shape = pixels.clone();
+        if effects.outline.is_some() {
+            for &(pixel_x, pixel_y) in &pixels {
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        shape.insert((pixel_x + dx, pixel_y + dy));
+                    }
+                }
+            }
+        }
+        if let Some((dx, dy, shadow)) = effects.shadow {
+            for &(pixel_x, pixel_y) in &shape {
+                self.fill_rect(x + pixel_x + dx, y + pixel_y + dy, 1, 1, z, shadow);
+            }
+        }
+        if let Some(outline) = effects.outline {
+            for &(pixel_x, pixel_y) in shape.difference(&pixels) {
+                self.fill_rect(x + pixel_x, y + pixel_y, 1, 1, z, outline);
+            }
+        }
+        for &(pixel_x, pixel_y) in &pixels {
+            self.fill_rect(x + pixel_x, y + pixel_y, 1, 1, z, color);
+        }
+    }
+
+    /// Draws `text` in `font` like [`Canvas::draw_text_font`] as a block whose top-left corner
+    /// is at pixel (`x`, `y`), with its lines wrapped and aligned by `layout`, its width in
+    /// pixels.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text_layout(
+        &mut self,
+        font: &Font,
+        x: isize,
+        y: isize,
+        z: usize,
+        color: Color,
+        text: &str,
+        layout: &TextLayout,
+    ) {
+        let advance = font.width + font.letter_spacing;
+        let lines = layout.lines(text, |_| advance, font.letter_spacing);
+        let widest = lines
+            .iter()
+            .map(|line| font.line_width(line))
+            .max()
+            .unwrap_or(0);
+        for (index, line) in lines.into_iter().enumerate() {
+            let indent = layout.indent(font.line_width(line), widest) as isize;
+            let line_y = y + (index * (font.height + font.line_spacing)) as isize;
+            self.draw_text_font(font, x + indent, line_y, z, color, line);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Field of view: which cells of a grid can be seen from an origin.
+
+use std::cmp::Ordering;
+
+use crate::TileMap;
+
+/// The shadowcasting algorithm used to compute a [`FieldOfView`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FovAlgorithm {
+    /// Symmetric shadowcasting: a floor cell is visible when its center can be seen, which makes
+    /// visibility symmetric, so if A sees B then B sees A. Walls are visible when any part of
+    /// them is lit, giving clean room outlines.
+    #[default]
+    Symmetric,
+    /// Classic recursive shadowcasting, the long-standing roguelike default. It works on eighths
+    /// of the view with floating point slopes and is not symmetric.
+    Recursive,
+    /// Like [`FovAlgorithm::Symmetric`], but every cell with any part in view is visible, which
+    /// shows more of the surroundings around corners and pillars at the cost of symmetry.
+    Permissive,
+}
+
+/// The cells of a grid visible from an origin.
+///
+/// # Example
+///
+/// ```
+/// use rael::{FieldOfView, FovAlgorithm};
+///
+/// let map = [
+///     "##########",
+///     "#........#",
+///     "#...#....#",
+///     "#........#",
+///     "##########",
+/// ];
+/// let fov = FieldOfView::compute(10, 5, (2, 2), None, FovAlgorithm::Symmetric, |x, y| {
+///     map[y].as_bytes()[x] == b'#'
+/// });
+/// assert!(fov.is_visible(4, 2)); // The pillar itself.
+/// assert!(!fov.is_visible(6, 2)); // The cell in its shadow.
+/// assert!(fov.is_visible(0, 0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldOfView {
+    width: usize,
+    height: usize,
+    visible: Vec<bool>,
+}
+
+/// A slope as the fraction `numerator / denominator`, with a positive denominator.
+#[derive(Debug, Clone, Copy)]
+struct Slope {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Slope {
+    fn new(numerator: i64, denominator: i64) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Compares `value` with `depth` times this slope.
+    fn compare_scaled(self, value: i64, depth: i64) -> Ordering {
+        (value * self.denominator).cmp(&(depth * self.numerator))
+    }
+
+    /// Returns `depth` times this slope, rounded to the nearest integer with ties rounded up.
+    fn round_ties_up(self, depth: i64) -> i64 {
+        (2 * depth * self.numerator + self.denominator).div_euclid(2 * self.denominator)
+    }
+
+    /// Returns `depth` times this slope, rounded to the nearest integer with ties rounded down.
+    fn round_ties_down(self, depth: i64) -> i64 {
+        -(self.denominator - 2 * depth * self.numerator).div_euclid(2 * self.denominator)
+    }
+}
+
+/// The state shared by the recursive scans of a field of view computation.
+struct Caster<F> {
+    width: usize,
+    height: usize,
+    origin: (isize, isize),
+    radius: Option<usize>,
+    opaque: F,
+    visible: Vec<bool>,
+}
+
+impl<F: FnMut(usize, usize) -> bool> Caster<F> {
+    fn in_bounds(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    fn in_radius(&self, x: isize, y: isize) -> bool {
+        let Some(radius) = self.radius else {
+            return true;
+        };
+        let (dx, dy) = (x - self.origin.0, y - self.origin.1);
+        (dx * dx + dy * dy) as usize <= radius * radius
+    }
+
+    /// Returns `true` if the cell blocks sight. Cells outside the grid do.
+    fn is_opaque(&mut self, x: isize, y: isize) -> bool {
+        !self.in_bounds(x, y) || (self.opaque)(x as usize, y as usize)
+    }
+
+    fn reveal(&mut self, x: isize, y: isize) {
+        if self.in_bounds(x, y) && self.in_radius(x, y) {
+            self.visible[y as usize * self.width + x as usize] = true;
+        }
+    }
+
+    fn max_depth(&self) -> i64 {
+        self.radius.unwrap_or(self.width.max(self.height)) as i64
+    }
+
+    /// Converts a (`depth`, `column`) position in a quadrant to grid coordinates. Quadrants are
+    /// numbered north, east, south and west.
+    fn transform(&self, quadrant: u8, depth: i64, column: i64) -> (isize, isize) {
+        let (depth, column) = (depth as isize, column as isize);
+        let (x, y) = self.origin;
+        match quadrant {
+            0 => (x + column, y - depth),
+            1 => (x + depth, y + column),
+            2 => (x + column, y + depth),
+            _ => (x - depth, y + column),
+        }
+    }
+
+    /// Scans one row of a quadrant for symmetric shadowcasting, then the rows behind it.
+    fn scan(&mut self, quadrant: u8, depth: i64, mut start: Slope, end: Slope, permissive: bool) {
+        if depth > self.max_depth() {
+            return;
+        }
+        let mut previous_wall = None;
+        for column in start.round_ties_up(depth)..=end.round_ties_down(depth) {
+            let (x, y) = self.transform(quadrant, depth, column);
+            let wall = self.is_opaque(x, y);
+            let symmetric = start.compare_scaled(column, depth) != Ordering::Less
+                && end.compare_scaled(column, depth) != Ordering::Greater;
+            if wall || symmetric || permissive {
+                self.reveal(x, y);
+            }
+            if previous_wall == Some(true) && !wall {
+                start = Slope::new(2 * column - 1, 2 * depth);
+            }
+            if previous_wall == Some(false) && wall {
+                let next_end = Slope::new(2 * column - 1, 2 * depth);
+                self.scan(quadrant, depth + 1, start, next_end, permissive);
+            }
+            previous_wall = Some(wall);
+        }
+        if previous_wall == Some(false) {
+            self.scan(quadrant, depth + 1, start, end, permissive);
+        }
+    }
+
+    /// Lights one octant for recursive shadowcasting, starting at row `row` between the slopes
+    /// `start` and `end`, with the octant given by the transform `(xx, xy, yx, yy)`.
+    fn cast_light(&mut self, row: i64, mut start: f32, end: f32, octant: (i64, i64, i64, i64)) {
+        if start < end {
+            return;
+        }
+        let (xx, xy, yx, yy) = octant;
+        let mut new_start = 0.0;
+        for distance in row..=self.max_depth() {
+            let dy = -distance;
+            let mut blocked = false;
+            for dx in -distance..=0 {
+                let x = self.origin.0 + (dx * xx + dy * xy) as isize;
+                let y = self.origin.1 + (dx * yx + dy * yy) as isize;
+                let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right_slope {
+                    continue;
+                }
+                if end > left_slope {
+                    break;
+                }
+                self.reveal(x, y);
+                let opaque = self.is_opaque(x, y);
+                if blocked {
+                    if opaque {
+                        new_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = new_start;
+                    }
+                } else if opaque && distance < self.max_depth() {
+                    blocked = true;
+                    self.cast_light(distance + 1, start, left_slope, octant);
+                    new_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
+
+impl FieldOfView {
+    /// Computes the cells of a `width` by `height` grid visible from `origin`.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The cell to look from. It is always visible.
+    /// * `radius` - How far sight reaches, in cells, or `None` for no limit.
+    /// * `algorithm` - The shadowcasting algorithm to use.
+    /// * `opaque` - Returns `true` if the cell at (`x`, `y`) blocks sight.
+    pub fn compute(
+        width: usize,
+        height: usize,
+        origin: (usize, usize),
+        radius: Option<usize>,
+        algorithm: FovAlgorithm,
+        opaque: impl FnMut(usize, usize) -> bool,
+    ) -> Self {
+        let mut caster = Caster {
+            width,
+            height,
+            origin: (origin.0 as isize, origin.1 as isize),
+            radius,
+            opaque,
+            visible: vec![false; width * height],
+        };
+        caster.reveal(caster.origin.0, caster.origin.1);
+        match algorithm {
+            FovAlgorithm::Symmetric | FovAlgorithm::Permissive => {
+                let permissive = algorithm == FovAlgorithm::Permissive;
+                for quadrant in 0..4 {
+                    caster.scan(quadrant, 1, Slope::new(-1, 1), Slope::new(1, 1), permissive);
+                }
+            }
+            FovAlgorithm::Recursive => {
+                const OCTANTS: [(i64, i64, i64, i64); 8] = [
+                    (1, 0, 0, 1),
+                    (0, 1, 1, 0),
+                    (0, -1, 1, 0),
+                    (-1, 0, 0, 1),
+                    (-1, 0, 0, -1),
+                    (0, -1, -1, 0),
+                    (0, 1, -1, 0),
+                    (1, 0, 0, -1),
+                ];
+                for octant in OCTANTS {
+                    caster.cast_light(1, 1.0, 0.0, octant);
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            visible: caster.visible,
+        }
+    }
+
+    /// Returns the width of the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns `true` if the cell at (`x`, `y`) is visible.
+    pub fn is_visible(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.visible[y * self.width + x]
+    }
+
+    /// Returns an iterator over the visible cells, row by row.
+    pub fn visible_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.visible
+            .iter()
+            .enumerate()
+            .filter(|(_, visible)| **visible)
+            .map(|(index, _)| (index % self.width, index / self.width))
+    }
+}
+
+impl TileMap {
+    /// Computes the cells of the map visible from `origin`.
+    ///
+    /// `opaque` returns `true` if cells holding the given tile block sight. See
+    /// [`FieldOfView::compute`] for the other arguments.
+    pub fn field_of_view(
+        &self,
+        origin: (usize, usize),
+        radius: Option<usize>,
+        algorithm: FovAlgorithm,
+        mut opaque: impl FnMut(Option<usize>) -> bool,
+    ) -> FieldOfView {
+        FieldOfView::compute(
+            self.width(),
+            self.height(),
+            origin,
+            radius,
+            algorithm,
+            |x, y| opaque(self.get(x, y)),
+        )
+    }
+}
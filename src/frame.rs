@@ -0,0 +1,581 @@
+//! Snapshots of what a canvas shows, and comparisons between them.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::dither::{dither, dither_to_mode};
+use crate::{push_cell, Canvas, CellGlyph, Color, ColorMode, CompositedCell, DitherMode, Palette};
+
+/// A terminal cell that differs between two canvases or frames, as reported by [`Canvas::diff`]
+/// and [`Frame::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellDiff {
+    /// The terminal column of the cell.
+    pub column: usize,
+    /// The terminal row of the cell.
+    pub row: usize,
+    /// The colors of the cell before.
+    pub before: CompositedCell,
+    /// The colors of the cell after.
+    pub after: CompositedCell,
+    /// The character drawn over the cell before, and its color.
+    pub before_glyph: Option<(char, Color)>,
+    /// The character drawn over the cell after, and its color.
+    pub after_glyph: Option<(char, Color)>,
+}
+
+/// A vertical scroll of whole terminal rows between two frames, done by the terminal itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Scroll {
+    /// The first terminal row of the scroll region.
+    top: usize,
+    /// The last terminal row of the scroll region.
+    bottom: usize,
+    /// How many rows the contents move up, or down if negative.
+    amount: isize,
+}
+
+/// A snapshot of the composited contents of a [`Canvas`]: the colors of every terminal cell and
+/// the characters drawn over them, with all z-layers and shading applied.
+///
+/// Frames are what recorders store, and they stay valid however the canvas changes afterwards.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Frame};
+///
+/// let red = Color { r: 255, g: 0, b: 0 };
+/// let mut canvas = Canvas::new(10, 5, Color { r: 0, g: 0, b: 0 });
+/// canvas.set_pixel(2, 3, 4, red);
+///
+/// let frame = Frame::capture(&canvas);
+/// canvas.clear();
+/// assert_eq!(frame.pixel(2, 3), Some(red));
+/// assert_eq!(frame.cell(2, 1).unwrap().bottom_color, red);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<CompositedCell>,
+    glyphs: Vec<Option<CellGlyph>>,
+    color_mode: ColorMode,
+    /// How the frame was dithered to its color mode, if at all.
+    dither_mode: DitherMode,
+    terminal_default: Option<Color>,
+}
+
+impl Frame {
+    /// Captures what `canvas` currently shows.
+    pub fn capture(canvas: &Canvas) -> Self {
+        let mut cells = Vec::with_capacity(canvas.width * canvas.height);
+        let mut glyphs = Vec::with_capacity(canvas.width * canvas.height);
+        for row in 0..canvas.height {
+            for column in 0..canvas.width {
+                let (cell, glyph) = canvas.composited(column, row);
+                cells.push(cell);
+                glyphs.push(glyph);
+            }
+        }
+        Self {
+            width: canvas.width,
+            height: canvas.height,
+            cells,
+            glyphs,
+            color_mode: canvas.color_mode,
+            dither_mode: DitherMode::None,
+            terminal_default: canvas.terminal_default_color(),
+        }
+    }
+
+    /// Returns what `canvas` showed when it was last rendered, from the cells it keeps, without
+    /// compositing it again.
+    #[cfg(feature = "std")]
+    pub(crate) fn rendered(canvas: &Canvas) -> Self {
+        let glyphs = (0..canvas.height)
+            .flat_map(|row| (0..canvas.width).map(move |column| (column, row)))
+            .map(|(column, row)| canvas.composited_glyph(column, row))
+            .collect();
+        Self {
+            width: canvas.width,
+            height: canvas.height,
+            cells: canvas.composited_cells.clone(),
+            glyphs,
+            color_mode: canvas.color_mode,
+            dither_mode: DitherMode::None,
+            terminal_default: canvas.terminal_default_color(),
+        }
+    }
+
+    /// Creates a frame from its row-major cells and glyphs.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_parts(
+        width: usize,
+        height: usize,
+        cells: Vec<CompositedCell>,
+        glyphs: Vec<Option<CellGlyph>>,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            cells,
+            glyphs,
+            color_mode: ColorMode::TrueColor,
+            dither_mode: DitherMode::None,
+            terminal_default: None,
+        }
+    }
+
+    /// Sends the colors of the frame in `mode` instead, if the terminal shows fewer colors in it
+    /// than in the mode of the frame.
+    #[cfg(feature = "std")]
+    pub(crate) fn limit_color_mode(&mut self, mode: ColorMode) {
+        let colors = |mode| match mode {
+            ColorMode::TrueColor => 2,
+            ColorMode::Xterm256 => 1,
+            ColorMode::Ansi16 => 0,
+        };
+        if colors(mode) < colors(self.color_mode) {
+            self.color_mode = mode;
+            self.dither(None, self.dither_mode);
+        }
+    }
+
+    /// Replaces the colors of the frame by the colors of `palette`, if any, or else by the
+    /// palette colors of the color mode of the frame, mixed by `mode`.
+    pub(crate) fn dither(&mut self, palette: Option<&Palette>, mode: DitherMode) {
+        let Some(palette) = palette else {
+            self.dither_mode = mode;
+            let (width, color_mode) = (self.width, self.color_mode);
+            dither_to_mode(
+                &mut self.cells,
+                width,
+                color_mode,
+                mode,
+                self.terminal_default,
+            );
+            return;
+        };
+        let quantize = |color| palette.quantize(color);
+        let spread = palette.spread();
+        dither(
+            &mut self.cells,
+            self.width,
+            quantize,
+            spread,
+            mode,
+            self.terminal_default,
+        );
+        for glyph in self.glyphs.iter_mut().flatten() {
+            glyph.color = palette.quantize(glyph.color);
+        }
+        // The colors are final, sent in the color mode as they are.
+        self.dither_mode = DitherMode::None;
+    }
+
+    /// Returns the width of the frame in terminal columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the frame in terminal rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the colors of the terminal cell at (`column`, `row`).
+    pub fn cell(&self, column: usize, row: usize) -> Option<CompositedCell> {
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        Some(self.cells[row * self.width + column])
+    }
+
+    /// Returns the color of the half-block pixel at (`x`, `y`).
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        let cell = self.cell(x, y / 2)?;
+        Some(if y.is_multiple_of(2) {
+            cell.top_color
+        } else {
+            cell.bottom_color
+        })
+    }
+
+    /// Returns the character drawn over the terminal cell at (`column`, `row`) and its color.
+    /// The cell right of a wide character, covered by it, has none.
+    pub fn glyph(&self, column: usize, row: usize) -> Option<(char, Color)> {
+        self.styled_glyph(column, row)
+            .map(|glyph| (glyph.ch, glyph.color))
+    }
+
+    /// Returns the character drawn over the terminal cell at (`column`, `row`) like
+    /// [`Frame::glyph`], with its mark and styles.
+    pub(crate) fn styled_glyph(&self, column: usize, row: usize) -> Option<CellGlyph> {
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        self.glyphs[row * self.width + column].filter(|glyph| !glyph.is_continuation())
+    }
+
+    /// Sets the color of the half-block pixel at (`x`, `y`), leaving any character drawn over
+    /// its cell. Pixels outside the frame are ignored.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x >= self.width || y >= self.height * 2 {
+            return;
+        }
+        let cell = &mut self.cells[y / 2 * self.width + x];
+        if y.is_multiple_of(2) {
+            cell.top_color = color;
+        } else {
+            cell.bottom_color = color;
+        }
+    }
+
+    /// Draws the character `ch` in `color` over the terminal cell at (`column`, `row`), or
+    /// removes the character drawn over it with `None`. Cells outside the frame are ignored.
+    pub fn set_glyph(&mut self, column: usize, row: usize, glyph: Option<(char, Color)>) {
+        if column >= self.width || row >= self.height {
+            return;
+        }
+        self.glyphs[row * self.width + column] = glyph.map(|(ch, color)| CellGlyph::new(ch, color));
+    }
+
+    /// Returns the colors of the terminal cell at (`column`, `row`), inside the frame, and the
+    /// character drawn over it.
+    pub(crate) fn cell_and_glyph(
+        &self,
+        column: usize,
+        row: usize,
+    ) -> (CompositedCell, Option<CellGlyph>) {
+        let index = row * self.width + column;
+        (self.cells[index], self.glyphs[index])
+    }
+
+    /// Draws the frame onto layer `z` of `canvas`, with its characters over it. Parts of the
+    /// frame beyond the canvas are cut off.
+    pub fn draw(&self, canvas: &mut Canvas, z: usize) {
+        for row in 0..self.height {
+            for column in 0..self.width {
+                let index = row * self.width + column;
+                let cell = self.cells[index];
+                canvas.set_pixel(column, row * 2, z, cell.top_color);
+                canvas.set_pixel(column, row * 2 + 1, z, cell.bottom_color);
+                if let Some(glyph) = self.glyphs[index] {
+                    canvas.set_glyph(column, row, glyph);
+                }
+            }
+        }
+    }
+
+    /// Returns the cells that differ between this frame and `other`, row by row, with this frame
+    /// as the before state. Only the area covered by both frames is compared.
+    pub fn diff(&self, other: &Frame) -> Vec<CellDiff> {
+        let mut diffs = Vec::new();
+        for row in 0..self.height.min(other.height) {
+            for column in 0..self.width.min(other.width) {
+                let (before, after) = (self.cell(column, row), other.cell(column, row));
+                let (before_glyph, after_glyph) =
+                    (self.glyph(column, row), other.glyph(column, row));
+                let restyled = self.glyphs[row * self.width + column]
+                    != other.glyphs[row * other.width + column];
+                if before != after || restyled {
+                    diffs.push(CellDiff {
+                        column,
+                        row,
+                        before: before.unwrap(),
+                        after: after.unwrap(),
+                        before_glyph,
+                        after_glyph,
+                    });
+                }
+            }
+        }
+        diffs
+    }
+
+    /// Returns the ANSI escape codes drawing the whole frame, like [`Canvas::render`].
+    pub fn to_ansi(&self) -> String {
+        self.ansi_since(None)
+    }
+
+    /// Returns what a terminal showing `previous` shows after drawing only the terminal rows of
+    /// `field` of this frame, `0` for the even rows and `1` for the odd rows: this frame with the
+    /// other rows taken from `previous`. Every row is taken from this frame if there is no field,
+    /// no previous frame or its size differs.
+    #[cfg(feature = "std")]
+    pub(crate) fn interlaced(&self, previous: Option<&Frame>, field: Option<usize>) -> Frame {
+        let mut frame = self.clone();
+        let previous = previous
+            .filter(|previous| previous.width == self.width && previous.height == self.height);
+        if let (Some(previous), Some(field)) = (previous, field) {
+            for row in (1 - field..self.height).step_by(2) {
+                let cells = row * self.width..(row + 1) * self.width;
+                frame.cells[cells.clone()].copy_from_slice(&previous.cells[cells.clone()]);
+                frame.glyphs[cells.clone()].copy_from_slice(&previous.glyphs[cells]);
+            }
+        }
+        frame
+    }
+
+    /// Returns what a terminal showing `previous` shows after drawing the changed cells of this
+    /// frame that fit within `budget` bytes of escape codes, highest `priority` first, as given
+    /// for every terminal column and row. The cells that did not fit are left as in `previous`.
+    /// Every cell is drawn if there is no previous frame or its size differs.
+    #[cfg(feature = "std")]
+    pub(crate) fn within_budget(
+        &self,
+        previous: Option<&Frame>,
+        budget: usize,
+        priority: impl Fn(usize, usize) -> u8,
+    ) -> Frame {
+        let Some(previous) = previous
+            .filter(|previous| previous.width == self.width && previous.height == self.height)
+        else {
+            return self.clone();
+        };
+        let mut changed: Vec<(u8, usize)> = (0..self.cells.len())
+            .filter(|index| {
+                previous.cells[*index] != self.cells[*index]
+                    || previous.glyphs[*index] != self.glyphs[*index]
+            })
+            .map(|index| (priority(index % self.width, index / self.width), index))
+            .collect();
+        changed.sort_by_key(|(priority, index)| (core::cmp::Reverse(*priority), *index));
+
+        let mut frame = previous.clone();
+        let (mut spent, mut output) = (0, String::new());
+        for (_, index) in changed {
+            output.clear();
+            let (column, row) = (index % self.width, index / self.width);
+            // Writing to a `String` cannot fail.
+            let _ = push_cell(
+                &mut output,
+                column,
+                row,
+                self.cells[index],
+                self.glyphs[index],
+                self.color_mode,
+                self.terminal_default,
+            );
+            spent += output.len();
+            if spent > budget {
+                break;
+            }
+            frame.cells[index] = self.cells[index];
+            frame.glyphs[index] = self.glyphs[index];
+        }
+        frame
+    }
+
+    /// Returns the ANSI escape codes turning `previous` into this frame, drawing only the cells
+    /// that changed. Every cell is drawn if there is no previous frame or its size or how its
+    /// colors are sent differs.
+    ///
+    /// When rows moved up or down as a block, such as in a scrolling log, the terminal scrolls
+    /// them itself within a scroll region and only the rows it exposes are drawn.
+    pub(crate) fn ansi_since(&self, previous: Option<&Frame>) -> String {
+        let mut previous = previous.filter(|previous| {
+            previous.width == self.width
+                && previous.height == self.height
+                && previous.color_mode == self.color_mode
+                && previous.terminal_default == self.terminal_default
+        });
+        let mut buffer = String::new();
+        let scrolled;
+        if let Some(scroll) = previous.and_then(|previous| self.find_scroll(previous)) {
+            let (top, bottom) = (scroll.top + 1, scroll.bottom + 1);
+            // Writing to a `String` cannot fail.
+            let _ = if scroll.amount > 0 {
+                write!(
+                    buffer,
+                    "\u{1b}[{top};{bottom}r\u{1b}[{}S\u{1b}[r",
+                    scroll.amount
+                )
+            } else {
+                write!(
+                    buffer,
+                    "\u{1b}[{top};{bottom}r\u{1b}[{}T\u{1b}[r",
+                    -scroll.amount
+                )
+            };
+            scrolled = previous.map(|previous| previous.scrolled(scroll, self));
+            previous = scrolled.as_ref();
+        }
+        for row in 0..self.height {
+            for column in 0..self.width {
+                let index = row * self.width + column;
+                let (cell, glyph) = (self.cells[index], self.glyphs[index]);
+                if previous.is_some_and(|previous| {
+                    previous.cells[index] == cell && previous.glyphs[index] == glyph
+                }) {
+                    continue;
+                }
+                // Writing to a `String` cannot fail.
+                let _ = push_cell(
+                    &mut buffer,
+                    column,
+                    row,
+                    cell,
+                    glyph,
+                    self.color_mode,
+                    self.terminal_default,
+                );
+            }
+        }
+        buffer
+    }
+
+    /// Finds the vertical scroll of `previous` that leaves the fewest rows of this frame to
+    /// draw, if it saves drawing at least [`Frame::MIN_SCROLLED_ROWS`] rows.
+    fn find_scroll(&self, previous: &Frame) -> Option<Scroll> {
+        let hashes: Vec<u64> = (0..self.height).map(|row| self.row_hash(row)).collect();
+        let previous_hashes: Vec<u64> =
+            (0..self.height).map(|row| previous.row_hash(row)).collect();
+        let mut best: Option<(usize, Scroll)> = None;
+        for distance in 1..self.height {
+            for amount in [distance as isize, -(distance as isize)] {
+                // The rows showing the previous row `amount` rows below them, in runs.
+                let mut run_start = None;
+                let mut saved = 0;
+                for row in 0..=self.height {
+                    let source = row as isize + amount;
+                    let moved = row < self.height
+                        && (0..self.height as isize).contains(&source)
+                        && hashes[row] == previous_hashes[source as usize]
+                        && self.rows_equal(row, previous, source as usize);
+                    if moved {
+                        run_start.get_or_insert(row);
+                        // Rows showing the same as before are not drawn anyway.
+                        if hashes[row] != previous_hashes[row]
+                            || !self.rows_equal(row, previous, row)
+                        {
+                            saved += 1;
+                        }
+                        continue;
+                    }
+                    let Some(first) = run_start.take() else {
+                        continue;
+                    };
+                    let last = row - 1;
+                    if saved >= Self::MIN_SCROLLED_ROWS
+                        && best.is_none_or(|(best_saved, _)| saved > best_saved)
+                    {
+                        let (top, bottom) = if amount > 0 {
+                            (first, last + distance)
+                        } else {
+                            (first - distance, last)
+                        };
+                        best = Some((
+                            saved,
+                            Scroll {
+                                top,
+                                bottom,
+                                amount,
+                            },
+                        ));
+                    }
+                    saved = 0;
+                }
+            }
+        }
+        best.map(|(_, scroll)| scroll)
+    }
+
+    /// The fewest rows a scroll has to save drawing to be worth scrolling the terminal.
+    const MIN_SCROLLED_ROWS: usize = 3;
+
+    /// Returns what the terminal shows after `scroll` is done on this frame. The rows it exposes
+    /// are set apart from the rows of `next`, so they are drawn.
+    fn scrolled(&self, scroll: Scroll, next: &Frame) -> Frame {
+        let mut frame = self.clone();
+        for row in scroll.top..=scroll.bottom {
+            let cells = row * self.width..(row + 1) * self.width;
+            let source = row as isize + scroll.amount;
+            if (scroll.top as isize..=scroll.bottom as isize).contains(&source) {
+                let source = source as usize * self.width;
+                frame.cells[cells.clone()]
+                    .copy_from_slice(&self.cells[source..source + self.width]);
+                frame.glyphs[cells].copy_from_slice(&self.glyphs[source..source + self.width]);
+            } else {
+                for index in cells {
+                    let cell = next.cells[index];
+                    let opposite = |color: Color| Color {
+                        r: 255 - color.r,
+                        g: 255 - color.g,
+                        b: 255 - color.b,
+                    };
+                    frame.cells[index] = CompositedCell {
+                        top_color: opposite(cell.top_color),
+                        bottom_color: opposite(cell.bottom_color),
+                    };
+                    frame.glyphs[index] = None;
+                }
+            }
+        }
+        frame
+    }
+
+    /// Returns whether terminal row `row` of this frame shows the same as row `other_row` of
+    /// `other`.
+    fn rows_equal(&self, row: usize, other: &Frame, other_row: usize) -> bool {
+        let (cells, other_cells) = (
+            row * self.width..(row + 1) * self.width,
+            other_row * self.width..(other_row + 1) * self.width,
+        );
+        self.cells[cells.clone()] == other.cells[other_cells.clone()]
+            && self.glyphs[cells] == other.glyphs[other_cells]
+    }
+
+    /// Returns an FNV-1a hash of what terminal row `row` shows, for quickly telling rows apart.
+    fn row_hash(&self, row: usize) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut add = |value: u32| {
+            for byte in value.to_le_bytes() {
+                hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        let color = |color: Color| u32::from_le_bytes([color.r, color.g, color.b, 0]);
+        for index in row * self.width..(row + 1) * self.width {
+            add(color(self.cells[index].top_color));
+            add(color(self.cells[index].bottom_color));
+            if let Some(glyph) = self.glyphs[index] {
+                add(glyph.ch as u32);
+                add(glyph.mark.map_or(0, |mark| mark as u32));
+                add(color(glyph.color));
+                add(glyph.attributes.bits() as u32);
+            }
+        }
+        hash
+    }
+}
+
+impl Canvas {
+    /// Returns the terminal cells that show something different on `other`, row by row, with
+    /// this canvas as the before state. Only the area covered by both canvases is compared.
+    ///
+    /// To compare a canvas with an earlier snapshot of itself, capture both as [`Frame`]s and
+    /// use [`Frame::diff`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let black = Color { r: 0, g: 0, b: 0 };
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let before = Canvas::new(10, 5, black);
+    /// let mut after = Canvas::new(10, 5, black);
+    /// after.set_pixel(4, 3, 0, red);
+    ///
+    /// let diffs = before.diff(&after);
+    /// assert_eq!(diffs.len(), 1);
+    /// assert_eq!((diffs[0].column, diffs[0].row), (4, 1));
+    /// assert_eq!(diffs[0].before.bottom_color, black);
+    /// assert_eq!(diffs[0].after.bottom_color, red);
+    /// ```
+    pub fn diff(&self, other: &Canvas) -> Vec<CellDiff> {
+        Frame::capture(self).diff(&Frame::capture(other))
+    }
+}
@@ -0,0 +1,190 @@
+//! The visibility, opacity and names of the z-layers of a canvas.
+
+use alloc::string::ToString;
+
+use crate::Canvas;
+
+/// How a z-layer is composited, set per layer on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LayerSettings {
+    /// Whether the layer is left out of compositing.
+    hidden: bool,
+    /// The opacity every pixel of the layer is multiplied by, from `0` to `255`.
+    opacity: u8,
+}
+
+impl Default for LayerSettings {
+    fn default() -> Self {
+        Self {
+            hidden: false,
+            opacity: u8::MAX,
+        }
+    }
+}
+
+impl Canvas {
+    /// Hides or shows layer `z` as a whole. A hidden layer keeps its pixels and can still be
+    /// drawn on, but is left out of compositing until shown again, such as a debug overlay
+    /// toggled by a key. Layers past the last one are ignored. Every layer starts shown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(4, 2, Color::BLACK);
+    /// canvas.set_pixel(0, 0, 9, Color::RED);
+    /// canvas.set_layer_visible(9, false);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color::BLACK);
+    /// canvas.set_layer_visible(9, true);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color::RED);
+    /// ```
+    pub fn set_layer_visible(&mut self, z: usize, visible: bool) {
+        if let Some(settings) = self.layer_settings_mut(z) {
+            settings.hidden = !visible;
+        }
+    }
+
+    /// Returns whether layer `z` is shown, as set by [`Canvas::set_layer_visible`].
+    pub fn is_layer_visible(&self, z: usize) -> bool {
+        !self.layer_settings(z).hidden
+    }
+
+    /// Sets the opacity of layer `z` as a whole, from `0` (invisible) to `255` (opaque), which
+    /// the opacity of each of its pixels is multiplied by, such as to fade an interface in or
+    /// out. Layers past the last one are ignored. Every layer starts opaque.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(4, 2, Color::BLACK);
+    /// canvas.set_pixel(0, 0, 1, Color::WHITE);
+    /// canvas.set_layer_opacity(1, 128);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color::from_hex(0x808080));
+    /// ```
+    pub fn set_layer_opacity(&mut self, z: usize, opacity: u8) {
+        if let Some(settings) = self.layer_settings_mut(z) {
+            settings.opacity = opacity;
+        }
+    }
+
+    /// Returns the opacity of layer `z`, set by [`Canvas::set_layer_opacity`].
+    pub fn layer_opacity(&self, z: usize) -> u8 {
+        self.layer_settings(z).opacity
+    }
+
+    /// Names layer `z` `name`, so code can refer to it by what it holds, such as `"background"`
+    /// or `"ui"`, instead of by its `z`. Names are unique: a name given again moves to the new
+    /// layer. A layer may have several names. Layers past the last one are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(20, 10, Color::BLACK);
+    /// canvas.name_layer(0, "background");
+    /// let ui = canvas.add_named_layer("ui");
+    /// assert_eq!(canvas.layer("ui"), Some(ui));
+    /// canvas.set_pixel(0, 0, ui, Color::RED);
+    /// canvas.set_named_layer_visible("ui", false);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color::BLACK);
+    /// assert!(!canvas.clear_named_layer("particles"));
+    /// ```
+    pub fn name_layer(&mut self, z: usize, name: &str) {
+        if z >= self.max_z_layers {
+            return;
+        }
+        match self.layer_names.iter_mut().find(|(named, _)| named == name) {
+            Some((_, named_z)) => *named_z = z,
+            None => self.layer_names.push((name.to_string(), z)),
+        }
+    }
+
+    /// Adds a transparent z-layer above every other layer, like [`Canvas::add_layer`], names it
+    /// `name` and returns its `z`.
+    pub fn add_named_layer(&mut self, name: &str) -> usize {
+        let z = self.add_layer();
+        self.name_layer(z, name);
+        z
+    }
+
+    /// Removes the name `name`, returning whether it named a layer. The layer keeps its pixels.
+    pub fn remove_layer_name(&mut self, name: &str) -> bool {
+        let count = self.layer_names.len();
+        self.layer_names.retain(|(named, _)| named != name);
+        self.layer_names.len() != count
+    }
+
+    /// Returns the `z` of the layer named `name`, if any.
+    pub fn layer(&self, name: &str) -> Option<usize> {
+        self.layer_names
+            .iter()
+            .find(|(named, _)| named == name)
+            .map(|&(_, z)| z)
+    }
+
+    /// Returns the names of layers with the `z` each names, in the order they were given.
+    pub fn layer_names(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.layer_names.iter().map(|(name, z)| (name.as_str(), *z))
+    }
+
+    /// Makes every pixel of the layer named `name` transparent, like [`Canvas::clear_layer`],
+    /// returning whether the name names a layer.
+    pub fn clear_named_layer(&mut self, name: &str) -> bool {
+        let Some(z) = self.layer(name) else {
+            return false;
+        };
+        self.clear_layer(z);
+        true
+    }
+
+    /// Hides or shows the layer named `name`, like [`Canvas::set_layer_visible`], returning
+    /// whether the name names a layer.
+    pub fn set_named_layer_visible(&mut self, name: &str, visible: bool) -> bool {
+        let Some(z) = self.layer(name) else {
+            return false;
+        };
+        self.set_layer_visible(z, visible);
+        true
+    }
+
+    /// Sets the opacity of the layer named `name`, like [`Canvas::set_layer_opacity`],
+    /// returning whether the name names a layer.
+    pub fn set_named_layer_opacity(&mut self, name: &str, opacity: u8) -> bool {
+        let Some(z) = self.layer(name) else {
+            return false;
+        };
+        self.set_layer_opacity(z, opacity);
+        true
+    }
+
+    /// Returns the opacity the pixels of layer `z` are composited with: its opacity, or `0`
+    /// if it is hidden.
+    pub(crate) fn layer_alpha(&self, z: usize) -> u8 {
+        let settings = self.layer_settings(z);
+        if settings.hidden {
+            0
+        } else {
+            settings.opacity
+        }
+    }
+
+    /// Returns the settings of layer `z`.
+    fn layer_settings(&self, z: usize) -> LayerSettings {
+        self.layer_settings.get(z).copied().unwrap_or_default()
+    }
+
+    /// Returns the settings of layer `z` to change, or `None` past the last layer.
+    fn layer_settings_mut(&mut self, z: usize) -> Option<&mut LayerSettings> {
+        if z >= self.max_z_layers {
+            return None;
+        }
+        if self.layer_settings.len() <= z {
+            self.layer_settings.resize(z + 1, LayerSettings::default());
+        }
+        Some(&mut self.layer_settings[z])
+    }
+}
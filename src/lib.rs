@@ -1,9 +1,205 @@
 //! A simple terminal rendering engine for creating text-based UIs and games.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+mod accessibility;
+#[cfg(feature = "std")]
+mod autotile;
+#[cfg(feature = "std")]
+mod backend;
+#[cfg(feature = "std")]
+mod brush;
+#[cfg(feature = "std")]
+mod camera;
+#[cfg(feature = "std")]
+mod capabilities;
+#[cfg(feature = "std")]
+mod clipboard;
+#[cfg(feature = "std")]
+mod collision;
+mod color;
+#[cfg(feature = "std")]
+pub mod chart;
+#[cfg(feature = "std")]
+mod compose;
+#[cfg(feature = "std")]
+mod crash;
+#[cfg(feature = "std")]
+mod debug_overlay;
+mod dither;
+#[cfg(feature = "std")]
+mod draw;
+#[cfg(feature = "std")]
+mod ecs;
+#[cfg(feature = "std")]
+mod figlet;
+#[cfg(feature = "std")]
+mod fog;
+#[cfg(feature = "std")]
+mod font;
+#[cfg(feature = "std")]
+mod fov;
+mod frame;
+mod layers;
+mod markup;
+#[cfg(feature = "std")]
+mod marquee;
+#[cfg(feature = "std")]
+mod minimap;
+pub mod palette;
+#[cfg(feature = "std")]
+mod path;
+#[cfg(feature = "std")]
+mod pathfinding;
+#[cfg(feature = "std")]
+mod perf_graph;
+#[cfg(feature = "std")]
+mod physics;
+#[cfg(feature = "std")]
+pub mod procgen;
+#[cfg(feature = "std")]
+mod raycaster;
+#[cfg(feature = "std")]
+mod recorder;
+#[cfg(feature = "std")]
+mod render3d;
+mod render_pass;
+#[cfg(feature = "std")]
+mod render_thread;
+#[cfg(feature = "std")]
+mod replay;
+#[cfg(feature = "std")]
+mod rng;
+#[cfg(feature = "std")]
+mod scene;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "std")]
+mod server;
+#[cfg(feature = "std")]
+mod sink;
+#[cfg(feature = "std")]
+mod spatial;
+#[cfg(feature = "std")]
+mod sprite;
+#[cfg(feature = "std")]
+mod terminal;
+#[cfg(feature = "std")]
+mod terrain;
+#[cfg(feature = "std")]
+pub mod testing;
+mod text;
+#[cfg(feature = "tiled")]
+pub mod tiled;
+#[cfg(feature = "std")]
+mod tilemap;
+mod transform;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "ratatui")]
+mod widget;
+#[cfg(feature = "std")]
+mod world;
+pub mod xterm;
+
+pub use accessibility::SemanticRegion;
+#[cfg(feature = "std")]
+pub use autotile::{Autotile, AutotileMode};
+#[cfg(feature = "std")]
+pub use backend::{Backend, CrosstermBackend};
+#[cfg(feature = "std")]
+pub use brush::Brush;
+#[cfg(feature = "std")]
+pub use camera::Camera;
+#[cfg(feature = "std")]
+pub use capabilities::{detect_color_support, Capabilities, CAPABILITY_QUERY};
+#[cfg(feature = "std")]
+pub use clipboard::clipboard_escape;
+#[cfg(feature = "std")]
+pub use collision::CollisionMask;
+pub use color::ParseColorError;
+#[cfg(feature = "std")]
+pub use crash::{install_crash_dump, last_frame};
+#[cfg(feature = "std")]
+pub use debug_overlay::DebugOverlay;
+pub use dither::DitherMode;
+#[cfg(feature = "std")]
+pub use draw::{LineCap, LineStyle};
+#[cfg(feature = "std")]
+pub use ecs::{Entity, World};
+#[cfg(feature = "std")]
+pub use figlet::{FigletError, FigletFont};
+#[cfg(feature = "std")]
+pub use fog::{FogOfWar, Visibility};
+#[cfg(feature = "std")]
+pub use font::{Font, FontError, TextEffects};
+#[cfg(feature = "std")]
+pub use fov::{FieldOfView, FovAlgorithm};
+pub use frame::{CellDiff, Frame};
+pub use markup::{parse_markup, MarkupError, StyledSpan};
+#[cfg(feature = "std")]
+pub use marquee::Marquee;
+#[cfg(feature = "std")]
+pub use minimap::Minimap;
+pub use palette::Palette;
+#[cfg(feature = "std")]
+pub use path::Path;
+#[cfg(feature = "std")]
+pub use pathfinding::{find_path, Diagonals, DijkstraMap, FlowField};
+#[cfg(feature = "std")]
+pub use perf_graph::{Corner, PerfGraph};
+#[cfg(feature = "std")]
+pub use physics::{FixedTimestep, Kinematics};
+#[cfg(feature = "std")]
+pub use raycaster::{Billboard, RaycastView, Raycaster};
+#[cfg(feature = "std")]
+pub use recorder::Recorder;
+#[cfg(feature = "std")]
+pub use render3d::{Mat4, Mesh, MeshStyle, PerspectiveCamera, Renderer3d, Vec3};
+pub use render_pass::RenderPass;
+#[cfg(feature = "std")]
+pub use render_thread::RenderThread;
+#[cfg(feature = "std")]
+pub use replay::Replay;
+#[cfg(feature = "std")]
+pub use rng::Rng;
+#[cfg(feature = "std")]
+pub use scene::{Scene, SceneError};
+#[cfg(feature = "std")]
+pub use server::{Client, ClientId, Server, ServerEvent};
+#[cfg(feature = "std")]
+pub use sink::{MultiSink, SinkId};
+#[cfg(feature = "std")]
+pub use spatial::{Bounds, SpatialHash};
+#[cfg(feature = "std")]
+pub use sprite::Sprite;
+#[cfg(feature = "std")]
+pub use terminal::Terminal;
+#[cfg(feature = "std")]
+pub use terrain::{HeightMap, TerrainRenderer, TerrainView};
+pub use text::{measure_text, wrap_text, Align, Attributes, TextLayout};
+#[cfg(feature = "std")]
+pub use tilemap::{TileMap, Tileset};
+pub use transform::Transform;
+#[cfg(feature = "video")]
+pub use video::{Video, VideoError};
+#[cfg(feature = "ratatui")]
+pub use widget::CanvasView;
+#[cfg(feature = "std")]
+pub use world::{Chunk, ChunkSource, ChunkedWorld};
+
 /// Represents an RGB color with red, green, and blue components.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     /// The red component of the color (0-255).
     pub r: u8,
@@ -13,17 +209,81 @@ pub struct Color {
     pub b: u8,
 }
 
-/// Represents a single half-block pixel with a specific color.
+/// An axis-aligned rectangle in half-block pixel coordinates.
+///
+/// `x` is a terminal column and `y` is a half-block row, matching the coordinates used by
+/// [`Canvas::set_pixel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    /// The left edge of the rectangle.
+    pub x: usize,
+    /// The top edge of the rectangle.
+    pub y: usize,
+    /// The width of the rectangle in pixels.
+    pub width: usize,
+    /// The height of the rectangle in half-block pixels.
+    pub height: usize,
+}
+
+impl Rect {
+    /// Creates a new `Rect` from its top-left corner and size.
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns whether the pixel at (`x`, `y`) is inside the rectangle. Rectangles reaching
+    /// past the largest coordinate contain every pixel from their corner on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::Rect;
+    ///
+    /// let everything = Rect::new(2, 0, usize::MAX, usize::MAX);
+    /// assert!(everything.contains(usize::MAX, 7));
+    /// assert!(!everything.contains(1, 7));
+    /// ```
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x - self.x < self.width && y >= self.y && y - self.y < self.height
+    }
+}
+
+/// Represents a single half-block pixel with a specific color and opacity.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TerminalPixel {
     /// The color of this half-block pixel.
     pub color: Color,
+    /// The opacity of this half-block pixel, from `0` (invisible) to `255` (opaque). The layers
+    /// below show through pixels that are not opaque.
+    pub alpha: u8,
+}
+
+impl TerminalPixel {
+    /// A pixel with nothing drawn, showing the layers below it, or the default color of the
+    /// canvas where no layer has a pixel drawn.
+    pub const TRANSPARENT: TerminalPixel = TerminalPixel {
+        color: Color { r: 0, g: 0, b: 0 },
+        alpha: 0,
+    };
+
+    /// Returns whether nothing is drawn in this pixel, whatever its color.
+    pub const fn is_transparent(self) -> bool {
+        self.alpha == 0
+    }
 }
 
 /// Represents a single terminal character cell after compositing half-block pixels.
 /// This is used for differential rendering, storing the final top and bottom colors
 /// that will be displayed in a single terminal character cell.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CompositedCell {
     /// The color of the top half of the terminal character cell.
     pub top_color: Color,
@@ -31,6 +291,77 @@ pub struct CompositedCell {
     pub bottom_color: Color,
 }
 
+/// A character drawn over a terminal cell instead of its half-block pixels.
+/// Used for marks that need more resolution than half-blocks, like braille plots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CellGlyph {
+    /// The character to draw, or [`WIDE_CONTINUATION`] for the right half of a wide character.
+    pub ch: char,
+    /// A zero-width character drawn with the character, such as a combining accent.
+    pub mark: Option<char>,
+    /// The foreground color of the character.
+    pub color: Color,
+    /// The styles of the character.
+    pub attributes: Attributes,
+}
+
+impl CellGlyph {
+    /// Creates a glyph of `ch` in `color`, without styles.
+    pub(crate) fn new(ch: char, color: Color) -> Self {
+        Self {
+            ch,
+            mark: None,
+            color,
+            attributes: Attributes::NONE,
+        }
+    }
+
+    /// Returns whether the glyph is the right half of a wide character, drawn with the cell on
+    /// its left.
+    pub(crate) fn is_continuation(&self) -> bool {
+        self.ch == WIDE_CONTINUATION
+    }
+}
+
+/// Returns the `grid` of `size` cells, as (width, height), cut or extended with `fill` to
+/// `new_size`, every cell staying at its position.
+fn resized_grid<T: Copy>(
+    grid: &[T],
+    size: (usize, usize),
+    new_size: (usize, usize),
+    fill: T,
+) -> Vec<T> {
+    let mut resized = vec![fill; new_size.0 * new_size.1];
+    let width = size.0.min(new_size.0);
+    for y in 0..size.1.min(new_size.1) {
+        resized[y * new_size.0..y * new_size.0 + width]
+            .copy_from_slice(&grid[y * size.0..y * size.0 + width]);
+    }
+    resized
+}
+
+/// The character held by the cell covered by the right half of a wide character, such as a CJK
+/// ideograph, which terminals draw over two cells.
+pub(crate) const WIDE_CONTINUATION: char = '\0';
+
+/// A character of the text plane, placed with [`Canvas::put_str`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct TextCell {
+    /// The character to show, or [`WIDE_CONTINUATION`] for the right half of a wide character.
+    pub ch: char,
+    /// A zero-width character shown with the character, such as a combining accent.
+    pub mark: Option<char>,
+    /// The color of the character.
+    pub foreground: Color,
+    /// The background of the cell, or `None` to keep the colors of the pixels behind.
+    pub background: Option<Color>,
+    /// The styles of the character.
+    pub attributes: Attributes,
+    /// Whether the character is shown only where no pixel of the cell is drawn.
+    pub behind: bool,
+}
+
 /// A canvas for drawing to the terminal, like a digital picasso.
 ///
 /// The `Canvas` represents a 3D grid of `TerminalPixel`s that can be drawn to.
@@ -42,9 +373,11 @@ pub struct Canvas {
     pub width: usize,
     /// The height of the canvas in terminal character rows.
     pub height: usize,
-    /// The final width of the canvas in terminal character columns.
+    /// The width of the canvas in pixels, which drawing coordinates span: the width in columns
+    /// times the supersampling factor.
     pub final_width: usize,
-    /// The final height of the canvas in terminal character rows.
+    /// The height of the canvas in pixels, which drawing coordinates span: two pixels per row
+    /// times the supersampling factor.
     pub final_height: usize,
     /// Stores the 3D grid of half-block pixels. Indexed by (x, y_half_block, z_layer).
     pixels: Vec<TerminalPixel>,
@@ -52,10 +385,120 @@ pub struct Canvas {
     composited_cells: Vec<CompositedCell>,
     /// Stores the 2D grid of previously composited terminal cells. Used for differential rendering.
     previous_composited_cells: Vec<CompositedCell>,
-    /// The default color used for clearing the canvas and for transparent pixels.
+    /// Stores the characters drawn over terminal cells, on top of every z-layer.
+    glyphs: Vec<Option<CellGlyph>>,
+    /// Stores the characters of the text plane, shown over or behind the pixels and glyphs.
+    text: Vec<Option<TextCell>>,
+    /// Stores the brightness of every half-block pixel, applied on top of every z-layer.
+    shade: Vec<u8>,
+    /// The background color, shown wherever no z-layer has a pixel drawn. Pixels drawn in it
+    /// on any layer are opaque like any other color.
     pub default_color: Color,
-    /// The maximum number of z-layers supported by the canvas.
+    /// Whether pixels in the default color are drawn in the terminal's default background.
+    terminal_default: bool,
+    /// The number of z-layers of the canvas, set by [`Canvas::set_layer_count`].
     max_z_layers: usize,
+    /// The visibility and opacity of each z-layer, by `z`. Layers past the end are shown.
+    layer_settings: Vec<layers::LayerSettings>,
+    /// The names of z-layers, with the `z` each names.
+    layer_names: Vec<(String, usize)>,
+    /// How many pixels across and down make up every half-block pixel shown.
+    supersampling: usize,
+    /// The width of a pixel on screen divided by its height, which circles are corrected for.
+    pixel_aspect: f32,
+    /// The transform the drawing primitives apply to what they draw.
+    transform: Transform,
+    /// The transforms saved by [`Canvas::push_transform`], the last saved last.
+    transform_stack: Vec<Transform>,
+    /// The color grade every composited color goes through, if any.
+    color_grade: Option<Box<dyn Fn(Color) -> Color + Send>>,
+    /// The flash shown over the canvas, with the number of frames it still lasts.
+    flash: Option<(Flash, usize)>,
+    /// How colors are sent to the terminal.
+    color_mode: ColorMode,
+    /// How colors between the colors of the palette are drawn.
+    dither_mode: DitherMode,
+    /// The palette every color shown is restricted to, if any.
+    palette: Option<Palette>,
+    /// Whether fractional positions alternate between the nearest pixels over frames.
+    motion_smoothing: bool,
+    /// The number of frames presented, which moves the motion smoothing pattern on.
+    frames: u64,
+    /// The terminal rows drawn by the next frame when interlaced, `0` for even and `1` for odd.
+    interlace: Option<usize>,
+    /// The area outside of which drawing is ignored, if any.
+    clip: Option<Rect>,
+    /// The opacity of the pixels drawn, from `0` (invisible) to `255` (opaque).
+    opacity: u8,
+    /// The areas whose changes are presented first under a byte budget, with their priorities.
+    priorities: Vec<(Rect, u8)>,
+    /// The render passes run on every frame, in order.
+    passes: Vec<Box<dyn RenderPass>>,
+    /// The semantic regions describing the canvas, for screen readers.
+    regions: Vec<SemanticRegion>,
+    /// The label of the region that has the focus.
+    focused_region: Option<String>,
+}
+
+/// How colors are sent to the terminal.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, ColorMode};
+///
+/// let mut canvas = Canvas::new(1, 1, Color { r: 255, g: 135, b: 0 });
+/// canvas.set_color_mode(ColorMode::Xterm256);
+/// assert!(canvas.render().contains("\x1b[38;5;208m"));
+/// // Orange is closest to the yellow of the 16 ANSI colors.
+/// canvas.set_color_mode(ColorMode::Ansi16);
+/// assert!(canvas.render().contains("\x1b[33m"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorMode {
+    /// 24-bit colors, sent exactly as drawn.
+    #[default]
+    TrueColor,
+    /// The closest colors of the xterm 256-color palette, for terminals without 24-bit colors.
+    /// See [`xterm`].
+    Xterm256,
+    /// The closest of the 16 ANSI system colors, for the most basic terminals and for
+    /// multiplexers configured without 256 colors. Terminal themes change how these look. See
+    /// [`palette::ansi`].
+    Ansi16,
+}
+
+impl ColorMode {
+    /// Returns whether `a` and `b` are sent as the same color.
+    fn same_color(self, a: Color, b: Color) -> bool {
+        match self {
+            ColorMode::TrueColor => a == b,
+            ColorMode::Xterm256 => xterm::nearest(a) == xterm::nearest(b),
+            ColorMode::Ansi16 => palette::ansi::nearest(a) == palette::ansi::nearest(b),
+        }
+    }
+}
+
+/// A full-screen flash shown over a canvas for a few frames, as feedback for alerts.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Flash};
+///
+/// let mut canvas = Canvas::new(10, 5, Color { r: 0, g: 0, b: 0 });
+/// canvas.flash(Flash::Invert, 2);
+/// assert_eq!(canvas.composited_cell(0, 0).top_color, Color { r: 255, g: 255, b: 255 });
+/// let _ = canvas.render();
+/// let _ = canvas.render();
+/// assert!(!canvas.is_flashing());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flash {
+    /// Inverts every color, characters included.
+    Invert,
+    /// Covers the whole canvas, characters included, with one color, such as white.
+    Color(Color),
 }
 
 impl Canvas {
@@ -71,12 +514,79 @@ impl Canvas {
     ///
     /// # Returns
     ///
-    /// A new `Canvas` instance.
+    /// A new `Canvas` instance, with 10 z-layers.
     pub fn new(width: usize, height: usize, default_color: Color) -> Self {
-        let initial_pixel = TerminalPixel {
-            color: default_color,
-        };
+        Self::with_layers(width, height, default_color, Self::DEFAULT_MAX_Z_LAYERS)
+    }
+
+    /// Creates a new `Canvas` like [`Canvas::new`], with `layers` z-layers, at least one. Every
+    /// layer holds a full set of pixels, so simple scenes save memory with fewer layers, while
+    /// complex ones, with parallax backgrounds, tiles, entities, particles and interface, can
+    /// have as many as they need. More layers can be added later by [`Canvas::add_layer`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::with_layers(20, 10, Color::BLACK, 2);
+    /// assert_eq!(canvas.layer_count(), 2);
+    /// let debug = canvas.add_layer();
+    /// assert_eq!(debug, 2);
+    /// canvas.set_pixel(0, 0, debug, Color::RED);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color::RED);
+    /// ```
+    pub fn with_layers(width: usize, height: usize, default_color: Color, layers: usize) -> Self {
+        let layers = layers.max(1);
+        let (composited_cells, previous_composited_cells) =
+            Self::fresh_composited_cells(width * height, default_color);
 
+        let total_half_block_pixels = width * height * 2 * layers;
+        let total_terminal_cells = width * height;
+
+        Self {
+            width,
+            height,
+            final_width: width,
+            final_height: height * 2,
+            pixels: vec![TerminalPixel::TRANSPARENT; total_half_block_pixels],
+            composited_cells,
+            previous_composited_cells,
+            glyphs: vec![None; total_terminal_cells],
+            text: vec![None; total_terminal_cells],
+            shade: vec![u8::MAX; total_terminal_cells * 2],
+            default_color,
+            terminal_default: false,
+            max_z_layers: layers,
+            layer_settings: Vec::new(),
+            layer_names: Vec::new(),
+            supersampling: 1,
+            pixel_aspect: 1.0,
+            transform: Transform::IDENTITY,
+            transform_stack: Vec::new(),
+            clip: None,
+            opacity: u8::MAX,
+            color_grade: None,
+            flash: None,
+            color_mode: ColorMode::TrueColor,
+            dither_mode: DitherMode::None,
+            palette: None,
+            motion_smoothing: false,
+            frames: 0,
+            interlace: None,
+            priorities: Vec::new(),
+            passes: Vec::new(),
+            regions: Vec::new(),
+            focused_region: None,
+        }
+    }
+
+    /// Returns the composited cells of a canvas of `cells` cells that was never rendered, and
+    /// previous cells differing from them, so the first render draws every cell.
+    fn fresh_composited_cells(
+        cells: usize,
+        default_color: Color,
+    ) -> (Vec<CompositedCell>, Vec<CompositedCell>) {
         let initial_composited_cell = CompositedCell {
             top_color: default_color,
             bottom_color: default_color,
@@ -91,40 +601,191 @@ impl Canvas {
             top_color: opposite_color,
             bottom_color: opposite_color,
         };
+        (
+            vec![initial_composited_cell; cells],
+            vec![different_composited_cell; cells],
+        )
+    }
 
-        let total_half_block_pixels = width * height * 2 * Self::DEFAULT_MAX_Z_LAYERS;
-        let total_terminal_cells = width * height;
+    /// Clears the entire canvas to the `default_color`.
+    /// All half-block pixels across all z-layers are made transparent.
+    pub fn clear(&mut self) {
+        self.pixels.fill(TerminalPixel::TRANSPARENT);
+        for glyph in self.glyphs.iter_mut() {
+            *glyph = None;
+        }
+        self.text.fill(None);
+        self.shade.fill(u8::MAX);
+    }
 
-        Self {
-            width,
-            height,
-            final_width: width,
-            final_height: height * 2,
-            pixels: vec![initial_pixel; total_half_block_pixels],
-            composited_cells: vec![initial_composited_cell; total_terminal_cells],
-            previous_composited_cells: vec![different_composited_cell; total_terminal_cells],
-            default_color,
-            max_z_layers: Self::DEFAULT_MAX_Z_LAYERS,
+    /// Returns the number of z-layers of the canvas, which `z` coordinates are below.
+    pub fn layer_count(&self) -> usize {
+        self.max_z_layers
+    }
+
+    /// Adds a transparent z-layer above every other layer and returns its `z`.
+    pub fn add_layer(&mut self) -> usize {
+        self.set_layer_count(self.max_z_layers + 1);
+        self.max_z_layers - 1
+    }
+
+    /// Sets the number of z-layers of the canvas, at least one. New layers are transparent and
+    /// shown, and removing layers from the top loses their pixels.
+    pub fn set_layer_count(&mut self, layers: usize) {
+        let layers = layers.max(1);
+        let layer = self.final_width * self.final_height;
+        self.pixels.resize(layer * layers, TerminalPixel::TRANSPARENT);
+        self.layer_settings.truncate(layers);
+        self.layer_names.retain(|&(_, z)| z < layers);
+        self.max_z_layers = layers;
+    }
+
+    /// Makes every pixel of layer `z` transparent, leaving the other layers, the glyphs, the
+    /// text and the shade untouched. Wipe the layers of moving sprites and particles every
+    /// frame while the background and tile map drawn on lower layers stay. Layers past the
+    /// last one are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(4, 2, Color::BLACK);
+    /// canvas.fill_rect(0, 0, 4, 4, 0, Color::BLUE);
+    /// canvas.set_pixel(1, 0, 1, Color::RED);
+    /// canvas.clear_layer(1);
+    /// assert_eq!(canvas.composited_cell(1, 0).top_color, Color::BLUE);
+    /// ```
+    pub fn clear_layer(&mut self, z: usize) {
+        if z >= self.max_z_layers {
+            return;
         }
+        let layer = self.final_width * self.final_height;
+        self.pixels[z * layer..(z + 1) * layer].fill(TerminalPixel::TRANSPARENT);
     }
 
-    /// Clears the entire canvas to the `default_color`.
-    /// All half-block pixels across all z-layers are reset to the `default_color`.
-    pub fn clear(&mut self) {
-        let initial_pixel = TerminalPixel {
-            color: self.default_color,
+    /// Makes the pixels of layer `z` inside `area`, in pixels, transparent, like
+    /// [`Canvas::clear_layer`]. Parts of the area outside the canvas are ignored.
+    pub fn clear_layer_rect(&mut self, z: usize, area: Rect) {
+        let right = area.x.saturating_add(area.width).min(self.final_width);
+        let bottom = area.y.saturating_add(area.height).min(self.final_height);
+        if area.x >= right {
+            return;
+        }
+        for y in area.y..bottom {
+            let Some(start) = self.get_index(area.x, y, z) else {
+                return;
+            };
+            self.pixels[start..start + right - area.x].fill(TerminalPixel::TRANSPARENT);
+        }
+    }
+
+    /// Changes the default color of the canvas, shown wherever no z-layer has a pixel drawn.
+    ///
+    /// Pixels drawn keep their colors, the old default color included, and transparent pixels
+    /// show the new background. Every cell is drawn again by the next render.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let blue = Color { r: 0, g: 0, b: 255 };
+    /// let mut canvas = Canvas::new(4, 2, Color { r: 0, g: 0, b: 0 });
+    /// canvas.set_pixel(0, 0, 3, red);
+    /// canvas.set_default_color(blue);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, red);
+    /// assert_eq!(canvas.composited_cell(0, 0).bottom_color, blue);
+    /// ```
+    pub fn set_default_color(&mut self, color: Color) {
+        if self.default_color == color {
+            return;
+        }
+        self.default_color = color;
+        let opposite_color = Color {
+            r: 255 - color.r,
+            g: 255 - color.g,
+            b: 255 - color.b,
         };
-        for pixel in self.pixels.iter_mut() {
-            *pixel = initial_pixel;
+        self.previous_composited_cells.fill(CompositedCell {
+            top_color: opposite_color,
+            bottom_color: opposite_color,
+        });
+    }
+
+    /// Sets whether pixels showing the default color are drawn in the terminal's own default
+    /// background color instead, such as a translucent terminal background or the color of the
+    /// user's theme. Characters over such pixels keep the terminal's background too. Applies to
+    /// [`Canvas::render`] and to every way of presenting the canvas. Defaults to `false`.
+    pub fn set_terminal_default(&mut self, enabled: bool) {
+        self.terminal_default = enabled;
+    }
+
+    /// Returns whether pixels showing the default color are drawn in the terminal's default
+    /// background color.
+    pub fn is_terminal_default(&self) -> bool {
+        self.terminal_default
+    }
+
+    /// Returns the color drawn in the terminal's default background color, if any.
+    pub(crate) fn terminal_default_color(&self) -> Option<Color> {
+        self.terminal_default.then(|| self.graded(self.default_color))
+    }
+
+    /// Darkens the half-block pixel at (x, y) after all z-layers have been composited.
+    ///
+    /// The shade is a darkening layer above every z-layer, used for effects like fog of war and
+    /// lighting. It is reset to full brightness by [`Canvas::clear`].
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The terminal column coordinate (0-indexed).
+    /// * `y` - The half-block row coordinate (0-indexed).
+    /// * `brightness` - How much of the pixel's color is kept, from `0` (black) to `255`
+    ///   (unchanged).
+    pub fn set_shade(&mut self, x: usize, y: usize, brightness: u8) {
+        if x < self.final_width && y < self.final_height {
+            self.shade[y * self.final_width + x] = brightness;
+        }
+    }
+
+    /// Returns the brightness of the half-block pixel at (x, y), as set by [`Canvas::set_shade`].
+    pub fn shade(&self, x: usize, y: usize) -> u8 {
+        if x < self.final_width && y < self.final_height {
+            self.shade[y * self.final_width + x]
+        } else {
+            u8::MAX
         }
     }
 
+    /// Returns the glyph drawn over the terminal cell at (`column`, `row`), if any.
+    pub(crate) fn glyph(&self, column: usize, row: usize) -> Option<CellGlyph> {
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        self.glyphs[row * self.width + column]
+    }
+
+    /// Draws `glyph` over the terminal cell at (`column`, `row`), hiding its half-block pixels.
+    /// The cell's top half-block color is used as the background of the glyph.
+    pub(crate) fn set_glyph(&mut self, column: usize, row: usize, glyph: CellGlyph) {
+        if column >= self.width || row >= self.height {
+            return;
+        }
+        let factor = self.supersampling;
+        if self.is_clipped(column * factor, row * 2 * factor) {
+            return;
+        }
+        self.glyphs[row * self.width + column] = Some(glyph);
+    }
+
     fn get_index(&self, x: usize, y: usize, z: usize) -> Option<usize> {
-        if x >= self.width { return None; }
-        if y >= self.height * 2 { return None; } // y is now half-block row
+        if x >= self.final_width { return None; }
+        if y >= self.final_height { return None; } // y is now half-block row
         if z >= self.max_z_layers { return None; }
 
-        Some(x + (y * self.width) + (z * self.width * self.height * 2))
+        Some(x + (y * self.final_width) + (z * self.final_width * self.final_height))
     }
 
     /// Sets a half-block pixel at the specified (x, y) coordinate and z-layer with the given color.
@@ -133,18 +794,605 @@ impl Canvas {
     ///
     /// * `x` - The terminal column coordinate (0-indexed).
     /// * `y` - The half-block row coordinate (0-indexed).
-    ///         - `y = 0` corresponds to the top half of the first terminal cell row.
-    ///         - `y = 1` corresponds to the bottom half of the first terminal cell row.
-    ///         - `y = 2` corresponds to the top half of the second terminal cell row, and so on.
+    ///   - `y = 0` corresponds to the top half of the first terminal cell row.
+    ///   - `y = 1` corresponds to the bottom half of the first terminal cell row.
+    ///   - `y = 2` corresponds to the top half of the second terminal cell row, and so on.
     /// * `z` - The z-layer (depth) of the pixel. Higher `z` values are drawn on top of lower `z` values.
     /// * `color` - The `Color` to set for the pixel.
+    ///
+    /// The pixel takes the opacity set by [`Canvas::set_opacity`].
     pub fn set_pixel(&mut self, x: usize, y: usize, z: usize, color: Color) {
+        self.set_pixel_alpha(x, y, z, color, self.opacity);
+    }
+
+    /// Sets the half-block pixel at (`x`, `y`) on layer `z` to `color` with the opacity `alpha`,
+    /// from `0` (invisible) to `255` (opaque), like [`Canvas::set_pixel`]. The layers below show
+    /// through the pixel in proportion, for smoke, glass and fading particles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(4, 2, Color { r: 0, g: 0, b: 0 });
+    /// canvas.set_pixel(0, 0, 0, Color { r: 200, g: 0, b: 0 });
+    /// canvas.set_pixel_alpha(0, 0, 1, Color { r: 0, g: 0, b: 200 }, 128);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color { r: 100, g: 0, b: 100 });
+    /// ```
+    pub fn set_pixel_alpha(&mut self, x: usize, y: usize, z: usize, color: Color, alpha: u8) {
+        if self.is_clipped(x, y) {
+            return;
+        }
         if let Some(index) = self.get_index(x, y, z) {
-            let pixel = &mut self.pixels[index];
-            pixel.color = color;
+            self.pixels[index] = TerminalPixel { color, alpha };
+        }
+    }
+
+    /// Makes the half-block pixel at (`x`, `y`) on layer `z` transparent, showing the layers
+    /// below it, unlike drawing it in the default color. Pixels outside the clip rectangle are
+    /// left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let black = Color { r: 0, g: 0, b: 0 };
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let mut canvas = Canvas::new(4, 2, black);
+    /// canvas.set_pixel(0, 0, 0, red);
+    /// // The default color is opaque on an upper layer, hiding the red pixel.
+    /// canvas.set_pixel(0, 0, 1, black);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, black);
+    /// canvas.clear_pixel(0, 0, 1);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, red);
+    /// ```
+    pub fn clear_pixel(&mut self, x: usize, y: usize, z: usize) {
+        if self.is_clipped(x, y) {
+            return;
+        }
+        if let Some(index) = self.get_index(x, y, z) {
+            self.pixels[index] = TerminalPixel::TRANSPARENT;
+        }
+    }
+
+    /// Sets the opacity of the pixels drawn from now on, from `0` (invisible) to `255` (opaque),
+    /// by [`Canvas::set_pixel`] and every drawing primitive. The layers below show through the
+    /// pixels drawn, such as a glass panel over a scene or a drop shadow. Pixels drawn replace
+    /// the pixels of their layer, anti-aliased edges excepted. Defaults to `255`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(10, 5, Color { r: 0, g: 0, b: 0 });
+    /// canvas.fill_rect(0, 0, 10, 10, 0, Color { r: 255, g: 255, b: 255 });
+    /// canvas.set_opacity(64);
+    /// canvas.fill_rect(0, 0, 5, 10, 1, Color { r: 0, g: 0, b: 255 });
+    /// canvas.set_opacity(255);
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color { r: 191, g: 191, b: 255 });
+    /// ```
+    pub fn set_opacity(&mut self, opacity: u8) {
+        self.opacity = opacity;
+    }
+
+    /// Returns the opacity of the pixels drawn, set by [`Canvas::set_opacity`].
+    pub fn opacity(&self) -> u8 {
+        self.opacity
+    }
+
+    /// Composites all z-layers and the shade of the terminal cell at (`column`, `row`), returning
+    /// the colors it shows. Characters drawn over the cell are not included.
+    ///
+    /// Cells outside the canvas are reported as fully transparent, in the `default_color`. On a
+    /// supersampled canvas, every half-block is the average of the pixels it covers.
+    pub fn composited_cell(&self, column: usize, row: usize) -> CompositedCell {
+        let mut cell = CompositedCell {
+            top_color: self.default_color,
+            bottom_color: self.default_color,
+        };
+        if column >= self.width || row >= self.height {
+            return cell;
+        }
+        let factor = self.supersampling;
+        for (y, color) in [(row * 2, &mut cell.top_color), (row * 2 + 1, &mut cell.bottom_color)] {
+            if factor == 1 {
+                *color = self.composited_pixel(column, y);
+            } else {
+                // Box filter the pixels covering the half-block.
+                let mut sum = [0u32; 3];
+                for sample_y in y * factor..(y + 1) * factor {
+                    for sample_x in column * factor..(column + 1) * factor {
+                        let sample = self.composited_pixel(sample_x, sample_y);
+                        sum[0] += sample.r as u32;
+                        sum[1] += sample.g as u32;
+                        sum[2] += sample.b as u32;
+                    }
+                }
+                let samples = (factor * factor) as u32;
+                *color = Color {
+                    r: (sum[0] / samples) as u8,
+                    g: (sum[1] / samples) as u8,
+                    b: (sum[2] / samples) as u8,
+                };
+            }
+            *color = self.graded(*color);
+            if let Some((flash, _)) = self.flash {
+                *color = flash.apply(*color);
+            }
+        }
+        cell
+    }
+
+    /// Composites all z-layers and the shade of the pixel at (`x`, `y`), in drawing coordinates.
+    fn composited_pixel(&self, x: usize, y: usize) -> Color {
+        let Some(bottom) = self.get_index(x, y, 0) else {
+            return shade_color(self.default_color, self.shade(x, y));
+        };
+        let layer = self.final_width * self.final_height;
+        let stack = (0..self.max_z_layers).map(|z| {
+            let pixel = self.pixels[bottom + z * layer];
+            let alpha = (pixel.alpha as u32 * self.layer_alpha(z) as u32 + 127) / 255;
+            TerminalPixel {
+                alpha: alpha as u8,
+                ..pixel
+            }
+        });
+        // Layers under the highest opaque pixel are hidden by it.
+        let lowest = stack
+            .clone()
+            .rposition(|pixel| pixel.alpha == u8::MAX)
+            .unwrap_or(0);
+        let color = stack
+            .skip(lowest)
+            .filter(|pixel| !pixel.is_transparent())
+            .fold(self.default_color, |below, pixel| {
+                pixel.color.blend(below, pixel.alpha)
+            });
+        shade_color(color, self.shade(x, y))
+    }
+
+    /// Returns the character shown over the terminal cell at (`column`, `row`), from the text
+    /// plane or the glyphs, with the shade of the cell applied to its color.
+    pub(crate) fn composited_glyph(&self, column: usize, row: usize) -> Option<CellGlyph> {
+        let glyph = match self.visible_text(column, row) {
+            Some(text) => CellGlyph {
+                ch: text.ch,
+                mark: text.mark,
+                color: text.foreground,
+                attributes: text.attributes,
+            },
+            None => self.glyph(column, row)?,
+        };
+        let color = self.composited_cell_color(glyph.color, column, row)?;
+        Some(CellGlyph { color, ..glyph })
+    }
+
+    /// Composites the terminal cell at (`column`, `row`) like [`Canvas::composited_cell`],
+    /// with the background of the text shown over it, if any, and returns it with the character
+    /// shown over it. The right half of a wide character shows the colors of its left half.
+    pub(crate) fn composited(
+        &self,
+        column: usize,
+        row: usize,
+    ) -> (CompositedCell, Option<CellGlyph>) {
+        let text = self.visible_text(column, row);
+        let colors_column = match text {
+            Some(text) if text.ch == WIDE_CONTINUATION => column - 1,
+            _ => column,
+        };
+        let mut cell = self.composited_cell(colors_column, row);
+        let background = text
+            .and_then(|text| text.background)
+            .and_then(|background| self.composited_cell_color(background, column, row));
+        if let Some(background) = background {
+            cell.top_color = background;
+            cell.bottom_color = background;
+        }
+        (cell, self.composited_glyph(column, row))
+    }
+
+    /// Returns `color` as shown over the terminal cell at (`column`, `row`), with the shade of
+    /// the cell and the flash applied, or `None` if the flash covers it.
+    fn composited_cell_color(&self, color: Color, column: usize, row: usize) -> Option<Color> {
+        let factor = self.supersampling;
+        let color = shade_color(color, self.shade(column * factor, row * 2 * factor));
+        let color = self.graded(color);
+        match self.flash {
+            Some((Flash::Color(_), _)) => None,
+            Some((flash, _)) => Some(flash.apply(color)),
+            None => Some(color),
+        }
+    }
+
+    /// Returns the character of the text plane shown at (`column`, `row`): one placed over the
+    /// graphics, or one placed behind them where the cell has no glyph and no pixel drawn.
+    fn visible_text(&self, column: usize, row: usize) -> Option<TextCell> {
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        let text = self.text[row * self.width + column]?;
+        // Both halves of a wide character are hidden together.
+        let is_continuation = |column: usize| {
+            column < self.width
+                && self.text[row * self.width + column]
+                    .is_some_and(|text| text.ch == WIDE_CONTINUATION)
+        };
+        let columns = if text.ch == WIDE_CONTINUATION {
+            column - 1..=column
+        } else if is_continuation(column + 1) {
+            column..=column + 1
+        } else {
+            column..=column
+        };
+        let hidden = text.behind
+            && columns.into_iter().any(|column| {
+                self.glyph(column, row).is_some() || !self.is_cell_empty(column, row)
+            });
+        (!hidden).then_some(text)
+    }
+
+    /// Returns whether no pixel of the terminal cell at (`column`, `row`) is drawn on any layer.
+    fn is_cell_empty(&self, column: usize, row: usize) -> bool {
+        self.is_half_block_empty(column, row * 2) && self.is_half_block_empty(column, row * 2 + 1)
+    }
+
+    /// Returns whether no pixel of the half-block at (`column`, `y`), in half-block rows, is
+    /// drawn on any layer shown.
+    pub(crate) fn is_half_block_empty(&self, column: usize, y: usize) -> bool {
+        let factor = self.supersampling;
+        let mut layers = (0..self.max_z_layers).filter(|&z| self.layer_alpha(z) > 0);
+        layers.all(|z| {
+            (y * factor..(y + 1) * factor).all(|y| {
+                let Some(start) = self.get_index(column * factor, y, z) else {
+                    return true;
+                };
+                self.pixels[start..start + factor]
+                    .iter()
+                    .all(|pixel| pixel.is_transparent())
+            })
+        })
+    }
+
+    /// Shows `flash` over the whole canvas for the next `frames` frames, replacing any flash
+    /// in progress. A frame is counted every time the canvas is rendered with
+    /// [`Canvas::render`], or presented by a terminal, a multi-sink or a server client.
+    pub fn flash(&mut self, flash: Flash, frames: usize) {
+        self.flash = (frames > 0).then_some((flash, frames));
+    }
+
+    /// Sets the color grade every color shown goes through once the z-layers are composited,
+    /// characters included, before the cells are compared with the last frame. Tint the whole
+    /// scene for a night time or a sepia flashback without drawing it differently. Runs before
+    /// the flash, the palette and the render passes. Replaces the previous grade.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(4, 2, Color::BLACK);
+    /// canvas.fill_rect(0, 0, 4, 4, 0, Color::from_hex(0xC08040));
+    /// // Night: darker and bluer.
+    /// canvas.set_color_grade(|color| color * Color::from_hex(0x6070A0));
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color::from_hex(0x483828));
+    /// canvas.clear_color_grade();
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color::from_hex(0xC08040));
+    /// ```
+    pub fn set_color_grade(&mut self, grade: impl Fn(Color) -> Color + Send + 'static) {
+        self.color_grade = Some(Box::new(grade));
+    }
+
+    /// Removes the color grade, showing colors as composited.
+    pub fn clear_color_grade(&mut self) {
+        self.color_grade = None;
+    }
+
+    /// Returns whether a color grade is set.
+    pub fn has_color_grade(&self) -> bool {
+        self.color_grade.is_some()
+    }
+
+    /// Returns `color` through the color grade, if any.
+    fn graded(&self, color: Color) -> Color {
+        match &self.color_grade {
+            Some(grade) => grade(color),
+            None => color,
+        }
+    }
+
+    /// Returns whether a flash is shown over the canvas.
+    pub fn is_flashing(&self) -> bool {
+        self.flash.is_some()
+    }
+
+    /// Counts a presented frame, ending the flash shown over the canvas after its last frame and
+    /// moving the motion smoothing pattern on.
+    pub(crate) fn count_frame(&mut self) {
+        self.frames = self.frames.wrapping_add(1);
+        if let Some((_, frames)) = &mut self.flash {
+            *frames -= 1;
+            if *frames == 0 {
+                self.flash = None;
+            }
         }
     }
 
+    /// Sets the width of a pixel on screen divided by its height, so circles look round with
+    /// fonts whose cells are not exactly twice as tall as they are wide. For example, `0.9` for
+    /// cells of 9 by 20 screen pixels, whose half-blocks are 9 by 10. Defaults to `1.0`, for
+    /// square pixels.
+    pub fn set_pixel_aspect(&mut self, aspect: f32) {
+        self.pixel_aspect = aspect;
+    }
+
+    /// Returns the width of a pixel on screen divided by its height.
+    pub fn pixel_aspect(&self) -> f32 {
+        self.pixel_aspect
+    }
+
+    /// Sets whether objects at fractional positions, placed with [`Canvas::subpixel_position`],
+    /// alternate between the two nearest pixels over frames, in proportion to how close they
+    /// are to each. Slow-moving sprites then glide instead of jumping a whole pixel at a time,
+    /// at the cost of a slight shimmer on their edges. Defaults to `false`, where fractional
+    /// positions are rounded.
+    pub fn set_motion_smoothing(&mut self, enabled: bool) {
+        self.motion_smoothing = enabled;
+    }
+
+    /// Returns whether fractional positions alternate between the nearest pixels over frames.
+    pub fn is_motion_smoothing(&self) -> bool {
+        self.motion_smoothing
+    }
+
+    /// Returns the pixel to draw something at the fractional position (`x`, `y`) on in the
+    /// current frame: the nearest pixel, or with motion smoothing, one of the two nearest pixels
+    /// on each axis, following an ordered dithering pattern over 8 frames so that the position
+    /// averages out to (`x`, `y`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(10, 5, Color { r: 0, g: 0, b: 0 });
+    /// assert_eq!(canvas.subpixel_position(2.25, 3.75), (2, 4));
+    ///
+    /// canvas.set_motion_smoothing(true);
+    /// let mut total_x = 0;
+    /// for _ in 0..8 {
+    ///     total_x += canvas.subpixel_position(2.25, 3.75).0;
+    ///     let _ = canvas.render();
+    /// }
+    /// // A quarter of the frames are drawn one pixel to the right.
+    /// assert_eq!(total_x, 2 * 8 + 2);
+    /// ```
+    pub fn subpixel_position(&self, x: f32, y: f32) -> (isize, isize) {
+        // Thresholds spread evenly over 8 frames, in an order where every run of frames
+        // covers them about evenly.
+        const PATTERN: [f32; 8] = [
+            0.0625, 0.5625, 0.3125, 0.8125, 0.1875, 0.6875, 0.4375, 0.9375,
+        ];
+        let snap = |position: f32, phase: usize| {
+            let truncated = position as isize;
+            let whole = if truncated as f32 > position {
+                truncated - 1
+            } else {
+                truncated
+            };
+            let threshold = if self.motion_smoothing {
+                PATTERN[(self.frames as usize + phase) % PATTERN.len()]
+            } else {
+                0.5
+            };
+            whole + (position - whole as f32 >= threshold) as isize
+        };
+        (snap(x, 0), snap(y, 4))
+    }
+
+    /// Keeps `factor` by `factor` pixels for every half-block pixel shown, such as `2` or `4`,
+    /// averaging them when the canvas is composited. Lines, circles and rotated sprites get
+    /// smoother edges, for `factor` squared times the memory.
+    ///
+    /// Drawing coordinates are multiplied by `factor` and span [`Canvas::final_width`] by
+    /// [`Canvas::final_height`] pixels, while terminal cells, characters and the areas given to
+    /// [`Canvas::set_priority`] keep their coordinates. Changing the factor clears every pixel
+    /// and the shade. Defaults to `1`, no supersampling.
+    ///
+    /// [`Canvas::final_width`]: Canvas#structfield.final_width
+    /// [`Canvas::final_height`]: Canvas#structfield.final_height
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(10, 5, Color { r: 0, g: 0, b: 0 });
+    /// canvas.set_supersampling(2);
+    /// assert_eq!((canvas.final_width, canvas.final_height), (20, 20));
+    /// // One of the four pixels of a half-block gives it a quarter of the color.
+    /// canvas.set_pixel(0, 0, 0, Color { r: 200, g: 200, b: 200 });
+    /// assert_eq!(canvas.composited_cell(0, 0).top_color, Color { r: 50, g: 50, b: 50 });
+    /// ```
+    pub fn set_supersampling(&mut self, factor: usize) {
+        let factor = factor.max(1);
+        self.supersampling = factor;
+        self.final_width = self.width * factor;
+        self.final_height = self.height * 2 * factor;
+        let pixels = self.final_width * self.final_height;
+        self.pixels = vec![TerminalPixel::TRANSPARENT; pixels * self.max_z_layers];
+        self.shade = vec![u8::MAX; pixels];
+    }
+
+    /// Changes the size of the canvas to `width` columns by `height` rows, such as when the
+    /// terminal is resized, keeping what is drawn where it is: the pixels of every layer, the
+    /// characters, the text and the shade. What falls outside the new size is cut off, and new
+    /// areas are transparent. Every setting is kept, and every cell is drawn again by the next
+    /// render.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let mut canvas = Canvas::new(10, 5, Color::BLACK);
+    /// canvas.set_pixel(2, 3, 0, Color::RED);
+    /// canvas.resize(20, 8);
+    /// assert_eq!((canvas.final_width, canvas.final_height), (20, 16));
+    /// assert_eq!(canvas.composited_cell(2, 1).bottom_color, Color::RED);
+    /// ```
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let factor = self.supersampling;
+        let (old_width, old_height) = (self.final_width, self.final_height);
+        let (new_width, new_height) = (width * factor, height * 2 * factor);
+        let layer = old_width * old_height;
+        let mut pixels = Vec::with_capacity(new_width * new_height * self.max_z_layers);
+        for z in 0..self.max_z_layers {
+            pixels.extend(resized_grid(
+                &self.pixels[z * layer..(z + 1) * layer],
+                (old_width, old_height),
+                (new_width, new_height),
+                TerminalPixel::TRANSPARENT,
+            ));
+        }
+        self.pixels = pixels;
+        let (old_size, new_size) = ((old_width, old_height), (new_width, new_height));
+        self.shade = resized_grid(&self.shade, old_size, new_size, u8::MAX);
+        let (old_size, new_size) = ((self.width, self.height), (width, height));
+        self.glyphs = resized_grid(&self.glyphs, old_size, new_size, None);
+        self.text = resized_grid(&self.text, old_size, new_size, None);
+        (self.composited_cells, self.previous_composited_cells) =
+            Self::fresh_composited_cells(width * height, self.default_color);
+        self.width = width;
+        self.height = height;
+        self.final_width = new_width;
+        self.final_height = new_height;
+    }
+
+    /// Returns how many pixels across and down make up every half-block pixel shown.
+    pub fn supersampling(&self) -> usize {
+        self.supersampling
+    }
+
+    /// Sets how colors are sent to the terminal, such as [`ColorMode::Xterm256`] for terminals
+    /// without 24-bit colors, as found by `Capabilities::color_mode`. Applies to
+    /// [`Canvas::render`] and to every way of presenting the canvas. Defaults to
+    /// [`ColorMode::TrueColor`].
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.color_mode = mode;
+    }
+
+    /// Returns how colors are sent to the terminal.
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    /// Sets whether every frame draws only half of the terminal rows, the even rows and the odd
+    /// rows in turn, halving the output for very slow links such as serial consoles or
+    /// high-latency SSH sessions. Changes take two frames to show in full, which is hardly
+    /// visible on mostly static scenes. Applies to [`Canvas::render`] and to every way of
+    /// presenting the canvas. Defaults to `false`.
+    pub fn set_interlaced(&mut self, interlaced: bool) {
+        self.interlace = interlaced.then_some(self.interlace.unwrap_or(0));
+    }
+
+    /// Returns whether the canvas is drawn interlaced.
+    pub fn is_interlaced(&self) -> bool {
+        self.interlace.is_some()
+    }
+
+    /// Returns the terminal rows to draw in the next frame when interlaced, `0` for the even
+    /// rows and `1` for the odd rows, and moves on to the other rows.
+    pub(crate) fn next_interlace_field(&mut self) -> Option<usize> {
+        let field = self.interlace?;
+        self.interlace = Some(1 - field);
+        Some(field)
+    }
+
+    /// Limits drawing to the pixels of `clip`, or lifts the limit with `None`, so content drawn
+    /// into a scrollable panel or a split-screen view cannot bleed out of it. Defaults to
+    /// `None`.
+    ///
+    /// Pixels set with [`Canvas::set_pixel`] or by any drawing primitive, and characters whose
+    /// cell starts outside the area, are silently ignored. Clearing the canvas is not limited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color, Rect};
+    ///
+    /// let red = Color { r: 255, g: 0, b: 0 };
+    /// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+    /// // A panel on the left half of the screen.
+    /// canvas.set_clip_rect(Some(Rect::new(0, 0, 10, 20)));
+    /// canvas.fill_rect(5, 0, 10, 4, 0, red);
+    /// canvas.set_clip_rect(None);
+    /// assert_eq!(canvas.composited_cell(9, 0).top_color, red);
+    /// assert_eq!(canvas.composited_cell(10, 0).top_color, Color { r: 0, g: 0, b: 0 });
+    ///
+    /// // A clip reaching past the canvas limits nothing.
+    /// canvas.set_clip_rect(Some(Rect::new(0, 0, usize::MAX, usize::MAX)));
+    /// canvas.fill_rect(12, 0, 8, 2, 0, red);
+    /// assert_eq!(canvas.composited_cell(19, 0).top_color, red);
+    /// ```
+    pub fn set_clip_rect(&mut self, clip: Option<Rect>) {
+        self.clip = clip;
+    }
+
+    /// Returns the area drawing is limited to, as set by [`Canvas::set_clip_rect`].
+    pub fn clip_rect(&self) -> Option<Rect> {
+        self.clip
+    }
+
+    /// Returns whether the pixel at (`x`, `y`) is outside the area drawing is limited to.
+    fn is_clipped(&self, x: usize, y: usize) -> bool {
+        self.clip.is_some_and(|clip| !clip.contains(x, y))
+    }
+
+    /// Gives the pixels of `area` a `priority` for presenting under a byte budget, such as the
+    /// player and the interface above the background. When a frame does not fit in the budget,
+    /// the changed cells with the highest priorities are sent first and the rest wait for the
+    /// next frames. Where areas overlap, the highest priority counts, and other cells have
+    /// priority `0`. Areas stay until [`Canvas::clear_priorities`].
+    ///
+    /// Byte budgets are set with `Terminal::set_byte_budget`, `Client::set_byte_budget` and
+    /// `MultiSink::set_byte_budget`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color, Rect};
+    ///
+    /// let mut canvas = Canvas::new(20, 10, Color::BLACK);
+    /// canvas.set_priority(Rect::new(0, 0, usize::MAX, usize::MAX), 1);
+    /// canvas.set_priority(Rect::new(4, 3, 2, 2), 9);
+    /// assert_eq!(canvas.priority(4, 1), 9);
+    /// assert_eq!(canvas.priority(4, 2), 9);
+    /// assert_eq!(canvas.priority(19, 9), 1);
+    /// ```
+    pub fn set_priority(&mut self, area: Rect, priority: u8) {
+        self.priorities.push((area, priority));
+    }
+
+    /// Removes every area given a priority.
+    pub fn clear_priorities(&mut self) {
+        self.priorities.clear();
+    }
+
+    /// Returns the priority of the terminal cell at (`column`, `row`), the highest of the areas
+    /// covering either of its pixels.
+    pub fn priority(&self, column: usize, row: usize) -> u8 {
+        self.priorities
+            .iter()
+            .filter(|(area, _)| {
+                area.contains(column, row * 2) || area.contains(column, row * 2 + 1)
+            })
+            .map(|(_, priority)| *priority)
+            .max()
+            .unwrap_or(0)
+    }
+
     /// Renders the current state of the canvas to a string containing ANSI escape codes.
     ///
     /// This function composites all z-layers for each terminal character cell to determine
@@ -157,68 +1405,172 @@ impl Canvas {
     /// A `String` containing ANSI escape codes to update the terminal.
     pub fn render(&mut self) -> String {
         let mut buffer = String::new();
+        // Writing to a `String` cannot fail.
+        let _ = self.render_into(&mut buffer);
+        buffer
+    }
+
+    /// Renders the current state of the canvas like [`Canvas::render`], writing the ANSI escape
+    /// codes to `out` instead of a new string.
+    ///
+    /// `out` can be a fixed-size buffer or a serial port writer, which is how displays attached
+    /// to embedded devices are driven without the `std` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `out`, leaving the rest of the frame unwritten.
+    pub fn render_into<W: fmt::Write>(&mut self, out: &mut W) -> fmt::Result {
+        let field = self.next_interlace_field();
+        let quantized = self.palette.is_some()
+            || self.dither_mode != DitherMode::None && self.color_mode != ColorMode::TrueColor;
+        let frame = (!self.passes.is_empty() || quantized).then(|| self.composite());
         for terminal_cell_y in 0..self.height {
+            if field.is_some_and(|field| terminal_cell_y % 2 != field) {
+                continue;
+            }
             for terminal_cell_x in 0..self.width {
-                let top_half_pixel_y = terminal_cell_y * 2;
-                let bottom_half_pixel_y = terminal_cell_y * 2 + 1;
-
-                let mut current_top_color = self.default_color;
-                let mut current_bottom_color = self.default_color;
-
-                // Find the highest z-layer color for the top half-block
-                for z in (0..self.max_z_layers).rev() {
-                    if let Some(index) = self.get_index(terminal_cell_x, top_half_pixel_y, z) {
-                        let pixel = &self.pixels[index];
-                        if pixel.color != self.default_color {
-                            current_top_color = pixel.color;
-                            break;
-                        }
-                    }
-                }
-
-                // Find the highest z-layer color for the bottom half-block
-                for z in (0..self.max_z_layers).rev() {
-                    if let Some(index) = self.get_index(terminal_cell_x, bottom_half_pixel_y, z) {
-                        let pixel = &self.pixels[index];
-                        if pixel.color != self.default_color {
-                            current_bottom_color = pixel.color;
-                            break;
-                        }
-                    }
-                }
-
-                // Create a temporary CompositedCell for comparison with previous frame
-                let current_composited_cell = CompositedCell {
-                    top_color: current_top_color,
-                    bottom_color: current_bottom_color,
+                let (current_composited_cell, glyph) = match &frame {
+                    Some(frame) => frame.cell_and_glyph(terminal_cell_x, terminal_cell_y),
+                    None => self.composited(terminal_cell_x, terminal_cell_y),
                 };
-
                 let terminal_cell_index = terminal_cell_y * self.width + terminal_cell_x;
 
-                buffer.push_str(&format!("\u{1b}[{};{}H", terminal_cell_y + 1, terminal_cell_x + 1));
-                if current_top_color == current_bottom_color {
-                    buffer.push_str(&format!(
-                        "\u{1b}[38;2;{};{};{}m█",
-                        current_top_color.r,
-                        current_top_color.g,
-                        current_top_color.b
-                    ));
-                } else {
-                    buffer.push_str(&format!(
-                        "\u{1b}[48;2;{};{};{}m\u{1b}[38;2;{};{};{}m▄",
-                        current_top_color.r,
-                        current_top_color.g,
-                        current_top_color.b,
-                        current_bottom_color.r,
-                        current_bottom_color.g,
-                        current_bottom_color.b
-                    ));
-                }
+                push_cell(
+                    out,
+                    terminal_cell_x,
+                    terminal_cell_y,
+                    current_composited_cell,
+                    glyph,
+                    self.color_mode,
+                    self.terminal_default_color(),
+                )?;
                 // Update composited_cells with the current composited cell
                 self.composited_cells[terminal_cell_index] = current_composited_cell;
             }
         }
         self.previous_composited_cells = self.composited_cells.clone();
-        buffer
+        #[cfg(feature = "std")]
+        match &frame {
+            Some(frame) => crash::remember_frame(frame),
+            None => crash::remember_rendered(self),
+        }
+        self.count_frame();
+        Ok(())
+    }
+}
+
+impl Flash {
+    /// Returns what `color` looks like under the flash.
+    fn apply(self, color: Color) -> Color {
+        match self {
+            Flash::Invert => Color {
+                r: 255 - color.r,
+                g: 255 - color.g,
+                b: 255 - color.b,
+            },
+            Flash::Color(flash) => flash,
+        }
+    }
+}
+
+/// Scales `color` by `brightness`, where `255` keeps it unchanged and `0` makes it black.
+pub(crate) fn shade_color(color: Color, brightness: u8) -> Color {
+    if brightness == u8::MAX {
+        return color;
+    }
+    let scale = |channel: u8| (channel as u16 * brightness as u16 / 255) as u8;
+    Color {
+        r: scale(color.r),
+        g: scale(color.g),
+        b: scale(color.b),
+    }
+}
+
+/// Appends the ANSI escape codes drawing a terminal cell at (`column`, `row`) to `buffer`, with
+/// colors sent in `mode` and the `terminal_default` color, if any, left to the terminal's default
+/// background.
+pub(crate) fn push_cell<W: fmt::Write>(
+    buffer: &mut W,
+    column: usize,
+    row: usize,
+    cell: CompositedCell,
+    glyph: Option<CellGlyph>,
+    mode: ColorMode,
+    terminal_default: Option<Color>,
+) -> fmt::Result {
+    let (top, bottom) = (cell.top_color, cell.bottom_color);
+    let is_default = |color: Color| terminal_default == Some(color);
+    if glyph.is_some_and(|glyph| glyph.is_continuation()) {
+        // Drawn with the cell on its left.
+        return Ok(());
+    }
+    write!(buffer, "\u{1b}[{};{}H", row + 1, column + 1)?;
+    if let Some(glyph) = glyph {
+        if is_default(top) {
+            buffer.write_str("\u{1b}[49m")?;
+        } else {
+            push_color(buffer, 48, top, mode)?;
+        }
+        push_color(buffer, 38, glyph.color, mode)?;
+        if glyph.attributes.is_empty() {
+            buffer.write_char(glyph.ch)?;
+            return glyph.mark.map_or(Ok(()), |mark| buffer.write_char(mark));
+        }
+        buffer.write_str("\u{1b}[")?;
+        for (index, code) in glyph.attributes.codes().enumerate() {
+            if index > 0 {
+                buffer.write_char(';')?;
+            }
+            write!(buffer, "{code}")?;
+        }
+        // Turn the styles off again, so the cells drawn after keep only their colors.
+        buffer.write_char('m')?;
+        buffer.write_char(glyph.ch)?;
+        if let Some(mark) = glyph.mark {
+            buffer.write_char(mark)?;
+        }
+        buffer.write_str("\u{1b}[22;23;24;27m")
+    } else if is_default(top) && is_default(bottom) {
+        buffer.write_str("\u{1b}[49m ")
+    } else if is_default(top) {
+        buffer.write_str("\u{1b}[49m")?;
+        push_color(buffer, 38, bottom, mode)?;
+        buffer.write_char('▄')
+    } else if is_default(bottom) {
+        buffer.write_str("\u{1b}[49m")?;
+        push_color(buffer, 38, top, mode)?;
+        buffer.write_char('▀')
+    } else if mode.same_color(top, bottom) {
+        push_color(buffer, 38, top, mode)?;
+        buffer.write_char('█')
+    } else {
+        push_color(buffer, 48, top, mode)?;
+        push_color(buffer, 38, bottom, mode)?;
+        buffer.write_char('▄')
+    }
+}
+
+/// Appends the ANSI escape code setting the foreground (`38`) or background (`48`) color.
+fn push_color<W: fmt::Write>(
+    buffer: &mut W,
+    ground: u8,
+    color: Color,
+    mode: ColorMode,
+) -> fmt::Result {
+    match mode {
+        ColorMode::TrueColor => write!(
+            buffer,
+            "\u{1b}[{ground};2;{};{};{}m",
+            color.r, color.g, color.b
+        ),
+        ColorMode::Xterm256 => write!(buffer, "\u{1b}[{ground};5;{}m", xterm::nearest(color)),
+        ColorMode::Ansi16 => {
+            // 30 to 37 set the foreground to the normal colors and 90 to 97 to the bright ones,
+            // with the backgrounds 10 higher.
+            let index = palette::ansi::nearest(color);
+            let base = if ground == 38 { 30 } else { 40 };
+            let code = if index < 8 { base + index } else { base + 60 + index - 8 };
+            write!(buffer, "\u{1b}[{code}m")
+        }
     }
 }
@@ -1,36 +1,18 @@
-use crossterm::{
-    cursor::{Hide, Show},
-    event::{poll, read, Event, KeyCode},
-    execute,
-    style::Print,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use rael::{Canvas, Color};
-use std::io::{stdout, Write};
+use crossterm::event::{Event, KeyCode};
+use rael::{Canvas, Color, Terminal};
 use std::time::Duration;
 
-// A simple struct to ensure cleanup is performed when it's dropped.
-struct CleanUp;
-
-impl Drop for CleanUp {
-    fn drop(&mut self) {
-        _ = execute!(stdout(), LeaveAlternateScreen, Show);
-        _ = disable_raw_mode();
-    }
-}
-
 fn main() -> std::io::Result<()> {
-    let _clean_up = CleanUp;
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen, Hide)?;
+    // The terminal is restored when `terminal` is dropped.
+    let mut terminal = Terminal::stdout()?;
 
-    let (width, height) = crossterm::terminal::size()?;
+    let (width, height) = terminal.size()?;
     let mut canvas = Canvas::new(
-        width as usize,
-        height as usize,
+        width,
+        height,
         Color { r: 0, g: 0, b: 0 }, // Default background color
     );
+    let (width, height) = (width as u16, height as u16);
 
     let floor_y_terminal_cell = (height * 4 / 5) as usize;
 
@@ -39,11 +21,9 @@ fn main() -> std::io::Result<()> {
     // --- Main Game Loop ---
     loop {
         // --- Input Handling ---
-        if poll(Duration::from_millis(0))? {
-            if let Ok(Event::Key(key_event)) = read() {
-                if key_event.code == KeyCode::Char('q') {
-                    break; // Exit loop on 'q'
-                }
+        if let Some(Event::Key(key_event)) = terminal.poll_event(Duration::from_millis(0))? {
+            if key_event.code == KeyCode::Char('q') {
+                break; // Exit loop on 'q'
             }
         }
 
@@ -177,9 +157,7 @@ fn main() -> std::io::Result<()> {
             },
         );
         // Render the canvas to the terminal
-        let output = canvas.render();
-        execute!(stdout, Print(output))?;
-        stdout.flush()?;
+        terminal.draw(&mut canvas)?;
 
         frame += 1;
         std::thread::sleep(Duration::from_millis(16)); // Aim for ~60 FPS
@@ -0,0 +1,238 @@
+//! Inline markup for styled text on the text plane, such as `"[red]HP[/] 25/[bold]100[/]"`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::palette::ansi;
+use crate::text::text_width;
+use crate::{Attributes, Canvas, Color};
+
+/// An error in the markup of styled text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupError {
+    /// A tag names a style that is neither a color nor an attribute.
+    UnknownStyle(String),
+    /// A closing tag has no open tag to close.
+    UnmatchedClose(String),
+    /// A tag is opened with `[` but never ends with `]`.
+    UnterminatedTag,
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkupError::UnknownStyle(style) => write!(f, "unknown style `{style}`"),
+            MarkupError::UnmatchedClose(tag) => write!(f, "closing tag `[/{tag}]` closes nothing"),
+            MarkupError::UnterminatedTag => write!(f, "a tag is missing its closing `]`"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MarkupError {}
+
+/// A run of text sharing one style, parsed from markup by [`parse_markup`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    /// The text of the span.
+    pub text: String,
+    /// The color of the text, or `None` for the default color.
+    pub foreground: Option<Color>,
+    /// The background of the text, or `None` for the default background.
+    pub background: Option<Color>,
+    /// The styles of the text.
+    pub attributes: Attributes,
+}
+
+/// The style set by a tag, over the styles of the tags around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Style {
+    foreground: Option<Color>,
+    background: Option<Color>,
+    attributes: Attributes,
+}
+
+/// Parses `markup` into runs of styled text.
+///
+/// Tags in square brackets style the text up to the matching closing tag: `[/]` closes the
+/// last open tag, and `[/bold red]` the last open `[bold red]`. A tag holds any of:
+///
+/// - the attributes `bold`, `dim`, `italic`, `underline` and `reverse`;
+/// - a foreground color: one of the names of the 16 system colors, `black`, `red`, `green`,
+///   `yellow`, `blue`, `magenta`, `cyan` and `white`, each also prefixed by `bright_`, with
+///   `gray` for `bright_black`, or a hex color such as `#ff8800` or `#f80`;
+/// - `on` followed by a background color.
+///
+/// Tags left open end with the text, and `\[` stands for a literal `[`.
+///
+/// # Example
+///
+/// ```
+/// use rael::{parse_markup, Attributes};
+///
+/// let spans = parse_markup("[red]HP[/] 25/[bold]100[/]")?;
+/// assert_eq!(spans.len(), 3);
+/// assert_eq!(spans[0].text, "HP");
+/// assert_eq!(spans[1].foreground, None);
+/// assert_eq!(spans[2].attributes, Attributes::BOLD);
+/// # Ok::<(), rael::MarkupError>(())
+/// ```
+pub fn parse_markup(markup: &str) -> Result<Vec<StyledSpan>, MarkupError> {
+    let mut spans: Vec<StyledSpan> = Vec::new();
+    // The open tags, with the style inside each.
+    let mut open: Vec<(&str, Style)> = Vec::new();
+    let mut text = String::new();
+    let mut rest = markup;
+    loop {
+        let style = open.last().map_or(Style::default(), |&(_, style)| style);
+        let Some(start) = rest.find(['[', '\\']) else {
+            text.push_str(rest);
+            push_span(&mut spans, &mut text, style);
+            return Ok(spans);
+        };
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(escaped) = rest.strip_prefix("\\[") {
+            text.push('[');
+            rest = escaped;
+            continue;
+        }
+        if let Some(backslash) = rest.strip_prefix('\\') {
+            text.push('\\');
+            rest = backslash;
+            continue;
+        }
+        let end = rest.find(']').ok_or(MarkupError::UnterminatedTag)?;
+        let tag = rest[1..end].trim();
+        rest = &rest[end + 1..];
+        push_span(&mut spans, &mut text, style);
+        if let Some(closed) = tag.strip_prefix('/') {
+            let closed = closed.trim();
+            let index = if closed.is_empty() {
+                open.len().checked_sub(1)
+            } else {
+                open.iter().rposition(|&(tag, _)| tag == closed)
+            };
+            let index = index.ok_or_else(|| MarkupError::UnmatchedClose(closed.into()))?;
+            open.truncate(index);
+        } else {
+            open.push((tag, parse_tag(tag, style)?));
+        }
+    }
+}
+
+/// Ends the span of `text` in `style`, merging it into the last span if they share a style.
+fn push_span(spans: &mut Vec<StyledSpan>, text: &mut String, style: Style) {
+    if text.is_empty() {
+        return;
+    }
+    let text = core::mem::take(text);
+    if let Some(last) = spans.last_mut().filter(|last| {
+        last.foreground == style.foreground
+            && last.background == style.background
+            && last.attributes == style.attributes
+    }) {
+        last.text.push_str(&text);
+        return;
+    }
+    spans.push(StyledSpan {
+        text,
+        foreground: style.foreground,
+        background: style.background,
+        attributes: style.attributes,
+    });
+}
+
+/// Returns `style` with the styles of `tag` applied over it.
+fn parse_tag(tag: &str, mut style: Style) -> Result<Style, MarkupError> {
+    let mut words = tag.split_whitespace();
+    while let Some(word) = words.next() {
+        let attribute = match word {
+            "bold" => Attributes::BOLD,
+            "dim" => Attributes::DIM,
+            "italic" => Attributes::ITALIC,
+            "underline" => Attributes::UNDERLINE,
+            "reverse" => Attributes::REVERSE,
+            "on" => {
+                let color = words.next().unwrap_or_default();
+                let background = parse_color(color)
+                    .ok_or_else(|| MarkupError::UnknownStyle(alloc::format!("on {color}")))?;
+                style.background = Some(background);
+                continue;
+            }
+            color => {
+                let foreground =
+                    parse_color(color).ok_or_else(|| MarkupError::UnknownStyle(color.into()))?;
+                style.foreground = Some(foreground);
+                continue;
+            }
+        };
+        style.attributes = style.attributes | attribute;
+    }
+    Ok(style)
+}
+
+/// Returns the color named by `name`, a system color name or a hex color.
+fn parse_color(name: &str) -> Option<Color> {
+    if name.starts_with('#') {
+        return Color::from_hex_str(name).ok();
+    }
+    let name = match name {
+        "gray" | "grey" => "bright_black",
+        name => name,
+    };
+    ansi::by_name(name)
+}
+
+impl Canvas {
+    /// Places `markup` on the text plane from the terminal cell at (`column`, `row`) rightwards,
+    /// like [`Canvas::put_str_styled`], with every run of text in the styles of its tags, as
+    /// parsed by [`parse_markup`]. Text without a color is drawn in `foreground` over
+    /// `background`, and `'\n'` continues on the next row, from `column`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error in the markup, placing nothing, if it is not valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let mut canvas = Canvas::new(20, 5, Color { r: 0, g: 0, b: 0 });
+    /// canvas.put_markup(1, 1, "[red]HP[/] 25/[bold]100[/]", white, None)?;
+    /// let output = canvas.render();
+    /// assert!(output.contains("\x1b[38;2;205;0;0mH"));
+    /// assert!(output.contains("\x1b[1m1\x1b[22;23;24;27m"));
+    /// # Ok::<(), rael::MarkupError>(())
+    /// ```
+    pub fn put_markup(
+        &mut self,
+        column: usize,
+        row: usize,
+        markup: &str,
+        foreground: Color,
+        background: Option<Color>,
+    ) -> Result<(), MarkupError> {
+        let (mut x, mut y) = (column, row);
+        for span in parse_markup(markup)? {
+            for (index, line) in span.text.split('\n').enumerate() {
+                if index > 0 {
+                    (x, y) = (column, y + 1);
+                }
+                self.put_str_styled(
+                    x,
+                    y,
+                    line,
+                    span.foreground.unwrap_or(foreground),
+                    span.background.or(background),
+                    span.attributes,
+                );
+                x += text_width(line);
+            }
+        }
+        Ok(())
+    }
+}
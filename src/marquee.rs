@@ -0,0 +1,167 @@
+//! Scrolling marquee text, for news tickers and the song titles of music players.
+
+use crate::{Canvas, Color, Font, Rect};
+
+/// A line of pixel text scrolling horizontally through an area of the canvas, wrapping around
+/// with a gap between its end and its start, like a news ticker.
+///
+/// The marquee keeps its scroll offset: call [`Marquee::tick`] once per frame to move the text
+/// and [`Marquee::draw`] to draw it. Text is drawn in [`Font::builtin`] unless set otherwise,
+/// centered vertically in the area, and cut off at its edges.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Marquee, Rect};
+///
+/// let mut canvas = Canvas::new(40, 10, Color { r: 0, g: 0, b: 0 });
+/// let mut ticker = Marquee::new("NOW PLAYING: CHIPTUNE NO. 7", Rect::new(0, 12, 40, 8))
+///     .color(Color { r: 255, g: 200, b: 0 })
+///     .speed(0.5);
+/// for _ in 0..3 {
+///     canvas.clear();
+///     ticker.draw(&mut canvas, 1);
+///     ticker.tick();
+/// }
+/// assert_eq!(ticker.offset(), 1.5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marquee {
+    text: String,
+    area: Rect,
+    font: Font,
+    color: Color,
+    speed: f32,
+    gap: usize,
+    offset: f32,
+}
+
+impl Marquee {
+    /// Creates a marquee scrolling `text` left through `area`, in pixels, by a pixel per frame.
+    /// Line breaks in the text are shown as spaces.
+    pub fn new(text: impl Into<String>, area: Rect) -> Self {
+        Self {
+            text: Self::single_line(text.into()),
+            area,
+            font: Font::builtin().clone(),
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+            },
+            speed: 1.0,
+            gap: 12,
+            offset: 0.0,
+        }
+    }
+
+    /// Sets the font the text is drawn in.
+    pub fn font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
+    /// Sets the color of the text. Defaults to white.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets how many pixels the text moves left every tick, fractions included. Negative speeds
+    /// move it right. Defaults to `1.0`.
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the pixels between the end of the text and its next start. Defaults to `12`.
+    pub fn gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Returns the scrolling text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the scrolling text, such as with the title of the next song, keeping the
+    /// position of the scroll.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = Self::single_line(text.into());
+        self.offset %= self.period();
+    }
+
+    /// Returns the area the text scrolls through.
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    /// Moves the area the text scrolls through, such as when the terminal is resized.
+    pub fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+
+    /// Returns how many pixels the text has scrolled left from the left edge of the area, from
+    /// `0` up to the width of the text and the gap.
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// Moves the text by its speed, wrapping around once the text and the gap have passed.
+    pub fn tick(&mut self) {
+        self.offset = (self.offset + self.speed).rem_euclid(self.period());
+    }
+
+    /// Scrolls the text back to its start at the left edge of the area.
+    pub fn reset(&mut self) {
+        self.offset = 0.0;
+    }
+
+    /// Draws the text at its scroll offset into the area of `canvas`, on layer `z`, as many
+    /// times as needed to fill the area.
+    pub fn draw(&self, canvas: &mut Canvas, z: usize) {
+        let area = self.area;
+        let previous_clip = canvas.clip_rect();
+        let clip = match previous_clip {
+            Some(clip) => {
+                let (left, top) = (area.x.max(clip.x), area.y.max(clip.y));
+                let right = area
+                    .x
+                    .saturating_add(area.width)
+                    .min(clip.x.saturating_add(clip.width));
+                let bottom = area
+                    .y
+                    .saturating_add(area.height)
+                    .min(clip.y.saturating_add(clip.height));
+                Rect::new(
+                    left,
+                    top,
+                    right.saturating_sub(left),
+                    bottom.saturating_sub(top),
+                )
+            }
+            None => area,
+        };
+        canvas.set_clip_rect(Some(clip));
+        let period = self.period() as isize;
+        let y = area.y as isize + (area.height as isize - self.font.height() as isize) / 2;
+        let mut x = area.x as isize - self.offset.floor() as isize;
+        let right = area.x.saturating_add(area.width).min(canvas.final_width) as isize;
+        while x < right {
+            canvas.draw_text_font(&self.font, x, y, z, self.color, &self.text);
+            x += period;
+        }
+        canvas.set_clip_rect(previous_clip);
+    }
+
+    /// Returns the pixels between two starts of the text.
+    fn period(&self) -> f32 {
+        (self.font.measure_text(&self.text).0 + self.gap).max(1) as f32
+    }
+
+    /// Returns `text` with its line breaks replaced by spaces.
+    fn single_line(text: String) -> String {
+        text.replace(['\r', '\n'], " ")
+    }
+}
@@ -0,0 +1,236 @@
+//! Minimaps: small, downsampled overviews of a world.
+
+use crate::{Camera, Canvas, Color, Rect, TileMap};
+
+/// A downsampled overview of a world too large for the screen, with the part the camera shows
+/// outlined.
+///
+/// Every minimap pixel averages `scale` by `scale` world pixels, read from a world canvas or
+/// drawn from a [`TileMap`]. The minimap keeps the averages and only recomputes the parts marked
+/// with [`Minimap::invalidate`], so a large world costs little to keep up to date.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Camera, Canvas, Color, Minimap, Rect};
+///
+/// // A world of 256x128 pixels, shown in a minimap of 32x16 pixels.
+/// let mut world = Canvas::new(256, 64, Color { r: 0, g: 80, b: 0 });
+/// let mut minimap = Minimap::new(32, 16).scale(8);
+/// minimap.update_from_canvas(&world);
+///
+/// // A lake appears: only the minimap pixels it covers are recomputed.
+/// for y in 40..56 {
+///     for x in 100..140 {
+///         world.set_pixel(x, y, 0, Color { r: 0, g: 0, b: 200 });
+///     }
+/// }
+/// minimap.invalidate(Rect::new(100, 40, 40, 16));
+/// minimap.update_from_canvas(&world);
+/// assert_eq!(minimap.pixel(13, 5), Some(Color { r: 0, g: 0, b: 200 }));
+///
+/// let mut screen = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// minimap.draw(&mut screen, 48, 0, 5);
+/// minimap.draw_viewport(&mut screen, 48, 0, 6, &Camera::new(64, 32), 80, 48);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Minimap {
+    width: usize,
+    height: usize,
+    scale: usize,
+    viewport_color: Color,
+    pixels: Vec<Option<Color>>,
+    stale: Vec<bool>,
+}
+
+impl Minimap {
+    /// Creates a minimap of `width` by `height` pixels, with every pixel to be computed by the
+    /// next update.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            scale: 4,
+            viewport_color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+            },
+            pixels: vec![None; width * height],
+            stale: vec![true; width * height],
+        }
+    }
+
+    /// Sets how many world pixels each minimap pixel covers across and down, at least `1`.
+    /// Defaults to `4`.
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self.invalidate_all();
+        self
+    }
+
+    /// Sets the color of the outline drawn by [`Minimap::draw_viewport`]. Defaults to white.
+    pub fn viewport_color(mut self, color: Color) -> Self {
+        self.viewport_color = color;
+        self
+    }
+
+    /// Returns the width of the minimap in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the minimap in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the average color of the minimap pixel at (`x`, `y`), or `None` if its world
+    /// pixels are all empty or it is out of bounds.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.pixels[y * self.width + x]
+    }
+
+    /// Marks the minimap pixels covering `area`, in world pixels, to be recomputed by the next
+    /// update.
+    pub fn invalidate(&mut self, area: Rect) {
+        let first_x = area.x / self.scale;
+        let first_y = area.y / self.scale;
+        let last_x = (area.x + area.width).div_ceil(self.scale).min(self.width);
+        let last_y = (area.y + area.height).div_ceil(self.scale).min(self.height);
+        for y in first_y..last_y {
+            for x in first_x..last_x {
+                self.stale[y * self.width + x] = true;
+            }
+        }
+    }
+
+    /// Marks every minimap pixel to be recomputed by the next update.
+    pub fn invalidate_all(&mut self) {
+        self.stale.fill(true);
+    }
+
+    /// Recomputes the pixels marked as changed from `world`, a canvas holding the whole world,
+    /// with all its z-layers and shading.
+    pub fn update_from_canvas(&mut self, world: &Canvas) {
+        self.update(|x, y| {
+            let cell = world.composited_cell(x, y / 2);
+            (x < world.width && y < world.final_height).then_some(if y.is_multiple_of(2) {
+                cell.top_color
+            } else {
+                cell.bottom_color
+            })
+        });
+    }
+
+    /// Recomputes the pixels marked as changed from the tiles of `map`. Empty cells and
+    /// transparent tile pixels are left out of the averages.
+    pub fn update_from_tilemap(&mut self, map: &TileMap) {
+        let tileset = map.tileset();
+        let (tile_width, tile_height) = (tileset.tile_width(), tileset.tile_height());
+        if tile_width == 0 || tile_height == 0 {
+            return;
+        }
+        self.update(|x, y| {
+            let tile = map.get(x / tile_width, y / tile_height)?;
+            tileset.get(tile)?.get(x % tile_width, y % tile_height)
+        });
+    }
+
+    /// Recomputes the stale pixels as the average of the world pixels they cover, as returned
+    /// by `sample`.
+    fn update(&mut self, sample: impl Fn(usize, usize) -> Option<Color>) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y * self.width + x;
+                if !self.stale[index] {
+                    continue;
+                }
+                let (mut sum, mut count) = ([0u32; 3], 0);
+                for world_y in y * self.scale..(y + 1) * self.scale {
+                    for world_x in x * self.scale..(x + 1) * self.scale {
+                        if let Some(color) = sample(world_x, world_y) {
+                            sum[0] += color.r as u32;
+                            sum[1] += color.g as u32;
+                            sum[2] += color.b as u32;
+                            count += 1;
+                        }
+                    }
+                }
+                self.pixels[index] = (count > 0).then(|| Color {
+                    r: (sum[0] / count) as u8,
+                    g: (sum[1] / count) as u8,
+                    b: (sum[2] / count) as u8,
+                });
+                self.stale[index] = false;
+            }
+        }
+    }
+
+    /// Draws the minimap with its top-left corner at pixel (`x`, `y`) of `canvas`, on layer
+    /// `z`. Empty pixels are left untouched.
+    pub fn draw(&self, canvas: &mut Canvas, x: usize, y: usize, z: usize) {
+        for row in 0..self.height {
+            for column in 0..self.width {
+                if let Some(color) = self.pixels[row * self.width + column] {
+                    canvas.set_pixel(x + column, y + row, z, color);
+                }
+            }
+        }
+    }
+
+    /// Outlines the part of the world shown through `camera` on a viewport of `viewport_width`
+    /// by `viewport_height` pixels, over a minimap drawn at (`x`, `y`), on layer `z`. The
+    /// outline is clipped to the minimap.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_viewport(
+        &self,
+        canvas: &mut Canvas,
+        x: usize,
+        y: usize,
+        z: usize,
+        camera: &Camera,
+        viewport_width: usize,
+        viewport_height: usize,
+    ) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let scale = self.scale as isize;
+        let left = camera.x.div_euclid(scale);
+        let top = camera.y.div_euclid(scale);
+        let right = (camera.x + viewport_width as isize - 1).div_euclid(scale);
+        let bottom = (camera.y + viewport_height as isize - 1).div_euclid(scale);
+        let (max_x, max_y) = (self.width as isize - 1, self.height as isize - 1);
+        if right < 0 || bottom < 0 || left > max_x || top > max_y {
+            return;
+        }
+        let mut plot = |column: isize, row: isize| {
+            canvas.set_pixel(
+                x + column as usize,
+                y + row as usize,
+                z,
+                self.viewport_color,
+            );
+        };
+        for column in left.max(0)..=right.min(max_x) {
+            if top >= 0 {
+                plot(column, top);
+            }
+            if bottom <= max_y {
+                plot(column, bottom);
+            }
+        }
+        for row in top.max(0)..=bottom.min(max_y) {
+            if left >= 0 {
+                plot(left, row);
+            }
+            if right <= max_x {
+                plot(right, row);
+            }
+        }
+    }
+}
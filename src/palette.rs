@@ -0,0 +1,196 @@
+//! Named colors shared by programs and widgets: the 16 ANSI system colors and the named colors
+//! of the web, and restricted palettes the output of a canvas is snapped to.
+//!
+//! The basic colors are also associated constants of [`Color`], such as [`Color::RED`].
+//!
+//! # Example
+//!
+//! ```
+//! use rael::palette::{ansi, web};
+//! use rael::Color;
+//!
+//! assert_eq!(web::CORNFLOWER_BLUE, Color::from_hex(0x6495ED));
+//! assert_eq!(web::by_name("CornflowerBlue"), Some(web::CORNFLOWER_BLUE));
+//! assert_eq!(ansi::by_name("bright_red"), Some(ansi::BRIGHT_RED));
+//! assert_eq!(ansi::COLORS[9], ("bright_red", ansi::BRIGHT_RED));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{xterm, Canvas, Color};
+
+/// Defines a constant for every named color, and the list of them all with their names.
+macro_rules! named_colors {
+    ($kind:literal, $($constant:ident = $name:literal, $hex:literal;)*) => {
+        $(
+            #[doc = concat!("The ", $kind, " `", $name, "`, `", stringify!($hex), "`.")]
+            pub const $constant: Color = Color::from_hex($hex);
+        )*
+
+        /// Every color of the palette with its name, in order.
+        pub const COLORS: &[(&str, Color)] = &[$(($name, $constant)),*];
+    };
+}
+
+pub mod ansi;
+pub mod web;
+
+/// Returns the color of `colors` named `name`, in any case.
+fn find(colors: &[(&str, Color)], name: &str) -> Option<Color> {
+    colors
+        .iter()
+        .find(|(color_name, _)| color_name.eq_ignore_ascii_case(name))
+        .map(|&(_, color)| color)
+}
+
+impl Color {
+    /// Black, `0x000000`.
+    pub const BLACK: Color = Color::from_hex(0x000000);
+    /// White, `0xFFFFFF`.
+    pub const WHITE: Color = Color::from_hex(0xFFFFFF);
+    /// Middle gray, `0x808080`.
+    pub const GRAY: Color = Color::from_hex(0x808080);
+    /// Full red, `0xFF0000`.
+    pub const RED: Color = Color::from_hex(0xFF0000);
+    /// Full green, `0x00FF00`, the web color `lime`.
+    pub const GREEN: Color = Color::from_hex(0x00FF00);
+    /// Full blue, `0x0000FF`.
+    pub const BLUE: Color = Color::from_hex(0x0000FF);
+    /// Full yellow, `0xFFFF00`.
+    pub const YELLOW: Color = Color::from_hex(0xFFFF00);
+    /// Full cyan, `0x00FFFF`.
+    pub const CYAN: Color = Color::from_hex(0x00FFFF);
+    /// Full magenta, `0xFF00FF`.
+    pub const MAGENTA: Color = Color::from_hex(0xFF00FF);
+}
+
+/// An ordered list of colors that everything a canvas shows is snapped to, such as the four
+/// greens of the original Game Boy, for games that keep to the hard limits of old hardware.
+///
+/// Set it with [`Canvas::set_palette`]. Every color is drawn in the palette color closest to it,
+/// or mixed from the closest palette colors by the dither mode of the canvas.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Palette};
+///
+/// let palette = Palette::game_boy();
+/// assert_eq!(palette.nearest(Color::WHITE), Some(3));
+/// assert_eq!(palette.quantize(Color::BLACK), Color::from_hex(0x0F380F));
+///
+/// let mut canvas = Canvas::new(4, 2, Color::BLACK);
+/// canvas.set_palette(Some(palette));
+/// assert!(canvas.render().contains("\x1b[38;2;15;56;15m"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    colors: Vec<Color>,
+}
+
+impl Palette {
+    /// Creates a palette of `colors`, in order.
+    pub fn new(colors: impl IntoIterator<Item = Color>) -> Self {
+        Self {
+            colors: colors.into_iter().collect(),
+        }
+    }
+
+    /// Returns the four greens of the original Game Boy, from darkest to lightest.
+    pub fn game_boy() -> Self {
+        Self::from_hex(&[0x0F380F, 0x306230, 0x8BAC0F, 0x9BBC0F])
+    }
+
+    /// Returns the 16 colors of the PICO-8 fantasy console, in its order.
+    pub fn pico8() -> Self {
+        Self::from_hex(&[
+            0x000000, 0x1D2B53, 0x7E2553, 0x008751, 0xAB5236, 0x5F574F, 0xC2C3C7, 0xFFF1E8,
+            0xFF004D, 0xFFA300, 0xFFEC27, 0x00E436, 0x29ADFF, 0x83769C, 0xFF77A8, 0xFFCCAA,
+        ])
+    }
+
+    /// Returns the 16 ANSI system colors, with the xterm defaults.
+    pub fn ansi() -> Self {
+        Self::new(ansi::COLORS.iter().map(|&(_, color)| color))
+    }
+
+    /// Returns the colors of the palette, in order.
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Returns the number of colors in the palette.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Returns whether the palette has no colors.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Returns the index of the palette color closest to `color`, or `None` if the palette is
+    /// empty.
+    pub fn nearest(&self, color: Color) -> Option<usize> {
+        (0..self.colors.len()).min_by_key(|&index| xterm::distance(color, self.colors[index]))
+    }
+
+    /// Returns the palette color closest to `color`, or `color` itself if the palette is empty.
+    pub fn quantize(&self, color: Color) -> Color {
+        self.nearest(color)
+            .map_or(color, |index| self.colors[index])
+    }
+
+    /// Returns how far apart the colors of the palette are on average, in channel values, as
+    /// the distance from every color to the closest other color.
+    pub(crate) fn spread(&self) -> f32 {
+        if self.colors.len() < 2 {
+            return 0.0;
+        }
+        let total: u32 = self
+            .colors
+            .iter()
+            .enumerate()
+            .map(|(index, &color)| {
+                let others = self
+                    .colors
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != index);
+                others
+                    .map(|(_, &other)| xterm::distance(color, other))
+                    .min()
+                    .unwrap_or(0)
+                    .isqrt()
+            })
+            .sum();
+        total as f32 / self.colors.len() as f32
+    }
+
+    /// Creates a palette from `0xRRGGBB` hex codes.
+    fn from_hex(colors: &[u32]) -> Self {
+        Self::new(colors.iter().map(|&hex| Color::from_hex(hex)))
+    }
+}
+
+impl FromIterator<Color> for Palette {
+    fn from_iter<I: IntoIterator<Item = Color>>(colors: I) -> Self {
+        Self::new(colors)
+    }
+}
+
+impl Canvas {
+    /// Restricts every color the canvas shows to `palette`, characters included, or lifts the
+    /// restriction with `None`. Colors are snapped to the closest palette color, or dithered
+    /// with the dither mode of the canvas, before they are sent in its color mode. Colors left
+    /// to the terminal's default background are not changed. Defaults to `None`.
+    pub fn set_palette(&mut self, palette: Option<Palette>) {
+        self.palette = palette;
+    }
+
+    /// Returns the palette every color the canvas shows is restricted to, if any.
+    pub fn palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+}
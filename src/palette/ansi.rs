@@ -0,0 +1,50 @@
+//! The 16 ANSI system colors, with the xterm defaults. Most terminals let themes change these,
+//! so the colors shown for the same index differ from terminal to terminal.
+
+use crate::{xterm, Color};
+
+named_colors! {
+    "ANSI color",
+    BLACK = "black", 0x000000;
+    RED = "red", 0xCD0000;
+    GREEN = "green", 0x00CD00;
+    YELLOW = "yellow", 0xCDCD00;
+    BLUE = "blue", 0x0000EE;
+    MAGENTA = "magenta", 0xCD00CD;
+    CYAN = "cyan", 0x00CDCD;
+    WHITE = "white", 0xE5E5E5;
+    BRIGHT_BLACK = "bright_black", 0x7F7F7F;
+    BRIGHT_RED = "bright_red", 0xFF0000;
+    BRIGHT_GREEN = "bright_green", 0x00FF00;
+    BRIGHT_YELLOW = "bright_yellow", 0xFFFF00;
+    BRIGHT_BLUE = "bright_blue", 0x5C5CFF;
+    BRIGHT_MAGENTA = "bright_magenta", 0xFF00FF;
+    BRIGHT_CYAN = "bright_cyan", 0x00FFFF;
+    BRIGHT_WHITE = "bright_white", 0xFFFFFF;
+}
+
+/// Returns the index, from 0 to 15, of the ANSI color closest to `color`.
+///
+/// # Example
+///
+/// ```
+/// use rael::palette::ansi;
+/// use rael::Color;
+///
+/// assert_eq!(ansi::nearest(Color::from_hex(0xFF8700)), 3);
+/// assert_eq!(ansi::COLORS[3].1, ansi::YELLOW);
+/// ```
+pub fn nearest(color: Color) -> u8 {
+    let mut nearest = 0;
+    for (index, &(_, candidate)) in COLORS.iter().enumerate() {
+        if xterm::distance(color, candidate) < xterm::distance(color, COLORS[nearest].1) {
+            nearest = index;
+        }
+    }
+    nearest as u8
+}
+
+/// Returns the ANSI color named `name`, such as `red` or `bright_blue`, in any case.
+pub fn by_name(name: &str) -> Option<Color> {
+    super::find(COLORS, name)
+}
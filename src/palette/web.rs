@@ -0,0 +1,155 @@
+//! The named colors of the web, as defined by CSS, such as `cornflowerblue` and `tomato`.
+
+use crate::Color;
+
+named_colors! {
+    "web color",
+    ALICE_BLUE = "aliceblue", 0xF0F8FF;
+    ANTIQUE_WHITE = "antiquewhite", 0xFAEBD7;
+    AQUA = "aqua", 0x00FFFF;
+    AQUAMARINE = "aquamarine", 0x7FFFD4;
+    AZURE = "azure", 0xF0FFFF;
+    BEIGE = "beige", 0xF5F5DC;
+    BISQUE = "bisque", 0xFFE4C4;
+    BLACK = "black", 0x000000;
+    BLANCHED_ALMOND = "blanchedalmond", 0xFFEBCD;
+    BLUE = "blue", 0x0000FF;
+    BLUE_VIOLET = "blueviolet", 0x8A2BE2;
+    BROWN = "brown", 0xA52A2A;
+    BURLY_WOOD = "burlywood", 0xDEB887;
+    CADET_BLUE = "cadetblue", 0x5F9EA0;
+    CHARTREUSE = "chartreuse", 0x7FFF00;
+    CHOCOLATE = "chocolate", 0xD2691E;
+    CORAL = "coral", 0xFF7F50;
+    CORNFLOWER_BLUE = "cornflowerblue", 0x6495ED;
+    CORNSILK = "cornsilk", 0xFFF8DC;
+    CRIMSON = "crimson", 0xDC143C;
+    CYAN = "cyan", 0x00FFFF;
+    DARK_BLUE = "darkblue", 0x00008B;
+    DARK_CYAN = "darkcyan", 0x008B8B;
+    DARK_GOLDENROD = "darkgoldenrod", 0xB8860B;
+    DARK_GRAY = "darkgray", 0xA9A9A9;
+    DARK_GREEN = "darkgreen", 0x006400;
+    DARK_KHAKI = "darkkhaki", 0xBDB76B;
+    DARK_MAGENTA = "darkmagenta", 0x8B008B;
+    DARK_OLIVE_GREEN = "darkolivegreen", 0x556B2F;
+    DARK_ORANGE = "darkorange", 0xFF8C00;
+    DARK_ORCHID = "darkorchid", 0x9932CC;
+    DARK_RED = "darkred", 0x8B0000;
+    DARK_SALMON = "darksalmon", 0xE9967A;
+    DARK_SEA_GREEN = "darkseagreen", 0x8FBC8F;
+    DARK_SLATE_BLUE = "darkslateblue", 0x483D8B;
+    DARK_SLATE_GRAY = "darkslategray", 0x2F4F4F;
+    DARK_TURQUOISE = "darkturquoise", 0x00CED1;
+    DARK_VIOLET = "darkviolet", 0x9400D3;
+    DEEP_PINK = "deeppink", 0xFF1493;
+    DEEP_SKY_BLUE = "deepskyblue", 0x00BFFF;
+    DIM_GRAY = "dimgray", 0x696969;
+    DODGER_BLUE = "dodgerblue", 0x1E90FF;
+    FIREBRICK = "firebrick", 0xB22222;
+    FLORAL_WHITE = "floralwhite", 0xFFFAF0;
+    FOREST_GREEN = "forestgreen", 0x228B22;
+    FUCHSIA = "fuchsia", 0xFF00FF;
+    GAINSBORO = "gainsboro", 0xDCDCDC;
+    GHOST_WHITE = "ghostwhite", 0xF8F8FF;
+    GOLD = "gold", 0xFFD700;
+    GOLDENROD = "goldenrod", 0xDAA520;
+    GRAY = "gray", 0x808080;
+    GREEN = "green", 0x008000;
+    GREEN_YELLOW = "greenyellow", 0xADFF2F;
+    HONEYDEW = "honeydew", 0xF0FFF0;
+    HOT_PINK = "hotpink", 0xFF69B4;
+    INDIAN_RED = "indianred", 0xCD5C5C;
+    INDIGO = "indigo", 0x4B0082;
+    IVORY = "ivory", 0xFFFFF0;
+    KHAKI = "khaki", 0xF0E68C;
+    LAVENDER = "lavender", 0xE6E6FA;
+    LAVENDER_BLUSH = "lavenderblush", 0xFFF0F5;
+    LAWN_GREEN = "lawngreen", 0x7CFC00;
+    LEMON_CHIFFON = "lemonchiffon", 0xFFFACD;
+    LIGHT_BLUE = "lightblue", 0xADD8E6;
+    LIGHT_CORAL = "lightcoral", 0xF08080;
+    LIGHT_CYAN = "lightcyan", 0xE0FFFF;
+    LIGHT_GOLDENROD_YELLOW = "lightgoldenrodyellow", 0xFAFAD2;
+    LIGHT_GRAY = "lightgray", 0xD3D3D3;
+    LIGHT_GREEN = "lightgreen", 0x90EE90;
+    LIGHT_PINK = "lightpink", 0xFFB6C1;
+    LIGHT_SALMON = "lightsalmon", 0xFFA07A;
+    LIGHT_SEA_GREEN = "lightseagreen", 0x20B2AA;
+    LIGHT_SKY_BLUE = "lightskyblue", 0x87CEFA;
+    LIGHT_SLATE_GRAY = "lightslategray", 0x778899;
+    LIGHT_STEEL_BLUE = "lightsteelblue", 0xB0C4DE;
+    LIGHT_YELLOW = "lightyellow", 0xFFFFE0;
+    LIME = "lime", 0x00FF00;
+    LIME_GREEN = "limegreen", 0x32CD32;
+    LINEN = "linen", 0xFAF0E6;
+    MAGENTA = "magenta", 0xFF00FF;
+    MAROON = "maroon", 0x800000;
+    MEDIUM_AQUAMARINE = "mediumaquamarine", 0x66CDAA;
+    MEDIUM_BLUE = "mediumblue", 0x0000CD;
+    MEDIUM_ORCHID = "mediumorchid", 0xBA55D3;
+    MEDIUM_PURPLE = "mediumpurple", 0x9370DB;
+    MEDIUM_SEA_GREEN = "mediumseagreen", 0x3CB371;
+    MEDIUM_SLATE_BLUE = "mediumslateblue", 0x7B68EE;
+    MEDIUM_SPRING_GREEN = "mediumspringgreen", 0x00FA9A;
+    MEDIUM_TURQUOISE = "mediumturquoise", 0x48D1CC;
+    MEDIUM_VIOLET_RED = "mediumvioletred", 0xC71585;
+    MIDNIGHT_BLUE = "midnightblue", 0x191970;
+    MINT_CREAM = "mintcream", 0xF5FFFA;
+    MISTY_ROSE = "mistyrose", 0xFFE4E1;
+    MOCCASIN = "moccasin", 0xFFE4B5;
+    NAVAJO_WHITE = "navajowhite", 0xFFDEAD;
+    NAVY = "navy", 0x000080;
+    OLD_LACE = "oldlace", 0xFDF5E6;
+    OLIVE = "olive", 0x808000;
+    OLIVE_DRAB = "olivedrab", 0x6B8E23;
+    ORANGE = "orange", 0xFFA500;
+    ORANGE_RED = "orangered", 0xFF4500;
+    ORCHID = "orchid", 0xDA70D6;
+    PALE_GOLDENROD = "palegoldenrod", 0xEEE8AA;
+    PALE_GREEN = "palegreen", 0x98FB98;
+    PALE_TURQUOISE = "paleturquoise", 0xAFEEEE;
+    PALE_VIOLET_RED = "palevioletred", 0xDB7093;
+    PAPAYA_WHIP = "papayawhip", 0xFFEFD5;
+    PEACH_PUFF = "peachpuff", 0xFFDAB9;
+    PERU = "peru", 0xCD853F;
+    PINK = "pink", 0xFFC0CB;
+    PLUM = "plum", 0xDDA0DD;
+    POWDER_BLUE = "powderblue", 0xB0E0E6;
+    PURPLE = "purple", 0x800080;
+    REBECCA_PURPLE = "rebeccapurple", 0x663399;
+    RED = "red", 0xFF0000;
+    ROSY_BROWN = "rosybrown", 0xBC8F8F;
+    ROYAL_BLUE = "royalblue", 0x4169E1;
+    SADDLE_BROWN = "saddlebrown", 0x8B4513;
+    SALMON = "salmon", 0xFA8072;
+    SANDY_BROWN = "sandybrown", 0xF4A460;
+    SEA_GREEN = "seagreen", 0x2E8B57;
+    SEASHELL = "seashell", 0xFFF5EE;
+    SIENNA = "sienna", 0xA0522D;
+    SILVER = "silver", 0xC0C0C0;
+    SKY_BLUE = "skyblue", 0x87CEEB;
+    SLATE_BLUE = "slateblue", 0x6A5ACD;
+    SLATE_GRAY = "slategray", 0x708090;
+    SNOW = "snow", 0xFFFAFA;
+    SPRING_GREEN = "springgreen", 0x00FF7F;
+    STEEL_BLUE = "steelblue", 0x4682B4;
+    TAN = "tan", 0xD2B48C;
+    TEAL = "teal", 0x008080;
+    THISTLE = "thistle", 0xD8BFD8;
+    TOMATO = "tomato", 0xFF6347;
+    TURQUOISE = "turquoise", 0x40E0D0;
+    VIOLET = "violet", 0xEE82EE;
+    WHEAT = "wheat", 0xF5DEB3;
+    WHITE = "white", 0xFFFFFF;
+    WHITE_SMOKE = "whitesmoke", 0xF5F5F5;
+    YELLOW = "yellow", 0xFFFF00;
+    YELLOW_GREEN = "yellowgreen", 0x9ACD32;
+}
+
+/// Returns the web color named `name`, such as `cornflowerblue`, in any case. The `grey`
+/// spellings of the grays are accepted too.
+pub fn by_name(name: &str) -> Option<Color> {
+    let name = name.to_ascii_lowercase().replace("grey", "gray");
+    super::find(COLORS, &name)
+}
@@ -0,0 +1,167 @@
+//! Paths: shapes made of lines and curves, built step by step and stroked or filled at once.
+
+use crate::draw::flatten;
+use crate::{Canvas, Color};
+
+/// A step of a path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    CurveTo(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+/// A shape made of straight lines and cubic Bézier curves in pixel coordinates, such as an
+/// outline of a glyph, a rounded panel or a curved arrow, drawn with [`Canvas::stroke_path`]
+/// and [`Canvas::fill_path`].
+///
+/// A path is made of subpaths: each [`move_to`](Path::move_to) starts one, and
+/// [`close`](Path::close) joins its end back to its start. Coordinates may be fractional and
+/// outside the canvas.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Path};
+///
+/// let white = Color { r: 255, g: 255, b: 255 };
+/// let mut canvas = Canvas::new(30, 10, Color { r: 0, g: 0, b: 0 });
+/// // A speech bubble: a rectangle with a rounded right side and a tail.
+/// let bubble = Path::new()
+///     .move_to(2.0, 2.0)
+///     .line_to(20.0, 2.0)
+///     .curve_to(28.0, 2.0, 28.0, 14.0, 20.0, 14.0)
+///     .line_to(8.0, 14.0)
+///     .line_to(4.0, 18.0)
+///     .line_to(4.0, 14.0)
+///     .line_to(2.0, 14.0)
+///     .close();
+/// canvas.fill_path(&bubble, 0, white);
+/// assert_eq!(canvas.composited_cell(10, 4).top_color, white);
+/// assert_eq!(canvas.composited_cell(27, 0).top_color, Color { r: 0, g: 0, b: 0 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Path {
+    commands: Vec<Command>,
+}
+
+impl Path {
+    /// Creates an empty path.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new subpath at (`x`, `y`).
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(Command::MoveTo(x, y));
+        self
+    }
+
+    /// Adds a straight line from the current point to (`x`, `y`). Without a current point,
+    /// starts a new subpath at (`x`, `y`) instead.
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(Command::LineTo(x, y));
+        self
+    }
+
+    /// Adds a cubic Bézier curve from the current point to (`x`, `y`), leaving towards
+    /// (`x1`, `y1`) and arriving from (`x2`, `y2`). Without a current point, the curve starts
+    /// at (`x1`, `y1`).
+    pub fn curve_to(mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) -> Self {
+        self.commands.push(Command::CurveTo(x1, y1, x2, y2, x, y));
+        self
+    }
+
+    /// Joins the current subpath back to its start with a straight line. The next line or
+    /// curve starts a new subpath there.
+    pub fn close(mut self) -> Self {
+        self.commands.push(Command::Close);
+        self
+    }
+
+    /// Returns whether the path has no lines or curves.
+    pub fn is_empty(&self) -> bool {
+        !self
+            .commands
+            .iter()
+            .any(|command| matches!(command, Command::LineTo(..) | Command::CurveTo(..)))
+    }
+
+    /// Returns the subpaths as the points of their lines, taken through `transform`, with
+    /// curves split into lines, and whether they are closed.
+    fn flattened(
+        &self,
+        transform: impl Fn((f64, f64)) -> (f64, f64),
+    ) -> Vec<(Vec<(f64, f64)>, bool)> {
+        let mut subpaths = Vec::new();
+        let mut points: Vec<(f64, f64)> = Vec::new();
+        let mut start = None;
+        let at = |x: f32, y: f32| transform((x as f64, y as f64));
+        for command in &self.commands {
+            match *command {
+                Command::MoveTo(x, y) => {
+                    if points.len() > 1 {
+                        subpaths.push((points, false));
+                    }
+                    points = vec![at(x, y)];
+                    start = Some(at(x, y));
+                }
+                Command::LineTo(x, y) => {
+                    let end = at(x, y);
+                    if points.is_empty() {
+                        points.push(start.unwrap_or(end));
+                    }
+                    points.push(end);
+                }
+                Command::CurveTo(x1, y1, x2, y2, x, y) => {
+                    let (control1, control2) = (at(x1, y1), at(x2, y2));
+                    if points.is_empty() {
+                        points.push(start.unwrap_or(control1));
+                    }
+                    let from = points[points.len() - 1];
+                    flatten([from, control1, control2, at(x, y)], 0, &mut points);
+                }
+                Command::Close => {
+                    if points.len() > 1 {
+                        subpaths.push((points, true));
+                    }
+                    points = Vec::new();
+                }
+            }
+        }
+        if points.len() > 1 {
+            subpaths.push((points, false));
+        }
+        subpaths
+    }
+}
+
+impl Canvas {
+    /// Draws the outline of `path` on layer `z`, as one-pixel lines between its points, with
+    /// curves split until their pieces are straight to within a quarter of a pixel. Closed
+    /// subpaths are joined back to their start. Parts outside the canvas are clipped.
+    pub fn stroke_path(&mut self, path: &Path, z: usize, color: Color) {
+        let round = |(x, y): (f64, f64)| (x.round() as isize, y.round() as isize);
+        for (points, closed) in path.flattened(|point| self.transformed(point)) {
+            let mut corners: Vec<(isize, isize)> = points.into_iter().map(round).collect();
+            if closed {
+                corners.push(corners[0]);
+            }
+            self.polyline(&corners, z, color);
+        }
+    }
+
+    /// Fills the inside of `path` on layer `z`, closing every subpath. Subpaths are filled
+    /// together by the even-odd rule, so a subpath inside another cuts a hole into it, as in the
+    /// letter O. Pixels are filled when their center is inside the path, and parts outside the
+    /// canvas are clipped.
+    pub fn fill_path(&mut self, path: &Path, z: usize, color: Color) {
+        let rings: Vec<Vec<(f64, f64)>> = path
+            .flattened(|point| self.transformed(point))
+            .into_iter()
+            .map(|(points, _)| points)
+            .collect();
+        self.fill_rings(&rings, z, color);
+    }
+}
@@ -0,0 +1,446 @@
+//! Pathfinding on grids of cells: A* for single paths, Dijkstra maps and flow fields for AI.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::TileMap;
+
+/// The cost of an orthogonal step, scaled so diagonal steps can cost roughly √2 times as much
+/// while staying integers.
+const ORTHOGONAL: u64 = 10;
+const DIAGONAL: u64 = 14;
+
+/// The offsets of the orthogonal neighbors of a cell, followed by the diagonal ones.
+const NEIGHBORS: [(isize, isize); 8] = [
+    (0, -1),
+    (1, 0),
+    (0, 1),
+    (-1, 0),
+    (1, -1),
+    (1, 1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Whether paths may move diagonally between cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Diagonals {
+    /// Only orthogonal moves are allowed.
+    #[default]
+    Never,
+    /// Diagonal moves are allowed, even between two blocked cells.
+    Always,
+    /// Diagonal moves are allowed only when both orthogonal cells next to the move are passable,
+    /// so paths do not cut the corners of walls.
+    NoCornerCutting,
+}
+
+/// The cost of entering every cell of a grid, looked up lazily and remembered.
+struct Costs<F> {
+    width: usize,
+    height: usize,
+    known: Vec<Option<Option<u32>>>,
+    cost: F,
+}
+
+impl<F: FnMut(usize, usize) -> Option<u32>> Costs<F> {
+    fn new(width: usize, height: usize, cost: F) -> Self {
+        Self {
+            width,
+            height,
+            known: vec![None; width * height],
+            cost,
+        }
+    }
+
+    /// Returns the cost of entering the cell at (`x`, `y`), or `None` if it is out of bounds or
+    /// impassable. Costs below 1 are raised to 1.
+    fn get(&mut self, x: isize, y: isize) -> Option<u32> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        let index = y as usize * self.width + x as usize;
+        *self.known[index]
+            .get_or_insert_with(|| (self.cost)(x as usize, y as usize).map(|cost| cost.max(1)))
+    }
+}
+
+/// Calls `visit` with the index of every neighbor reachable from cell `index` of a grid
+/// `width` cells wide and the cost of the step, scaled by [`ORTHOGONAL`]. `cost` returns the cost
+/// of entering a cell, or `None` if it is out of bounds or impassable.
+fn for_each_neighbor(
+    width: usize,
+    index: usize,
+    diagonals: Diagonals,
+    mut cost: impl FnMut(isize, isize) -> Option<u32>,
+    mut visit: impl FnMut(usize, u64),
+) {
+    let (x, y) = ((index % width) as isize, (index / width) as isize);
+    let count = if diagonals == Diagonals::Never { 4 } else { 8 };
+    for (dx, dy) in &NEIGHBORS[..count] {
+        let (nx, ny) = (x + dx, y + dy);
+        let Some(step_cost) = cost(nx, ny) else {
+            continue;
+        };
+        let diagonal = *dx != 0 && *dy != 0;
+        if diagonal
+            && diagonals == Diagonals::NoCornerCutting
+            && (cost(nx, y).is_none() || cost(x, ny).is_none())
+        {
+            continue;
+        }
+        let step = if diagonal { DIAGONAL } else { ORTHOGONAL };
+        visit(ny as usize * width + nx as usize, step_cost as u64 * step);
+    }
+}
+
+/// Finds the cheapest path between two cells of a `width` by `height` grid with A*.
+///
+/// `cost` returns the cost of entering a cell, or `None` if the cell cannot be entered.
+/// Costs are at least 1, and diagonal moves cost about √2 times as much as orthogonal ones.
+///
+/// Returns the cells of the path from `start` to `goal`, both included, or `None` if the goal
+/// cannot be reached.
+///
+/// # Example
+///
+/// ```
+/// use rael::{find_path, Diagonals};
+///
+/// let map = [
+///     "....#....",
+///     ".##.#.##.",
+///     "....#....",
+///     ".........",
+/// ];
+/// let path = find_path(9, 4, (0, 0), (8, 0), Diagonals::Never, |x, y| {
+///     (map[y].as_bytes()[x] == b'.').then_some(1)
+/// })
+/// .unwrap();
+/// assert_eq!(path.len(), 15);
+/// ```
+pub fn find_path(
+    width: usize,
+    height: usize,
+    start: (usize, usize),
+    goal: (usize, usize),
+    diagonals: Diagonals,
+    cost: impl FnMut(usize, usize) -> Option<u32>,
+) -> Option<Vec<(usize, usize)>> {
+    if start.0 >= width || start.1 >= height || goal.0 >= width || goal.1 >= height {
+        return None;
+    }
+    let mut costs = Costs::new(width, height, cost);
+    let heuristic = |index: usize| {
+        let dx = (index % width).abs_diff(goal.0) as u64;
+        let dy = (index / width).abs_diff(goal.1) as u64;
+        match diagonals {
+            Diagonals::Never => (dx + dy) * ORTHOGONAL,
+            _ => dx.max(dy) * ORTHOGONAL + dx.min(dy) * (DIAGONAL - ORTHOGONAL),
+        }
+    };
+
+    let start = start.1 * width + start.0;
+    let goal = goal.1 * width + goal.0;
+    let mut distance = vec![u64::MAX; width * height];
+    let mut came_from = vec![usize::MAX; width * height];
+    let mut open = BinaryHeap::new();
+    distance[start] = 0;
+    open.push(Reverse((heuristic(start), 0, start)));
+
+    while let Some(Reverse((_, cell_distance, cell))) = open.pop() {
+        if cell == goal {
+            let mut path = vec![(cell % width, cell / width)];
+            let mut cell = cell;
+            while cell != start {
+                cell = came_from[cell];
+                path.push((cell % width, cell / width));
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if cell_distance > distance[cell] {
+            continue;
+        }
+        let cost = |x, y| costs.get(x, y);
+        for_each_neighbor(width, cell, diagonals, cost, |neighbor, step| {
+            let neighbor_distance = cell_distance + step;
+            if neighbor_distance < distance[neighbor] {
+                distance[neighbor] = neighbor_distance;
+                came_from[neighbor] = cell;
+                open.push(Reverse((
+                    neighbor_distance + heuristic(neighbor),
+                    neighbor_distance,
+                    neighbor,
+                )));
+            }
+        });
+    }
+    None
+}
+
+/// Runs Dijkstra's algorithm from cells with the given starting distances, scaled by
+/// [`ORTHOGONAL`], and returns the distance of every cell, or `None` for unreachable cells.
+fn dijkstra(
+    width: usize,
+    costs: &[Option<u32>],
+    diagonals: Diagonals,
+    seeds: impl IntoIterator<Item = (usize, i64)>,
+) -> Vec<Option<i64>> {
+    let mut distance = vec![None; costs.len()];
+    let mut open = BinaryHeap::new();
+    for (cell, seed) in seeds {
+        if distance[cell].is_none_or(|distance| seed < distance) {
+            distance[cell] = Some(seed);
+            open.push(Reverse((seed, cell)));
+        }
+    }
+    let height = costs.len() / width.max(1);
+    let cost = |x: isize, y: isize| {
+        if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+            return None;
+        }
+        costs[y as usize * width + x as usize]
+    };
+    while let Some(Reverse((cell_distance, cell))) = open.pop() {
+        if distance[cell].is_some_and(|distance| cell_distance > distance) {
+            continue;
+        }
+        for_each_neighbor(width, cell, diagonals, cost, |neighbor, step| {
+            let neighbor_distance = cell_distance + step as i64;
+            if distance[neighbor].is_none_or(|distance| neighbor_distance < distance) {
+                distance[neighbor] = Some(neighbor_distance);
+                open.push(Reverse((neighbor_distance, neighbor)));
+            }
+        });
+    }
+    distance
+}
+
+/// The distance from every cell of a grid to the nearest of a set of goal cells.
+///
+/// Also known as a distance map, it drives many agents at once: rolling downhill approaches the
+/// nearest goal, and [`DijkstraMap::fleeing`] turns it into a map that runs away from the goals
+/// while heading for open space rather than into dead ends.
+///
+/// Distances use the same costs as [`find_path`]: an orthogonal step into a cell of cost 1 adds
+/// 1 to the distance.
+///
+/// # Example
+///
+/// ```
+/// use rael::{DijkstraMap, Diagonals};
+///
+/// // Monsters approach the nearest of two players on an open 20x10 field.
+/// let players = [(2, 2), (15, 7)];
+/// let map = DijkstraMap::new(20, 10, &players, Diagonals::Always, |_, _| Some(1));
+/// assert_eq!(map.distance(2, 2), Some(0.0));
+/// assert_eq!(map.downhill(5, 2), Some((4, 2)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DijkstraMap {
+    width: usize,
+    height: usize,
+    diagonals: Diagonals,
+    costs: Vec<Option<u32>>,
+    distances: Vec<Option<i64>>,
+}
+
+impl DijkstraMap {
+    /// Computes the distance from every cell of a `width` by `height` grid to the nearest of
+    /// `goals`.
+    ///
+    /// `cost` returns the cost of entering a cell, or `None` if the cell cannot be entered, as
+    /// for [`find_path`]. Goals outside the grid are ignored.
+    pub fn new(
+        width: usize,
+        height: usize,
+        goals: &[(usize, usize)],
+        diagonals: Diagonals,
+        mut cost: impl FnMut(usize, usize) -> Option<u32>,
+    ) -> Self {
+        let costs: Vec<_> = (0..width * height)
+            .map(|index| cost(index % width, index / width).map(|cost| cost.max(1)))
+            .collect();
+        let seeds = goals
+            .iter()
+            .filter(|(x, y)| *x < width && *y < height)
+            .map(|(x, y)| (y * width + x, 0));
+        let distances = dijkstra(width, &costs, diagonals, seeds);
+        Self {
+            width,
+            height,
+            diagonals,
+            costs,
+            distances,
+        }
+    }
+
+    /// Returns the width of the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the distance from (`x`, `y`) to the nearest goal, or `None` if no goal can be
+    /// reached from there.
+    pub fn distance(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.distances[y * self.width + x].map(|distance| distance as f32 / ORTHOGONAL as f32)
+    }
+
+    /// Returns the neighbor of (`x`, `y`) with the lowest distance, if it is lower than the
+    /// distance of (`x`, `y`) itself. Following it step by step leads to the nearest goal.
+    pub fn downhill(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let cell = y * self.width + x;
+        let mut best = (self.distances[cell]?, None);
+        let cost = |x: isize, y: isize| {
+            if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+                return None;
+            }
+            self.costs[y as usize * self.width + x as usize]
+        };
+        for_each_neighbor(self.width, cell, self.diagonals, cost, |neighbor, _| {
+            if let Some(distance) = self.distances[neighbor] {
+                if distance < best.0 {
+                    best = (distance, Some(neighbor));
+                }
+            }
+        });
+        best.1.map(|cell| (cell % self.width, cell / self.width))
+    }
+
+    /// Returns a map for running away from the goals of this map.
+    ///
+    /// The distances are multiplied by `-factor` and smoothed out again, so agents rolling
+    /// downhill move away from the goals but prefer escape routes over corners. A factor around
+    /// `1.2` works well, and larger factors make agents bolder about running past the goals to
+    /// reach open space.
+    pub fn fleeing(&self, factor: f32) -> Self {
+        let seeds = self
+            .distances
+            .iter()
+            .enumerate()
+            .filter_map(|(cell, distance)| {
+                Some((cell, (-((*distance)? as f32) * factor).round() as i64))
+            });
+        let distances = dijkstra(self.width, &self.costs, self.diagonals, seeds);
+        Self {
+            distances,
+            ..self.clone()
+        }
+    }
+
+    /// Builds a flow field pointing every cell downhill, for many agents heading to the same
+    /// goals.
+    pub fn flow_field(&self) -> FlowField {
+        let next = (0..self.width * self.height)
+            .map(|cell| {
+                self.downhill(cell % self.width, cell / self.width)
+                    .map(|(x, y)| y * self.width + x)
+            })
+            .collect();
+        FlowField {
+            width: self.width,
+            height: self.height,
+            next,
+        }
+    }
+}
+
+/// The direction of the next step towards the nearest goal, for every cell of a grid.
+///
+/// Computing the field once lets any number of agents sharing the same goals move with a single
+/// lookup each, instead of running a path search per agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowField {
+    width: usize,
+    height: usize,
+    next: Vec<Option<usize>>,
+}
+
+impl FlowField {
+    /// Computes the flow field of a `width` by `height` grid towards the nearest of `goals`.
+    /// See [`DijkstraMap::new`] for the arguments.
+    pub fn new(
+        width: usize,
+        height: usize,
+        goals: &[(usize, usize)],
+        diagonals: Diagonals,
+        cost: impl FnMut(usize, usize) -> Option<u32>,
+    ) -> Self {
+        DijkstraMap::new(width, height, goals, diagonals, cost).flow_field()
+    }
+
+    /// Returns the width of the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the cell to move to from (`x`, `y`), or `None` at a goal or where no goal can be
+    /// reached.
+    pub fn next(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.next[y * self.width + x].map(|cell| (cell % self.width, cell / self.width))
+    }
+
+    /// Returns the step to take from (`x`, `y`) as an offset of -1, 0 or 1 on each axis.
+    pub fn direction(&self, x: usize, y: usize) -> Option<(isize, isize)> {
+        let (nx, ny) = self.next(x, y)?;
+        Some((nx as isize - x as isize, ny as isize - y as isize))
+    }
+}
+
+impl TileMap {
+    /// Finds the cheapest path between two cells of the map with A*.
+    ///
+    /// `cost` returns the cost of entering a cell holding the given tile, or `None` if such
+    /// cells cannot be entered. See [`find_path`] for details.
+    pub fn find_path(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+        diagonals: Diagonals,
+        mut cost: impl FnMut(Option<usize>) -> Option<u32>,
+    ) -> Option<Vec<(usize, usize)>> {
+        find_path(
+            self.width(),
+            self.height(),
+            start,
+            goal,
+            diagonals,
+            |x, y| cost(self.get(x, y)),
+        )
+    }
+
+    /// Computes the distance from every cell of the map to the nearest of `goals`.
+    ///
+    /// `cost` returns the cost of entering a cell holding the given tile, or `None` if such
+    /// cells cannot be entered. See [`DijkstraMap::new`] for details.
+    pub fn dijkstra_map(
+        &self,
+        goals: &[(usize, usize)],
+        diagonals: Diagonals,
+        mut cost: impl FnMut(Option<usize>) -> Option<u32>,
+    ) -> DijkstraMap {
+        DijkstraMap::new(self.width(), self.height(), goals, diagonals, |x, y| {
+            cost(self.get(x, y))
+        })
+    }
+}
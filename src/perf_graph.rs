@@ -0,0 +1,231 @@
+//! An on-screen graph of frame times and output sizes, for spotting performance hitches.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::{Canvas, CellGlyph, Color};
+
+/// A corner of the canvas, where an overlay is anchored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Corner {
+    /// The top-left corner.
+    TopLeft,
+    /// The top-right corner.
+    #[default]
+    TopRight,
+    /// The bottom-left corner.
+    BottomLeft,
+    /// The bottom-right corner.
+    BottomRight,
+}
+
+/// A rolling graph of recent frame times and bytes sent, drawn in a corner of the canvas like
+/// the performance overlays of game engines.
+///
+/// Every frame is a bar, green within the frame time budget, yellow up to twice the budget and
+/// red beyond it, with the budget marked by a dotted line. The bytes sent per frame, scaled to
+/// the largest in view, are drawn as a cyan line. The top row reads the time and size of the
+/// latest frame.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{Canvas, Color, Corner, PerfGraph, Terminal};
+///
+/// let mut terminal = Terminal::stdout()?;
+/// let (width, height) = terminal.size()?;
+/// let mut canvas = Canvas::new(width, height, Color { r: 0, g: 0, b: 0 });
+/// let mut graph = PerfGraph::new(40, 12).corner(Corner::BottomRight);
+/// loop {
+///     canvas.clear();
+///     // Draw the scene, then the graph on the top layer.
+///     graph.draw(&mut canvas, 9);
+///     terminal.draw(&mut canvas)?;
+///     graph.tick(terminal.last_draw_bytes());
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct PerfGraph {
+    width: usize,
+    height: usize,
+    corner: Corner,
+    budget: Duration,
+    samples: VecDeque<(Duration, Option<usize>)>,
+    last_tick: Option<Instant>,
+}
+
+impl PerfGraph {
+    const BACKGROUND: Color = Color {
+        r: 20,
+        g: 20,
+        b: 28,
+    };
+    const WITHIN_BUDGET: Color = Color { r: 0, g: 200, b: 0 };
+    const OVER_BUDGET: Color = Color {
+        r: 230,
+        g: 200,
+        b: 0,
+    };
+    const FAR_OVER_BUDGET: Color = Color { r: 230, g: 0, b: 0 };
+    const BUDGET_LINE: Color = Color {
+        r: 160,
+        g: 160,
+        b: 160,
+    };
+    const BYTES: Color = Color {
+        r: 0,
+        g: 220,
+        b: 230,
+    };
+    const TEXT: Color = Color {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    /// Creates a graph of `width` by `height` pixels, keeping one frame per pixel column. The
+    /// top 2 pixels hold the text row.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            corner: Corner::default(),
+            budget: Duration::from_secs(1) / 60,
+            samples: VecDeque::with_capacity(width),
+            last_tick: None,
+        }
+    }
+
+    /// Sets the corner of the canvas the graph is drawn in. Defaults to [`Corner::TopRight`].
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Sets the frame time budget, such as a 60th of a second for 60 frames per second. The
+    /// bars reach the top of the graph at twice the budget. Defaults to a 60th of a second.
+    pub fn budget(mut self, budget: Duration) -> Self {
+        self.budget = budget.max(Duration::from_micros(1));
+        self
+    }
+
+    /// Adds a frame that took `frame_time` and sent `bytes` bytes, if known, dropping the oldest
+    /// frame once the graph is full.
+    pub fn record(&mut self, frame_time: Duration, bytes: Option<usize>) {
+        if self.samples.len() == self.width {
+            self.samples.pop_front();
+        }
+        if self.width > 0 {
+            self.samples.push_back((frame_time, bytes));
+        }
+    }
+
+    /// Adds a frame that took the time since the previous tick and sent `bytes` bytes, such as
+    /// [`Terminal::last_draw_bytes`]. Call it once per frame; the first call only starts timing.
+    ///
+    /// [`Terminal::last_draw_bytes`]: crate::Terminal::last_draw_bytes
+    pub fn tick(&mut self, bytes: Option<usize>) {
+        let now = Instant::now();
+        if let Some(last_tick) = self.last_tick.replace(now) {
+            self.record(now - last_tick, bytes);
+        }
+    }
+
+    /// Returns the average time of the frames in the graph, if any.
+    pub fn average_frame_time(&self) -> Option<Duration> {
+        let total: Duration = self.samples.iter().map(|(time, _)| *time).sum();
+        (!self.samples.is_empty()).then(|| total / self.samples.len() as u32)
+    }
+
+    /// Returns the longest time of the frames in the graph, if any.
+    pub fn worst_frame_time(&self) -> Option<Duration> {
+        self.samples.iter().map(|(time, _)| *time).max()
+    }
+
+    /// Removes every frame from the graph.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.last_tick = None;
+    }
+
+    /// Draws the graph in its corner of `canvas`, on layer `z`.
+    pub fn draw(&self, canvas: &mut Canvas, z: usize) {
+        let width = self.width.min(canvas.final_width);
+        let height = self.height.min(canvas.final_height);
+        if width == 0 || height < 3 {
+            return;
+        }
+        let left = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => 0,
+            Corner::TopRight | Corner::BottomRight => canvas.final_width - width,
+        };
+        // The text row needs the graph to start on the top pixel of a cell.
+        let top = match self.corner {
+            Corner::TopLeft | Corner::TopRight => 0,
+            Corner::BottomLeft | Corner::BottomRight => (canvas.final_height - height) & !1,
+        };
+        for y in top..top + height {
+            for x in left..left + width {
+                canvas.set_pixel(x, y, z, Self::BACKGROUND);
+            }
+        }
+
+        let graph_height = height - 2;
+        let bottom = top + height - 1;
+        let budget_line = bottom - graph_height / 2;
+        let max_bytes = self
+            .samples
+            .iter()
+            .filter_map(|(_, bytes)| *bytes)
+            .max()
+            .unwrap_or(0);
+        // The newest frame is on the right edge.
+        let first_column = left + width - self.samples.len().min(width);
+        let samples = self
+            .samples
+            .iter()
+            .skip(self.samples.len().saturating_sub(width));
+        for (x, (time, bytes)) in (first_column..).zip(samples) {
+            let ratio = time.as_secs_f32() / self.budget.as_secs_f32();
+            let color = if ratio <= 1.0 {
+                Self::WITHIN_BUDGET
+            } else if ratio <= 2.0 {
+                Self::OVER_BUDGET
+            } else {
+                Self::FAR_OVER_BUDGET
+            };
+            let bar = ((ratio / 2.0 * graph_height as f32).ceil() as usize).min(graph_height);
+            for y in bottom + 1 - bar..=bottom {
+                canvas.set_pixel(x, y, z, color);
+            }
+            if let Some(bytes) = bytes.filter(|_| max_bytes > 0) {
+                let level = bytes * (graph_height - 1) / max_bytes;
+                canvas.set_pixel(x, bottom - level, z, Self::BYTES);
+            }
+        }
+        for x in (left..left + width).step_by(2) {
+            canvas.set_pixel(x, budget_line, z, Self::BUDGET_LINE);
+        }
+
+        if let Some((time, bytes)) = self.samples.back() {
+            let mut text = format!("{:.1}ms", time.as_secs_f64() * 1000.0);
+            if let Some(bytes) = bytes {
+                text.push(' ');
+                text.push_str(&format_bytes(*bytes));
+            }
+            for (column, ch) in (left..left + width).zip(text.chars()) {
+                canvas.set_glyph(column, top / 2, CellGlyph::new(ch, Self::TEXT));
+            }
+        }
+    }
+}
+
+/// Formats a byte count with a unit, such as `512B` or `3.2KB`.
+fn format_bytes(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{bytes}B"),
+        1024..1_048_576 => format!("{:.1}KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1}MB", bytes as f64 / 1_048_576.0),
+    }
+}
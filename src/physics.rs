@@ -0,0 +1,185 @@
+//! Simple motion: kinematic bodies and a fixed timestep to advance them with.
+
+use std::time::Duration;
+
+/// The position and motion of a body, advanced with [`Kinematics::step`].
+///
+/// Positions are in half-block pixels and times in seconds, so a velocity of `10.0` moves a body
+/// ten pixels per second. The y axis points down, so gravity is usually positive.
+///
+/// # Example
+///
+/// ```
+/// use rael::Kinematics;
+///
+/// let mut ball = Kinematics::new(0.0, 40.0).gravity(60.0).max_speed(100.0);
+/// ball.velocity = (20.0, -50.0); // Throw it up and to the right.
+/// for _ in 0..60 {
+///     ball.step(1.0 / 60.0);
+/// }
+/// let (x, y) = ball.pixel_position();
+/// assert_eq!(x, 20);
+/// assert!(y < 40);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kinematics {
+    /// The position of the body.
+    pub position: (f32, f32),
+    /// The velocity of the body, in pixels per second.
+    pub velocity: (f32, f32),
+    /// The acceleration applied by the body itself, such as thrust, in pixels per second squared.
+    pub acceleration: (f32, f32),
+    /// The downward acceleration of gravity, in pixels per second squared.
+    pub gravity: f32,
+    /// The fraction of its velocity the body loses every second, from `0.0` (no drag) up.
+    pub drag: f32,
+    /// The highest speed the body can reach, if any.
+    pub max_speed: Option<f32>,
+}
+
+impl Kinematics {
+    /// Creates a body at rest at (`x`, `y`), with no gravity, drag or speed limit.
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            position: (x, y),
+            velocity: (0.0, 0.0),
+            acceleration: (0.0, 0.0),
+            gravity: 0.0,
+            drag: 0.0,
+            max_speed: None,
+        }
+    }
+
+    /// Sets the downward acceleration of gravity.
+    pub fn gravity(mut self, gravity: f32) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Sets the fraction of its velocity the body loses every second.
+    pub fn drag(mut self, drag: f32) -> Self {
+        self.drag = drag.max(0.0);
+        self
+    }
+
+    /// Limits the speed of the body.
+    pub fn max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = Some(max_speed.max(0.0));
+        self
+    }
+
+    /// Adds `(dx, dy)` to the velocity at once, as for a jump or an explosion.
+    pub fn impulse(&mut self, dx: f32, dy: f32) {
+        self.velocity.0 += dx;
+        self.velocity.1 += dy;
+    }
+
+    /// Returns the speed of the body, in pixels per second.
+    pub fn speed(&self) -> f32 {
+        self.velocity.0.hypot(self.velocity.1)
+    }
+
+    /// Returns the position rounded to the nearest pixel, ready for drawing.
+    pub fn pixel_position(&self) -> (isize, isize) {
+        (
+            self.position.0.round() as isize,
+            self.position.1.round() as isize,
+        )
+    }
+
+    /// Advances the body by `dt` seconds.
+    ///
+    /// The velocity is updated before the position (semi-implicit Euler), which stays stable for
+    /// the step sizes of a game loop. Stepping by a constant `dt`, for example with a
+    /// [`FixedTimestep`], makes the motion independent of the frame rate.
+    pub fn step(&mut self, dt: f32) {
+        self.velocity.0 += self.acceleration.0 * dt;
+        self.velocity.1 += (self.acceleration.1 + self.gravity) * dt;
+        if self.drag > 0.0 {
+            let factor = (-self.drag * dt).exp();
+            self.velocity.0 *= factor;
+            self.velocity.1 *= factor;
+        }
+        if let Some(max_speed) = self.max_speed {
+            let speed = self.speed();
+            if speed > max_speed {
+                self.velocity.0 *= max_speed / speed;
+                self.velocity.1 *= max_speed / speed;
+            }
+        }
+        self.position.0 += self.velocity.0 * dt;
+        self.position.1 += self.velocity.1 * dt;
+    }
+}
+
+/// Splits the variable time between frames into a whole number of fixed steps.
+///
+/// Leftover time is carried over to the next frame, so the simulation runs at the same pace
+/// whatever the frame rate. The number of steps per frame is capped, so a long stall does not
+/// make the game try to catch up for ever.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{FixedTimestep, Kinematics};
+/// use std::time::Instant;
+///
+/// let mut timestep = FixedTimestep::new(60.0);
+/// let mut player = Kinematics::new(10.0, 10.0).gravity(80.0);
+/// let mut last_frame = Instant::now();
+/// loop {
+///     let now = Instant::now();
+///     for _ in 0..timestep.advance(now - last_frame) {
+///         player.step(timestep.step());
+///     }
+///     last_frame = now;
+///     // Draw the frame...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedTimestep {
+    step: f32,
+    accumulator: f32,
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    /// Creates a timestep running `steps_per_second` steps per second, with at most 5 steps per
+    /// frame.
+    pub fn new(steps_per_second: f32) -> Self {
+        Self {
+            step: 1.0 / steps_per_second.max(f32::MIN_POSITIVE),
+            accumulator: 0.0,
+            max_steps: 5,
+        }
+    }
+
+    /// Sets the largest number of steps [`FixedTimestep::advance`] returns for a single frame.
+    pub fn max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Returns the length of a step, in seconds.
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Adds the time `elapsed` since the last frame and returns how many steps to run now.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed.as_secs_f32();
+        let steps = (self.accumulator / self.step).floor() as u32;
+        if steps > self.max_steps {
+            self.accumulator = 0.0;
+            return self.max_steps;
+        }
+        self.accumulator -= steps as f32 * self.step;
+        steps
+    }
+
+    /// Returns how far the simulation is into the next step, from `0.0` to `1.0`, for
+    /// interpolating positions between the last two steps when drawing.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+}
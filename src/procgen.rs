@@ -0,0 +1,134 @@
+//! Procedural generation of dungeons, caves and tile maps.
+//!
+//! The dungeon and cave generators are builders producing a [`GeneratedMap`] of floor and wall
+//! cells, which can be turned into a [`TileMap`] or used directly for pathfinding and field of
+//! view. [`WaveFunctionCollapse`] instead produces tile maps that follow the patterns of a
+//! sample map. Generators are seeded, so the same seed always produces the same map.
+
+mod bsp;
+mod cave;
+mod walk;
+mod wfc;
+
+pub use bsp::BspDungeon;
+pub use cave::CaveGenerator;
+pub use walk::DrunkardsWalk;
+pub use wfc::WaveFunctionCollapse;
+
+use crate::{Rect, TileMap, Tileset};
+
+/// A grid of floor and wall cells made by a generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedMap {
+    width: usize,
+    height: usize,
+    floor: Vec<bool>,
+    rooms: Vec<Rect>,
+}
+
+impl GeneratedMap {
+    /// Creates a map of `width` by `height` cells, all walls.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            floor: vec![false; width * height],
+            rooms: Vec::new(),
+        }
+    }
+
+    /// Returns the width of the map in cells.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the map in cells.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns `true` if the cell at (`x`, `y`) is floor. Cells outside the map are walls.
+    pub fn is_floor(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.floor[y * self.width + x]
+    }
+
+    /// Makes the cell at (`x`, `y`) floor or wall. Out of bounds cells are ignored.
+    pub fn set_floor(&mut self, x: usize, y: usize, floor: bool) {
+        if x < self.width && y < self.height {
+            self.floor[y * self.width + x] = floor;
+        }
+    }
+
+    /// Returns the rooms of the map, in cells, for generators that make rooms.
+    pub fn rooms(&self) -> &[Rect] {
+        &self.rooms
+    }
+
+    /// Returns an iterator over the floor cells, row by row.
+    pub fn floor_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.floor
+            .iter()
+            .enumerate()
+            .filter(|(_, floor)| **floor)
+            .map(|(index, _)| (index % self.width, index / self.width))
+    }
+
+    /// Returns the number of floor cells.
+    pub fn floor_count(&self) -> usize {
+        self.floor.iter().filter(|floor| **floor).count()
+    }
+
+    /// Builds a [`TileMap`] drawn with `tileset`, putting `floor` on floor cells and `wall` on
+    /// wall cells.
+    pub fn to_tile_map(
+        &self,
+        tileset: Tileset,
+        floor: Option<usize>,
+        wall: Option<usize>,
+    ) -> TileMap {
+        let mut map = TileMap::new(self.width, self.height, tileset);
+        for (index, is_floor) in self.floor.iter().enumerate() {
+            let tile = if *is_floor { floor } else { wall };
+            map.set(index % self.width, index / self.width, tile);
+        }
+        map
+    }
+
+    /// Turns every floor region but the largest into walls, so the whole map is reachable.
+    pub(crate) fn keep_largest_region(&mut self) {
+        let mut region = vec![usize::MAX; self.floor.len()];
+        let mut sizes = Vec::new();
+        for start in 0..self.floor.len() {
+            if !self.floor[start] || region[start] != usize::MAX {
+                continue;
+            }
+            let id = sizes.len();
+            let mut size = 0;
+            let mut stack = vec![start];
+            region[start] = id;
+            while let Some(cell) = stack.pop() {
+                size += 1;
+                let (x, y) = (cell % self.width, cell / self.width);
+                let neighbors = [
+                    (x > 0).then(|| cell - 1),
+                    (x + 1 < self.width).then(|| cell + 1),
+                    (y > 0).then(|| cell - self.width),
+                    (y + 1 < self.height).then(|| cell + self.width),
+                ];
+                for neighbor in neighbors.into_iter().flatten() {
+                    if self.floor[neighbor] && region[neighbor] == usize::MAX {
+                        region[neighbor] = id;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+        let Some(largest) = (0..sizes.len()).max_by_key(|id| sizes[*id]) else {
+            return;
+        };
+        for (floor, region) in self.floor.iter_mut().zip(region) {
+            *floor = *floor && region == largest;
+        }
+    }
+}
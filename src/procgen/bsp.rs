@@ -0,0 +1,157 @@
+//! Room-and-corridor dungeons made by binary space partitioning.
+
+use super::GeneratedMap;
+use crate::rng::Rng;
+use crate::Rect;
+
+/// A dungeon of rectangular rooms joined by corridors.
+///
+/// The map is split in two again and again, each part gets a room, and the two halves of every
+/// split are joined by an L-shaped corridor, so every room is reachable.
+///
+/// # Example
+///
+/// ```
+/// use rael::procgen::BspDungeon;
+///
+/// let dungeon = BspDungeon::new(80, 40).min_room_size(4).seed(42).generate();
+/// assert!(dungeon.rooms().len() > 4);
+/// let first = dungeon.rooms()[0];
+/// assert!(dungeon.is_floor(first.x, first.y));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BspDungeon {
+    width: usize,
+    height: usize,
+    min_room_size: usize,
+    max_depth: usize,
+    seed: u64,
+}
+
+impl BspDungeon {
+    /// Creates a generator for a `width` by `height` dungeon.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            min_room_size: 4,
+            max_depth: 6,
+            seed: 0,
+        }
+    }
+
+    /// Sets the smallest width and height of a room. Defaults to 4.
+    pub fn min_room_size(mut self, size: usize) -> Self {
+        self.min_room_size = size.max(1);
+        self
+    }
+
+    /// Sets how many times the map is split, which caps the number of rooms at
+    /// `2^depth`. Defaults to 6.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets the random seed. Defaults to 0.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generates the dungeon.
+    pub fn generate(&self) -> GeneratedMap {
+        let mut map = GeneratedMap::new(self.width, self.height);
+        let mut rng = Rng::new(self.seed);
+        if self.width < 3 || self.height < 3 {
+            return map;
+        }
+        // Keep a wall around the whole map.
+        let area = Rect::new(1, 1, self.width - 2, self.height - 2);
+        self.split(&mut map, &mut rng, area, 0);
+        map
+    }
+
+    /// Fills `area` with rooms and returns the center of one of them, to connect to.
+    fn split(
+        &self,
+        map: &mut GeneratedMap,
+        rng: &mut Rng,
+        area: Rect,
+        depth: usize,
+    ) -> Option<(usize, usize)> {
+        // A part must hold a room and the wall between it and its neighbor.
+        let min_part = self.min_room_size + 1;
+        let can_split_x = area.width >= min_part * 2;
+        let can_split_y = area.height >= min_part * 2;
+        if depth < self.max_depth && (can_split_x || can_split_y) {
+            let vertical = match (can_split_x, can_split_y) {
+                (true, true) => {
+                    // Split across the longer side, or at random for square areas.
+                    if area.width * 4 > area.height * 5 {
+                        true
+                    } else if area.height * 4 > area.width * 5 {
+                        false
+                    } else {
+                        rng.chance(0.5)
+                    }
+                }
+                (vertical, _) => vertical,
+            };
+            let (first, second) = if vertical {
+                let at = rng.range(min_part, area.width - min_part + 1);
+                (
+                    Rect::new(area.x, area.y, at, area.height),
+                    Rect::new(area.x + at, area.y, area.width - at, area.height),
+                )
+            } else {
+                let at = rng.range(min_part, area.height - min_part + 1);
+                (
+                    Rect::new(area.x, area.y, area.width, at),
+                    Rect::new(area.x, area.y + at, area.width, area.height - at),
+                )
+            };
+            let a = self.split(map, rng, first, depth + 1);
+            let b = self.split(map, rng, second, depth + 1);
+            if let (Some(a), Some(b)) = (a, b) {
+                carve_corridor(map, rng, a, b);
+            }
+            return a.or(b);
+        }
+
+        // Leave a one cell wall on the right and bottom sides, shared with the next part.
+        let max_width = area.width.saturating_sub(1);
+        let max_height = area.height.saturating_sub(1);
+        if max_width < self.min_room_size || max_height < self.min_room_size {
+            return None;
+        }
+        let width = rng.range(self.min_room_size, max_width + 1);
+        let height = rng.range(self.min_room_size, max_height + 1);
+        let x = area.x + rng.range(0, max_width - width + 1);
+        let y = area.y + rng.range(0, max_height - height + 1);
+        for row in y..y + height {
+            for column in x..x + width {
+                map.set_floor(column, row, true);
+            }
+        }
+        map.rooms.push(Rect::new(x, y, width, height));
+        Some((x + width / 2, y + height / 2))
+    }
+}
+
+/// Carves an L-shaped corridor between two cells, turning horizontally or vertically first at
+/// random.
+fn carve_corridor(map: &mut GeneratedMap, rng: &mut Rng, from: (usize, usize), to: (usize, usize)) {
+    let corner = if rng.chance(0.5) {
+        (to.0, from.1)
+    } else {
+        (from.0, to.1)
+    };
+    for (a, b) in [(from, corner), (corner, to)] {
+        for y in a.1.min(b.1)..=a.1.max(b.1) {
+            for x in a.0.min(b.0)..=a.0.max(b.0) {
+                map.set_floor(x, y, true);
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Organic caves made with a cellular automaton.
+
+use super::GeneratedMap;
+use crate::rng::Rng;
+
+/// A cave of irregular, organic shapes.
+///
+/// The map starts as random noise, then is smoothed by repeatedly turning every cell into a wall
+/// when most of its neighbors are walls, and into floor otherwise.
+///
+/// # Example
+///
+/// ```
+/// use rael::procgen::CaveGenerator;
+///
+/// let cave = CaveGenerator::new(60, 30).fill_probability(0.45).seed(7).generate();
+/// assert!(cave.floor_count() > 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaveGenerator {
+    width: usize,
+    height: usize,
+    fill_probability: f32,
+    iterations: usize,
+    connected: bool,
+    seed: u64,
+}
+
+impl CaveGenerator {
+    /// Creates a generator for a `width` by `height` cave.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            fill_probability: 0.45,
+            iterations: 5,
+            connected: true,
+            seed: 0,
+        }
+    }
+
+    /// Sets the share of cells that start as walls. Higher values give narrower caves.
+    /// Defaults to 0.45.
+    pub fn fill_probability(mut self, probability: f32) -> Self {
+        self.fill_probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets how many smoothing passes are run. Defaults to 5.
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets whether floor areas cut off from the largest one are filled in, so the whole cave is
+    /// reachable. On by default.
+    pub fn connected(mut self, connected: bool) -> Self {
+        self.connected = connected;
+        self
+    }
+
+    /// Sets the random seed. Defaults to 0.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generates the cave.
+    pub fn generate(&self) -> GeneratedMap {
+        let (width, height) = (self.width, self.height);
+        let mut map = GeneratedMap::new(width, height);
+        let mut rng = Rng::new(self.seed);
+        let interior = |x: usize, y: usize| x > 0 && y > 0 && x + 1 < width && y + 1 < height;
+        for y in 0..height {
+            for x in 0..width {
+                if interior(x, y) && !rng.chance(self.fill_probability) {
+                    map.set_floor(x, y, true);
+                }
+            }
+        }
+
+        for _ in 0..self.iterations {
+            let previous = map.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    if !interior(x, y) {
+                        continue;
+                    }
+                    let walls = (y - 1..=y + 1)
+                        .flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+                        .filter(|&(nx, ny)| (nx, ny) != (x, y) && !previous.is_floor(nx, ny))
+                        .count();
+                    let wall = walls >= 5 || (walls == 4 && !previous.is_floor(x, y));
+                    map.set_floor(x, y, !wall);
+                }
+            }
+        }
+
+        if self.connected {
+            map.keep_largest_region();
+        }
+        map
+    }
+}
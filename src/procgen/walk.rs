@@ -0,0 +1,99 @@
+//! Winding caves dug by random walkers.
+
+use super::GeneratedMap;
+use crate::rng::Rng;
+
+/// A winding cave dug by a "drunkard" stumbling around at random.
+///
+/// Walkers start from the center of the map and dig until the requested share of the map is
+/// floor. Every walker starts on an already dug cell, so the cave is always connected.
+///
+/// # Example
+///
+/// ```
+/// use rael::procgen::DrunkardsWalk;
+///
+/// let cave = DrunkardsWalk::new(60, 30).floor_fraction(0.3).seed(1).generate();
+/// assert!(cave.floor_count() >= 58 * 28 * 3 / 10);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrunkardsWalk {
+    width: usize,
+    height: usize,
+    floor_fraction: f32,
+    walk_length: usize,
+    seed: u64,
+}
+
+impl DrunkardsWalk {
+    /// Creates a generator for a `width` by `height` cave.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            floor_fraction: 0.4,
+            walk_length: 200,
+            seed: 0,
+        }
+    }
+
+    /// Sets the share of the inside of the map to dig. Defaults to 0.4.
+    pub fn floor_fraction(mut self, fraction: f32) -> Self {
+        self.floor_fraction = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets how many steps each walker takes before a new one starts. Short walks give blobby
+    /// caves, long walks give long tunnels. Defaults to 200.
+    pub fn walk_length(mut self, steps: usize) -> Self {
+        self.walk_length = steps.max(1);
+        self
+    }
+
+    /// Sets the random seed. Defaults to 0.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generates the cave.
+    pub fn generate(&self) -> GeneratedMap {
+        let (width, height) = (self.width, self.height);
+        let mut map = GeneratedMap::new(width, height);
+        if width < 3 || height < 3 {
+            return map;
+        }
+        let mut rng = Rng::new(self.seed);
+        let target = (((width - 2) * (height - 2)) as f32 * self.floor_fraction).ceil() as usize;
+        let mut dug = 0;
+        let mut dig = |map: &mut GeneratedMap, x: usize, y: usize| {
+            if !map.is_floor(x, y) {
+                map.set_floor(x, y, true);
+                dug += 1;
+            }
+            dug >= target
+        };
+
+        let (mut x, mut y) = (width / 2, height / 2);
+        if dig(&mut map, x, y) {
+            return map;
+        }
+        loop {
+            for _ in 0..self.walk_length {
+                match rng.range(0, 4) {
+                    0 if x > 1 => x -= 1,
+                    1 if x + 2 < width => x += 1,
+                    2 if y > 1 => y -= 1,
+                    3 if y + 2 < height => y += 1,
+                    _ => continue,
+                }
+                if dig(&mut map, x, y) {
+                    return map;
+                }
+            }
+            // Start the next walker from a random dug cell.
+            let floor: Vec<_> = map.floor_cells().collect();
+            (x, y) = floor[rng.range(0, floor.len())];
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Wave function collapse: new maps that follow the tile adjacencies of a sample.
+
+use crate::rng::Rng;
+use crate::{TileMap, Tileset};
+
+/// The offsets of the up, right, down and left neighbors of a cell.
+const DIRECTIONS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Generates maps that look like a sample map, using the simple tiled model of wave function
+/// collapse.
+///
+/// The generator learns which tiles appear next to each other in the sample, and how often each
+/// tile appears. Generated maps only place tiles next to each other if they were neighbors in the
+/// same direction somewhere in the sample, and use tiles with roughly the same frequencies.
+/// Empty cells count as a tile of their own.
+///
+/// Generation can run into a contradiction, where no tile fits a cell. The generator then starts
+/// over, up to [`WaveFunctionCollapse::attempts`] times.
+///
+/// # Example
+///
+/// ```
+/// use rael::procgen::WaveFunctionCollapse;
+/// use rael::{Sprite, TileMap, Tileset};
+///
+/// // A sample of water (0) with islands of sand (1) and grass (2), where grass never
+/// // touches water.
+/// let rows = ["0000000", "0111110", "0122210", "0111110", "0000000"];
+/// let mut sample = TileMap::new(7, 5, Tileset::new(2, 2));
+/// for (y, row) in rows.iter().enumerate() {
+///     for (x, tile) in row.bytes().enumerate() {
+///         sample.set(x, y, Some((tile - b'0') as usize));
+///     }
+/// }
+///
+/// let map = WaveFunctionCollapse::from_sample(&sample).seed(3).generate(30, 20).unwrap();
+/// assert_eq!(map.width(), 30);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveFunctionCollapse {
+    tiles: Vec<Option<usize>>,
+    weights: Vec<f32>,
+    /// `allowed[direction][a][b]` is `true` if tile `b` may sit in `direction` from tile `a`.
+    allowed: [Vec<Vec<bool>>; 4],
+    tileset: Tileset,
+    periodic: bool,
+    attempts: usize,
+    seed: u64,
+}
+
+impl WaveFunctionCollapse {
+    /// Learns the tiles, their frequencies and their adjacencies from `sample`.
+    pub fn from_sample(sample: &TileMap) -> Self {
+        let mut tiles: Vec<Option<usize>> = Vec::new();
+        let mut weights = Vec::new();
+        let mut indices = vec![0; sample.width() * sample.height()];
+        for y in 0..sample.height() {
+            for x in 0..sample.width() {
+                let tile = sample.get(x, y);
+                let index = match tiles.iter().position(|known| *known == tile) {
+                    Some(index) => index,
+                    None => {
+                        tiles.push(tile);
+                        weights.push(0.0);
+                        tiles.len() - 1
+                    }
+                };
+                weights[index] += 1.0;
+                indices[y * sample.width() + x] = index;
+            }
+        }
+
+        let count = tiles.len();
+        let mut allowed: [Vec<Vec<bool>>; 4] =
+            std::array::from_fn(|_| vec![vec![false; count]; count]);
+        for y in 0..sample.height() {
+            for x in 0..sample.width() {
+                let tile = indices[y * sample.width() + x];
+                for (direction, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+                    let (nx, ny) = (x as isize + dx, y as isize + dy);
+                    if nx < 0
+                        || ny < 0
+                        || nx as usize >= sample.width()
+                        || ny as usize >= sample.height()
+                    {
+                        continue;
+                    }
+                    let neighbor = indices[ny as usize * sample.width() + nx as usize];
+                    allowed[direction][tile][neighbor] = true;
+                }
+            }
+        }
+
+        Self {
+            tiles,
+            weights,
+            allowed,
+            tileset: sample.tileset().clone(),
+            periodic: false,
+            attempts: 10,
+            seed: 0,
+        }
+    }
+
+    /// Sets whether the generated map wraps around, so its left edge fits its right edge and its
+    /// top edge fits its bottom edge, for seamless tiling. Off by default.
+    pub fn periodic(mut self, periodic: bool) -> Self {
+        self.periodic = periodic;
+        self
+    }
+
+    /// Sets how many times generation starts over after a contradiction. Defaults to 10.
+    pub fn attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets the random seed. Defaults to 0.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns the number of distinct tiles learned from the sample, counting empty cells.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Generates a `width` by `height` map drawn with the tileset of the sample, or `None` if
+    /// every attempt ran into a contradiction.
+    pub fn generate(&self, width: usize, height: usize) -> Option<TileMap> {
+        if self.tiles.is_empty() {
+            return None;
+        }
+        let mut rng = Rng::new(self.seed);
+        for _ in 0..self.attempts {
+            if let Some(cells) = self.run(width, height, &mut rng) {
+                let mut map = TileMap::new(width, height, self.tileset.clone());
+                for (index, tile) in cells.into_iter().enumerate() {
+                    map.set(index % width, index / width, self.tiles[tile]);
+                }
+                return Some(map);
+            }
+        }
+        None
+    }
+
+    /// Returns the cell next to `cell` in `direction`, if there is one.
+    fn neighbor(
+        &self,
+        width: usize,
+        height: usize,
+        cell: usize,
+        direction: usize,
+    ) -> Option<usize> {
+        let (dx, dy) = DIRECTIONS[direction];
+        let (mut x, mut y) = ((cell % width) as isize + dx, (cell / width) as isize + dy);
+        if self.periodic {
+            x = x.rem_euclid(width as isize);
+            y = y.rem_euclid(height as isize);
+        } else if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+            return None;
+        }
+        Some(y as usize * width + x as usize)
+    }
+
+    /// Runs one attempt, returning the tile of every cell or `None` on a contradiction.
+    fn run(&self, width: usize, height: usize, rng: &mut Rng) -> Option<Vec<usize>> {
+        let count = self.tiles.len();
+        let cells = width * height;
+        let mut wave = vec![true; cells * count];
+        let mut stack = Vec::new();
+
+        loop {
+            // Observe the undecided cell with the lowest entropy, with a little noise to break
+            // ties.
+            let mut best = None;
+            let mut best_entropy = f32::MAX;
+            for cell in 0..cells {
+                let options = &wave[cell * count..(cell + 1) * count];
+                let (mut sum, mut sum_log, mut possible) = (0.0, 0.0, 0);
+                for (tile, option) in options.iter().enumerate() {
+                    if *option {
+                        let weight = self.weights[tile];
+                        sum += weight;
+                        sum_log += weight * weight.ln();
+                        possible += 1;
+                    }
+                }
+                if possible == 0 {
+                    return None;
+                }
+                if possible == 1 {
+                    continue;
+                }
+                let entropy = sum.ln() - sum_log / sum + rng.next_f32() * 1e-4;
+                if entropy < best_entropy {
+                    best_entropy = entropy;
+                    best = Some(cell);
+                }
+            }
+            let Some(cell) = best else {
+                return Some(
+                    (0..cells)
+                        .map(|cell| {
+                            wave[cell * count..(cell + 1) * count]
+                                .iter()
+                                .position(|option| *option)
+                                .unwrap_or(0)
+                        })
+                        .collect(),
+                );
+            };
+
+            // Collapse it to a single tile, picked by weight.
+            let options = &mut wave[cell * count..(cell + 1) * count];
+            let total: f32 = (0..count)
+                .filter(|tile| options[*tile])
+                .map(|tile| self.weights[tile])
+                .sum();
+            let mut pick = rng.next_f32() * total;
+            let mut chosen = 0;
+            for tile in (0..count).filter(|tile| options[*tile]) {
+                chosen = tile;
+                pick -= self.weights[tile];
+                if pick < 0.0 {
+                    break;
+                }
+            }
+            for (tile, option) in options.iter_mut().enumerate() {
+                *option = tile == chosen;
+            }
+
+            // Propagate the choice to the neighbors until nothing changes.
+            stack.push(cell);
+            while let Some(cell) = stack.pop() {
+                for direction in 0..4 {
+                    let Some(neighbor) = self.neighbor(width, height, cell, direction) else {
+                        continue;
+                    };
+                    let mut changed = false;
+                    for tile in 0..count {
+                        if !wave[neighbor * count + tile] {
+                            continue;
+                        }
+                        let supported = (0..count).any(|from| {
+                            wave[cell * count + from] && self.allowed[direction][from][tile]
+                        });
+                        if !supported {
+                            wave[neighbor * count + tile] = false;
+                            changed = true;
+                        }
+                    }
+                    if changed {
+                        if !wave[neighbor * count..(neighbor + 1) * count].contains(&true) {
+                            return None;
+                        }
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,409 @@
+//! A Wolfenstein-style raycaster drawing first-person views of tile maps.
+
+use std::f32::consts::FRAC_PI_3;
+
+use crate::{shade_color, Canvas, Color, Rect, Sprite, TileMap};
+
+/// The position and heading of the viewer of a [`Raycaster`], in map cells.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RaycastView {
+    /// The x position, where cell `(x, y)` covers `x..x + 1`.
+    pub x: f32,
+    /// The y position, where cell `(x, y)` covers `y..y + 1`.
+    pub y: f32,
+    /// The heading in radians. `0.0` faces along the x axis, and the angle grows clockwise on
+    /// the map.
+    pub angle: f32,
+}
+
+impl RaycastView {
+    /// Creates a view at (`x`, `y`) facing `angle`.
+    pub fn new(x: f32, y: f32, angle: f32) -> Self {
+        Self { x, y, angle }
+    }
+
+    /// Returns the unit vector the view is facing.
+    pub fn direction(&self) -> (f32, f32) {
+        (self.angle.cos(), self.angle.sin())
+    }
+
+    /// Moves `forward` cells ahead and `strafe` cells to the right, sliding along the walls of
+    /// `map` instead of entering them.
+    pub fn walk(&mut self, map: &TileMap, forward: f32, strafe: f32) {
+        let (dx, dy) = self.direction();
+        let step_x = dx * forward - dy * strafe;
+        let step_y = dy * forward + dx * strafe;
+        if !is_wall(map, self.x + step_x, self.y) {
+            self.x += step_x;
+        }
+        if !is_wall(map, self.x, self.y + step_y) {
+            self.y += step_y;
+        }
+    }
+}
+
+/// A sprite standing upright in a raycast scene, such as an item or an enemy.
+///
+/// Billboards always face the viewer, stand on the floor and are one cell tall, with their
+/// width following the aspect ratio of the sprite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Billboard<'a> {
+    /// The x position of the center of the billboard, in map cells.
+    pub x: f32,
+    /// The y position of the center of the billboard, in map cells.
+    pub y: f32,
+    /// The image of the billboard.
+    pub sprite: &'a Sprite,
+}
+
+/// Returns `true` if the map position falls in a wall. Positions outside the map do.
+fn is_wall(map: &TileMap, x: f32, y: f32) -> bool {
+    if x < 0.0 || y < 0.0 || x >= map.width() as f32 || y >= map.height() as f32 {
+        return true;
+    }
+    map.get(x as usize, y as usize).is_some()
+}
+
+/// Renders first-person views of a [`TileMap`], where every non-empty cell is a wall textured
+/// with its tile sprite.
+///
+/// Walls fade with distance, and walls facing north or south are drawn slightly darker than the
+/// others so corners stay readable. Billboards are hidden behind closer walls.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Billboard, Canvas, Color, RaycastView, Raycaster, Rect, Sprite, TileMap, Tileset};
+///
+/// let mut tileset = Tileset::new(8, 8);
+/// let brick = tileset.push(Sprite::filled(8, 8, Color { r: 160, g: 70, b: 50 }));
+/// let mut map = TileMap::new(16, 16, tileset);
+/// for i in 0..16 {
+///     map.set(i, 0, Some(brick));
+///     map.set(i, 15, Some(brick));
+///     map.set(0, i, Some(brick));
+///     map.set(15, i, Some(brick));
+/// }
+///
+/// let barrel = Sprite::filled(4, 6, Color { r: 120, g: 90, b: 40 });
+/// let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// Raycaster::new().draw(
+///     &mut canvas,
+///     Rect::new(0, 0, 80, 48),
+///     &map,
+///     &RaycastView::new(8.0, 8.0, 0.0),
+///     &[Billboard { x: 11.5, y: 8.5, sprite: &barrel }],
+///     0,
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raycaster {
+    fov: f32,
+    ceiling: Color,
+    floor: Color,
+    fog_distance: f32,
+    wall_color: Color,
+}
+
+impl Default for Raycaster {
+    fn default() -> Self {
+        Self {
+            fov: FRAC_PI_3,
+            ceiling: Color {
+                r: 40,
+                g: 40,
+                b: 48,
+            },
+            floor: Color {
+                r: 70,
+                g: 62,
+                b: 54,
+            },
+            fog_distance: 12.0,
+            wall_color: Color {
+                r: 160,
+                g: 160,
+                b: 160,
+            },
+        }
+    }
+}
+
+impl Raycaster {
+    /// Creates a raycaster with a 60° field of view.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the horizontal field of view, in radians.
+    pub fn fov(mut self, fov: f32) -> Self {
+        self.fov = fov.clamp(0.1, 3.0);
+        self
+    }
+
+    /// Sets the colors of the ceiling and the floor.
+    pub fn colors(mut self, ceiling: Color, floor: Color) -> Self {
+        self.ceiling = ceiling;
+        self.floor = floor;
+        self
+    }
+
+    /// Sets the distance, in cells, at which walls, floor and billboards fade to black.
+    pub fn fog_distance(mut self, distance: f32) -> Self {
+        self.fog_distance = distance.max(f32::EPSILON);
+        self
+    }
+
+    /// Sets the color of walls whose tile is missing from the tileset.
+    pub fn wall_color(mut self, color: Color) -> Self {
+        self.wall_color = color;
+        self
+    }
+
+    /// Returns the brightness of something `distance` cells away.
+    fn brightness(&self, distance: f32) -> u8 {
+        ((1.0 - distance / self.fog_distance).clamp(0.0, 1.0) * 255.0) as u8
+    }
+
+    /// Draws the view from `view` into `area` on layer `z`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas to draw into.
+    /// * `area` - The area of the view in half-block pixel coordinates.
+    /// * `map` - The map, whose non-empty cells are walls.
+    /// * `view` - The position and heading of the viewer.
+    /// * `billboards` - The sprites standing in the scene.
+    /// * `z` - The z-layer to draw on.
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas,
+        area: Rect,
+        map: &TileMap,
+        view: &RaycastView,
+        billboards: &[Billboard],
+        z: usize,
+    ) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let (dir_x, dir_y) = view.direction();
+        // The camera plane, perpendicular to the heading, scaled to the field of view.
+        let plane = (self.fov / 2.0).tan();
+        let (plane_x, plane_y) = (-dir_y * plane, dir_x * plane);
+        let half_height = area.height as f32 / 2.0;
+        // Walls one cell tall fill the height of the view at the distance where the view is as
+        // wide as the camera plane, keeping cells square.
+        let scale = area.width as f32 / (2.0 * plane);
+
+        // Ceiling and floor, shaded by the distance of the floor seen on each row.
+        for row in 0..area.height {
+            let offset = (row as f32 + 0.5 - half_height).abs();
+            let distance = scale / (2.0 * offset);
+            let base = if (row as f32) < half_height {
+                self.ceiling
+            } else {
+                self.floor
+            };
+            let color = shade_color(base, self.brightness(distance));
+            for column in 0..area.width {
+                canvas.set_pixel(area.x + column, area.y + row, z, color);
+            }
+        }
+
+        let mut depth = vec![f32::INFINITY; area.width];
+        for (column, column_depth) in depth.iter_mut().enumerate() {
+            let camera_x = 2.0 * (column as f32 + 0.5) / area.width as f32 - 1.0;
+            let ray = (dir_x + plane_x * camera_x, dir_y + plane_y * camera_x);
+            let Some(hit) = cast(map, view, ray) else {
+                continue;
+            };
+            *column_depth = hit.distance;
+
+            let wall_height = scale / hit.distance.max(1e-4);
+            let top = half_height - wall_height / 2.0;
+            let first_row = top.max(0.0) as usize;
+            let last_row =
+                ((half_height + wall_height / 2.0).ceil().max(0.0) as usize).min(area.height);
+            let mut brightness = self.brightness(hit.distance);
+            if hit.horizontal {
+                brightness = (brightness as u16 * 3 / 4) as u8;
+            }
+            let texture = hit.tile.and_then(|tile| map.tileset().get(tile));
+            for row in first_row..last_row {
+                let v = (row as f32 + 0.5 - top) / wall_height;
+                let color = match texture {
+                    Some(texture) if texture.width() > 0 && texture.height() > 0 => {
+                        let tx = ((hit.offset * texture.width() as f32) as usize)
+                            .min(texture.width() - 1);
+                        let ty = ((v * texture.height() as f32) as usize).min(texture.height() - 1);
+                        texture.get(tx, ty).unwrap_or(self.wall_color)
+                    }
+                    _ => self.wall_color,
+                };
+                canvas.set_pixel(
+                    area.x + column,
+                    area.y + row,
+                    z,
+                    shade_color(color, brightness),
+                );
+            }
+        }
+
+        self.draw_billboards(canvas, area, view, billboards, &depth, scale, z);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_billboards(
+        &self,
+        canvas: &mut Canvas,
+        area: Rect,
+        view: &RaycastView,
+        billboards: &[Billboard],
+        depth: &[f32],
+        scale: f32,
+        z: usize,
+    ) {
+        let (dir_x, dir_y) = view.direction();
+        let half_height = area.height as f32 / 2.0;
+        // Project every billboard into (distance ahead, screen x), far ones first.
+        let mut projected: Vec<_> = billboards
+            .iter()
+            .filter_map(|billboard| {
+                let (dx, dy) = (billboard.x - view.x, billboard.y - view.y);
+                let ahead = dx * dir_x + dy * dir_y;
+                let side = -dx * dir_y + dy * dir_x;
+                (ahead > 0.1).then(|| {
+                    let screen_x = area.width as f32 / 2.0 + side * scale / ahead;
+                    (ahead, screen_x, billboard.sprite)
+                })
+            })
+            .collect();
+        projected.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        for (ahead, screen_x, sprite) in projected {
+            if sprite.width() == 0 || sprite.height() == 0 {
+                continue;
+            }
+            let height = scale / ahead;
+            let width = height * sprite.width() as f32 / sprite.height() as f32;
+            let left = screen_x - width / 2.0;
+            let top = half_height + height / 2.0 - height;
+            let brightness = self.brightness(ahead);
+            let first_column = left.max(0.0) as usize;
+            let last_column = ((left + width).ceil().max(0.0) as usize).min(area.width);
+            let first_row = top.max(0.0) as usize;
+            let last_row = ((top + height).ceil().max(0.0) as usize).min(area.height);
+            for (column, column_depth) in depth
+                .iter()
+                .enumerate()
+                .take(last_column)
+                .skip(first_column)
+            {
+                if ahead >= *column_depth {
+                    continue;
+                }
+                let sx = (((column as f32 + 0.5 - left) / width * sprite.width() as f32) as usize)
+                    .min(sprite.width() - 1);
+                for row in first_row..last_row {
+                    let sy = (((row as f32 + 0.5 - top) / height * sprite.height() as f32)
+                        as usize)
+                        .min(sprite.height() - 1);
+                    if let Some(color) = sprite.get(sx, sy) {
+                        canvas.set_pixel(
+                            area.x + column,
+                            area.y + row,
+                            z,
+                            shade_color(color, brightness),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Where a ray hits a wall.
+struct Hit {
+    /// The distance to the wall along the heading of the view, which avoids fisheye distortion.
+    distance: f32,
+    /// The tile of the wall, or `None` for the edge of the map.
+    tile: Option<usize>,
+    /// Where the ray hits along the face of the wall, from `0.0` to `1.0`.
+    offset: f32,
+    /// Whether the wall face runs along the x axis, facing north or south.
+    horizontal: bool,
+}
+
+/// Steps a ray through the cells of the map with a digital differential analyzer until it hits
+/// a wall.
+fn cast(map: &TileMap, view: &RaycastView, (ray_x, ray_y): (f32, f32)) -> Option<Hit> {
+    let mut cell_x = view.x.floor() as isize;
+    let mut cell_y = view.y.floor() as isize;
+    let delta_x = if ray_x == 0.0 {
+        f32::INFINITY
+    } else {
+        (1.0 / ray_x).abs()
+    };
+    let delta_y = if ray_y == 0.0 {
+        f32::INFINITY
+    } else {
+        (1.0 / ray_y).abs()
+    };
+    let (step_x, mut side_x) = if ray_x < 0.0 {
+        (-1, (view.x - cell_x as f32) * delta_x)
+    } else {
+        (1, (cell_x as f32 + 1.0 - view.x) * delta_x)
+    };
+    let (step_y, mut side_y) = if ray_y < 0.0 {
+        (-1, (view.y - cell_y as f32) * delta_y)
+    } else {
+        (1, (cell_y as f32 + 1.0 - view.y) * delta_y)
+    };
+
+    let limit = (map.width() + map.height()) * 2 + 2;
+    for _ in 0..limit {
+        let horizontal = side_y < side_x;
+        if horizontal {
+            side_y += delta_y;
+            cell_y += step_y;
+        } else {
+            side_x += delta_x;
+            cell_x += step_x;
+        }
+        let outside = cell_x < 0
+            || cell_y < 0
+            || cell_x as usize >= map.width()
+            || cell_y as usize >= map.height();
+        let tile = if outside {
+            None
+        } else {
+            map.get(cell_x as usize, cell_y as usize)
+        };
+        if !outside && tile.is_none() {
+            continue;
+        }
+        let distance = if horizontal {
+            side_y - delta_y
+        } else {
+            side_x - delta_x
+        };
+        let along = if horizontal {
+            view.x + distance * ray_x
+        } else {
+            view.y + distance * ray_y
+        };
+        let mut offset = along - along.floor();
+        // Mirror the faces seen from the other side so textures are not flipped.
+        if (!horizontal && ray_x < 0.0) || (horizontal && ray_y > 0.0) {
+            offset = 1.0 - offset;
+        }
+        return Some(Hit {
+            distance,
+            tile,
+            offset,
+            horizontal,
+        });
+    }
+    None
+}
@@ -0,0 +1,378 @@
+//! Recording rendered frames and exporting them as animated GIFs and asciinema casts.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::{Canvas, Color, Frame};
+
+/// How long the last recorded frame is shown when no delay was given for it.
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// Captures the frames of a canvas as they are drawn, with their timing, and exports them as an
+/// animated GIF or an asciinema cast.
+///
+/// In GIFs, every half-block pixel becomes a square of [`Recorder::scale`] GIF pixels.
+/// Characters drawn over cells, such as chart labels, are not rasterized: their cells show their
+/// background color. Casts replay the exact terminal output, characters included.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{Canvas, Color, Recorder};
+/// use std::time::Duration;
+///
+/// let mut canvas = Canvas::new(40, 12, Color { r: 0, g: 0, b: 0 });
+/// let mut recorder = Recorder::new().scale(6);
+/// for frame in 0..40 {
+///     canvas.clear();
+///     canvas.set_pixel(frame, 12, 0, Color { r: 255, g: 200, b: 0 });
+///     recorder.record_with_delay(&canvas, Duration::from_millis(50));
+/// }
+/// recorder.save_gif("clip.gif")?;
+/// recorder.save_cast("clip.cast")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recorder {
+    frames: Vec<Frame>,
+    delays: Vec<Duration>,
+    last_record: Option<Instant>,
+    scale: usize,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            frames: Vec::new(),
+            delays: Vec::new(),
+            last_record: None,
+            scale: 4,
+        }
+    }
+}
+
+impl Recorder {
+    /// Creates an empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many GIF pixels wide and tall every half-block pixel is. Defaults to `4`.
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Captures what `canvas` currently shows. The previous frame lasts until now, and this
+    /// frame lasts until the next call.
+    pub fn record(&mut self, canvas: &Canvas) {
+        let now = Instant::now();
+        if let (Some(last_record), Some(delay)) = (self.last_record, self.delays.last_mut()) {
+            *delay = now - last_record;
+        }
+        self.last_record = Some(now);
+        self.frames.push(Frame::capture(canvas));
+        self.delays.push(DEFAULT_DELAY);
+    }
+
+    /// Captures what `canvas` currently shows, to be shown for `delay`.
+    pub fn record_with_delay(&mut self, canvas: &Canvas, delay: Duration) {
+        self.last_record = None;
+        self.frames.push(Frame::capture(canvas));
+        self.delays.push(delay);
+    }
+
+    /// Returns an iterator over the recorded frames and how long each is shown.
+    pub fn frames(&self) -> impl Iterator<Item = (&Frame, Duration)> + '_ {
+        self.frames.iter().zip(self.delays.iter().copied())
+    }
+
+    /// Consumes the recorder, returning the recorded frames and how long each is shown.
+    pub fn into_frames(self) -> impl Iterator<Item = (Frame, Duration)> {
+        self.frames.into_iter().zip(self.delays)
+    }
+
+    /// Returns the number of recorded frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if no frame has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns the total length of the recording.
+    pub fn duration(&self) -> Duration {
+        self.delays.iter().sum()
+    }
+
+    /// Discards the recorded frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.delays.clear();
+        self.last_record = None;
+    }
+
+    /// Writes the recording to a file as an animated GIF that loops forever.
+    pub fn save_gif(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_gif(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the recording as an animated GIF that loops forever.
+    ///
+    /// Frames with more than 256 colors are reduced to a fixed palette of 256 colors. GIF
+    /// delays have a resolution of 10 milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing fails, or if the recording is empty or larger than 65535 pixels on a
+    /// side.
+    pub fn write_gif(&self, mut writer: impl Write) -> io::Result<()> {
+        let Some(first) = self.frames.first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot write a GIF without frames",
+            ));
+        };
+        let width = first.width() * self.scale;
+        let height = first.height() * 2 * self.scale;
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the recording is too large for a GIF",
+            ));
+        };
+
+        writer.write_all(b"GIF89a")?;
+        // Logical screen descriptor, without a global color table.
+        writer.write_all(&width.to_le_bytes())?;
+        writer.write_all(&height.to_le_bytes())?;
+        writer.write_all(&[0, 0, 0])?;
+        // Application extension making the animation loop forever.
+        writer.write_all(&[0x21, 0xFF, 11])?;
+        writer.write_all(b"NETSCAPE2.0")?;
+        writer.write_all(&[3, 1, 0, 0, 0])?;
+
+        for (frame, delay) in self.frames() {
+            let (palette, indices) = self.index_frame(frame, width as usize, height as usize);
+            let centiseconds = (delay.as_millis() / 10).clamp(1, u16::MAX as u128) as u16;
+            // Graphic control extension holding the frame delay.
+            writer.write_all(&[0x21, 0xF9, 4, 0])?;
+            writer.write_all(&centiseconds.to_le_bytes())?;
+            writer.write_all(&[0, 0])?;
+
+            // Image descriptor with a local color table of 2^(bits) entries.
+            let bits = palette.len().next_power_of_two().trailing_zeros().max(1) as u8;
+            writer.write_all(&[0x2C, 0, 0, 0, 0])?;
+            writer.write_all(&width.to_le_bytes())?;
+            writer.write_all(&height.to_le_bytes())?;
+            writer.write_all(&[0x80 | (bits - 1)])?;
+            for index in 0..1 << bits {
+                let color = palette
+                    .get(index)
+                    .copied()
+                    .unwrap_or(Color { r: 0, g: 0, b: 0 });
+                writer.write_all(&[color.r, color.g, color.b])?;
+            }
+
+            let min_code_size = bits.max(2);
+            writer.write_all(&[min_code_size])?;
+            for block in lzw_encode(&indices, min_code_size).chunks(255) {
+                writer.write_all(&[block.len() as u8])?;
+                writer.write_all(block)?;
+            }
+            writer.write_all(&[0])?;
+        }
+        writer.write_all(&[0x3B])
+    }
+
+    /// Writes the recording to a file as an asciinema v2 cast.
+    pub fn save_cast(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_cast(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the recording as an asciinema v2 cast, which can be played with `asciinema play`
+    /// or embedded in web pages with the asciinema player.
+    ///
+    /// Each frame only redraws the cells that changed since the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing fails or the recording is empty.
+    pub fn write_cast(&self, mut writer: impl Write) -> io::Result<()> {
+        let Some(first) = self.frames.first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot write a cast without frames",
+            ));
+        };
+        writeln!(
+            writer,
+            r#"{{"version": 2, "width": {}, "height": {}, "env": {{"TERM": "xterm-256color"}}}}"#,
+            first.width(),
+            first.height()
+        )?;
+        let mut time = Duration::ZERO;
+        let mut previous = None;
+        for (frame, delay) in self.frames() {
+            let mut output = String::new();
+            if previous.is_none() {
+                // Hide the cursor and start from an empty screen.
+                output.push_str("\u{1b}[?25l\u{1b}[2J");
+            }
+            output.push_str(&frame.ansi_since(previous));
+            writeln!(
+                writer,
+                "[{:.6}, \"o\", {}]",
+                time.as_secs_f64(),
+                json_string(&output)
+            )?;
+            time += delay;
+            previous = Some(frame);
+        }
+        Ok(())
+    }
+
+    /// Returns the palette of a frame and the palette index of every GIF pixel.
+    fn index_frame(&self, frame: &Frame, width: usize, height: usize) -> (Vec<Color>, Vec<u8>) {
+        // Frames smaller than the first one are padded with black.
+        let color_at = |x: usize, y: usize| frame.pixel(x, y).unwrap_or(Color { r: 0, g: 0, b: 0 });
+        let mut palette = Vec::new();
+        let mut lookup = HashMap::new();
+        let mut exact = true;
+        'pixels: for y in 0..height / self.scale {
+            for x in 0..width / self.scale {
+                let color = color_at(x, y);
+                if lookup.contains_key(&(color.r, color.g, color.b)) {
+                    continue;
+                }
+                if palette.len() == 256 {
+                    exact = false;
+                    break 'pixels;
+                }
+                lookup.insert((color.r, color.g, color.b), palette.len() as u8);
+                palette.push(color);
+            }
+        }
+        if !exact {
+            // 8 levels of red and green and 4 of blue, to which the eye is least sensitive.
+            palette = (0..=255u8)
+                .map(|index| Color {
+                    r: ((index >> 5) as u16 * 255 / 7) as u8,
+                    g: (((index >> 2) & 7) as u16 * 255 / 7) as u8,
+                    b: (index & 3) * 85,
+                })
+                .collect();
+        }
+        let index_of = |color: Color| {
+            if exact {
+                lookup[&(color.r, color.g, color.b)]
+            } else {
+                // The nearest level of every channel.
+                let level =
+                    |channel: u8, levels: u16| ((channel as u16 * (levels - 1) + 127) / 255) as u8;
+                level(color.r, 8) << 5 | level(color.g, 8) << 2 | level(color.b, 4)
+            }
+        };
+
+        let mut indices = Vec::with_capacity(width * height);
+        for y in 0..height {
+            indices.extend((0..width).map(|x| index_of(color_at(x / self.scale, y / self.scale))));
+        }
+        (palette, indices)
+    }
+}
+
+/// Quotes `text` as a JSON string.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Compresses palette indices with the variable-length LZW coding of GIF.
+fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut output = BitWriter::default();
+    let mut code_size = min_code_size + 1;
+    let mut next_code = end + 1;
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    output.write(clear, code_size);
+    let Some((&first, rest)) = indices.split_first() else {
+        output.write(end, code_size);
+        return output.finish();
+    };
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        output.write(prefix, code_size);
+        if next_code < 4096 {
+            table.insert((prefix, index), next_code);
+            next_code += 1;
+            if next_code > 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        } else {
+            // The table is full, so start over.
+            output.write(clear, code_size);
+            table.clear();
+            next_code = end + 1;
+            code_size = min_code_size + 1;
+        }
+        prefix = index as u16;
+    }
+    output.write(prefix, code_size);
+    output.write(end, code_size);
+    output.finish()
+}
+
+/// Packs codes of varying bit widths into bytes, least significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
@@ -0,0 +1,688 @@
+//! A small 3D pipeline drawing lines, triangles and meshes into the canvas with a depth buffer.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::{shade_color, Canvas, Color, Rect};
+
+/// A point or direction in 3D space.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec3 {
+    /// The x component, growing to the right.
+    pub x: f32,
+    /// The y component, growing upwards.
+    pub y: f32,
+    /// The z component, growing towards the viewer of an unrotated scene.
+    pub z: f32,
+}
+
+impl Vec3 {
+    /// Creates a vector from its components.
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Returns the dot product of the two vectors.
+    pub fn dot(self, other: Vec3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Returns the cross product of the two vectors.
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// Returns the length of the vector.
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the vector scaled to a length of one, or the zero vector unchanged.
+    pub fn normalize(self) -> Vec3 {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self * (1.0 / length)
+        }
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, factor: f32) -> Vec3 {
+        Vec3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// A 4x4 transformation matrix acting on column vectors, stored row by row.
+///
+/// Transforms combine with `*`, where `a * b` applies `b` first and then `a`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    /// The entries of the matrix, as `rows[row][column]`.
+    pub rows: [[f32; 4]; 4],
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat4 {
+    /// The transform leaving every point in place.
+    pub const IDENTITY: Mat4 = Mat4 {
+        rows: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    /// Returns a transform moving points by `offset`.
+    pub fn translation(offset: Vec3) -> Mat4 {
+        let mut matrix = Mat4::IDENTITY;
+        matrix.rows[0][3] = offset.x;
+        matrix.rows[1][3] = offset.y;
+        matrix.rows[2][3] = offset.z;
+        matrix
+    }
+
+    /// Returns a transform scaling points away from the origin by `factors` along each axis.
+    pub fn scale(factors: Vec3) -> Mat4 {
+        let mut matrix = Mat4::IDENTITY;
+        matrix.rows[0][0] = factors.x;
+        matrix.rows[1][1] = factors.y;
+        matrix.rows[2][2] = factors.z;
+        matrix
+    }
+
+    /// Returns a rotation by `angle` radians around the x axis.
+    pub fn rotation_x(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        let mut matrix = Mat4::IDENTITY;
+        matrix.rows[1] = [0.0, cos, -sin, 0.0];
+        matrix.rows[2] = [0.0, sin, cos, 0.0];
+        matrix
+    }
+
+    /// Returns a rotation by `angle` radians around the y axis.
+    pub fn rotation_y(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        let mut matrix = Mat4::IDENTITY;
+        matrix.rows[0] = [cos, 0.0, sin, 0.0];
+        matrix.rows[2] = [-sin, 0.0, cos, 0.0];
+        matrix
+    }
+
+    /// Returns a rotation by `angle` radians around the z axis.
+    pub fn rotation_z(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        let mut matrix = Mat4::IDENTITY;
+        matrix.rows[0] = [cos, -sin, 0.0, 0.0];
+        matrix.rows[1] = [sin, cos, 0.0, 0.0];
+        matrix
+    }
+
+    /// Returns a view transform for an eye at `eye` looking at `target`, with `up` pointing
+    /// towards the top of the view. The eye ends up at the origin looking down the negative z
+    /// axis.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+        let forward = (target - eye).normalize();
+        let right = forward.cross(up).normalize();
+        let up = right.cross(forward);
+        Mat4 {
+            rows: [
+                [right.x, right.y, right.z, -right.dot(eye)],
+                [up.x, up.y, up.z, -up.dot(eye)],
+                [-forward.x, -forward.y, -forward.z, forward.dot(eye)],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Returns a perspective projection with a vertical field of view of `fov` radians, an
+    /// `aspect` ratio of width over height, and the `near` and `far` clipping distances.
+    pub fn perspective(fov: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let focal = 1.0 / (fov / 2.0).tan();
+        Mat4 {
+            rows: [
+                [focal / aspect, 0.0, 0.0, 0.0],
+                [0.0, focal, 0.0, 0.0],
+                [
+                    0.0,
+                    0.0,
+                    (far + near) / (near - far),
+                    2.0 * far * near / (near - far),
+                ],
+                [0.0, 0.0, -1.0, 0.0],
+            ],
+        }
+    }
+
+    /// Transforms a point, dividing by the resulting `w` component.
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let [x, y, z, w] = self.transform([point.x, point.y, point.z, 1.0]);
+        if w == 0.0 || w == 1.0 {
+            Vec3::new(x, y, z)
+        } else {
+            Vec3::new(x / w, y / w, z / w)
+        }
+    }
+
+    /// Transforms a direction, ignoring the translation of the transform.
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        let [x, y, z, _] = self.transform([vector.x, vector.y, vector.z, 0.0]);
+        Vec3::new(x, y, z)
+    }
+
+    fn transform(&self, vector: [f32; 4]) -> [f32; 4] {
+        self.rows
+            .map(|row| row.iter().zip(vector).map(|(a, b)| a * b).sum())
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, other: Mat4) -> Mat4 {
+        let mut rows = [[0.0; 4]; 4];
+        for (row, out) in rows.iter_mut().enumerate() {
+            for (column, value) in out.iter_mut().enumerate() {
+                *value = (0..4)
+                    .map(|k| self.rows[row][k] * other.rows[k][column])
+                    .sum();
+            }
+        }
+        Mat4 { rows }
+    }
+}
+
+/// A camera looking at a 3D scene through a perspective projection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerspectiveCamera {
+    /// The position of the eye.
+    pub position: Vec3,
+    /// The point the camera looks at.
+    pub target: Vec3,
+    /// The direction towards the top of the view.
+    pub up: Vec3,
+    /// The vertical field of view, in radians.
+    pub fov: f32,
+    /// The distance of the near clipping plane. Anything closer is cut away.
+    pub near: f32,
+    /// The distance of the far clipping plane. Anything further is cut away.
+    pub far: f32,
+}
+
+impl Default for PerspectiveCamera {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(0.0, 0.0, 5.0),
+            target: Vec3::default(),
+            up: Vec3::new(0.0, 1.0, 0.0),
+            fov: std::f32::consts::FRAC_PI_3,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+}
+
+impl PerspectiveCamera {
+    /// Creates a camera at `position` looking at `target`, with the y axis up and a 60°
+    /// vertical field of view.
+    pub fn new(position: Vec3, target: Vec3) -> Self {
+        Self {
+            position,
+            target,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the transform from world space to the space of the camera.
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at(self.position, self.target, self.up)
+    }
+
+    /// Returns the projection for a view with the given `aspect` ratio of width over height.
+    pub fn projection(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective(self.fov, aspect, self.near, self.far)
+    }
+}
+
+/// A triangle mesh. Triangles list their vertices counter-clockwise when seen from the front.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    vertices: Vec<Vec3>,
+    triangles: Vec<[usize; 3]>,
+    color: Color,
+}
+
+impl Mesh {
+    /// Creates a white mesh from its vertices and the vertex indices of its triangles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a triangle refers to a vertex that does not exist.
+    pub fn new(vertices: Vec<Vec3>, triangles: Vec<[usize; 3]>) -> Self {
+        assert!(
+            triangles
+                .iter()
+                .flatten()
+                .all(|index| *index < vertices.len()),
+            "mesh triangles must refer to existing vertices"
+        );
+        Self {
+            vertices,
+            triangles,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+            },
+        }
+    }
+
+    /// Creates a cube `size` units wide centered on the origin.
+    pub fn cube(size: f32) -> Self {
+        let h = size / 2.0;
+        let vertices = (0..8)
+            .map(|i| {
+                let pick = |bit: usize| if i & bit != 0 { h } else { -h };
+                Vec3::new(pick(1), pick(2), pick(4))
+            })
+            .collect();
+        let triangles = vec![
+            [0, 2, 3],
+            [0, 3, 1],
+            [4, 5, 7],
+            [4, 7, 6],
+            [0, 4, 6],
+            [0, 6, 2],
+            [1, 3, 7],
+            [1, 7, 5],
+            [0, 1, 5],
+            [0, 5, 4],
+            [2, 6, 7],
+            [2, 7, 3],
+        ];
+        Self::new(vertices, triangles)
+    }
+
+    /// Sets the color of the mesh.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Returns the vertices of the mesh.
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.vertices
+    }
+
+    /// Returns the vertex indices of the triangles of the mesh.
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    /// Returns the vertex index pairs of the edges of the triangles, each listed once. Edges
+    /// between two coplanar triangles, such as the diagonals splitting the faces of a cube,
+    /// are left out.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<((usize, usize), Vec3)> = Vec::new();
+        let mut hidden = Vec::new();
+        for triangle in &self.triangles {
+            let normal = self.normal(triangle);
+            for i in 0..3 {
+                let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+                let key = (a.min(b), a.max(b));
+                match edges.iter().find(|(edge, _)| *edge == key) {
+                    Some((_, other)) if normal.dot(*other) > 0.9999 => hidden.push(key),
+                    Some(_) => {}
+                    None => edges.push((key, normal)),
+                }
+            }
+        }
+        edges
+            .into_iter()
+            .map(|(edge, _)| edge)
+            .filter(|edge| !hidden.contains(edge))
+            .collect()
+    }
+
+    fn normal(&self, triangle: &[usize; 3]) -> Vec3 {
+        let [a, b, c] = triangle.map(|index| self.vertices[index]);
+        (b - a).cross(c - a).normalize()
+    }
+}
+
+/// How [`Renderer3d::draw_mesh`] draws a mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MeshStyle {
+    /// Only the edges, including those at the back of the mesh.
+    #[default]
+    Wireframe,
+    /// Only the edges that are not hidden behind faces of the scene.
+    HiddenLine,
+    /// Filled faces, shaded by how much they face the light.
+    Solid,
+}
+
+/// A vertex after projection: its screen position and the inverse of its distance along the
+/// view direction, which unlike the distance itself varies linearly across the screen.
+#[derive(Debug, Clone, Copy)]
+struct ScreenPoint {
+    x: f32,
+    y: f32,
+    inverse_depth: f32,
+}
+
+/// Draws 3D lines, triangles and meshes into an area of the canvas, keeping a depth buffer so
+/// nearer shapes hide further ones regardless of drawing order.
+///
+/// Call [`Renderer3d::clear_depth`] at the start of every frame, after clearing the canvas.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Mat4, Mesh, MeshStyle, PerspectiveCamera, Rect, Renderer3d, Vec3};
+///
+/// let mut canvas = Canvas::new(60, 20, Color { r: 0, g: 0, b: 0 });
+/// let camera = PerspectiveCamera::new(Vec3::new(0.0, 1.5, 4.0), Vec3::default());
+/// let mut renderer = Renderer3d::new(Rect::new(0, 0, 60, 40), camera);
+/// let cube = Mesh::cube(2.0).color(Color { r: 80, g: 160, b: 255 });
+///
+/// for frame in 0..3 {
+///     canvas.clear();
+///     renderer.clear_depth();
+///     let spin = Mat4::rotation_y(frame as f32 * 0.1);
+///     renderer.draw_mesh(&mut canvas, &cube, &spin, MeshStyle::Solid, 0);
+///     let _ = canvas.render();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Renderer3d {
+    area: Rect,
+    camera: PerspectiveCamera,
+    view_projection: Mat4,
+    depth: Vec<f32>,
+    light: Vec3,
+    ambient: f32,
+}
+
+impl Renderer3d {
+    /// Creates a renderer drawing into `area`, in half-block pixel coordinates, as seen by
+    /// `camera`.
+    pub fn new(area: Rect, camera: PerspectiveCamera) -> Self {
+        let mut renderer = Self {
+            area,
+            camera,
+            view_projection: Mat4::IDENTITY,
+            depth: vec![0.0; area.width * area.height],
+            light: Vec3::new(-0.4, 0.8, 0.6).normalize(),
+            ambient: 0.25,
+        };
+        renderer.set_camera(camera);
+        renderer
+    }
+
+    /// Sets the direction towards the light shading solid meshes, and the brightness of faces
+    /// turned away from it, from `0.0` to `1.0`.
+    pub fn light(mut self, direction: Vec3, ambient: f32) -> Self {
+        self.light = direction.normalize();
+        self.ambient = ambient.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns the area the renderer draws into.
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    /// Returns the camera.
+    pub fn camera(&self) -> &PerspectiveCamera {
+        &self.camera
+    }
+
+    /// Replaces the camera, as when it moves between frames.
+    pub fn set_camera(&mut self, camera: PerspectiveCamera) {
+        let aspect = self.area.width.max(1) as f32 / self.area.height.max(1) as f32;
+        self.camera = camera;
+        self.view_projection = camera.projection(aspect) * camera.view();
+    }
+
+    /// Empties the depth buffer, so the next shapes are drawn over everything.
+    pub fn clear_depth(&mut self) {
+        self.depth.fill(0.0);
+    }
+
+    /// Draws a line between two world positions on layer `z`.
+    pub fn draw_line(&mut self, canvas: &mut Canvas, a: Vec3, b: Vec3, color: Color, z: usize) {
+        let Some((a, b)) = self.clip_line(self.clip(a), self.clip(b)) else {
+            return;
+        };
+        let (a, b) = (self.to_screen(a), self.to_screen(b));
+        let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let x = a.x + (b.x - a.x) * t;
+            let y = a.y + (b.y - a.y) * t;
+            let inverse_depth = a.inverse_depth + (b.inverse_depth - a.inverse_depth) * t;
+            // Lines lying on faces must win against them.
+            self.plot(
+                canvas,
+                x.floor(),
+                y.floor(),
+                inverse_depth * 1.05,
+                Some(color),
+                z,
+            );
+        }
+    }
+
+    /// Draws a filled triangle between three world positions on layer `z`. Both sides of the
+    /// triangle are drawn.
+    pub fn draw_triangle(
+        &mut self,
+        canvas: &mut Canvas,
+        vertices: [Vec3; 3],
+        color: Color,
+        z: usize,
+    ) {
+        self.fill_triangle(canvas, vertices, Some(color), false, z);
+    }
+
+    /// Draws `mesh`, placed in the world by the `model` transform, on layer `z`.
+    pub fn draw_mesh(
+        &mut self,
+        canvas: &mut Canvas,
+        mesh: &Mesh,
+        model: &Mat4,
+        style: MeshStyle,
+        z: usize,
+    ) {
+        let world: Vec<Vec3> = mesh
+            .vertices
+            .iter()
+            .map(|vertex| model.transform_point(*vertex))
+            .collect();
+        if style != MeshStyle::Wireframe {
+            for triangle in &mesh.triangles {
+                let vertices = triangle.map(|index| world[index]);
+                let color = (style == MeshStyle::Solid).then(|| {
+                    let normal = (vertices[1] - vertices[0])
+                        .cross(vertices[2] - vertices[0])
+                        .normalize();
+                    let light =
+                        self.ambient + (1.0 - self.ambient) * normal.dot(self.light).max(0.0);
+                    shade_color(mesh.color, (light * 255.0) as u8)
+                });
+                self.fill_triangle(canvas, vertices, color, true, z);
+            }
+        }
+        if style != MeshStyle::Solid {
+            for (a, b) in mesh.edges() {
+                self.draw_line(canvas, world[a], world[b], mesh.color, z);
+            }
+        }
+    }
+
+    /// Transforms a world position to homogeneous clip coordinates.
+    fn clip(&self, point: Vec3) -> [f32; 4] {
+        self.view_projection
+            .transform([point.x, point.y, point.z, 1.0])
+    }
+
+    /// Cuts the part of a line in front of the near plane, where `z >= -w`.
+    fn clip_line(&self, a: [f32; 4], b: [f32; 4]) -> Option<([f32; 4], [f32; 4])> {
+        let (da, db) = (a[2] + a[3], b[2] + b[3]);
+        match (da >= 0.0, db >= 0.0) {
+            (true, true) => Some((a, b)),
+            (false, false) => None,
+            (true, false) => Some((a, lerp4(a, b, da / (da - db)))),
+            (false, true) => Some((lerp4(a, b, da / (da - db)), b)),
+        }
+    }
+
+    fn to_screen(&self, point: [f32; 4]) -> ScreenPoint {
+        let [x, y, _, w] = point;
+        let w = w.max(f32::EPSILON);
+        ScreenPoint {
+            x: (x / w + 1.0) / 2.0 * self.area.width as f32,
+            y: (1.0 - y / w) / 2.0 * self.area.height as f32,
+            inverse_depth: 1.0 / w,
+        }
+    }
+
+    /// Draws a pixel of the area if it is nearer than what was drawn there, comparing inverse
+    /// depths. A `None` color only updates the depth buffer.
+    fn plot(
+        &mut self,
+        canvas: &mut Canvas,
+        x: f32,
+        y: f32,
+        inverse_depth: f32,
+        color: Option<Color>,
+        z: usize,
+    ) {
+        if x < 0.0 || y < 0.0 || inverse_depth * self.camera.far < 1.0 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        if x >= self.area.width || y >= self.area.height {
+            return;
+        }
+        let index = y * self.area.width + x;
+        if inverse_depth <= self.depth[index] {
+            return;
+        }
+        self.depth[index] = inverse_depth;
+        if let Some(color) = color {
+            canvas.set_pixel(self.area.x + x, self.area.y + y, z, color);
+        }
+    }
+
+    fn fill_triangle(
+        &mut self,
+        canvas: &mut Canvas,
+        vertices: [Vec3; 3],
+        color: Option<Color>,
+        cull_back: bool,
+        z: usize,
+    ) {
+        let clipped = vertices.map(|vertex| self.clip(vertex));
+        // Cut the triangle at the near plane, leaving a polygon of up to four vertices.
+        let mut polygon = Vec::with_capacity(4);
+        for i in 0..3 {
+            let (a, b) = (clipped[i], clipped[(i + 1) % 3]);
+            let (da, db) = (a[2] + a[3], b[2] + b[3]);
+            if da >= 0.0 {
+                polygon.push(a);
+            }
+            if (da >= 0.0) != (db >= 0.0) {
+                polygon.push(lerp4(a, b, da / (da - db)));
+            }
+        }
+        let points: Vec<ScreenPoint> = polygon.iter().map(|point| self.to_screen(*point)).collect();
+        for i in 1..points.len().saturating_sub(1) {
+            self.rasterize(
+                [points[0], points[i], points[i + 1]],
+                color,
+                cull_back,
+                canvas,
+                z,
+            );
+        }
+    }
+
+    fn rasterize(
+        &mut self,
+        [a, b, c]: [ScreenPoint; 3],
+        color: Option<Color>,
+        cull_back: bool,
+        canvas: &mut Canvas,
+        z: usize,
+    ) {
+        let edge = |p: ScreenPoint, q: ScreenPoint, x: f32, y: f32| {
+            (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x)
+        };
+        let area = edge(a, b, c.x, c.y);
+        // Screen y grows downwards, so front faces, counter-clockwise in the world, have a
+        // negative area here.
+        if area == 0.0 || (cull_back && area > 0.0) {
+            return;
+        }
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as usize;
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as usize;
+        let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(self.area.width);
+        let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(self.area.height);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let wa = edge(b, c, px, py) / area;
+                let wb = edge(c, a, px, py) / area;
+                let wc = 1.0 - wa - wb;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let inverse_depth =
+                    wa * a.inverse_depth + wb * b.inverse_depth + wc * c.inverse_depth;
+                self.plot(canvas, x as f32, y as f32, inverse_depth, color, z);
+            }
+        }
+    }
+}
+
+fn lerp4(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+}
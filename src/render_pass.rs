@@ -0,0 +1,86 @@
+//! Render passes: steps of the render pipeline run on every frame, between compositing the
+//! canvas and sending the frame to the terminal.
+
+use alloc::boxed::Box;
+
+use crate::{Canvas, Frame};
+
+/// A step of the render pipeline, run on every composited frame before it is sent, so crates
+/// can ship effects, overlays and exporters that work with every way of presenting a canvas.
+///
+/// The pipeline composites the z-layers, shade and characters of the canvas into a [`Frame`],
+/// runs the passes of the canvas on it in order, and sends the result. A pass can change the
+/// frame, such as grading its colors or drawing an overlay, or only read it, such as saving
+/// every frame to a file. Passes see the whole frame, even when only part of it is sent.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Frame, RenderPass};
+///
+/// /// Turns every pixel gray.
+/// struct Grayscale;
+///
+/// impl RenderPass for Grayscale {
+///     fn apply(&mut self, frame: &mut Frame) {
+///         for y in 0..frame.height() * 2 {
+///             for x in 0..frame.width() {
+///                 let color = frame.pixel(x, y).unwrap();
+///                 let gray = ((color.r as u16 + color.g as u16 + color.b as u16) / 3) as u8;
+///                 frame.set_pixel(x, y, Color { r: gray, g: gray, b: gray });
+///             }
+///         }
+///     }
+/// }
+///
+/// let mut canvas = Canvas::new(1, 1, Color { r: 90, g: 0, b: 0 });
+/// canvas.add_pass(Grayscale);
+/// assert!(canvas.render().contains("\x1b[38;2;30;30;30m"));
+/// ```
+pub trait RenderPass: Send {
+    /// Runs the pass on `frame`, the composited canvas or the output of the previous pass.
+    fn apply(&mut self, frame: &mut Frame);
+}
+
+impl Canvas {
+    /// Adds `pass` at the end of the render pipeline of the canvas, after the passes added
+    /// before it.
+    pub fn add_pass(&mut self, pass: impl RenderPass + 'static) {
+        self.passes.push(Box::new(pass));
+    }
+
+    /// Inserts `pass` into the render pipeline at position `index`, before the pass there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of passes.
+    pub fn insert_pass(&mut self, index: usize, pass: impl RenderPass + 'static) {
+        self.passes.insert(index, Box::new(pass));
+    }
+
+    /// Removes the pass at position `index` from the render pipeline and returns it, if any.
+    pub fn remove_pass(&mut self, index: usize) -> Option<Box<dyn RenderPass>> {
+        (index < self.passes.len()).then(|| self.passes.remove(index))
+    }
+
+    /// Removes every pass from the render pipeline.
+    pub fn clear_passes(&mut self) {
+        self.passes.clear();
+    }
+
+    /// Returns the number of passes in the render pipeline.
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// Composites the canvas into a frame, runs the render pipeline on it and dithers it, giving
+    /// the frame to send.
+    pub(crate) fn composite(&mut self) -> Frame {
+        let mut frame = Frame::capture(self);
+        for pass in &mut self.passes {
+            pass.apply(&mut frame);
+        }
+        frame.dither(self.palette.as_ref(), self.dither_mode);
+        frame
+    }
+}
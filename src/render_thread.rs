@@ -0,0 +1,229 @@
+//! Drawing to the terminal from a worker thread, so slow terminals never hold up the game.
+
+use std::fmt;
+use std::io;
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+use crate::{Backend, Canvas, Color, Terminal};
+
+/// The state shared by the game thread and the render thread.
+struct Shared {
+    slot: Mutex<Slot>,
+    ready: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Slot> {
+        self.slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The canvases in transit between the threads, with the statistics of the render thread.
+struct Slot {
+    /// The latest finished canvas, waiting to be drawn.
+    pending: Option<Canvas>,
+    /// A drawn canvas, for the game thread to draw the next frame on.
+    spare: Option<Canvas>,
+    redraw: bool,
+    stop: bool,
+    stopped: bool,
+    error: Option<io::Error>,
+    presented: u64,
+    dropped: u64,
+}
+
+/// A [`Terminal`] driven from a worker thread, which composites and sends the frames the game
+/// thread finishes, so game logic never waits for the terminal.
+///
+/// Three canvases take turns: the game draws on one, the latest finished frame waits in
+/// another, and the render thread draws the third. When the terminal cannot keep up, a finished
+/// frame still waiting when the next one arrives is dropped, so the terminal always gets the
+/// newest frame.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{Color, RenderThread, Terminal};
+///
+/// let terminal = Terminal::stdout()?;
+/// let (width, height) = terminal.size()?;
+/// let mut renderer = RenderThread::spawn(terminal, width, height, Color { r: 0, g: 0, b: 0 });
+/// for frame in 0..600 {
+///     let canvas = renderer.canvas_mut();
+///     canvas.clear();
+///     canvas.set_pixel(frame % width, 10, 0, Color { r: 255, g: 0, b: 0 });
+///     renderer.present()?;
+/// }
+/// eprintln!("dropped {} frames", renderer.dropped_frames());
+/// let terminal = renderer.finish()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct RenderThread<B: Backend + Send + 'static> {
+    canvas: Canvas,
+    width: usize,
+    height: usize,
+    default_color: Color,
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<Terminal<B>>>,
+}
+
+impl<B: Backend + Send + 'static> fmt::Debug for RenderThread<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderThread")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: Backend + Send + 'static> RenderThread<B> {
+    /// Moves `terminal` to a new render thread, with canvases of `width` by `height` cells
+    /// cleared to `default_color`.
+    pub fn spawn(terminal: Terminal<B>, width: usize, height: usize, default_color: Color) -> Self {
+        let shared = Arc::new(Shared {
+            slot: Mutex::new(Slot {
+                pending: None,
+                spare: Some(Canvas::new(width, height, default_color)),
+                redraw: false,
+                stop: false,
+                stopped: false,
+                error: None,
+                presented: 0,
+                dropped: 0,
+            }),
+            ready: Condvar::new(),
+        });
+        let worker = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || run(terminal, &shared))
+        };
+        Self {
+            canvas: Canvas::new(width, height, default_color),
+            width,
+            height,
+            default_color,
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// Returns the canvas to draw the next frame on.
+    ///
+    /// After [`RenderThread::present`], this is a canvas that showed an earlier frame, so draw
+    /// every frame in full, starting with [`Canvas::clear`].
+    pub fn canvas_mut(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    /// Hands the canvas over to the render thread without waiting for it, dropping the frame
+    /// handed over before if it was not drawn yet.
+    ///
+    /// Returns the error that stopped the render thread, if drawing failed.
+    pub fn present(&mut self) -> io::Result<()> {
+        let mut slot = self.shared.lock();
+        if let Some(error) = slot.error.take() {
+            return Err(error);
+        }
+        if slot.stopped {
+            return Err(io::Error::other("the render thread stopped"));
+        }
+        let next = match slot.pending.take() {
+            Some(dropped) => {
+                slot.dropped += 1;
+                Some(dropped)
+            }
+            None => slot.spare.take(),
+        };
+        let next = next
+            .filter(|canvas| (canvas.width, canvas.height) == (self.width, self.height))
+            .unwrap_or_else(|| Canvas::new(self.width, self.height, self.default_color));
+        slot.pending = Some(mem::replace(&mut self.canvas, next));
+        self.shared.ready.notify_one();
+        Ok(())
+    }
+
+    /// Resizes the canvases to `width` by `height` cells, such as after a resize event, and
+    /// makes the render thread clear the terminal and draw the next frame in full.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.canvas = Canvas::new(width, height, self.default_color);
+        self.shared.lock().redraw = true;
+    }
+
+    /// Returns the number of frames the render thread drew.
+    pub fn presented_frames(&self) -> u64 {
+        self.shared.lock().presented
+    }
+
+    /// Returns the number of frames dropped because the terminal could not keep up.
+    pub fn dropped_frames(&self) -> u64 {
+        self.shared.lock().dropped
+    }
+
+    /// Draws the last frame handed over, stops the render thread and returns the terminal.
+    pub fn finish(mut self) -> io::Result<Terminal<B>> {
+        let terminal = self.stop()?;
+        match self.shared.lock().error.take() {
+            Some(error) => Err(error),
+            None => Ok(terminal),
+        }
+    }
+
+    /// Stops the render thread once it drew the pending frame, and waits for it.
+    fn stop(&mut self) -> io::Result<Terminal<B>> {
+        self.shared.lock().stop = true;
+        self.shared.ready.notify_one();
+        let worker = self
+            .worker
+            .take()
+            .expect("the render thread is only stopped once");
+        worker
+            .join()
+            .map_err(|_| io::Error::other("the render thread panicked"))
+    }
+}
+
+impl<B: Backend + Send + 'static> Drop for RenderThread<B> {
+    fn drop(&mut self) {
+        if self.worker.is_some() {
+            let _ = self.stop();
+        }
+    }
+}
+
+/// Draws the canvases handed over until asked to stop or drawing fails.
+fn run<B: Backend>(mut terminal: Terminal<B>, shared: &Shared) -> Terminal<B> {
+    loop {
+        let (mut canvas, redraw) = {
+            let mut slot = shared.lock();
+            while slot.pending.is_none() && !slot.stop {
+                slot = shared
+                    .ready
+                    .wait(slot)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+            let Some(canvas) = slot.pending.take() else {
+                break;
+            };
+            (canvas, mem::take(&mut slot.redraw))
+        };
+        let result = if redraw { terminal.redraw() } else { Ok(()) };
+        let result = result.and_then(|()| terminal.draw(&mut canvas));
+        let mut slot = shared.lock();
+        slot.spare = Some(canvas);
+        match result {
+            Ok(()) => slot.presented += 1,
+            Err(error) => {
+                slot.error = Some(error);
+                break;
+            }
+        }
+    }
+    shared.lock().stopped = true;
+    terminal
+}
@@ -0,0 +1,161 @@
+//! Playing back recorded frames.
+
+use std::time::Duration;
+
+use crate::{Canvas, Frame, Recorder};
+
+/// Plays back frames recorded by a [`Recorder`], at their original speed or faster or slower.
+///
+/// The replay keeps a playback position that moves as time passes, and draws the frame shown
+/// at that position. It can also be stepped frame by frame, to review a recording closely.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Recorder, Replay};
+/// use std::time::Duration;
+///
+/// let mut canvas = Canvas::new(20, 5, Color { r: 0, g: 0, b: 0 });
+/// let mut recorder = Recorder::new();
+/// for x in 0..20 {
+///     canvas.clear();
+///     canvas.set_pixel(x, 4, 0, Color { r: 255, g: 255, b: 255 });
+///     recorder.record_with_delay(&canvas, Duration::from_millis(100));
+/// }
+///
+/// let mut replay = Replay::from(recorder).speed(2.0);
+/// replay.advance(Duration::from_millis(500));
+/// assert_eq!(replay.current_index(), 10);
+///
+/// canvas.clear();
+/// replay.draw(&mut canvas, 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    frames: Vec<Frame>,
+    /// The time at which every frame starts, followed by the end of the recording.
+    starts: Vec<Duration>,
+    position: Duration,
+    speed: f32,
+    looping: bool,
+}
+
+impl Replay {
+    /// Creates a replay of `frames`, each shown for the duration paired with it.
+    pub fn new(frames: impl IntoIterator<Item = (Frame, Duration)>) -> Self {
+        let mut starts = vec![Duration::ZERO];
+        let frames = frames
+            .into_iter()
+            .map(|(frame, delay)| {
+                starts.push(*starts.last().unwrap() + delay);
+                frame
+            })
+            .collect();
+        Self {
+            frames,
+            starts,
+            position: Duration::ZERO,
+            speed: 1.0,
+            looping: false,
+        }
+    }
+
+    /// Sets the playback speed, where `1.0` is the original speed and `0.5` half of it.
+    /// Negative speeds stop playback, and infinite and NaN speeds are ignored.
+    pub fn speed(mut self, speed: f32) -> Self {
+        if speed.is_finite() {
+            self.speed = speed.max(0.0);
+        }
+        self
+    }
+
+    /// Sets whether playback starts over after the last frame instead of stopping on it.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Returns the number of frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if there are no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns the frame at `index`.
+    pub fn frame(&self, index: usize) -> Option<&Frame> {
+        self.frames.get(index)
+    }
+
+    /// Returns the length of the recording at its original speed.
+    pub fn duration(&self) -> Duration {
+        *self.starts.last().unwrap()
+    }
+
+    /// Returns the playback position, in recording time.
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    /// Moves the playback position to `position`, in recording time.
+    pub fn seek(&mut self, position: Duration) {
+        let duration = self.duration();
+        self.position = if self.looping && !duration.is_zero() {
+            Duration::from_nanos((position.as_nanos() % duration.as_nanos()) as u64)
+        } else {
+            position.min(duration)
+        };
+    }
+
+    /// Moves playback forward by `elapsed` real time, scaled by the speed.
+    pub fn advance(&mut self, elapsed: Duration) {
+        // Steps too long for a duration go past the end of any recording.
+        let step = Duration::try_from_secs_f64(elapsed.as_secs_f64() * self.speed as f64)
+            .unwrap_or(Duration::MAX);
+        self.seek(self.position.saturating_add(step));
+    }
+
+    /// Returns `true` if playback reached the end. Looping replays never finish.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.position >= self.duration()
+    }
+
+    /// Returns the index of the frame shown at the playback position.
+    pub fn current_index(&self) -> usize {
+        let started = self.starts[1..].partition_point(|start| *start <= self.position);
+        started.min(self.frames.len().saturating_sub(1))
+    }
+
+    /// Returns the frame shown at the playback position.
+    pub fn current(&self) -> Option<&Frame> {
+        self.frames.get(self.current_index())
+    }
+
+    /// Moves the playback position to the start of the next frame, staying on the last one.
+    pub fn next_frame(&mut self) {
+        let index = (self.current_index() + 1).min(self.frames.len().saturating_sub(1));
+        self.position = self.starts[index];
+    }
+
+    /// Moves the playback position to the start of the previous frame, staying on the first one.
+    pub fn previous_frame(&mut self) {
+        let index = self.current_index().saturating_sub(1);
+        self.position = self.starts[index];
+    }
+
+    /// Draws the frame shown at the playback position onto layer `z` of `canvas`.
+    pub fn draw(&self, canvas: &mut Canvas, z: usize) {
+        if let Some(frame) = self.current() {
+            frame.draw(canvas, z);
+        }
+    }
+}
+
+impl From<Recorder> for Replay {
+    fn from(recorder: Recorder) -> Self {
+        Self::new(recorder.into_frames())
+    }
+}
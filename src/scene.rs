@@ -0,0 +1,499 @@
+//! A compact binary file format holding a whole canvas, for saving and loading art projects.
+//!
+//! A scene file starts with the magic bytes `RAEL`, a format version byte, the canvas size,
+//! supersampling factor and layer count, and the metadata entries. It follows with a palette of the colors used, every
+//! z-layer and the shade as run-length encoded runs, the characters drawn over cells, the
+//! opacity of every pixel as runs, the characters of the text plane, and the visibility,
+//! opacity and names of the layers.
+//! Numbers are stored as LEB128 variable-length integers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::{Attributes, Canvas, CellGlyph, Color, TerminalPixel, TextCell};
+
+/// The bytes every scene file starts with.
+const MAGIC: &[u8; 4] = b"RAEL";
+
+/// The version of the format written by this version of Rael. Version 1 has no supersampling
+/// factor, and versions before 3 have no opacity, with the pixels in the default color
+/// transparent and the others opaque. Versions before 4 have no text plane, and versions before
+/// 5 no marks or styles on the characters drawn over cells. Versions before 6 have every layer
+/// shown, opaque and unnamed.
+const VERSION: u8 = 6;
+
+/// An error raised while loading a scene.
+#[derive(Debug)]
+pub enum SceneError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not a valid scene file.
+    Invalid(String),
+    /// The file was written by a newer version of Rael, with a format version this one does not
+    /// know.
+    UnsupportedVersion(u8),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "failed to read scene: {error}"),
+            SceneError::Invalid(message) => write!(f, "invalid scene file: {message}"),
+            SceneError::UnsupportedVersion(version) => {
+                write!(f, "unsupported scene format version {version}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            SceneError::Invalid("the file ends early".into())
+        } else {
+            SceneError::Io(error)
+        }
+    }
+}
+
+/// A canvas with metadata, saved and loaded exactly, with every z-layer, the characters drawn
+/// over cells, the text plane, the shade and the visibility, opacity and names of the layers.
+///
+/// The state kept for differential rendering is not saved, so a loaded canvas redraws every cell
+/// on its first render.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, Scene};
+///
+/// let mut canvas = Canvas::new(40, 12, Color { r: 0, g: 0, b: 0 });
+/// canvas.set_pixel(3, 4, 2, Color { r: 255, g: 0, b: 0 });
+/// canvas.put_str(1, 0, "Sunset", Color::WHITE, None);
+/// canvas.set_layer_visible(1, false);
+/// let mut scene = Scene::new(canvas);
+/// scene.metadata.insert("title".into(), "Sunset".into());
+///
+/// let mut bytes = Vec::new();
+/// scene.write(&mut bytes)?;
+/// let loaded = Scene::read(bytes.as_slice())?;
+/// assert_eq!(loaded.metadata["title"], "Sunset");
+/// assert_eq!(loaded.canvas.diff(&scene.canvas), vec![]);
+/// assert!(!loaded.canvas.is_layer_visible(1));
+/// # Ok::<(), rael::SceneError>(())
+/// ```
+pub struct Scene {
+    /// The canvas of the scene.
+    pub canvas: Canvas,
+    /// Free-form entries describing the scene, such as its title or author.
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Scene {
+    /// Creates a scene of `canvas` with no metadata.
+    pub fn new(canvas: Canvas) -> Self {
+        Self {
+            canvas,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Loads a scene from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Saves the scene to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the scene in the scene file format.
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        let canvas = &self.canvas;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_number(&mut writer, canvas.width as u64)?;
+        write_number(&mut writer, canvas.height as u64)?;
+        write_number(&mut writer, canvas.supersampling as u64)?;
+        write_number(&mut writer, canvas.max_z_layers as u64)?;
+        write_color(&mut writer, canvas.default_color)?;
+        write_number(&mut writer, self.metadata.len() as u64)?;
+        for (key, value) in &self.metadata {
+            write_string(&mut writer, key)?;
+            write_string(&mut writer, value)?;
+        }
+
+        let mut palette = Vec::new();
+        let mut lookup = HashMap::new();
+        let mut index_of = |color: Color| {
+            *lookup
+                .entry((color.r, color.g, color.b))
+                .or_insert_with(|| {
+                    palette.push(color);
+                    palette.len() as u64 - 1
+                })
+        };
+        let pixels: Vec<u64> = canvas
+            .pixels
+            .iter()
+            .map(|pixel| index_of(pixel.color))
+            .collect();
+        let glyphs: Vec<(usize, CellGlyph, u64)> = canvas
+            .glyphs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, glyph)| Some((index, (*glyph)?)))
+            .map(|(index, glyph)| (index, glyph, index_of(glyph.color)))
+            .collect();
+        let text: Vec<(usize, TextCell, u64, u64)> = canvas
+            .text
+            .iter()
+            .enumerate()
+            .filter_map(|(index, text)| Some((index, (*text)?)))
+            .map(|(index, text)| {
+                let foreground = index_of(text.foreground);
+                // The background is stored one past its palette index, with 0 for none.
+                let background = text.background.map_or(0, |color| index_of(color) + 1);
+                (index, text, foreground, background)
+            })
+            .collect();
+
+        write_number(&mut writer, palette.len() as u64)?;
+        for color in &palette {
+            write_color(&mut writer, *color)?;
+        }
+        write_runs(&mut writer, &pixels)?;
+        let shade: Vec<u64> = canvas
+            .shade
+            .iter()
+            .map(|brightness| *brightness as u64)
+            .collect();
+        write_runs(&mut writer, &shade)?;
+        write_number(&mut writer, glyphs.len() as u64)?;
+        for (index, glyph, color) in glyphs {
+            write_number(&mut writer, index as u64)?;
+            write_number(&mut writer, glyph.ch as u64)?;
+            write_number(&mut writer, color)?;
+            write_mark(&mut writer, glyph.mark)?;
+            write_number(&mut writer, glyph.attributes.bits() as u64)?;
+        }
+        let alpha: Vec<u64> = canvas
+            .pixels
+            .iter()
+            .map(|pixel| pixel.alpha as u64)
+            .collect();
+        write_runs(&mut writer, &alpha)?;
+        write_number(&mut writer, text.len() as u64)?;
+        for (index, text, foreground, background) in text {
+            write_number(&mut writer, index as u64)?;
+            write_number(&mut writer, text.ch as u64)?;
+            write_mark(&mut writer, text.mark)?;
+            write_number(&mut writer, foreground)?;
+            write_number(&mut writer, background)?;
+            write_number(&mut writer, text.attributes.bits() as u64)?;
+            write_number(&mut writer, text.behind as u64)?;
+        }
+        for z in 0..canvas.max_z_layers {
+            write_number(&mut writer, canvas.is_layer_visible(z) as u64)?;
+            write_number(&mut writer, canvas.layer_opacity(z) as u64)?;
+        }
+        let names: Vec<(&str, usize)> = canvas.layer_names().collect();
+        write_number(&mut writer, names.len() as u64)?;
+        for (name, z) in names {
+            write_string(&mut writer, name)?;
+            write_number(&mut writer, z as u64)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a scene in the scene file format.
+    pub fn read(mut reader: impl Read) -> Result<Self, SceneError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(SceneError::Invalid("not a Rael scene file".into()));
+        }
+        let mut version = [0];
+        reader.read_exact(&mut version)?;
+        if !(1..=VERSION).contains(&version[0]) {
+            return Err(SceneError::UnsupportedVersion(version[0]));
+        }
+        let width = read_size(&mut reader)?;
+        let height = read_size(&mut reader)?;
+        let supersampling = if version[0] >= 2 {
+            read_size(&mut reader)?
+        } else {
+            1
+        };
+        if supersampling == 0 {
+            return Err(SceneError::Invalid("the supersampling factor is 0".into()));
+        }
+        let layers = read_size(&mut reader)?;
+        let default_color = read_color(&mut reader)?;
+        let cells = width
+            .checked_mul(height)
+            .ok_or_else(|| SceneError::Invalid("the canvas is too large".into()))?;
+        let samples = cells
+            .checked_mul(2)
+            .and_then(|pixels| pixels.checked_mul(supersampling))
+            .and_then(|pixels| pixels.checked_mul(supersampling))
+            .filter(|pixels| pixels.checked_mul(layers).is_some())
+            .ok_or_else(|| SceneError::Invalid("the canvas is too large".into()))?;
+        let mut metadata = BTreeMap::new();
+        for _ in 0..read_number(&mut reader)? {
+            let key = read_string(&mut reader)?;
+            metadata.insert(key, read_string(&mut reader)?);
+        }
+
+        let palette_len = read_size(&mut reader)?;
+        let palette = (0..palette_len)
+            .map(|_| read_color(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let color_at = |index: u64| {
+            palette
+                .get(index as usize)
+                .copied()
+                .ok_or_else(|| SceneError::Invalid(format!("color {index} is not in the palette")))
+        };
+        let mut pixels = read_runs(&mut reader, samples * layers)?
+            .into_iter()
+            .map(|index| {
+                let color = color_at(index)?;
+                Ok(TerminalPixel {
+                    color,
+                    alpha: if color == default_color { 0 } else { u8::MAX },
+                })
+            })
+            .collect::<Result<Vec<_>, SceneError>>()?;
+        let shade = read_runs(&mut reader, samples)?
+            .into_iter()
+            .map(|brightness| {
+                u8::try_from(brightness)
+                    .map_err(|_| SceneError::Invalid(format!("invalid brightness {brightness}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut glyphs = vec![None; cells];
+        for _ in 0..read_number(&mut reader)? {
+            let index = read_size(&mut reader)?;
+            let ch = read_char(&mut reader)?;
+            let color = color_at(read_number(&mut reader)?)?;
+            let (mark, attributes) = if version[0] >= 5 {
+                (read_mark(&mut reader)?, read_attributes(&mut reader)?)
+            } else {
+                (None, Attributes::NONE)
+            };
+            let cell = glyphs
+                .get_mut(index)
+                .ok_or_else(|| SceneError::Invalid(format!("glyph outside the canvas: {index}")))?;
+            *cell = Some(CellGlyph {
+                ch,
+                mark,
+                color,
+                attributes,
+            });
+        }
+        if version[0] >= 3 {
+            let alpha = read_runs(&mut reader, samples * layers)?;
+            for (pixel, alpha) in pixels.iter_mut().zip(alpha) {
+                pixel.alpha = u8::try_from(alpha)
+                    .map_err(|_| SceneError::Invalid(format!("invalid opacity {alpha}")))?;
+            }
+        }
+        let mut text = vec![None; cells];
+        if version[0] >= 4 {
+            for _ in 0..read_number(&mut reader)? {
+                let index = read_size(&mut reader)?;
+                let ch = read_char(&mut reader)?;
+                let mark = read_mark(&mut reader)?;
+                let foreground = color_at(read_number(&mut reader)?)?;
+                let background = match read_number(&mut reader)? {
+                    0 => None,
+                    index => Some(color_at(index - 1)?),
+                };
+                let attributes = read_attributes(&mut reader)?;
+                let behind = read_flag(&mut reader)?;
+                let cell = text.get_mut(index).ok_or_else(|| {
+                    SceneError::Invalid(format!("text outside the canvas: {index}"))
+                })?;
+                *cell = Some(TextCell {
+                    ch,
+                    mark,
+                    foreground,
+                    background,
+                    attributes,
+                    behind,
+                });
+            }
+        }
+
+        let mut canvas = Canvas::new(width, height, default_color);
+        canvas.supersampling = supersampling;
+        canvas.final_width = width * supersampling;
+        canvas.final_height = height * 2 * supersampling;
+        canvas.max_z_layers = layers;
+        canvas.pixels = pixels;
+        canvas.shade = shade;
+        canvas.glyphs = glyphs;
+        canvas.text = text;
+        if version[0] >= 6 {
+            for z in 0..layers {
+                canvas.set_layer_visible(z, read_flag(&mut reader)?);
+                let opacity = read_number(&mut reader)?;
+                let opacity = u8::try_from(opacity)
+                    .map_err(|_| SceneError::Invalid(format!("invalid opacity {opacity}")))?;
+                canvas.set_layer_opacity(z, opacity);
+            }
+            for _ in 0..read_number(&mut reader)? {
+                let name = read_string(&mut reader)?;
+                let z = read_size(&mut reader)?;
+                if z >= layers {
+                    return Err(SceneError::Invalid(format!(
+                        "layer {name:?} is not a layer: {z}"
+                    )));
+                }
+                canvas.name_layer(z, &name);
+            }
+        }
+        Ok(Self { canvas, metadata })
+    }
+}
+
+fn write_number(writer: &mut impl Write, mut number: u64) -> io::Result<()> {
+    loop {
+        let byte = (number & 0x7F) as u8;
+        number >>= 7;
+        if number == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_number(reader: &mut impl Read) -> Result<u64, SceneError> {
+    let mut number = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        number |= ((byte[0] & 0x7F) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(number);
+        }
+    }
+    Err(SceneError::Invalid("a number is too large".into()))
+}
+
+fn read_size(reader: &mut impl Read) -> Result<usize, SceneError> {
+    let number = read_number(reader)?;
+    usize::try_from(number).map_err(|_| SceneError::Invalid(format!("{number} is too large")))
+}
+
+fn read_char(reader: &mut impl Read) -> Result<char, SceneError> {
+    let code = read_number(reader)?;
+    u32::try_from(code)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| SceneError::Invalid(format!("invalid character {code}")))
+}
+
+/// Writes the zero-width character drawn with a character, one past its code, with 0 for none.
+fn write_mark(writer: &mut impl Write, mark: Option<char>) -> io::Result<()> {
+    write_number(writer, mark.map_or(0, |mark| mark as u64 + 1))
+}
+
+/// Reads a zero-width character written by [`write_mark`].
+fn read_mark(reader: &mut impl Read) -> Result<Option<char>, SceneError> {
+    match read_number(reader)? {
+        0 => Ok(None),
+        code => u32::try_from(code - 1)
+            .ok()
+            .and_then(char::from_u32)
+            .map(Some)
+            .ok_or_else(|| SceneError::Invalid(format!("invalid character {}", code - 1))),
+    }
+}
+
+fn read_flag(reader: &mut impl Read) -> Result<bool, SceneError> {
+    match read_number(reader)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        flag => Err(SceneError::Invalid(format!("invalid flag {flag}"))),
+    }
+}
+
+fn read_attributes(reader: &mut impl Read) -> Result<Attributes, SceneError> {
+    let bits = read_number(reader)?;
+    u8::try_from(bits)
+        .ok()
+        .and_then(Attributes::from_bits)
+        .ok_or_else(|| SceneError::Invalid(format!("invalid styles {bits}")))
+}
+
+fn write_color(writer: &mut impl Write, color: Color) -> io::Result<()> {
+    writer.write_all(&[color.r, color.g, color.b])
+}
+
+fn read_color(reader: &mut impl Read) -> Result<Color, SceneError> {
+    let mut rgb = [0; 3];
+    reader.read_exact(&mut rgb)?;
+    Ok(Color {
+        r: rgb[0],
+        g: rgb[1],
+        b: rgb[2],
+    })
+}
+
+fn write_string(writer: &mut impl Write, text: &str) -> io::Result<()> {
+    write_number(writer, text.len() as u64)?;
+    writer.write_all(text.as_bytes())
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String, SceneError> {
+    let len = read_number(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(SceneError::Invalid("the file ends early".into()));
+    }
+    String::from_utf8(bytes).map_err(|_| SceneError::Invalid("a string is not UTF-8".into()))
+}
+
+/// Writes `values` as runs of equal values, each a length followed by the value.
+fn write_runs(writer: &mut impl Write, values: &[u64]) -> io::Result<()> {
+    let mut rest = values;
+    while let Some(&value) = rest.first() {
+        let run = rest.iter().take_while(|other| **other == value).count();
+        write_number(writer, run as u64)?;
+        write_number(writer, value)?;
+        rest = &rest[run..];
+    }
+    Ok(())
+}
+
+/// Reads runs written by [`write_runs`] until `count` values are read.
+fn read_runs(reader: &mut impl Read, count: usize) -> Result<Vec<u64>, SceneError> {
+    let mut values = Vec::with_capacity(count.min(1 << 16));
+    while values.len() < count {
+        let run = read_size(reader)?;
+        let value = read_number(reader)?;
+        if run == 0 || run > count - values.len() {
+            return Err(SceneError::Invalid("a run does not fit the canvas".into()));
+        }
+        values.resize(values.len() + run, value);
+    }
+    Ok(values)
+}
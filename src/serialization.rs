@@ -0,0 +1,228 @@
+//! Serde support for canvases, sprites and tile maps, behind the `serde` feature.
+//!
+//! Types whose fields depend on each other, like the size and pixels of a sprite, go through
+//! plain data types that are checked when deserializing.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    Attributes, Canvas, CellGlyph, Color, Sprite, TerminalPixel, TextCell, TileMap, Tileset,
+};
+
+/// The serialized form of a [`Sprite`].
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SpriteData {
+    width: usize,
+    height: usize,
+    pixels: Vec<Option<Color>>,
+}
+
+impl TryFrom<SpriteData> for Sprite {
+    type Error = String;
+
+    fn try_from(data: SpriteData) -> Result<Self, Self::Error> {
+        if data.pixels.len() != data.width * data.height {
+            return Err(format!(
+                "a {}x{} sprite needs {} pixels, found {}",
+                data.width,
+                data.height,
+                data.width * data.height,
+                data.pixels.len()
+            ));
+        }
+        Ok(Sprite::from_pixels(data.width, data.height, data.pixels))
+    }
+}
+
+/// The serialized form of a [`TileMap`].
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TileMapData {
+    width: usize,
+    height: usize,
+    tiles: Vec<Option<usize>>,
+    tileset: Tileset,
+}
+
+impl TryFrom<TileMapData> for TileMap {
+    type Error = String;
+
+    fn try_from(data: TileMapData) -> Result<Self, Self::Error> {
+        if data.tiles.len() != data.width * data.height {
+            return Err(format!(
+                "a {}x{} tile map needs {} tiles, found {}",
+                data.width,
+                data.height,
+                data.width * data.height,
+                data.tiles.len()
+            ));
+        }
+        let mut map = TileMap::new(data.width, data.height, data.tileset);
+        for (index, tile) in data.tiles.into_iter().enumerate() {
+            map.set(index % data.width, index / data.width, tile);
+        }
+        Ok(map)
+    }
+}
+
+/// The serialized form of a [`Canvas`]: its size and supersampling factor, its z-layers one
+/// after the other with the opacity of their pixels, the characters drawn over its cells, its
+/// text plane, its shade and the visibility, opacity and names of its layers. The state kept
+/// for differential rendering is
+/// left out, so a deserialized canvas redraws every cell on its first render.
+#[derive(Serialize, Deserialize)]
+struct CanvasData {
+    width: usize,
+    height: usize,
+    default_color: Color,
+    #[serde(default = "no_supersampling")]
+    supersampling: usize,
+    layers: usize,
+    pixels: Vec<Color>,
+    /// The opacity of every pixel. Canvases serialized before pixels had opacity leave it
+    /// empty, with the pixels in the default color transparent.
+    #[serde(default)]
+    alpha: Vec<u8>,
+    glyphs: Vec<Option<(char, Color)>>,
+    /// The zero-width character drawn with every character over a cell. Canvases serialized
+    /// before characters had marks and styles leave it and `glyph_attributes` empty.
+    #[serde(default)]
+    glyph_marks: Vec<Option<char>>,
+    /// The styles of every character drawn over a cell.
+    #[serde(default)]
+    glyph_attributes: Vec<Attributes>,
+    /// The characters of the text plane. Canvases serialized before the text plane existed
+    /// leave it empty.
+    #[serde(default)]
+    text: Vec<Option<TextCell>>,
+    shade: Vec<u8>,
+    /// Whether every layer is shown. Canvases serialized before layers could be hidden, faded
+    /// or named leave it and `layer_opacity` empty, with every layer shown and opaque.
+    #[serde(default)]
+    layer_visible: Vec<bool>,
+    /// The opacity of every layer.
+    #[serde(default)]
+    layer_opacity: Vec<u8>,
+    /// The names of layers, with the `z` each names.
+    #[serde(default)]
+    layer_names: Vec<(String, usize)>,
+}
+
+impl Serialize for Canvas {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CanvasData {
+            width: self.width,
+            height: self.height,
+            default_color: self.default_color,
+            supersampling: self.supersampling,
+            layers: self.max_z_layers,
+            pixels: self.pixels.iter().map(|pixel| pixel.color).collect(),
+            alpha: self.pixels.iter().map(|pixel| pixel.alpha).collect(),
+            glyphs: self
+                .glyphs
+                .iter()
+                .map(|glyph| glyph.map(|glyph| (glyph.ch, glyph.color)))
+                .collect(),
+            glyph_marks: self
+                .glyphs
+                .iter()
+                .map(|glyph| glyph.and_then(|glyph| glyph.mark))
+                .collect(),
+            glyph_attributes: self
+                .glyphs
+                .iter()
+                .map(|glyph| glyph.map_or(Attributes::NONE, |glyph| glyph.attributes))
+                .collect(),
+            text: self.text.clone(),
+            shade: self.shade.clone(),
+            layer_visible: (0..self.max_z_layers)
+                .map(|z| self.is_layer_visible(z))
+                .collect(),
+            layer_opacity: (0..self.max_z_layers)
+                .map(|z| self.layer_opacity(z))
+                .collect(),
+            layer_names: self.layer_names.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Canvas {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = CanvasData::deserialize(deserializer)?;
+        let cells = data.width * data.height;
+        let pixels = cells * 2 * data.supersampling * data.supersampling;
+        if data.supersampling == 0
+            || data.pixels.len() != pixels * data.layers
+            || !(data.alpha.is_empty() || data.alpha.len() == data.pixels.len())
+            || data.glyphs.len() != cells
+            || !(data.glyph_marks.is_empty() || data.glyph_marks.len() == cells)
+            || !(data.glyph_attributes.is_empty() || data.glyph_attributes.len() == cells)
+            || !(data.text.is_empty() || data.text.len() == cells)
+            || data.shade.len() != pixels
+            || !(data.layer_visible.is_empty() || data.layer_visible.len() == data.layers)
+            || !(data.layer_opacity.is_empty() || data.layer_opacity.len() == data.layers)
+            || data.layer_names.iter().any(|&(_, z)| z >= data.layers)
+        {
+            return Err(serde::de::Error::custom(format!(
+                "the layers, layer settings, glyphs, text or shade do not match a {}x{} canvas with {} layers and {}x \
+                 supersampling",
+                data.width, data.height, data.layers, data.supersampling
+            )));
+        }
+        let mut canvas = Canvas::new(data.width, data.height, data.default_color);
+        canvas.set_supersampling(data.supersampling);
+        canvas.max_z_layers = data.layers;
+        canvas.pixels = if data.alpha.is_empty() {
+            data.pixels
+                .into_iter()
+                .map(|color| TerminalPixel {
+                    color,
+                    alpha: if color == data.default_color {
+                        0
+                    } else {
+                        u8::MAX
+                    },
+                })
+                .collect()
+        } else {
+            data.pixels
+                .into_iter()
+                .zip(data.alpha)
+                .map(|(color, alpha)| TerminalPixel { color, alpha })
+                .collect()
+        };
+        canvas.glyphs = data
+            .glyphs
+            .into_iter()
+            .enumerate()
+            .map(|(index, glyph)| {
+                glyph.map(|(ch, color)| CellGlyph {
+                    mark: data.glyph_marks.get(index).copied().flatten(),
+                    attributes: data
+                        .glyph_attributes
+                        .get(index)
+                        .copied()
+                        .unwrap_or_default(),
+                    ..CellGlyph::new(ch, color)
+                })
+            })
+            .collect();
+        if !data.text.is_empty() {
+            canvas.text = data.text;
+        }
+        canvas.shade = data.shade;
+        for (z, visible) in data.layer_visible.into_iter().enumerate() {
+            canvas.set_layer_visible(z, visible);
+        }
+        for (z, opacity) in data.layer_opacity.into_iter().enumerate() {
+            canvas.set_layer_opacity(z, opacity);
+        }
+        canvas.layer_names = data.layer_names;
+        Ok(canvas)
+    }
+}
+
+/// The supersampling factor of canvases serialized before supersampling existed.
+fn no_supersampling() -> usize {
+    1
+}
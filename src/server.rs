@@ -0,0 +1,464 @@
+//! Serving canvases to remote terminals over TCP.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::{Canvas, Color, Frame};
+
+/// The size given to clients that do not report the size of their terminal.
+const DEFAULT_SIZE: (usize, usize) = (80, 24);
+
+/// The largest size given to client canvases unless set otherwise, in columns and rows.
+const MAX_SIZE: (usize, usize) = (500, 200);
+
+/// Output is not queued past this many bytes: frames are dropped for clients this far behind.
+const MAX_PENDING: usize = 1 << 20;
+
+/// Input is not read past this many bytes per client and poll: the rest waits for the next
+/// poll, so a client flooding input cannot make the server buffer it all at once.
+const MAX_INPUT: usize = 64 << 10;
+
+/// How long a disconnected client is given to receive the rest of its output.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Telnet commands and options.
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const NAWS: u8 = 31;
+
+/// Switches to the alternate screen, hides the cursor and clears the screen.
+const ENTER_SCREEN: &str = "\u{1b}[?1049h\u{1b}[?25l\u{1b}[2J";
+/// Resets colors, shows the cursor and leaves the alternate screen.
+const LEAVE_SCREEN: &str = "\u{1b}[0m\u{1b}[?25h\u{1b}[?1049l";
+
+/// Identifies a client of a [`Server`]. Identifiers are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClientId(u64);
+
+/// Something that happened to the clients of a [`Server`], as returned by [`Server::poll`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerEvent {
+    /// A client connected. Its canvas has the default size until it reports its own.
+    Connected(ClientId),
+    /// A client reported a new size for its terminal, and its canvas was resized to match,
+    /// keeping what was drawn, up to the [maximum size](Server::set_max_client_size). Redraw
+    /// it to fit the new size.
+    Resized(ClientId),
+    /// A client sent input, with telnet commands removed. Keys arrive as the bytes the terminal
+    /// sends for them, escape sequences included.
+    Input(ClientId, Vec<u8>),
+    /// A client closed the connection or could no longer be reached, and was removed.
+    Disconnected(ClientId),
+}
+
+/// Where the telnet parser of a client is in the incoming bytes.
+#[derive(Debug)]
+enum TelnetState {
+    Data,
+    /// After a carriage return, which telnet may follow with a NUL byte to ignore.
+    CarriageReturn,
+    Command,
+    Option,
+    Subnegotiation(Vec<u8>),
+    SubnegotiationCommand(Vec<u8>),
+}
+
+/// A terminal connected to a [`Server`], with its own canvas.
+pub struct Client {
+    stream: TcpStream,
+    address: SocketAddr,
+    canvas: Canvas,
+    last_frame: Option<Frame>,
+    byte_budget: Option<usize>,
+    pending: Vec<u8>,
+    telnet: TelnetState,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("address", &self.address)
+            .field("width", &self.canvas.width)
+            .field("height", &self.canvas.height)
+            .field("pending", &self.pending.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Returns the address the client connected from.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the canvas shown to the client, sized to its terminal.
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Returns the canvas shown to the client, for drawing.
+    pub fn canvas_mut(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    /// Caps the output of a frame for this client at about `budget` bytes, or removes the cap
+    /// with `None`, as with [`Terminal::set_byte_budget`](crate::Terminal::set_byte_budget).
+    /// Defaults to `None`.
+    pub fn set_byte_budget(&mut self, budget: Option<usize>) {
+        self.byte_budget = budget;
+    }
+
+    /// Sends the cells of the canvas that changed since the last frame sent to this client.
+    ///
+    /// Output is queued and written without blocking. When a slow client still has a lot of
+    /// queued output, the frame is dropped for that client; the next one sent brings it up to
+    /// date.
+    pub fn present(&mut self) -> io::Result<()> {
+        self.flush()?;
+        if self.pending.len() < MAX_PENDING {
+            let field = self.canvas.next_interlace_field();
+            let mut frame = self
+                .canvas
+                .composite()
+                .interlaced(self.last_frame.as_ref(), field);
+            if let Some(budget) = self.byte_budget {
+                frame = frame.within_budget(self.last_frame.as_ref(), budget, |column, row| {
+                    self.canvas.priority(column, row)
+                });
+            }
+            let output = frame.ansi_since(self.last_frame.as_ref());
+            self.pending.extend(output.as_bytes());
+            self.last_frame = Some(frame);
+            self.canvas.count_frame();
+        }
+        self.flush()
+    }
+
+    /// Writes as much queued output as the connection takes without blocking.
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads what the client sent, returning `None` once the connection is closed. Reported
+    /// sizes are cut down to `max_size`.
+    fn receive(&mut self, max_size: (usize, usize)) -> io::Result<Option<(Vec<u8>, bool)>> {
+        let mut input = Vec::new();
+        let mut resized = false;
+        let mut buffer = [0; 4096];
+        let mut total = 0;
+        while total < MAX_INPUT {
+            let limit = buffer.len().min(MAX_INPUT - total);
+            let read = match self.stream.read(&mut buffer[..limit]) {
+                Ok(0) => return Ok(None),
+                Ok(read) => read,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+            total += read;
+            for &byte in &buffer[..read] {
+                let Some((width, height)) = self.parse_telnet(byte, &mut input) else {
+                    continue;
+                };
+                let (width, height) = (width.clamp(1, max_size.0), height.clamp(1, max_size.1));
+                if (width, height) != (self.canvas.width, self.canvas.height) {
+                    self.canvas.resize(width, height);
+                    self.last_frame = None;
+                    self.pending.extend(b"\x1b[2J");
+                    resized = true;
+                }
+            }
+        }
+        Ok(Some((input, resized)))
+    }
+
+    /// Feeds one byte to the telnet parser, pushing data bytes to `input`. Returns the size of
+    /// the terminal when the client reports it.
+    fn parse_telnet(&mut self, byte: u8, input: &mut Vec<u8>) -> Option<(usize, usize)> {
+        let state = std::mem::replace(&mut self.telnet, TelnetState::Data);
+        let (state, size) = match state {
+            TelnetState::Data | TelnetState::CarriageReturn if byte == IAC => {
+                (TelnetState::Command, None)
+            }
+            TelnetState::CarriageReturn if byte == 0 => (TelnetState::Data, None),
+            TelnetState::Data | TelnetState::CarriageReturn => {
+                input.push(byte);
+                let state = if byte == b'\r' {
+                    TelnetState::CarriageReturn
+                } else {
+                    TelnetState::Data
+                };
+                (state, None)
+            }
+            TelnetState::Command => match byte {
+                // An escaped 255 data byte.
+                IAC => {
+                    input.push(IAC);
+                    (TelnetState::Data, None)
+                }
+                DO | DONT | WILL | WONT => (TelnetState::Option, None),
+                SB => (TelnetState::Subnegotiation(Vec::new()), None),
+                _ => (TelnetState::Data, None),
+            },
+            TelnetState::Option => (TelnetState::Data, None),
+            TelnetState::Subnegotiation(data) if byte == IAC => {
+                (TelnetState::SubnegotiationCommand(data), None)
+            }
+            TelnetState::Subnegotiation(mut data) => {
+                if data.len() < 16 {
+                    data.push(byte);
+                }
+                (TelnetState::Subnegotiation(data), None)
+            }
+            TelnetState::SubnegotiationCommand(mut data) => match byte {
+                SE => {
+                    let size = match data[..] {
+                        [NAWS, w1, w0, h1, h0, ..] => Some((
+                            u16::from_be_bytes([w1, w0]) as usize,
+                            u16::from_be_bytes([h1, h0]) as usize,
+                        )),
+                        _ => None,
+                    };
+                    (TelnetState::Data, size)
+                }
+                IAC => {
+                    data.push(IAC);
+                    (TelnetState::Subnegotiation(data), None)
+                }
+                _ => (TelnetState::Data, None),
+            },
+        };
+        self.telnet = state;
+        size
+    }
+}
+
+/// A TCP server giving every connected terminal its own canvas, for multiplayer terminal
+/// games.
+///
+/// The server speaks the telnet protocol, so players connect with `telnet host port`. It asks
+/// clients for the size of their terminal and sizes their canvas to match, and puts their
+/// terminal in character mode so key presses arrive immediately. Plain TCP clients such as
+/// `nc` work too, with a canvas of the default 80x24 size. To reach the game over SSH, have
+/// the SSH server run `telnet localhost port` for the players' accounts.
+///
+/// The server never blocks: call [`Server::poll`] every frame to accept clients and read their
+/// input, then draw on each client's canvas and [`Server::present`] them all.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{Color, Server, ServerEvent};
+/// use std::time::{Duration, Instant};
+///
+/// let mut server = Server::bind("0.0.0.0:2323", Color { r: 0, g: 0, b: 0 })?;
+/// loop {
+///     for event in server.poll()? {
+///         if let ServerEvent::Input(id, bytes) = event {
+///             if bytes.contains(&b'q') {
+///                 server.disconnect(id);
+///             }
+///         }
+///     }
+///     for (_, client) in server.clients_mut() {
+///         let canvas = client.canvas_mut();
+///         canvas.clear();
+///         canvas.set_pixel(0, 0, 0, Color { r: 255, g: 255, b: 0 });
+///     }
+///     server.present();
+///     std::thread::sleep(Duration::from_millis(33));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    clients: BTreeMap<ClientId, Client>,
+    next_id: u64,
+    default_color: Color,
+    max_size: (usize, usize),
+    /// Disconnected clients still sending their queued output, with when to give up.
+    closing: Vec<(Client, Instant)>,
+}
+
+impl Server {
+    /// Starts listening on `address`. Client canvases are created with `default_color`.
+    pub fn bind(address: impl ToSocketAddrs, default_color: Color) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            clients: BTreeMap::new(),
+            next_id: 0,
+            default_color,
+            max_size: MAX_SIZE,
+            closing: Vec::new(),
+        })
+    }
+
+    /// Sets the largest canvas given to clients, in columns and rows. Clients report the size
+    /// of their terminal themselves, up to 65535 by 65535 cells, so larger sizes are cut down
+    /// to this one to keep a client from making the server allocate huge canvases. Applies to
+    /// sizes reported from now on. Defaults to 500 by 200.
+    pub fn set_max_client_size(&mut self, width: usize, height: usize) {
+        self.max_size = (width.max(1), height.max(1));
+    }
+
+    /// Returns the largest canvas given to clients, in columns and rows.
+    pub fn max_client_size(&self) -> (usize, usize) {
+        self.max_size
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts new clients and reads the input of connected ones, returning what happened.
+    pub fn poll(&mut self) -> io::Result<Vec<ServerEvent>> {
+        self.flush_closing();
+        let mut events = Vec::new();
+        loop {
+            let (stream, address) = match self.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+            if stream.set_nonblocking(true).is_err() {
+                continue;
+            }
+            let _ = stream.set_nodelay(true);
+            let mut pending = vec![IAC, DO, NAWS, IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD];
+            pending.extend(ENTER_SCREEN.as_bytes());
+            let width = DEFAULT_SIZE.0.min(self.max_size.0);
+            let height = DEFAULT_SIZE.1.min(self.max_size.1);
+            let id = ClientId(self.next_id);
+            self.next_id += 1;
+            self.clients.insert(
+                id,
+                Client {
+                    stream,
+                    address,
+                    canvas: Canvas::new(width, height, self.default_color),
+                    last_frame: None,
+                    byte_budget: None,
+                    pending,
+                    telnet: TelnetState::Data,
+                },
+            );
+            events.push(ServerEvent::Connected(id));
+        }
+
+        let mut disconnected = Vec::new();
+        for (&id, client) in &mut self.clients {
+            match client.receive(self.max_size) {
+                Ok(Some((input, resized))) => {
+                    if resized {
+                        events.push(ServerEvent::Resized(id));
+                    }
+                    if !input.is_empty() {
+                        events.push(ServerEvent::Input(id, input));
+                    }
+                }
+                Ok(None) | Err(_) => disconnected.push(id),
+            }
+        }
+        for id in disconnected {
+            self.clients.remove(&id);
+            events.push(ServerEvent::Disconnected(id));
+        }
+        Ok(events)
+    }
+
+    /// Returns the client with the given identifier, if it is still connected.
+    pub fn client(&self, id: ClientId) -> Option<&Client> {
+        self.clients.get(&id)
+    }
+
+    /// Returns the client with the given identifier, if it is still connected.
+    pub fn client_mut(&mut self, id: ClientId) -> Option<&mut Client> {
+        self.clients.get_mut(&id)
+    }
+
+    /// Returns the connected clients, in the order they connected.
+    pub fn clients(&self) -> impl Iterator<Item = (ClientId, &Client)> {
+        self.clients.iter().map(|(id, client)| (*id, client))
+    }
+
+    /// Returns the connected clients, in the order they connected.
+    pub fn clients_mut(&mut self) -> impl Iterator<Item = (ClientId, &mut Client)> {
+        self.clients.iter_mut().map(|(id, client)| (*id, client))
+    }
+
+    /// Returns the number of connected clients.
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Returns whether no client is connected.
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Sends every client the changes to its canvas, as [`Client::present`] does. Clients that
+    /// can no longer be reached are removed, and returned.
+    pub fn present(&mut self) -> Vec<ClientId> {
+        self.flush_closing();
+        let failed: Vec<ClientId> = self
+            .clients
+            .iter_mut()
+            .filter_map(|(id, client)| client.present().err().map(|_| *id))
+            .collect();
+        for id in &failed {
+            self.clients.remove(id);
+        }
+        failed
+    }
+
+    /// Restores the terminal of a client and closes its connection. The client is removed at
+    /// once, but the output it still has queued is sent without blocking by the next calls to
+    /// [`Server::poll`] and [`Server::present`], for up to a second, before the connection is
+    /// closed.
+    pub fn disconnect(&mut self, id: ClientId) {
+        if let Some(mut client) = self.clients.remove(&id) {
+            client.pending.extend(LEAVE_SCREEN.as_bytes());
+            self.closing.push((client, Instant::now() + CLOSE_TIMEOUT));
+            self.flush_closing();
+        }
+    }
+
+    /// Writes the output disconnected clients still have queued, without blocking, closing
+    /// their connections once it is sent, they fail, or their time is up.
+    fn flush_closing(&mut self) {
+        let now = Instant::now();
+        self.closing.retain_mut(|(client, deadline)| {
+            let sending = client.flush().is_ok() && !client.pending.is_empty() && now < *deadline;
+            if !sending {
+                let _ = client.stream.shutdown(Shutdown::Both);
+            }
+            sending
+        });
+    }
+}
@@ -0,0 +1,241 @@
+//! Presenting one canvas to several outputs at once.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::{crash, Canvas, Frame};
+
+/// Starts a frame sent in full: resets colors and styles and moves the cursor home, since the
+/// output before it may have stopped in the middle of an escape sequence.
+const FULL_FRAME_START: &[u8] = b"\x1b[0m\x1b[H";
+
+/// Identifies an output of a [`MultiSink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SinkId(u64);
+
+/// An output of a [`MultiSink`], with the last frame it received.
+struct Sink {
+    id: SinkId,
+    writer: Box<dyn Write>,
+    last_frame: Option<Frame>,
+    byte_budget: Option<usize>,
+    /// Output of the last frame the writer has not taken yet.
+    pending: Vec<u8>,
+}
+
+impl Sink {
+    /// Writes as much pending output as the writer takes without blocking, returning whether
+    /// all of it was written.
+    fn write_pending(&mut self) -> io::Result<bool> {
+        while !self.pending.is_empty() {
+            match self.writer.write(&self.pending) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        match self.writer.flush() {
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(true),
+            result => result.map(|()| true),
+        }
+    }
+}
+
+/// Sends what a canvas shows to several writers at once, such as the local terminal, a
+/// recording file and a spectator's socket.
+///
+/// Every writer gets only the cells that changed since the last frame it received, so writers
+/// can be added in the middle of a session: their first frame is drawn in full. A writer that
+/// fails to take a frame gets its next one in full too, since it may have lost part of the
+/// output.
+///
+/// Writers are written in turn on the calling thread, so a blocking writer that stalls, such
+/// as a blocking socket to a spectator that stopped reading, holds up every other output.
+/// Set sockets to non-blocking: the output a non-blocking writer does not take at once is kept
+/// and written by the next presents, and the writer misses frames until it has taken it all.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{Canvas, Color, MultiSink};
+/// use std::fs::File;
+/// use std::net::TcpStream;
+///
+/// let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// let mut sinks = MultiSink::new();
+/// sinks.add(std::io::stdout());
+/// sinks.add(File::create("session.ansi")?);
+/// let socket = TcpStream::connect("127.0.0.1:4000")?;
+/// socket.set_nonblocking(true)?;
+/// let spectator = sinks.add(socket);
+///
+/// canvas.set_pixel(10, 10, 0, Color { r: 255, g: 0, b: 0 });
+/// for (id, error) in sinks.present(&mut canvas) {
+///     if id == spectator {
+///         eprintln!("the spectator left: {error}");
+///         sinks.remove(id);
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Default)]
+pub struct MultiSink {
+    sinks: Vec<Sink>,
+    next_id: u64,
+}
+
+impl fmt::Debug for MultiSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiSink")
+            .field(
+                "sinks",
+                &self.sinks.iter().map(|sink| sink.id).collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl MultiSink {
+    /// Creates a sink with no outputs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an output, which gets the whole frame the next time the canvas is presented.
+    pub fn add(&mut self, writer: impl Write + 'static) -> SinkId {
+        let id = SinkId(self.next_id);
+        self.next_id += 1;
+        self.sinks.push(Sink {
+            id,
+            writer: Box::new(writer),
+            last_frame: None,
+            byte_budget: None,
+            pending: Vec::new(),
+        });
+        id
+    }
+
+    /// Removes an output, returning its writer.
+    pub fn remove(&mut self, id: SinkId) -> Option<Box<dyn Write>> {
+        let index = self.sinks.iter().position(|sink| sink.id == id)?;
+        Some(self.sinks.remove(index).writer)
+    }
+
+    /// Makes an output get the whole frame the next time the canvas is presented, for example
+    /// after its terminal was cleared.
+    pub fn redraw(&mut self, id: SinkId) {
+        if let Some(sink) = self.sinks.iter_mut().find(|sink| sink.id == id) {
+            sink.last_frame = None;
+        }
+    }
+
+    /// Caps the output of a frame for an output at about `budget` bytes, or removes the cap with
+    /// `None`, as with [`Terminal::set_byte_budget`](crate::Terminal::set_byte_budget). Defaults
+    /// to `None`.
+    pub fn set_byte_budget(&mut self, id: SinkId, budget: Option<usize>) {
+        if let Some(sink) = self.sinks.iter_mut().find(|sink| sink.id == id) {
+            sink.byte_budget = budget;
+        }
+    }
+
+    /// Returns the number of outputs.
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Returns whether there are no outputs.
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Writes and flushes the changes to `canvas` to every output, returning the outputs that
+    /// failed with their errors. Failing outputs are kept, and get the whole frame next time,
+    /// starting with a reset in case they stopped in the middle of an escape sequence.
+    /// Non-blocking outputs still taking an earlier frame miss this one. Counts a frame of the
+    /// flash shown over the canvas, if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color, MultiSink};
+    /// use std::cell::RefCell;
+    /// use std::io::{self, Write};
+    /// use std::rc::Rc;
+    ///
+    /// // A connection that takes the first 10 bytes of a frame, then fails once.
+    /// struct Flaky {
+    ///     taken: Rc<RefCell<Vec<u8>>>,
+    ///     writes: usize,
+    /// }
+    ///
+    /// impl Write for Flaky {
+    ///     fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+    ///         self.writes += 1;
+    ///         let taken = match self.writes {
+    ///             1 => 10,
+    ///             2 => return Err(io::ErrorKind::ConnectionReset.into()),
+    ///             _ => bytes.len(),
+    ///         };
+    ///         self.taken.borrow_mut().extend(&bytes[..taken]);
+    ///         Ok(taken)
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let taken = Rc::new(RefCell::new(Vec::new()));
+    /// let mut canvas = Canvas::new(4, 2, Color::BLACK);
+    /// let mut sinks = MultiSink::new();
+    /// sinks.add(Flaky { taken: taken.clone(), writes: 0 });
+    /// canvas.set_pixel(0, 0, 0, Color::RED);
+    /// assert_eq!(sinks.present(&mut canvas).len(), 1);
+    /// taken.borrow_mut().clear();
+    /// // The next frame is sent whole, from a clean state.
+    /// assert!(sinks.present(&mut canvas).is_empty());
+    /// assert!(taken.borrow().starts_with(b"\x1b[0m\x1b[H"));
+    /// ```
+    pub fn present(&mut self, canvas: &mut Canvas) -> Vec<(SinkId, io::Error)> {
+        let frame = canvas.composite();
+        let field = canvas.next_interlace_field();
+        let mut errors = Vec::new();
+        for sink in &mut self.sinks {
+            // A writer still taking its last frame misses this one.
+            match sink.write_pending() {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(error) => {
+                    sink.pending.clear();
+                    sink.last_frame = None;
+                    errors.push((sink.id, error));
+                    continue;
+                }
+            }
+            let mut frame = frame.interlaced(sink.last_frame.as_ref(), field);
+            if let Some(budget) = sink.byte_budget {
+                frame = frame.within_budget(sink.last_frame.as_ref(), budget, |column, row| {
+                    canvas.priority(column, row)
+                });
+            }
+            if sink.last_frame.is_none() {
+                sink.pending.extend(FULL_FRAME_START);
+            }
+            sink.pending
+                .extend(frame.ansi_since(sink.last_frame.as_ref()).as_bytes());
+            sink.last_frame = Some(frame);
+            if let Err(error) = sink.write_pending() {
+                sink.pending.clear();
+                sink.last_frame = None;
+                errors.push((sink.id, error));
+            }
+        }
+        crash::remember_frame(&frame);
+        canvas.count_frame();
+        errors
+    }
+}
@@ -0,0 +1,220 @@
+//! A spatial hash for finding the entities near a point or overlapping an area.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// The bounding box of an entry of a [`SpatialHash`], in world pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    /// The x coordinate of the left edge.
+    pub x: isize,
+    /// The y coordinate of the top edge.
+    pub y: isize,
+    /// The width of the box.
+    pub width: usize,
+    /// The height of the box.
+    pub height: usize,
+}
+
+impl Bounds {
+    /// Creates a bounding box.
+    pub fn new(x: isize, y: isize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns `true` if the two boxes share at least one pixel.
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.x < other.x + other.width as isize
+            && other.x < self.x + self.width as isize
+            && self.y < other.y + other.height as isize
+            && other.y < self.y + self.height as isize
+    }
+
+    /// Returns the squared distance from (`x`, `y`) to the closest point of the box.
+    fn distance_squared(&self, x: isize, y: isize) -> isize {
+        let dx = (self.x - x)
+            .max(x - (self.x + self.width as isize - 1))
+            .max(0);
+        let dy = (self.y - y)
+            .max(y - (self.y + self.height as isize - 1))
+            .max(0);
+        dx * dx + dy * dy
+    }
+}
+
+/// An index of entities by position, bucketing their bounding boxes into a grid of square cells.
+///
+/// Queries only visit the cells they cover, so their cost depends on how many entities are
+/// nearby rather than on the total number of entities. Pick a cell size around the size of a
+/// typical entity, or of a typical query.
+///
+/// Entities are identified by a key of type `K`, such as an index or an entity ID.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Bounds, SpatialHash};
+///
+/// let mut index = SpatialHash::new(16);
+/// index.insert(1, Bounds::new(0, 0, 4, 4));
+/// index.insert(2, Bounds::new(2, 2, 4, 4));
+/// index.insert(3, Bounds::new(500, -300, 4, 4));
+///
+/// assert_eq!(index.query_radius(0, 0, 10).len(), 2);
+/// assert_eq!(index.pairs(), vec![(1, 2)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpatialHash<K> {
+    cell_size: usize,
+    cells: HashMap<(isize, isize), Vec<K>>,
+    entries: HashMap<K, Bounds>,
+}
+
+impl<K: Copy + Eq + Hash + Ord> SpatialHash<K> {
+    /// Creates an empty index with cells of `cell_size` by `cell_size` pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_size` is zero.
+    pub fn new(cell_size: usize) -> Self {
+        assert!(cell_size > 0, "the cell size must not be zero");
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the range of cells covered by `bounds`.
+    fn cell_range(&self, bounds: &Bounds) -> ((isize, isize), (isize, isize)) {
+        let size = self.cell_size as isize;
+        let right = bounds.x + bounds.width.max(1) as isize - 1;
+        let bottom = bounds.y + bounds.height.max(1) as isize - 1;
+        (
+            (bounds.x.div_euclid(size), bounds.y.div_euclid(size)),
+            (right.div_euclid(size), bottom.div_euclid(size)),
+        )
+    }
+
+    /// Adds an entity, or moves it if it is already in the index.
+    pub fn insert(&mut self, key: K, bounds: Bounds) {
+        if let Some(old) = self.entries.get(&key) {
+            if self.cell_range(old) == self.cell_range(&bounds) {
+                self.entries.insert(key, bounds);
+                return;
+            }
+            self.remove(key);
+        }
+        let ((left, top), (right, bottom)) = self.cell_range(&bounds);
+        for y in top..=bottom {
+            for x in left..=right {
+                self.cells.entry((x, y)).or_default().push(key);
+            }
+        }
+        self.entries.insert(key, bounds);
+    }
+
+    /// Removes an entity, returning its bounds if it was in the index.
+    pub fn remove(&mut self, key: K) -> Option<Bounds> {
+        let bounds = self.entries.remove(&key)?;
+        let ((left, top), (right, bottom)) = self.cell_range(&bounds);
+        for y in top..=bottom {
+            for x in left..=right {
+                if let Some(cell) = self.cells.get_mut(&(x, y)) {
+                    cell.retain(|other| *other != key);
+                    if cell.is_empty() {
+                        self.cells.remove(&(x, y));
+                    }
+                }
+            }
+        }
+        Some(bounds)
+    }
+
+    /// Removes every entity.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+    }
+
+    /// Returns the bounds of an entity.
+    pub fn bounds(&self, key: K) -> Option<Bounds> {
+        self.entries.get(&key).copied()
+    }
+
+    /// Returns the number of entities.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the index holds no entities.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entities stored in the cells covered by `area`, each one once.
+    fn candidates(&self, area: &Bounds) -> HashSet<K> {
+        let ((left, top), (right, bottom)) = self.cell_range(area);
+        let mut found = HashSet::new();
+        for y in top..=bottom {
+            for x in left..=right {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend(cell.iter().copied());
+                }
+            }
+        }
+        found
+    }
+
+    /// Returns the entities whose bounds overlap `area`, sorted by key.
+    pub fn query(&self, area: Bounds) -> Vec<K> {
+        let mut found: Vec<K> = self
+            .candidates(&area)
+            .into_iter()
+            .filter(|key| self.entries[key].intersects(&area))
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// Returns the entities whose bounds come within `radius` pixels of (`x`, `y`), sorted by key.
+    pub fn query_radius(&self, x: isize, y: isize, radius: usize) -> Vec<K> {
+        let area = Bounds::new(
+            x - radius as isize,
+            y - radius as isize,
+            radius * 2 + 1,
+            radius * 2 + 1,
+        );
+        let limit = (radius * radius) as isize;
+        let mut found: Vec<K> = self
+            .candidates(&area)
+            .into_iter()
+            .filter(|key| self.entries[key].distance_squared(x, y) <= limit)
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// Returns every pair of entities whose bounds overlap, for the broad phase of collision
+    /// detection. Each pair is listed once with the smaller key first, and the pairs are sorted.
+    pub fn pairs(&self) -> Vec<(K, K)> {
+        let mut pairs = HashSet::new();
+        for cell in self.cells.values() {
+            for (index, a) in cell.iter().enumerate() {
+                for b in &cell[index + 1..] {
+                    if self.entries[a].intersects(&self.entries[b]) {
+                        pairs.insert(if a < b { (*a, *b) } else { (*b, *a) });
+                    }
+                }
+            }
+        }
+        let mut pairs: Vec<_> = pairs.into_iter().collect();
+        pairs.sort_unstable();
+        pairs
+    }
+}
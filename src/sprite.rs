@@ -0,0 +1,179 @@
+//! Sprites: small images of half-block pixels that can be drawn onto a [`Canvas`].
+
+use crate::{Canvas, Color};
+
+/// A rectangular image of half-block pixels. Pixels set to `None` are transparent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "crate::serialization::SpriteData")
+)]
+pub struct Sprite {
+    width: usize,
+    height: usize,
+    pixels: Vec<Option<Color>>,
+}
+
+impl Sprite {
+    /// Creates a fully transparent sprite.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![None; width * height],
+        }
+    }
+
+    /// Creates a sprite filled with a single color.
+    pub fn filled(width: usize, height: usize, color: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Some(color); width * height],
+        }
+    }
+
+    /// Creates a sprite from row-major pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pixels` does not hold exactly `width * height` pixels.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Option<Color>>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "a {width}x{height} sprite needs {} pixels",
+            width * height
+        );
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Returns the width of the sprite in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the sprite in half-block pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the row-major pixels of the sprite.
+    pub fn pixels(&self) -> &[Option<Color>] {
+        &self.pixels
+    }
+
+    /// Returns the pixel at (`x`, `y`), or `None` if it is transparent or out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.pixels[y * self.width + x]
+    }
+
+    /// Sets the pixel at (`x`, `y`). Out of bounds pixels are ignored.
+    pub fn set(&mut self, x: usize, y: usize, color: Option<Color>) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    /// Returns the `width` by `height` region of this sprite starting at (`x`, `y`) as a new
+    /// sprite. Parts of the region outside this sprite are transparent.
+    pub fn sub_sprite(&self, x: usize, y: usize, width: usize, height: usize) -> Sprite {
+        let mut sprite = Sprite::new(width, height);
+        for sy in 0..height {
+            for sx in 0..width {
+                sprite.set(sx, sy, self.get(x + sx, y + sy));
+            }
+        }
+        sprite
+    }
+}
+
+impl Canvas {
+    /// Draws `sprite` with its top-left corner at (`x`, `y`) on layer `z`.
+    ///
+    /// Transparent pixels leave the layer untouched, and parts of the sprite falling outside the
+    /// canvas are clipped. The sprite is drawn through the [transform](Canvas::transform) of the
+    /// canvas, so it can be turned and resized.
+    ///
+    /// # Arguments
+    ///
+    /// * `sprite` - The sprite to draw.
+    /// * `x` - The terminal column of the left edge of the sprite. May be negative.
+    /// * `y` - The half-block row of the top edge of the sprite. May be negative.
+    /// * `z` - The z-layer to draw on.
+    pub fn draw_sprite(&mut self, sprite: &Sprite, x: isize, y: isize, z: usize) {
+        let Some((dx, dy)) = self.pixel_offset() else {
+            self.draw_sprite_transformed(sprite, x, y, z);
+            return;
+        };
+        let (x, y) = (x + dx, y + dy);
+        let start_x = (-x).max(0) as usize;
+        let start_y = (-y).max(0) as usize;
+        let end_x = sprite
+            .width
+            .min((self.final_width as isize - x).max(0) as usize);
+        let end_y = sprite
+            .height
+            .min((self.final_height as isize - y).max(0) as usize);
+        for sy in start_y..end_y {
+            let row = &sprite.pixels[sy * sprite.width..(sy + 1) * sprite.width];
+            for (sx, pixel) in row.iter().enumerate().take(end_x).skip(start_x) {
+                if let Some(color) = *pixel {
+                    let px = (x + sx as isize) as usize;
+                    let py = (y + sy as isize) as usize;
+                    self.set_pixel(px, py, z, color);
+                }
+            }
+        }
+    }
+
+    /// Draws `sprite` with its top-left corner at (`x`, `y`) through a transform that turns or
+    /// resizes it, setting every pixel whose center the transform takes from inside the sprite
+    /// to the sprite pixel it came from.
+    fn draw_sprite_transformed(&mut self, sprite: &Sprite, x: isize, y: isize, z: usize) {
+        let Some(inverse) = self.transform().inverse() else {
+            return;
+        };
+        let (left, top) = (x as f64, y as f64);
+        let (right, bottom) = (left + sprite.width as f64, top + sprite.height as f64);
+        let corners = [(left, top), (right, top), (right, bottom), (left, bottom)]
+            .map(|corner| self.transformed(corner));
+        // The pixels covered by the box around the transformed corners.
+        let covered = |values: [f64; 4], limit: usize| {
+            let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            (low.floor().max(0.0) as usize)..(high.ceil().min(limit as f64) as usize)
+        };
+        let columns = covered(corners.map(|(x, _)| x), self.final_width);
+        let rows = covered(corners.map(|(_, y)| y), self.final_height);
+        for py in rows {
+            for px in columns.clone() {
+                let (sx, sy) = inverse.apply(px as f32 + 0.5, py as f32 + 0.5);
+                let (sx, sy) = ((sx - x as f32).floor(), (sy - y as f32).floor());
+                if sx < 0.0 || sy < 0.0 {
+                    continue;
+                }
+                if let Some(color) = sprite.get(sx as usize, sy as usize) {
+                    self.set_pixel(px, py, z, color);
+                }
+            }
+        }
+    }
+
+    /// Draws `sprite` with its top-left corner at the fractional position (`x`, `y`) on layer
+    /// `z`, snapped to a pixel by [`Canvas::subpixel_position`]. With motion smoothing turned on,
+    /// a sprite moving by less than a pixel per frame alternates between the nearest pixels
+    /// instead of stair-stepping.
+    pub fn draw_sprite_subpixel(&mut self, sprite: &Sprite, x: f32, y: f32, z: usize) {
+        let (x, y) = self.subpixel_position(x, y);
+        self.draw_sprite(sprite, x, y, z);
+    }
+}
@@ -0,0 +1,402 @@
+//! Setting up the terminal for full-screen drawing and restoring it afterwards.
+
+use std::io::{self, Stdout};
+use std::time::Duration;
+
+use crossterm::event::Event;
+
+use crate::{
+    clipboard_escape, crash, Backend, Canvas, Capabilities, CrosstermBackend, Frame,
+    SemanticRegion, CAPABILITY_QUERY,
+};
+
+/// Turns on reporting of mouse presses, drags and moves, in the SGR format.
+const MOUSE_CAPTURE_ON: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1003h\x1b[?1015h\x1b[?1006h";
+/// Turns off reporting of mouse events.
+const MOUSE_CAPTURE_OFF: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1003l\x1b[?1002l\x1b[?1000l";
+
+/// A terminal taken over for drawing canvases: in raw mode, on the alternate screen and with
+/// the cursor hidden, until it is dropped.
+///
+/// The terminal remembers the last frame it drew, so [`Terminal::draw`] only sends the cells
+/// that changed. When the terminal is resized, the next frame is drawn in full.
+///
+/// The real cursor can be shown at a cell with [`Terminal::set_cursor`], for example at the
+/// insertion point of a text field so input methods place their popups there. It is put back
+/// after every draw, which moves it.
+///
+/// [`Terminal::detect_capabilities`] finds out what the terminal supports. Terminals with
+/// synchronized output then show every frame at once, never half drawn, and canvases are drawn
+/// in no more colors than the terminal shows, such as the xterm 256-color palette for a canvas
+/// in 24-bit colors.
+///
+/// In screen reader mode, set with [`Terminal::set_screen_reader_mode`], the terminal shows the
+/// [semantic regions](crate::SemanticRegion) of canvases as lines of text instead of their
+/// pixels, writing a new line whenever a region changes or gets the focus.
+///
+/// # Example
+///
+/// ```no_run
+/// use crossterm::event::{Event, KeyCode};
+/// use rael::{Canvas, Color, Terminal};
+/// use std::time::Duration;
+///
+/// let mut terminal = Terminal::stdout()?;
+/// let (width, height) = terminal.size()?;
+/// let mut canvas = Canvas::new(width, height, Color { r: 0, g: 0, b: 0 });
+/// loop {
+///     if let Some(Event::Key(key)) = terminal.poll_event(Duration::from_millis(16))? {
+///         if key.code == KeyCode::Char('q') {
+///             break;
+///         }
+///     }
+///     canvas.set_pixel(3, 3, 0, Color { r: 255, g: 0, b: 0 });
+///     terminal.draw(&mut canvas)?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Terminal<B: Backend> {
+    backend: B,
+    last_frame: Option<Frame>,
+    bracketed_paste: bool,
+    mouse_capture: bool,
+    cursor: Option<(usize, usize)>,
+    last_draw_bytes: Option<usize>,
+    byte_budget: Option<usize>,
+    capabilities: Capabilities,
+    screen_reader: bool,
+    /// The regions and focus last written in screen reader mode, if the screen shows them.
+    spoken: Option<(Vec<SemanticRegion>, Option<String>)>,
+}
+
+impl Terminal<CrosstermBackend<Stdout>> {
+    /// Takes over the terminal of standard output, through crossterm.
+    pub fn stdout() -> io::Result<Self> {
+        Self::new(CrosstermBackend::new(io::stdout()))
+    }
+}
+
+impl<B: Backend> Terminal<B> {
+    /// Takes over the terminal driven by `backend`.
+    pub fn new(mut backend: B) -> io::Result<Self> {
+        backend.enable_raw_mode()?;
+        backend.enter_alternate_screen()?;
+        backend.hide_cursor()?;
+        backend.clear()?;
+        backend.flush()?;
+        Ok(Self {
+            backend,
+            last_frame: None,
+            bracketed_paste: false,
+            mouse_capture: false,
+            cursor: None,
+            last_draw_bytes: None,
+            byte_budget: None,
+            capabilities: Capabilities::default(),
+            screen_reader: false,
+            spoken: None,
+        })
+    }
+
+    /// Returns the backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the backend, for using it directly.
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// Returns the size of the terminal, in columns and rows.
+    pub fn size(&self) -> io::Result<(usize, usize)> {
+        self.backend.size()
+    }
+
+    /// Waits up to `timeout` for an event, returning `None` if there was none.
+    pub fn poll_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        let event = self.backend.poll_event(timeout)?;
+        if let Some(Event::Resize(..)) = event {
+            self.redraw()?;
+        }
+        Ok(event)
+    }
+
+    /// Returns what the terminal supports, as detected by [`Terminal::detect_capabilities`] or
+    /// set by [`Terminal::set_capabilities`]. Defaults to [`Capabilities::default`].
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Sets what the terminal supports, overriding detection.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Detects what the terminal supports from the environment and terminfo, then asks the
+    /// terminal, waiting up to `timeout` for its answers, and uses the result from then on.
+    ///
+    /// Like [`Terminal::cursor_position`], the answers are read from the input, so call it at
+    /// startup rather than while events are pending. Backends that cannot ask the terminal keep
+    /// what the environment tells.
+    pub fn detect_capabilities(&mut self, timeout: Duration) -> io::Result<Capabilities> {
+        let mut capabilities = Capabilities::from_env();
+        if self.backend.supports_ansi() {
+            match self.backend.query(CAPABILITY_QUERY.as_bytes(), timeout) {
+                Ok(answer) => {
+                    capabilities.apply_response(&answer);
+                }
+                Err(error) if error.kind() == io::ErrorKind::Unsupported => {}
+                Err(error) => return Err(error),
+            }
+        } else {
+            // Consoles without escape codes are drawn with their 16 colors.
+            capabilities.truecolor = false;
+            capabilities.colors = 16;
+            capabilities.synchronized_output = false;
+        }
+        self.capabilities = capabilities;
+        Ok(capabilities)
+    }
+
+    /// Sets the title of the terminal window.
+    pub fn set_title(&mut self, title: &str) -> io::Result<()> {
+        self.backend.set_title(title)?;
+        self.backend.flush()
+    }
+
+    /// Shows the cursor at (`column`, `row`), or hides it with `None`. The cursor is hidden by
+    /// default.
+    pub fn set_cursor(&mut self, position: Option<(usize, usize)>) -> io::Result<()> {
+        self.cursor = position;
+        self.place_cursor()?;
+        self.backend.flush()
+    }
+
+    /// Returns where the cursor is shown, as set by [`Terminal::set_cursor`].
+    pub fn cursor(&self) -> Option<(usize, usize)> {
+        self.cursor
+    }
+
+    /// Asks the terminal where the cursor is, in columns and rows.
+    ///
+    /// This waits for the terminal to answer, and the answer is read from the input, so call it
+    /// between frames rather than while events are pending.
+    pub fn cursor_position(&mut self) -> io::Result<(usize, usize)> {
+        self.backend.cursor_position()
+    }
+
+    /// Moves the cursor to where it is shown and shows it, or hides it.
+    fn place_cursor(&mut self) -> io::Result<()> {
+        match self.cursor {
+            Some((column, row)) => {
+                self.backend.move_cursor(column, row)?;
+                self.backend.show_cursor()
+            }
+            None => self.backend.hide_cursor(),
+        }
+    }
+
+    /// Rings the terminal bell, which beeps or flashes the window depending on the terminal
+    /// settings. See [`Canvas::flash`] for a flash that does not depend on them.
+    pub fn bell(&mut self) -> io::Result<()> {
+        self.backend.write(b"\x07")?;
+        self.backend.flush()
+    }
+
+    /// Copies `text` to the clipboard of the user, through the terminal with an OSC 52 escape
+    /// code. See [`clipboard_escape`] for which terminals support it.
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] if the terminal does not understand escape
+    /// codes.
+    pub fn copy_to_clipboard(&mut self, text: &str) -> io::Result<()> {
+        self.write_escape(&clipboard_escape(text))
+    }
+
+    /// Sets whether pasted text arrives in one [`Event::Paste`] from [`Terminal::poll_event`],
+    /// rather than as a key event per character. This is how text fields read the clipboard,
+    /// since terminals rarely let programs read it directly. Defaults to `false`.
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] if the terminal does not understand escape
+    /// codes.
+    pub fn set_bracketed_paste(&mut self, enabled: bool) -> io::Result<()> {
+        self.write_escape(if enabled {
+            "\x1b[?2004h"
+        } else {
+            "\x1b[?2004l"
+        })?;
+        self.bracketed_paste = enabled;
+        Ok(())
+    }
+
+    /// Sets whether the terminal reports mouse presses, drags and moves as [`Event::Mouse`]
+    /// from [`Terminal::poll_event`]. This stops the terminal from selecting text with the
+    /// mouse. Defaults to `false`.
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] if the terminal does not understand escape
+    /// codes.
+    pub fn set_mouse_capture(&mut self, enabled: bool) -> io::Result<()> {
+        self.write_escape(if enabled {
+            MOUSE_CAPTURE_ON
+        } else {
+            MOUSE_CAPTURE_OFF
+        })?;
+        self.mouse_capture = enabled;
+        Ok(())
+    }
+
+    /// Writes and flushes an escape code that does not change what the screen shows.
+    fn write_escape(&mut self, escape: &str) -> io::Result<()> {
+        if !self.backend.supports_ansi() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the terminal does not support escape codes",
+            ));
+        }
+        self.backend.write(escape.as_bytes())?;
+        self.backend.flush()
+    }
+
+    /// Sets whether [`Terminal::draw`] writes the semantic regions of canvases as plain lines of
+    /// text for screen readers, rather than their pixels. The next draw starts over with every
+    /// region. Defaults to `false`.
+    pub fn set_screen_reader_mode(&mut self, enabled: bool) -> io::Result<()> {
+        self.screen_reader = enabled;
+        self.redraw()?;
+        self.backend.flush()
+    }
+
+    /// Returns whether the terminal is in screen reader mode.
+    pub fn is_screen_reader_mode(&self) -> bool {
+        self.screen_reader
+    }
+
+    /// Clears the screen and makes the next [`Terminal::draw`] draw every cell, or every region
+    /// in screen reader mode.
+    pub fn redraw(&mut self) -> io::Result<()> {
+        self.last_frame = None;
+        self.spoken = None;
+        self.backend.clear()
+    }
+
+    /// Returns how many bytes the last [`Terminal::draw`] sent, for watching how much output
+    /// frames cost, or `None` if the backend does not count them.
+    pub fn last_draw_bytes(&self) -> Option<usize> {
+        self.last_draw_bytes
+    }
+
+    /// Caps the escape codes of a draw at about `budget` bytes, or removes the cap with `None`.
+    /// Changed cells with the highest [priorities](Canvas::set_priority) are sent first, and the
+    /// cells that do not fit are sent by later draws. Defaults to `None`.
+    pub fn set_byte_budget(&mut self, budget: Option<usize>) {
+        self.byte_budget = budget;
+    }
+
+    /// Returns the cap on the escape codes of a draw, if any.
+    pub fn byte_budget(&self) -> Option<usize> {
+        self.byte_budget
+    }
+
+    /// Draws what `canvas` shows, sending only the cells that changed since the last draw.
+    /// Counts a frame of the flash shown over the canvas, if any.
+    pub fn draw(&mut self, canvas: &mut Canvas) -> io::Result<()> {
+        let written = self.backend.bytes_written();
+        let field = canvas.next_interlace_field();
+        if self.screen_reader {
+            self.speak(canvas)?;
+            crash::remember_canvas(canvas);
+        } else {
+            self.draw_pixels(canvas, field)?;
+            if let Some(frame) = &self.last_frame {
+                crash::remember_frame(frame);
+            }
+        }
+        self.last_draw_bytes = written
+            .zip(self.backend.bytes_written())
+            .map(|(before, after)| (after - before) as usize);
+        canvas.count_frame();
+        Ok(())
+    }
+
+    /// Draws the cells of `canvas` that changed since the last draw, in the rows of `field`
+    /// only if interlaced.
+    fn draw_pixels(&mut self, canvas: &mut Canvas, field: Option<usize>) -> io::Result<()> {
+        let mut frame = canvas
+            .composite()
+            .interlaced(self.last_frame.as_ref(), field);
+        frame.limit_color_mode(self.capabilities.color_mode());
+        if let Some(budget) = self.byte_budget {
+            frame = frame.within_budget(self.last_frame.as_ref(), budget, |column, row| {
+                canvas.priority(column, row)
+            });
+        }
+        let synchronized = self.capabilities.synchronized_output && self.backend.supports_ansi();
+        if synchronized {
+            self.backend.write(b"\x1b[?2026h")?;
+        }
+        if self.cursor.is_some() {
+            // Hide the cursor while it jumps around the changed cells.
+            self.backend.hide_cursor()?;
+        }
+        self.backend.draw_frame(&frame, self.last_frame.as_ref())?;
+        if self.cursor.is_some() {
+            self.place_cursor()?;
+        }
+        if synchronized {
+            self.backend.write(b"\x1b[?2026l")?;
+        }
+        self.backend.flush()?;
+        self.last_frame = Some(frame);
+        Ok(())
+    }
+
+    /// Writes a line for every region of `canvas` whose value changed or that got the focus
+    /// since the last draw, or for every region after the screen was cleared.
+    fn speak(&mut self, canvas: &Canvas) -> io::Result<()> {
+        let focus = canvas.focus().map(|region| region.label.clone());
+        let spoken = self.spoken.take();
+        if spoken.is_none() {
+            self.backend.move_cursor(0, 0)?;
+        }
+        let mut output = String::new();
+        for region in canvas.regions_in_reading_order() {
+            let focused = focus.as_ref() == Some(&region.label);
+            let changed = match &spoken {
+                None => true,
+                Some((regions, previous_focus)) => {
+                    (focused && previous_focus != &focus)
+                        || !regions.iter().any(|previous| {
+                            previous.label == region.label && previous.value == region.value
+                        })
+                }
+            };
+            if changed {
+                output.push_str(&region.text(focused));
+                output.push_str("\r\n");
+            }
+        }
+        self.backend.write(output.as_bytes())?;
+        self.backend.flush()?;
+        self.spoken = Some((canvas.regions().to_vec(), focus));
+        Ok(())
+    }
+}
+
+impl<B: Backend> Drop for Terminal<B> {
+    fn drop(&mut self) {
+        if self.bracketed_paste {
+            let _ = self.backend.write(b"\x1b[?2004l");
+        }
+        if self.mouse_capture {
+            let _ = self.backend.write(MOUSE_CAPTURE_OFF.as_bytes());
+        }
+        if self.backend.supports_ansi() {
+            let _ = self.backend.write(b"\x1b[0m");
+        }
+        let _ = self.backend.show_cursor();
+        let _ = self.backend.leave_alternate_screen();
+        let _ = self.backend.disable_raw_mode();
+        let _ = self.backend.flush();
+    }
+}
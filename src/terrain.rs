@@ -0,0 +1,256 @@
+//! A voxel space renderer drawing heightmap terrain in perspective.
+
+use crate::{Canvas, Color, Rect};
+
+/// A grid of terrain heights with a color for every cell.
+///
+/// The map wraps around at its edges, so views can fly over it forever.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightMap {
+    width: usize,
+    height: usize,
+    heights: Vec<f32>,
+    colors: Vec<Color>,
+}
+
+impl HeightMap {
+    /// Creates a flat, black map of `width` by `height` cells at height `0.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is zero.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::from_fn(width, height, |_, _| (0.0, Color { r: 0, g: 0, b: 0 }))
+    }
+
+    /// Creates a map of `width` by `height` cells whose height and color at (`x`, `y`) are
+    /// given by `cell`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is zero.
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        mut cell: impl FnMut(usize, usize) -> (f32, Color),
+    ) -> Self {
+        assert!(width > 0 && height > 0, "height maps cannot be empty");
+        let (heights, colors) = (0..width * height)
+            .map(|index| cell(index % width, index / width))
+            .unzip();
+        Self {
+            width,
+            height,
+            heights,
+            colors,
+        }
+    }
+
+    /// Returns the width of the map in cells.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the map in cells. See [`HeightMap::height_at`] for terrain heights.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the terrain height at (`x`, `y`), wrapping around the edges of the map.
+    pub fn height_at(&self, x: isize, y: isize) -> f32 {
+        self.heights[self.index(x, y)]
+    }
+
+    /// Returns the color at (`x`, `y`), wrapping around the edges of the map.
+    pub fn color_at(&self, x: isize, y: isize) -> Color {
+        self.colors[self.index(x, y)]
+    }
+
+    /// Sets the terrain height and color at (`x`, `y`), wrapping around the edges of the map.
+    pub fn set(&mut self, x: isize, y: isize, height: f32, color: Color) {
+        let index = self.index(x, y);
+        self.heights[index] = height;
+        self.colors[index] = color;
+    }
+
+    fn index(&self, x: isize, y: isize) -> usize {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        y * self.width + x
+    }
+}
+
+/// The position and heading of the viewer of a [`TerrainRenderer`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TerrainView {
+    /// The x position on the map, in cells.
+    pub x: f32,
+    /// The y position on the map, in cells.
+    pub y: f32,
+    /// The altitude of the eye, in the units of the map heights.
+    pub altitude: f32,
+    /// The heading in radians. `0.0` faces along the x axis, and the angle grows clockwise on
+    /// the map.
+    pub angle: f32,
+    /// How far the horizon sits below the middle of the view, as a fraction of its height.
+    /// Negative values raise it, as when looking down.
+    pub pitch: f32,
+}
+
+impl TerrainView {
+    /// Creates a view at (`x`, `y`) and `altitude` facing `angle`, looking straight ahead.
+    pub fn new(x: f32, y: f32, altitude: f32, angle: f32) -> Self {
+        Self {
+            x,
+            y,
+            altitude,
+            angle,
+            pitch: 0.0,
+        }
+    }
+}
+
+/// Renders [`HeightMap`] terrain in perspective with the voxel space technique, drawing every
+/// cell as a colored column from its height down to the ground.
+///
+/// Terrain is drawn front to back, and distant terrain fades into the sky color.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, Color, HeightMap, Rect, TerrainRenderer, TerrainView};
+///
+/// let map = HeightMap::from_fn(64, 64, |x, y| {
+///     let height = ((x as f32 / 5.0).sin() + (y as f32 / 7.0).cos()) * 8.0 + 16.0;
+///     let green = (80.0 + height * 4.0) as u8;
+///     (height, Color { r: 40, g: green, b: 30 })
+/// });
+///
+/// let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// let view = TerrainView::new(32.0, 32.0, 30.0, 0.0);
+/// TerrainRenderer::new()
+///     .draw_distance(48.0)
+///     .draw(&mut canvas, Rect::new(0, 0, 80, 48), &map, &view, 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainRenderer {
+    fov: f32,
+    draw_distance: f32,
+    height_scale: f32,
+    sky: Color,
+}
+
+impl Default for TerrainRenderer {
+    fn default() -> Self {
+        Self {
+            fov: std::f32::consts::FRAC_PI_2,
+            draw_distance: 100.0,
+            height_scale: 1.0,
+            sky: Color {
+                r: 120,
+                g: 170,
+                b: 230,
+            },
+        }
+    }
+}
+
+impl TerrainRenderer {
+    /// Creates a renderer with a 90° field of view and a draw distance of 100 cells.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the horizontal field of view, in radians.
+    pub fn fov(mut self, fov: f32) -> Self {
+        self.fov = fov.clamp(0.1, 3.0);
+        self
+    }
+
+    /// Sets how far terrain is drawn, in cells. Terrain fades into the sky towards this
+    /// distance.
+    pub fn draw_distance(mut self, distance: f32) -> Self {
+        self.draw_distance = distance.max(1.0);
+        self
+    }
+
+    /// Sets how much terrain heights are stretched. At `1.0`, the default, a unit of height is
+    /// as long as the side of a cell.
+    pub fn height_scale(mut self, scale: f32) -> Self {
+        self.height_scale = scale;
+        self
+    }
+
+    /// Sets the color of the sky.
+    pub fn sky(mut self, color: Color) -> Self {
+        self.sky = color;
+        self
+    }
+
+    /// Draws the terrain of `map` seen from `view` into `area` on layer `z`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - The canvas to draw into.
+    /// * `area` - The area of the view in half-block pixel coordinates.
+    /// * `map` - The terrain to draw.
+    /// * `view` - The position and heading of the viewer.
+    /// * `z` - The z-layer to draw on.
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas,
+        area: Rect,
+        map: &HeightMap,
+        view: &TerrainView,
+        z: usize,
+    ) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        for row in 0..area.height {
+            for column in 0..area.width {
+                canvas.set_pixel(area.x + column, area.y + row, z, self.sky);
+            }
+        }
+
+        let (sin, cos) = view.angle.sin_cos();
+        let plane = (self.fov / 2.0).tan();
+        let horizon = area.height as f32 * (0.5 + view.pitch);
+        // Pixels covered by a unit of length one cell away, keeping pixels square.
+        let scale = area.width as f32 / (2.0 * plane) * self.height_scale;
+        // The lowest pixel drawn so far in each column. Nearer terrain hides what lies behind
+        // it, so further terrain only draws above this.
+        let mut lowest = vec![area.height as f32; area.width];
+        let mut distance = 1.0;
+        let mut step = 0.2;
+        while distance < self.draw_distance {
+            // The row of cells at `distance`, from the left to the right edge of the view.
+            let (left_x, left_y) = (
+                view.x + distance * (cos + sin * plane),
+                view.y + distance * (sin - cos * plane),
+            );
+            let (right_x, right_y) = (
+                view.x + distance * (cos - sin * plane),
+                view.y + distance * (sin + cos * plane),
+            );
+            let fade = distance / self.draw_distance;
+            for (column, lowest) in lowest.iter_mut().enumerate() {
+                let t = (column as f32 + 0.5) / area.width as f32;
+                let x = (left_x + (right_x - left_x) * t).floor() as isize;
+                let y = (left_y + (right_y - left_y) * t).floor() as isize;
+                let top = (view.altitude - map.height_at(x, y)) / distance * scale + horizon;
+                if top >= *lowest {
+                    continue;
+                }
+                let color = map.color_at(x, y).lerp(self.sky, fade * fade);
+                for row in top.max(0.0) as usize..*lowest as usize {
+                    canvas.set_pixel(area.x + column, area.y + row, z, color);
+                }
+                *lowest = top.max(0.0);
+            }
+            distance += step;
+            // Far rows cover less of the screen, so they can be sampled more sparsely.
+            step += 0.01;
+        }
+    }
+}
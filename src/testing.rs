@@ -0,0 +1,394 @@
+//! Snapshot testing: comparing what a canvas shows against golden files.
+//!
+//! [`assert_frame_matches!`](crate::assert_frame_matches) composites a canvas headlessly and
+//! compares it with a golden file stored next to the tests. Golden files are plain text, with a
+//! palette and one line of color symbols per half-block row, so they read like a picture and
+//! diff well in version control.
+//!
+//! Golden files are written instead of compared when the `RAEL_UPDATE_GOLDEN` environment
+//! variable is set, which is how new snapshots are created and changed ones accepted:
+//!
+//! ```text
+//! RAEL_UPDATE_GOLDEN=1 cargo test
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Attributes, CellGlyph, Color, CompositedCell, Frame};
+
+/// The environment variable that makes snapshot assertions write their golden files.
+pub const UPDATE_ENV_VAR: &str = "RAEL_UPDATE_GOLDEN";
+
+/// The extension given to golden file paths that have none.
+const EXTENSION: &str = "frame";
+
+/// The number of mismatched pixels listed in a failure message.
+const LISTED_MISMATCHES: usize = 20;
+
+/// The styles of characters with their names in golden files.
+const ATTRIBUTE_NAMES: [(Attributes, &str); 5] = [
+    (Attributes::BOLD, "bold"),
+    (Attributes::DIM, "dim"),
+    (Attributes::ITALIC, "italic"),
+    (Attributes::UNDERLINE, "underline"),
+    (Attributes::REVERSE, "reverse"),
+];
+
+/// Asserts that a canvas shows what a golden file holds.
+///
+/// The path is relative to the directory of the crate being tested, and gets a `.frame`
+/// extension if it has none. See the [`testing`](crate::testing) module for how golden files are
+/// created.
+///
+/// # Panics
+///
+/// Panics with a colored report of the mismatched pixels if the canvas differs from the golden
+/// file, or if the golden file is missing or invalid.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{assert_frame_matches, Canvas, Color};
+///
+/// let mut canvas = Canvas::new(40, 12, Color { r: 0, g: 0, b: 0 });
+/// canvas.set_pixel(3, 4, 0, Color { r: 255, g: 0, b: 0 });
+/// assert_frame_matches!(canvas, "golden/title_screen");
+/// ```
+#[macro_export]
+macro_rules! assert_frame_matches {
+    ($canvas:expr, $name:expr $(,)?) => {
+        $crate::testing::assert_frame_matches(
+            &$crate::Frame::capture(&$canvas),
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($name),
+        )
+    };
+}
+
+/// Asserts that `frame` matches the golden file at `path`, or writes the file when the
+/// [`UPDATE_ENV_VAR`] environment variable is set. This is what
+/// [`assert_frame_matches!`](crate::assert_frame_matches) calls.
+///
+/// # Panics
+///
+/// Panics with a colored report of the mismatched pixels if the frame differs from the golden
+/// file, or if the golden file is missing or invalid.
+#[track_caller]
+pub fn assert_frame_matches(frame: &Frame, path: impl AsRef<Path>) {
+    let mut path = PathBuf::from(path.as_ref());
+    if path.extension().is_none() {
+        path.set_extension(EXTENSION);
+    }
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("could not create the golden file directory");
+        }
+        fs::write(&path, to_golden(frame)).expect("could not write the golden file");
+        return;
+    }
+    let golden = match fs::read_to_string(&path) {
+        Ok(golden) => golden,
+        Err(error) => panic!(
+            "could not read the golden file {}: {error}\nRun the test with {UPDATE_ENV_VAR}=1 to create it.",
+            path.display()
+        ),
+    };
+    let expected = match from_golden(&golden) {
+        Ok(expected) => expected,
+        Err(error) => panic!("invalid golden file {}: {error}", path.display()),
+    };
+    if let Some(report) = mismatch_report(&expected, frame) {
+        panic!(
+            "the frame does not match the golden file {}\n{report}Run the test with {UPDATE_ENV_VAR}=1 to accept the new frame.",
+            path.display()
+        );
+    }
+}
+
+/// Returns the symbol standing for the `index`th color of a golden file palette.
+fn symbol(index: usize) -> char {
+    const SYMBOLS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    match SYMBOLS.get(index) {
+        Some(symbol) => *symbol as char,
+        // Letters of the Latin Extended and following blocks, all printable.
+        None => char::from_u32(0x100 + (index - SYMBOLS.len()) as u32).unwrap_or('?'),
+    }
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// Returns the character `glyph` as written in a golden file, after its position: its color,
+/// its styles after a `+` if it has any, and the character followed by its mark.
+fn glyph_text(glyph: CellGlyph) -> String {
+    let mut text = hex(glyph.color);
+    if !glyph.attributes.is_empty() {
+        let names: Vec<&str> = ATTRIBUTE_NAMES
+            .iter()
+            .filter(|(attribute, _)| glyph.attributes.contains(*attribute))
+            .map(|(_, name)| *name)
+            .collect();
+        let _ = write!(text, " +{}", names.join(","));
+    }
+    text.push(' ');
+    text.push(glyph.ch);
+    text.extend(glyph.mark);
+    text
+}
+
+/// Reads the character of a golden file line written by [`glyph_text`], after its color.
+fn parse_glyph(color: &str, rest: &str) -> Result<CellGlyph, String> {
+    let mut attributes = Attributes::NONE;
+    let mut text = rest;
+    // A lone `+` is the character itself.
+    if let Some((styles, ch)) = rest.strip_prefix('+').and_then(|rest| rest.split_once(' ')) {
+        for name in styles.split(',') {
+            let (attribute, _) = ATTRIBUTE_NAMES
+                .iter()
+                .find(|(_, known)| *known == name)
+                .ok_or_else(|| format!("unknown style {name:?}"))?;
+            attributes = attributes | *attribute;
+        }
+        text = ch;
+    }
+    let mut chars = text.chars();
+    let (Some(ch), mark, None) = (chars.next(), chars.next(), chars.next()) else {
+        return Err(format!("invalid glyph character {text:?}"));
+    };
+    Ok(CellGlyph {
+        mark,
+        attributes,
+        ..CellGlyph::new(ch, parse_hex(color)?)
+    })
+}
+
+fn parse_hex(text: &str) -> Result<Color, String> {
+    let digits = text
+        .strip_prefix('#')
+        .filter(|digits| digits.len() == 6)
+        .ok_or_else(|| format!("expected a #rrggbb color, found {text:?}"))?;
+    let channel = |range| {
+        u8::from_str_radix(&digits[range], 16).map_err(|_| format!("invalid color {text:?}"))
+    };
+    Ok(Color {
+        r: channel(0..2)?,
+        g: channel(2..4)?,
+        b: channel(4..6)?,
+    })
+}
+
+/// Converts a frame to the text of a golden file.
+///
+/// The characters drawn over cells follow the rows of pixels, one per line: the column and
+/// row of the cell, the color, the styles after a `+`, such as `+bold,underline`, if any, and
+/// the character followed by its mark, if any.
+///
+/// # Example
+///
+/// ```
+/// use rael::testing::{from_golden, to_golden};
+/// use rael::{Canvas, Color, Frame};
+///
+/// let mut canvas = Canvas::new(3, 1, Color { r: 0, g: 0, b: 0 });
+/// canvas.set_pixel(1, 1, 0, Color { r: 255, g: 0, b: 0 });
+/// let frame = Frame::capture(&canvas);
+///
+/// let golden = to_golden(&frame);
+/// assert_eq!(golden, "frame 3 1\n0 #000000\n1 #ff0000\n\n000\n010\n");
+/// assert_eq!(from_golden(&golden), Ok(frame));
+/// ```
+pub fn to_golden(frame: &Frame) -> String {
+    let mut palette: Vec<Color> = Vec::new();
+    let mut symbols = HashMap::new();
+    let mut symbol_of = |color: Color| {
+        *symbols
+            .entry((color.r, color.g, color.b))
+            .or_insert_with(|| {
+                palette.push(color);
+                symbol(palette.len() - 1)
+            })
+    };
+    let mut rows = String::new();
+    let mut glyphs = String::new();
+    for y in 0..frame.height() * 2 {
+        for x in 0..frame.width() {
+            rows.push(symbol_of(frame.pixel(x, y).unwrap()));
+        }
+        rows.push('\n');
+    }
+    for row in 0..frame.height() {
+        for column in 0..frame.width() {
+            if let Some(glyph) = frame.styled_glyph(column, row) {
+                let _ = writeln!(glyphs, "{column} {row} {}", glyph_text(glyph));
+            }
+        }
+    }
+
+    let mut golden = format!("frame {} {}\n", frame.width(), frame.height());
+    for (index, color) in palette.iter().enumerate() {
+        let _ = writeln!(golden, "{} {}", symbol(index), hex(*color));
+    }
+    golden.push('\n');
+    golden.push_str(&rows);
+    if !glyphs.is_empty() {
+        golden.push('\n');
+        golden.push_str(&glyphs);
+    }
+    golden
+}
+
+/// Reads a frame from the text of a golden file, as written by [`to_golden`].
+pub fn from_golden(text: &str) -> Result<Frame, String> {
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or_default();
+    let size: Vec<usize> = header
+        .strip_prefix("frame ")
+        .map(|size| size.split(' ').filter_map(|n| n.parse().ok()).collect())
+        .unwrap_or_default();
+    let [width, height] = size[..] else {
+        return Err(format!(
+            "expected a `frame <width> <height>` header, found {header:?}"
+        ));
+    };
+
+    let mut palette = HashMap::new();
+    for line in lines.by_ref().take_while(|line| !line.is_empty()) {
+        let mut parts = line.split(' ');
+        let (Some(key), Some(color)) = (parts.next(), parts.next()) else {
+            return Err(format!("invalid palette entry {line:?}"));
+        };
+        let mut key_chars = key.chars();
+        let (Some(key), None) = (key_chars.next(), key_chars.next()) else {
+            return Err(format!("invalid palette symbol {key:?}"));
+        };
+        palette.insert(key, parse_hex(color)?);
+    }
+
+    let mut pixels = Vec::with_capacity(width * height * 2);
+    for y in 0..height * 2 {
+        let line = lines
+            .next()
+            .ok_or_else(|| format!("expected {} rows of pixels, found {y}", height * 2))?;
+        if line.chars().count() != width {
+            return Err(format!("row {y} should have {width} pixels: {line:?}"));
+        }
+        for key in line.chars() {
+            let color = palette
+                .get(&key)
+                .ok_or_else(|| format!("{key:?} is not in the palette"))?;
+            pixels.push(*color);
+        }
+    }
+    let cells = (0..width * height)
+        .map(|index| {
+            let (column, row) = (index % width, index / width);
+            CompositedCell {
+                top_color: pixels[row * 2 * width + column],
+                bottom_color: pixels[(row * 2 + 1) * width + column],
+            }
+        })
+        .collect();
+
+    let mut glyphs = vec![None; width * height];
+    for line in lines.filter(|line| !line.is_empty()) {
+        let mut parts = line.splitn(4, ' ');
+        let (Some(column), Some(row), Some(color), Some(ch)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("invalid glyph {line:?}"));
+        };
+        let (Ok(column), Ok(row)) = (column.parse::<usize>(), row.parse::<usize>()) else {
+            return Err(format!("invalid glyph position in {line:?}"));
+        };
+        if column >= width || row >= height {
+            return Err(format!("glyph outside the frame: {line:?}"));
+        }
+        let glyph = parse_glyph(color, ch).map_err(|error| format!("{error} in {line:?}"))?;
+        glyphs[row * width + column] = Some(glyph);
+    }
+    Ok(Frame::from_parts(width, height, cells, glyphs))
+}
+
+/// Returns a colored report of how `actual` differs from `expected`, or `None` if they match.
+fn mismatch_report(expected: &Frame, actual: &Frame) -> Option<String> {
+    if (expected.width(), expected.height()) != (actual.width(), actual.height()) {
+        return Some(format!(
+            "expected a {}x{} frame, found {}x{}\n",
+            expected.width(),
+            expected.height(),
+            actual.width(),
+            actual.height()
+        ));
+    }
+    let swatch = |color: Color| {
+        format!(
+            "\u{1b}[48;2;{};{};{}m  \u{1b}[0m {}",
+            color.r,
+            color.g,
+            color.b,
+            hex(color)
+        )
+    };
+    let mut map = String::new();
+    let mut listed = String::new();
+    let mut mismatches = 0;
+    for y in 0..actual.height() * 2 {
+        for x in 0..actual.width() {
+            let (want, got) = (expected.pixel(x, y).unwrap(), actual.pixel(x, y).unwrap());
+            let (want_glyph, got_glyph) = (
+                expected.styled_glyph(x, y / 2),
+                actual.styled_glyph(x, y / 2),
+            );
+            let glyphs_differ = y % 2 == 0 && want_glyph != got_glyph;
+            if want == got && !glyphs_differ {
+                map.push('.');
+                continue;
+            }
+            map.push_str("\u{1b}[1;31mX\u{1b}[0m");
+            mismatches += 1;
+            if mismatches > LISTED_MISMATCHES {
+                continue;
+            }
+            if want != got {
+                let _ = writeln!(
+                    listed,
+                    "  pixel ({x}, {y}): expected {}, found {}",
+                    swatch(want),
+                    swatch(got)
+                );
+            }
+            if glyphs_differ {
+                let describe = |glyph: Option<CellGlyph>| {
+                    glyph.map_or_else(
+                        || "none".to_string(),
+                        |glyph| format!("`{}`", glyph_text(glyph)),
+                    )
+                };
+                let _ = writeln!(
+                    listed,
+                    "  cell ({x}, {}): expected glyph {}, found {}",
+                    y / 2,
+                    describe(want_glyph),
+                    describe(got_glyph)
+                );
+            }
+        }
+        map.push('\n');
+    }
+    if mismatches == 0 {
+        return None;
+    }
+    let total = actual.width() * actual.height() * 2;
+    let mut report = format!("{mismatches} of {total} pixels differ:\n{listed}");
+    if mismatches > LISTED_MISMATCHES {
+        let _ = writeln!(report, "  ...");
+    }
+    let _ = write!(
+        report,
+        "Mismatches, one character per half-block pixel:\n{map}"
+    );
+    Some(report)
+}
@@ -0,0 +1,416 @@
+//! The text plane: real terminal characters placed over or behind the half-block pixels.
+
+use alloc::vec::Vec;
+use core::ops::BitOr;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{Canvas, Color, TextCell, WIDE_CONTINUATION};
+
+/// Styles of the characters of the text plane, combined with `|`, such as bold and underlined
+/// text for the selected entry of a menu. Terminals without a style show the characters plain.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Attributes, Canvas, Color};
+///
+/// let white = Color { r: 255, g: 255, b: 255 };
+/// let mut canvas = Canvas::new(20, 5, Color { r: 0, g: 0, b: 0 });
+/// let selected = Attributes::BOLD | Attributes::UNDERLINE;
+/// canvas.put_str_styled(2, 1, "New game", white, None, selected);
+/// assert!(canvas.render().contains("\x1b[1;4mN\x1b[22;23;24;27m"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attributes(u8);
+
+impl Attributes {
+    /// No style.
+    pub const NONE: Attributes = Attributes(0);
+    /// Bold or bright characters.
+    pub const BOLD: Attributes = Attributes(1);
+    /// Dim characters.
+    pub const DIM: Attributes = Attributes(1 << 1);
+    /// Italic characters.
+    pub const ITALIC: Attributes = Attributes(1 << 2);
+    /// Underlined characters.
+    pub const UNDERLINE: Attributes = Attributes(1 << 3);
+    /// Characters with their foreground and background colors swapped.
+    pub const REVERSE: Attributes = Attributes(1 << 4);
+
+    /// The styles with their SGR parameters, in the order they are sent.
+    const CODES: [(Attributes, u8); 5] = [
+        (Self::BOLD, 1),
+        (Self::DIM, 2),
+        (Self::ITALIC, 3),
+        (Self::UNDERLINE, 4),
+        (Self::REVERSE, 7),
+    ];
+
+    /// Returns whether every style of `other` is in these attributes.
+    pub fn contains(self, other: Attributes) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the styles as bits, one per style.
+    pub(crate) fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the styles of `bits`, as returned by [`Attributes::bits`], or `None` if a bit
+    /// is not a style.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_bits(bits: u8) -> Option<Attributes> {
+        let all = Self::CODES
+            .iter()
+            .fold(0, |all, (attribute, _)| all | attribute.0);
+        (bits & !all == 0).then_some(Attributes(bits))
+    }
+
+    /// Returns whether there is no style.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the SGR parameters turning the styles on.
+    pub(crate) fn codes(self) -> impl Iterator<Item = u8> {
+        Self::CODES
+            .into_iter()
+            .filter(move |&(attribute, _)| self.contains(attribute))
+            .map(|(_, code)| code)
+    }
+}
+
+impl BitOr for Attributes {
+    type Output = Attributes;
+
+    fn bitor(self, other: Attributes) -> Attributes {
+        Attributes(self.0 | other.0)
+    }
+}
+
+/// Where the lines of a block of text go within its width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Align {
+    /// Lines start at the left edge.
+    #[default]
+    Left,
+    /// Lines are centered, leaning left when they cannot be centered exactly.
+    Center,
+    /// Lines end at the right edge.
+    Right,
+}
+
+/// How a block of text is laid out by [`Canvas::put_str_layout`] and `Canvas::draw_text_layout`:
+/// the width it is wrapped to, if any, and the alignment of its lines.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Align, Canvas, Color, TextLayout};
+///
+/// let white = Color { r: 255, g: 255, b: 255 };
+/// let mut canvas = Canvas::new(20, 5, Color { r: 0, g: 0, b: 0 });
+/// let layout = TextLayout::new().max_width(10).align(Align::Center);
+/// canvas.put_str_layout(0, 0, "Press any key to start", &layout, white, None);
+/// // "Press any", "key to", "start", each centered in 10 columns.
+/// assert!(canvas.render().contains("\x1b[2;3H\x1b[48;2;0;0;0m\x1b[38;2;255;255;255mk"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextLayout {
+    max_width: Option<usize>,
+    align: Align,
+}
+
+impl TextLayout {
+    /// Creates a layout of lines aligned left and broken only at `'\n'`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the width lines are wrapped to, in terminal columns for text plane text and in
+    /// pixels for pixel text. Lines are aligned within it.
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Sets where lines go within the width of the block: the width wrapped to, or else the
+    /// width of the longest line. Defaults to [`Align::Left`].
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Returns the lines of `text`, wrapped to the width of the layout, if any, with `width`
+    /// giving the width of a character and `spacing` the space after every character but the
+    /// last of a line.
+    pub(crate) fn lines<'a>(
+        &self,
+        text: &'a str,
+        width: impl Fn(char) -> usize,
+        spacing: usize,
+    ) -> Vec<&'a str> {
+        match self.max_width {
+            Some(max_width) => wrap(text, max_width + spacing, width),
+            None => text.split('\n').collect(),
+        }
+    }
+
+    /// Returns how far a line `line_width` wide is moved right, in a block whose longest line
+    /// is `widest` wide.
+    pub(crate) fn indent(&self, line_width: usize, widest: usize) -> usize {
+        let width = self.max_width.unwrap_or(widest);
+        match self.align {
+            Align::Left => 0,
+            Align::Center => width.saturating_sub(line_width) / 2,
+            Align::Right => width.saturating_sub(line_width),
+        }
+    }
+}
+
+/// Returns the width of `text` and its number of lines, `'\n'` starting a new line, in terminal
+/// cells as placed by [`Canvas::put_str`], with wide characters taking two cells.
+///
+/// # Example
+///
+/// ```
+/// use rael::measure_text;
+///
+/// assert_eq!(measure_text("Score\n1 200 000"), (9, 2));
+/// assert_eq!(measure_text("日本"), (4, 1));
+/// ```
+pub fn measure_text(text: &str) -> (usize, usize) {
+    let lines = text.split('\n');
+    let width = lines.clone().map(text_width).max().unwrap_or(0);
+    (width, lines.count())
+}
+
+/// Returns the lines of `text` wrapped to `max_width` terminal cells, as placed by
+/// [`Canvas::put_str`]. Lines are broken at `'\n'` and between words, with the whitespace
+/// between the words dropped, and words longer than a line are broken where the line ends.
+pub fn wrap_text(text: &str, max_width: usize) -> Vec<&str> {
+    wrap(text, max_width, cell_width)
+}
+
+/// Returns the number of terminal cells `ch` takes on the text plane.
+pub(crate) fn cell_width(ch: char) -> usize {
+    ch.width().unwrap_or(1)
+}
+
+/// Returns the number of terminal cells `text` takes on the text plane.
+pub(crate) fn text_width(text: &str) -> usize {
+    text.chars().map(cell_width).sum()
+}
+
+/// Returns the lines of `text` wrapped to `max_width`, with `width` giving the width of every
+/// character, like [`wrap_text`].
+pub(crate) fn wrap(text: &str, max_width: usize, width: impl Fn(char) -> usize) -> Vec<&str> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        // The start, end and width of the line so far.
+        let mut line: Option<(usize, usize, usize)> = None;
+        for word in paragraph.split_whitespace() {
+            // Words are slices of the paragraph.
+            let start = word.as_ptr() as usize - paragraph.as_ptr() as usize;
+            let end = start + word.len();
+            let word_width: usize = word.chars().map(&width).sum();
+            if let Some((line_start, line_end, line_width)) = line {
+                let gap: usize = paragraph[line_end..start].chars().map(&width).sum();
+                if line_width + gap + word_width <= max_width {
+                    line = Some((line_start, end, line_width + gap + word_width));
+                    continue;
+                }
+                lines.push(&paragraph[line_start..line_end]);
+            }
+            // Break words longer than a line where the line ends, keeping at least one
+            // character on every line.
+            let (mut piece_start, mut piece_width) = (start, 0);
+            for (index, ch) in word.char_indices() {
+                let index = start + index;
+                let ch_width = width(ch);
+                if piece_width + ch_width > max_width && index > piece_start {
+                    lines.push(&paragraph[piece_start..index]);
+                    (piece_start, piece_width) = (index, 0);
+                }
+                piece_width += ch_width;
+            }
+            line = Some((piece_start, end, piece_width));
+        }
+        match line {
+            Some((start, end, _)) => lines.push(&paragraph[start..end]),
+            None => lines.push(""),
+        }
+    }
+    lines
+}
+
+impl Canvas {
+    /// Places `text` on the text plane from the terminal cell at (`column`, `row`) rightwards,
+    /// drawn in `foreground` over `background`, or over the colors of the pixels behind with
+    /// `None`. Useful for crisp interface text, which half-blocks cannot show.
+    ///
+    /// Characters take the cells terminals give them: wide characters, such as CJK ideographs,
+    /// cover two cells, and zero-width characters, such as combining accents, join the character
+    /// before them, which keeps one of them.
+    ///
+    /// The text plane is shown over every z-layer and glyph, and is sent by the differential
+    /// renderer like the pixels. Characters stay until they are overwritten, or removed by
+    /// [`Canvas::clear_text`] or [`Canvas::clear`], and a wide character partly overwritten
+    /// leaves a space in its other cell. Characters past the right edge of the canvas, or in cells
+    /// starting outside the clip rectangle, are skipped, and control characters are placed as
+    /// spaces.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let navy = Color { r: 0, g: 0, b: 80 };
+    /// let mut canvas = Canvas::new(20, 5, Color { r: 0, g: 0, b: 0 });
+    /// canvas.fill_rect(0, 0, 20, 10, 0, Color { r: 0, g: 120, b: 0 });
+    /// canvas.put_str(2, 1, "HP 25/100", white, Some(navy));
+    /// assert!(canvas.render().contains("\x1b[48;2;0;0;80m\x1b[38;2;255;255;255mH"));
+    /// ```
+    pub fn put_str(
+        &mut self,
+        column: usize,
+        row: usize,
+        text: &str,
+        foreground: Color,
+        background: Option<Color>,
+    ) {
+        self.put_str_styled(column, row, text, foreground, background, Attributes::NONE);
+    }
+
+    /// Places `text` on the text plane like [`Canvas::put_str`], in the styles of `attributes`.
+    pub fn put_str_styled(
+        &mut self,
+        column: usize,
+        row: usize,
+        text: &str,
+        foreground: Color,
+        background: Option<Color>,
+        attributes: Attributes,
+    ) {
+        let style = TextCell {
+            ch: ' ',
+            mark: None,
+            foreground,
+            background,
+            attributes,
+            behind: false,
+        };
+        self.place_text(column, row, text, style);
+    }
+
+    /// Places `text` on the text plane like [`Canvas::put_str`], but behind the graphics: every
+    /// character shows only while its cell has no glyph and no pixel drawn on any layer, such
+    /// as labels of a map that sprites walk over.
+    pub fn put_str_behind(
+        &mut self,
+        column: usize,
+        row: usize,
+        text: &str,
+        foreground: Color,
+        background: Option<Color>,
+    ) {
+        let style = TextCell {
+            ch: ' ',
+            mark: None,
+            foreground,
+            background,
+            attributes: Attributes::NONE,
+            behind: true,
+        };
+        self.place_text(column, row, text, style);
+    }
+
+    /// Places `text` on the text plane like [`Canvas::put_str`] as a block whose top-left corner
+    /// is the terminal cell at (`column`, `row`), with its lines, started by `'\n'`, wrapped and
+    /// aligned by `layout`.
+    pub fn put_str_layout(
+        &mut self,
+        column: usize,
+        row: usize,
+        text: &str,
+        layout: &TextLayout,
+        foreground: Color,
+        background: Option<Color>,
+    ) {
+        let lines = layout.lines(text, cell_width, 0);
+        let widest = lines.iter().map(|line| text_width(line)).max().unwrap_or(0);
+        for (index, line) in lines.into_iter().enumerate() {
+            let indent = layout.indent(text_width(line), widest);
+            self.put_str(column + indent, row + index, line, foreground, background);
+        }
+    }
+
+    /// Removes every character of the text plane.
+    pub fn clear_text(&mut self) {
+        self.text.fill(None);
+    }
+
+    /// Places the characters of `text` from (`column`, `row`) rightwards, in `style`.
+    fn place_text(&mut self, column: usize, row: usize, text: &str, style: TextCell) {
+        if row >= self.height {
+            return;
+        }
+        let factor = self.supersampling;
+        let is_clipped = |canvas: &Canvas, column: usize| {
+            column >= canvas.width || canvas.is_clipped(column * factor, row * 2 * factor)
+        };
+        let mut column = column;
+        // The cell of the last character placed, which zero-width characters join.
+        let mut last = None;
+        for ch in text.chars() {
+            let ch = if ch.is_control() { ' ' } else { ch };
+            let width = cell_width(ch);
+            if width == 0 {
+                if let Some(text) = last.and_then(|index: usize| self.text[index].as_mut()) {
+                    text.mark.get_or_insert(ch);
+                }
+                continue;
+            }
+            if column >= self.width {
+                break;
+            }
+            last = None;
+            if !(column..column + width).any(|column| is_clipped(self, column)) {
+                self.set_text_cell(column, row, TextCell { ch, ..style });
+                if width == 2 {
+                    let continuation = TextCell {
+                        ch: WIDE_CONTINUATION,
+                        ..style
+                    };
+                    self.set_text_cell(column + 1, row, continuation);
+                }
+                last = Some(row * self.width + column);
+            }
+            column += width;
+        }
+    }
+
+    /// Sets the text plane cell at (`column`, `row`) to `text`, turning the other half of any
+    /// wide character it overwrites into a space.
+    fn set_text_cell(&mut self, column: usize, row: usize, text: TextCell) {
+        let index = row * self.width + column;
+        let is_continuation =
+            |cell: Option<TextCell>| cell.is_some_and(|cell| cell.ch == WIDE_CONTINUATION);
+        let blank = |cell: &mut Option<TextCell>| {
+            if let Some(cell) = cell {
+                cell.ch = ' ';
+                cell.mark = None;
+            }
+        };
+        if is_continuation(self.text[index]) {
+            blank(&mut self.text[index - 1]);
+        }
+        if column + 1 < self.width && is_continuation(self.text[index + 1]) {
+            blank(&mut self.text[index + 1]);
+        }
+        self.text[index] = Some(text);
+    }
+}
@@ -0,0 +1,673 @@
+//! Loading of maps made with the [Tiled](https://www.mapeditor.org) editor.
+//!
+//! Both the XML (`.tmx`, `.tsx`) and JSON (`.tmj`, `.tsj`, `.json`) formats are supported,
+//! including external tilesets, group layers and object layers. Tile layer data may be stored as
+//! CSV, XML elements or uncompressed base64. Infinite maps and compressed layer data are not
+//! supported.
+//!
+//! Rael does not decode images, so turning tileset images into sprites is left to the caller
+//! through [`TiledMap::load_tileset`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::{Sprite, TileMap, Tileset};
+
+/// The bits of a global tile ID that Tiled uses to flag flipped and rotated tiles.
+const FLIP_FLAGS: u32 = 0xF000_0000;
+
+/// An error raised while loading a Tiled map.
+#[derive(Debug)]
+pub enum TiledError {
+    /// A map, tileset or image file could not be read.
+    Io(io::Error),
+    /// A file is not a valid Tiled document.
+    Parse(String),
+    /// The document uses a Tiled feature Rael does not support.
+    Unsupported(String),
+}
+
+impl fmt::Display for TiledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TiledError::Io(error) => write!(f, "failed to read Tiled file: {error}"),
+            TiledError::Parse(message) => write!(f, "invalid Tiled file: {message}"),
+            TiledError::Unsupported(message) => write!(f, "unsupported Tiled feature: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TiledError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TiledError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TiledError {
+    fn from(error: io::Error) -> Self {
+        TiledError::Io(error)
+    }
+}
+
+impl From<roxmltree::Error> for TiledError {
+    fn from(error: roxmltree::Error) -> Self {
+        TiledError::Parse(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for TiledError {
+    fn from(error: serde_json::Error) -> Self {
+        TiledError::Parse(error.to_string())
+    }
+}
+
+/// A map made with the Tiled editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledMap {
+    /// The width of the map in tiles.
+    pub width: usize,
+    /// The height of the map in tiles.
+    pub height: usize,
+    /// The width of a tile in the editor, in image pixels.
+    pub tile_width: usize,
+    /// The height of a tile in the editor, in image pixels.
+    pub tile_height: usize,
+    /// The tilesets used by the map, ordered by their first global tile ID.
+    pub tilesets: Vec<TiledTileset>,
+    /// The tile and object layers of the map, from bottom to top. Group layers are flattened.
+    pub layers: Vec<TiledLayer>,
+    /// The custom properties of the map, with every value converted to a string.
+    pub properties: HashMap<String, String>,
+}
+
+/// A tileset referenced by a [`TiledMap`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledTileset {
+    /// The global tile ID of the first tile of this tileset.
+    pub first_gid: u32,
+    /// The name of the tileset.
+    pub name: String,
+    /// The width of a tile, in image pixels.
+    pub tile_width: usize,
+    /// The height of a tile, in image pixels.
+    pub tile_height: usize,
+    /// The number of tiles in the tileset.
+    pub tile_count: u32,
+    /// The number of tile columns in the tileset image.
+    pub columns: u32,
+    /// The margin around the tiles in the tileset image, in image pixels.
+    pub margin: usize,
+    /// The spacing between the tiles in the tileset image, in image pixels.
+    pub spacing: usize,
+    /// The path of the tileset image, resolved against the file that referenced it.
+    pub image: Option<PathBuf>,
+    /// The width of the tileset image in image pixels, or `0` if unknown.
+    pub image_width: usize,
+    /// The height of the tileset image in image pixels, or `0` if unknown.
+    pub image_height: usize,
+}
+
+/// A layer of a [`TiledMap`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TiledLayer {
+    /// A layer of tiles.
+    Tiles(TileLayer),
+    /// A layer of free-form objects.
+    Objects(ObjectLayer),
+}
+
+/// A layer of tiles, stored as global tile IDs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileLayer {
+    /// The name of the layer.
+    pub name: String,
+    /// The width of the layer in tiles.
+    pub width: usize,
+    /// The height of the layer in tiles.
+    pub height: usize,
+    /// Whether the layer is visible in the editor.
+    pub visible: bool,
+    /// The row-major global tile IDs of the layer, with the flip flags cleared.
+    /// `0` marks an empty cell.
+    pub gids: Vec<u32>,
+}
+
+/// A layer of objects such as spawn points, triggers and collision shapes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectLayer {
+    /// The name of the layer.
+    pub name: String,
+    /// Whether the layer is visible in the editor.
+    pub visible: bool,
+    /// The objects of the layer.
+    pub objects: Vec<TiledObject>,
+}
+
+/// An object placed on an [`ObjectLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledObject {
+    /// The unique ID of the object.
+    pub id: u32,
+    /// The name of the object.
+    pub name: String,
+    /// The class (formerly called type) of the object.
+    pub class: String,
+    /// The x coordinate of the object, in image pixels.
+    pub x: f32,
+    /// The y coordinate of the object, in image pixels.
+    pub y: f32,
+    /// The width of the object, in image pixels.
+    pub width: f32,
+    /// The height of the object, in image pixels.
+    pub height: f32,
+    /// The global tile ID of tile objects, with the flip flags cleared.
+    pub gid: Option<u32>,
+    /// The custom properties of the object, with every value converted to a string.
+    pub properties: HashMap<String, String>,
+}
+
+impl TiledMap {
+    /// Loads a map from a `.tmx`, `.tmj` or `.json` file, along with its external tilesets.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TiledError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        match extension(path).as_str() {
+            "tmx" => Self::from_tmx(&text, base_dir),
+            "tmj" | "json" => Self::from_json(&text, base_dir),
+            other => Err(TiledError::Unsupported(format!(
+                "map file extension `{other}`"
+            ))),
+        }
+    }
+
+    /// Parses a map in the TMX (XML) format. External tilesets and images are resolved against
+    /// `base_dir`.
+    pub fn from_tmx(text: &str, base_dir: &Path) -> Result<Self, TiledError> {
+        let document = roxmltree::Document::parse(text)?;
+        let map = document.root_element();
+        if !map.has_tag_name("map") {
+            return Err(TiledError::Parse("the root element is not <map>".into()));
+        }
+        if opt_attr(map, "infinite")?.unwrap_or(0u8) != 0 {
+            return Err(TiledError::Unsupported("infinite maps".into()));
+        }
+
+        let mut tilesets = Vec::new();
+        for node in map.children().filter(|node| node.has_tag_name("tileset")) {
+            let first_gid = attr(node, "firstgid")?;
+            tilesets.push(match node.attribute("source") {
+                Some(source) => load_external_tileset(first_gid, &base_dir.join(source))?,
+                None => xml_tileset(node, first_gid, base_dir)?,
+            });
+        }
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut layers = Vec::new();
+        xml_layers(map, &mut layers)?;
+
+        Ok(Self {
+            width: attr(map, "width")?,
+            height: attr(map, "height")?,
+            tile_width: attr(map, "tilewidth")?,
+            tile_height: attr(map, "tileheight")?,
+            tilesets,
+            layers,
+            properties: xml_properties(map),
+        })
+    }
+
+    /// Parses a map in the JSON format. External tilesets and images are resolved against
+    /// `base_dir`.
+    pub fn from_json(text: &str, base_dir: &Path) -> Result<Self, TiledError> {
+        let map: Value = serde_json::from_str(text)?;
+        if map.get("infinite").and_then(Value::as_bool) == Some(true) {
+            return Err(TiledError::Unsupported("infinite maps".into()));
+        }
+
+        let mut tilesets = Vec::new();
+        for value in json_array(&map, "tilesets")? {
+            let first_gid = json_number(value, "firstgid")? as u32;
+            tilesets.push(match value.get("source").and_then(Value::as_str) {
+                Some(source) => load_external_tileset(first_gid, &base_dir.join(source))?,
+                None => json_tileset(value, first_gid, base_dir)?,
+            });
+        }
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut layers = Vec::new();
+        json_layers(json_array(&map, "layers")?, &mut layers)?;
+
+        Ok(Self {
+            width: json_number(&map, "width")? as usize,
+            height: json_number(&map, "height")? as usize,
+            tile_width: json_number(&map, "tilewidth")? as usize,
+            tile_height: json_number(&map, "tileheight")? as usize,
+            tilesets,
+            layers,
+            properties: json_properties(&map),
+        })
+    }
+
+    /// Returns the first tile layer called `name`.
+    pub fn tile_layer(&self, name: &str) -> Option<&TileLayer> {
+        self.layers.iter().find_map(|layer| match layer {
+            TiledLayer::Tiles(layer) if layer.name == name => Some(layer),
+            _ => None,
+        })
+    }
+
+    /// Returns the first object layer called `name`.
+    pub fn object_layer(&self, name: &str) -> Option<&ObjectLayer> {
+        self.layers.iter().find_map(|layer| match layer {
+            TiledLayer::Objects(layer) if layer.name == name => Some(layer),
+            _ => None,
+        })
+    }
+
+    /// Builds a single [`Tileset`] holding the tiles of every tileset of the map, so the tile
+    /// with global ID `gid` ends up at index `gid - 1`.
+    ///
+    /// `load_image` is called once per tileset and returns its image as a sprite sheet, which is
+    /// then cut into tiles using the tile size, margin and spacing of the tileset. It decides
+    /// how image pixels map to half-block pixels, for example by downscaling.
+    /// All tiles must end up the same size.
+    pub fn load_tileset(
+        &self,
+        mut load_image: impl FnMut(&TiledTileset) -> Result<Sprite, TiledError>,
+    ) -> Result<Tileset, TiledError> {
+        let mut result: Option<Tileset> = None;
+        for tiled in &self.tilesets {
+            let sheet = load_image(tiled)?;
+            // The sheet may have been scaled from the original image, so scale the tile geometry
+            // by the same factor.
+            let scale_x = |length: usize| match tiled.image_width {
+                0 => length,
+                image_width => length * sheet.width() / image_width,
+            };
+            let scale_y = |length: usize| match tiled.image_height {
+                0 => length,
+                image_height => length * sheet.height() / image_height,
+            };
+            let (tile_width, tile_height) = (scale_x(tiled.tile_width), scale_y(tiled.tile_height));
+
+            let tileset = result.get_or_insert_with(|| Tileset::new(tile_width, tile_height));
+            if tileset.tile_width() != tile_width || tileset.tile_height() != tile_height {
+                return Err(TiledError::Unsupported(
+                    "tilesets with different tile sizes".into(),
+                ));
+            }
+            // Keep gaps between tilesets so tile indices still follow global tile IDs.
+            while tileset.len() + 1 < tiled.first_gid as usize {
+                tileset.push(Sprite::new(tile_width, tile_height));
+            }
+
+            let columns = tiled.columns.max(1) as usize;
+            for tile in 0..tiled.tile_count as usize {
+                let x = tiled.margin + (tile % columns) * (tiled.tile_width + tiled.spacing);
+                let y = tiled.margin + (tile / columns) * (tiled.tile_height + tiled.spacing);
+                tileset.push(sheet.sub_sprite(scale_x(x), scale_y(y), tile_width, tile_height));
+            }
+        }
+        Ok(result.unwrap_or_else(|| Tileset::new(self.tile_width, self.tile_height)))
+    }
+
+    /// Converts a tile layer into a [`TileMap`] drawn with `tileset`, mapping global tile ID
+    /// `gid` to tile index `gid - 1` as produced by [`TiledMap::load_tileset`].
+    pub fn to_tile_map(&self, layer: &TileLayer, tileset: Tileset) -> TileMap {
+        let mut map = TileMap::new(layer.width, layer.height, tileset);
+        for (index, gid) in layer.gids.iter().enumerate() {
+            if *gid != 0 {
+                map.set(
+                    index % layer.width,
+                    index / layer.width,
+                    Some(*gid as usize - 1),
+                );
+            }
+        }
+        map
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn load_external_tileset(first_gid: u32, path: &Path) -> Result<TiledTileset, TiledError> {
+    let text = fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    match extension(path).as_str() {
+        "tsx" => {
+            let document = roxmltree::Document::parse(&text)?;
+            xml_tileset(document.root_element(), first_gid, base_dir)
+        }
+        "tsj" | "json" => json_tileset(&serde_json::from_str(&text)?, first_gid, base_dir),
+        other => Err(TiledError::Unsupported(format!(
+            "tileset file extension `{other}`"
+        ))),
+    }
+}
+
+/// Decodes standard base64, ignoring whitespace.
+fn decode_base64(text: &str) -> Result<Vec<u8>, TiledError> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for ch in text.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+        let value = match ch {
+            b'A'..=b'Z' => ch - b'A',
+            b'a'..=b'z' => ch - b'a' + 26,
+            b'0'..=b'9' => ch - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return Err(TiledError::Parse("invalid base64 layer data".into())),
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decodes base64 layer data into global tile IDs with the flip flags cleared.
+fn decode_gids(text: &str, compression: &str) -> Result<Vec<u32>, TiledError> {
+    if !compression.is_empty() {
+        return Err(TiledError::Unsupported(format!(
+            "`{compression}` compressed layer data"
+        )));
+    }
+    Ok(decode_base64(text)?
+        .chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) & !FLIP_FLAGS)
+        .collect())
+}
+
+fn attr<T: FromStr>(node: roxmltree::Node, name: &str) -> Result<T, TiledError> {
+    opt_attr(node, name)?.ok_or_else(|| {
+        TiledError::Parse(format!(
+            "<{}> is missing the `{name}` attribute",
+            node.tag_name().name()
+        ))
+    })
+}
+
+fn opt_attr<T: FromStr>(node: roxmltree::Node, name: &str) -> Result<Option<T>, TiledError> {
+    match node.attribute(name) {
+        None => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(|_| {
+            TiledError::Parse(format!(
+                "invalid `{name}` attribute `{value}` on <{}>",
+                node.tag_name().name()
+            ))
+        }),
+    }
+}
+
+fn xml_properties(node: roxmltree::Node) -> HashMap<String, String> {
+    node.children()
+        .filter(|child| child.has_tag_name("properties"))
+        .flat_map(|properties| properties.children())
+        .filter(|property| property.has_tag_name("property"))
+        .filter_map(|property| {
+            let name = property.attribute("name")?;
+            let value = property
+                .attribute("value")
+                .or_else(|| property.text())
+                .unwrap_or_default();
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn xml_tileset(
+    node: roxmltree::Node,
+    first_gid: u32,
+    base_dir: &Path,
+) -> Result<TiledTileset, TiledError> {
+    let image = node.children().find(|child| child.has_tag_name("image"));
+    Ok(TiledTileset {
+        first_gid,
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        tile_width: attr(node, "tilewidth")?,
+        tile_height: attr(node, "tileheight")?,
+        tile_count: opt_attr(node, "tilecount")?.unwrap_or(0),
+        columns: opt_attr(node, "columns")?.unwrap_or(0),
+        margin: opt_attr(node, "margin")?.unwrap_or(0),
+        spacing: opt_attr(node, "spacing")?.unwrap_or(0),
+        image: image
+            .and_then(|image| image.attribute("source"))
+            .map(|source| base_dir.join(source)),
+        image_width: match image {
+            Some(image) => opt_attr(image, "width")?.unwrap_or(0),
+            None => 0,
+        },
+        image_height: match image {
+            Some(image) => opt_attr(image, "height")?.unwrap_or(0),
+            None => 0,
+        },
+    })
+}
+
+fn xml_layers(parent: roxmltree::Node, layers: &mut Vec<TiledLayer>) -> Result<(), TiledError> {
+    for node in parent.children().filter(|node| node.is_element()) {
+        let name = node.attribute("name").unwrap_or_default().to_string();
+        let visible = opt_attr(node, "visible")?.unwrap_or(1u8) != 0;
+        match node.tag_name().name() {
+            "layer" => {
+                let data = node
+                    .children()
+                    .find(|child| child.has_tag_name("data"))
+                    .ok_or_else(|| TiledError::Parse(format!("layer `{name}` has no <data>")))?;
+                let text = data.text().unwrap_or_default();
+                let gids = match data.attribute("encoding") {
+                    Some("csv") => text
+                        .split(',')
+                        .map(|gid| gid.trim().parse::<u32>().map(|gid| gid & !FLIP_FLAGS))
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| TiledError::Parse(format!("invalid CSV in layer `{name}`")))?,
+                    Some("base64") => {
+                        decode_gids(text, data.attribute("compression").unwrap_or_default())?
+                    }
+                    Some(other) => {
+                        return Err(TiledError::Unsupported(format!("`{other}` layer encoding")))
+                    }
+                    None => data
+                        .children()
+                        .filter(|child| child.has_tag_name("tile"))
+                        .map(|tile| Ok(opt_attr::<u32>(tile, "gid")?.unwrap_or(0) & !FLIP_FLAGS))
+                        .collect::<Result<_, TiledError>>()?,
+                };
+                layers.push(TiledLayer::Tiles(TileLayer {
+                    name,
+                    width: attr(node, "width")?,
+                    height: attr(node, "height")?,
+                    visible,
+                    gids,
+                }));
+            }
+            "objectgroup" => {
+                let mut objects = Vec::new();
+                for object in node.children().filter(|child| child.has_tag_name("object")) {
+                    objects.push(TiledObject {
+                        id: opt_attr(object, "id")?.unwrap_or(0),
+                        name: object.attribute("name").unwrap_or_default().to_string(),
+                        class: object
+                            .attribute("class")
+                            .or_else(|| object.attribute("type"))
+                            .unwrap_or_default()
+                            .to_string(),
+                        x: opt_attr(object, "x")?.unwrap_or(0.0),
+                        y: opt_attr(object, "y")?.unwrap_or(0.0),
+                        width: opt_attr(object, "width")?.unwrap_or(0.0),
+                        height: opt_attr(object, "height")?.unwrap_or(0.0),
+                        gid: opt_attr::<u32>(object, "gid")?.map(|gid| gid & !FLIP_FLAGS),
+                        properties: xml_properties(object),
+                    });
+                }
+                layers.push(TiledLayer::Objects(ObjectLayer {
+                    name,
+                    visible,
+                    objects,
+                }));
+            }
+            "group" => xml_layers(node, layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn json_number(value: &Value, key: &str) -> Result<f64, TiledError> {
+    value
+        .get(key)
+        .and_then(Value::as_f64)
+        .ok_or_else(|| TiledError::Parse(format!("missing number `{key}`")))
+}
+
+fn json_string(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn json_array<'a>(value: &'a Value, key: &str) -> Result<&'a Vec<Value>, TiledError> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .ok_or_else(|| TiledError::Parse(format!("missing array `{key}`")))
+}
+
+fn json_properties(value: &Value) -> HashMap<String, String> {
+    let Some(properties) = value.get("properties").and_then(Value::as_array) else {
+        return HashMap::new();
+    };
+    properties
+        .iter()
+        .filter_map(|property| {
+            let name = property.get("name")?.as_str()?.to_string();
+            let value = match property.get("value")? {
+                Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            Some((name, value))
+        })
+        .collect()
+}
+
+fn json_tileset(
+    value: &Value,
+    first_gid: u32,
+    base_dir: &Path,
+) -> Result<TiledTileset, TiledError> {
+    let optional = |key| value.get(key).and_then(Value::as_u64).unwrap_or(0);
+    Ok(TiledTileset {
+        first_gid,
+        name: json_string(value, "name"),
+        tile_width: json_number(value, "tilewidth")? as usize,
+        tile_height: json_number(value, "tileheight")? as usize,
+        tile_count: optional("tilecount") as u32,
+        columns: optional("columns") as u32,
+        margin: optional("margin") as usize,
+        spacing: optional("spacing") as usize,
+        image: value
+            .get("image")
+            .and_then(Value::as_str)
+            .map(|image| base_dir.join(image)),
+        image_width: optional("imagewidth") as usize,
+        image_height: optional("imageheight") as usize,
+    })
+}
+
+fn json_layers(values: &[Value], layers: &mut Vec<TiledLayer>) -> Result<(), TiledError> {
+    for value in values {
+        let name = json_string(value, "name");
+        let visible = value
+            .get("visible")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        match value
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+        {
+            "tilelayer" => {
+                let gids = match value.get("data") {
+                    Some(Value::Array(gids)) => gids
+                        .iter()
+                        .map(|gid| gid.as_u64().map(|gid| gid as u32 & !FLIP_FLAGS))
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| {
+                            TiledError::Parse(format!("invalid tile data in layer `{name}`"))
+                        })?,
+                    Some(Value::String(text)) => {
+                        decode_gids(text, &json_string(value, "compression"))?
+                    }
+                    _ => {
+                        return Err(TiledError::Parse(format!(
+                            "layer `{name}` has no tile data"
+                        )))
+                    }
+                };
+                layers.push(TiledLayer::Tiles(TileLayer {
+                    name,
+                    width: json_number(value, "width")? as usize,
+                    height: json_number(value, "height")? as usize,
+                    visible,
+                    gids,
+                }));
+            }
+            "objectgroup" => {
+                let objects = json_array(value, "objects")?
+                    .iter()
+                    .map(|object| {
+                        let number = |key| object.get(key).and_then(Value::as_f64).unwrap_or(0.0);
+                        let class = match json_string(object, "class") {
+                            class if class.is_empty() => json_string(object, "type"),
+                            class => class,
+                        };
+                        TiledObject {
+                            id: number("id") as u32,
+                            name: json_string(object, "name"),
+                            class,
+                            x: number("x") as f32,
+                            y: number("y") as f32,
+                            width: number("width") as f32,
+                            height: number("height") as f32,
+                            gid: object
+                                .get("gid")
+                                .and_then(Value::as_u64)
+                                .map(|gid| gid as u32 & !FLIP_FLAGS),
+                            properties: json_properties(object),
+                        }
+                    })
+                    .collect();
+                layers.push(TiledLayer::Objects(ObjectLayer {
+                    name,
+                    visible,
+                    objects,
+                }));
+            }
+            "group" => json_layers(json_array(value, "layers")?, layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
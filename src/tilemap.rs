@@ -0,0 +1,211 @@
+//! Tile maps: grids of tile indices drawn with the sprites of a [`Tileset`].
+
+use crate::{Camera, Canvas, Sprite};
+
+/// A set of equally sized tile sprites, addressed by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tileset {
+    tile_width: usize,
+    tile_height: usize,
+    tiles: Vec<Sprite>,
+}
+
+impl Tileset {
+    /// Creates an empty tileset for tiles of `tile_width` by `tile_height` pixels.
+    pub fn new(tile_width: usize, tile_height: usize) -> Self {
+        Self {
+            tile_width,
+            tile_height,
+            tiles: Vec::new(),
+        }
+    }
+
+    /// Cuts a sprite sheet into tiles, left to right and top to bottom.
+    /// Partial tiles at the right and bottom edges of the sheet are ignored.
+    pub fn from_sheet(sheet: &Sprite, tile_width: usize, tile_height: usize) -> Self {
+        let mut tileset = Self::new(tile_width, tile_height);
+        if tile_width == 0 || tile_height == 0 {
+            return tileset;
+        }
+        for row in 0..sheet.height() / tile_height {
+            for column in 0..sheet.width() / tile_width {
+                tileset.tiles.push(sheet.sub_sprite(
+                    column * tile_width,
+                    row * tile_height,
+                    tile_width,
+                    tile_height,
+                ));
+            }
+        }
+        tileset
+    }
+
+    /// Adds a tile and returns its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sprite does not have the tile size of this tileset.
+    pub fn push(&mut self, tile: Sprite) -> usize {
+        assert!(
+            tile.width() == self.tile_width && tile.height() == self.tile_height,
+            "tiles of this tileset must be {}x{} pixels",
+            self.tile_width,
+            self.tile_height
+        );
+        self.tiles.push(tile);
+        self.tiles.len() - 1
+    }
+
+    /// Returns the tile at `index`.
+    pub fn get(&self, index: usize) -> Option<&Sprite> {
+        self.tiles.get(index)
+    }
+
+    /// Returns the number of tiles.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns `true` if the tileset has no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Returns the width of a tile in pixels.
+    pub fn tile_width(&self) -> usize {
+        self.tile_width
+    }
+
+    /// Returns the height of a tile in half-block pixels.
+    pub fn tile_height(&self) -> usize {
+        self.tile_height
+    }
+}
+
+/// A grid of tiles, each cell holding an index into a [`Tileset`] or nothing.
+///
+/// # Example
+///
+/// ```
+/// use rael::{Camera, Canvas, Color, Sprite, TileMap, Tileset};
+///
+/// let mut tileset = Tileset::new(4, 4);
+/// let grass = tileset.push(Sprite::filled(4, 4, Color { r: 40, g: 160, b: 40 }));
+/// let mut map = TileMap::new(100, 100, tileset);
+/// map.fill(Some(grass));
+///
+/// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+/// map.draw(&mut canvas, &Camera::new(120, 80), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "crate::serialization::TileMapData")
+)]
+pub struct TileMap {
+    width: usize,
+    height: usize,
+    tiles: Vec<Option<usize>>,
+    tileset: Tileset,
+}
+
+impl TileMap {
+    /// Creates an empty map of `width` by `height` tiles.
+    pub fn new(width: usize, height: usize, tileset: Tileset) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![None; width * height],
+            tileset,
+        }
+    }
+
+    /// Returns the width of the map in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the map in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the tileset used to draw the map.
+    pub fn tileset(&self) -> &Tileset {
+        &self.tileset
+    }
+
+    /// Returns the tileset used to draw the map, for adding tiles.
+    pub fn tileset_mut(&mut self) -> &mut Tileset {
+        &mut self.tileset
+    }
+
+    /// Returns the tile index at (`x`, `y`), or `None` if the cell is empty or out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.tiles[y * self.width + x]
+    }
+
+    /// Sets the tile index at (`x`, `y`). Out of bounds cells are ignored.
+    pub fn set(&mut self, x: usize, y: usize, tile: Option<usize>) {
+        if x < self.width && y < self.height {
+            self.tiles[y * self.width + x] = tile;
+        }
+    }
+
+    /// Sets every cell of the map to `tile`.
+    pub fn fill(&mut self, tile: Option<usize>) {
+        self.tiles.fill(tile);
+    }
+
+    /// Returns the (`x`, `y`) tile cell containing the world pixel (`world_x`, `world_y`).
+    pub fn cell_at(&self, world_x: isize, world_y: isize) -> Option<(usize, usize)> {
+        let (tile_width, tile_height) = (self.tileset.tile_width, self.tileset.tile_height);
+        if world_x < 0 || world_y < 0 || tile_width == 0 || tile_height == 0 {
+            return None;
+        }
+        let x = world_x as usize / tile_width;
+        let y = world_y as usize / tile_height;
+        (x < self.width && y < self.height).then_some((x, y))
+    }
+
+    /// Draws the tiles visible through `camera` onto layer `z`.
+    ///
+    /// Only the tiles overlapping the canvas are visited, so the cost depends on the size of the
+    /// canvas rather than the size of the map. Empty cells and tile indices missing from the
+    /// tileset are skipped.
+    pub fn draw(&self, canvas: &mut Canvas, camera: &Camera, z: usize) {
+        let (tile_width, tile_height) = (
+            self.tileset.tile_width as isize,
+            self.tileset.tile_height as isize,
+        );
+        if tile_width == 0 || tile_height == 0 {
+            return;
+        }
+        let first_column = camera.x.div_euclid(tile_width).max(0);
+        let first_row = camera.y.div_euclid(tile_height).max(0);
+        let last_column = (camera.x + canvas.final_width as isize)
+            .div_euclid(tile_width)
+            .min(self.width as isize - 1);
+        let last_row = (camera.y + canvas.final_height as isize)
+            .div_euclid(tile_height)
+            .min(self.height as isize - 1);
+
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                let Some(sprite) = self
+                    .get(column as usize, row as usize)
+                    .and_then(|tile| self.tileset.get(tile))
+                else {
+                    continue;
+                };
+                let (x, y) = camera.world_to_screen(column * tile_width, row * tile_height);
+                canvas.draw_sprite(sprite, x, y, z);
+            }
+        }
+    }
+}
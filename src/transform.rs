@@ -0,0 +1,228 @@
+//! Transforms: moving, turning and resizing what the drawing primitives draw.
+
+use crate::Canvas;
+
+/// A 2D affine transform of pixel coordinates, made of translations, rotations, scalings and
+/// their combinations, applied by the drawing primitives of a canvas to what they draw.
+///
+/// # Example
+///
+/// ```
+/// use rael::Transform;
+///
+/// let transform = Transform::scaling(2.0, 3.0).then(Transform::translation(10.0, 0.0));
+/// assert_eq!(transform.apply(1.0, 1.0), (12.0, 3.0));
+/// assert_eq!(transform.inverse().unwrap().apply(12.0, 3.0), (1.0, 1.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    // A point (x, y) goes to (xx * x + xy * y + dx, yx * x + yy * y + dy).
+    xx: f32,
+    yx: f32,
+    xy: f32,
+    yy: f32,
+    dx: f32,
+    dy: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    /// The transform leaving every point where it is.
+    pub const IDENTITY: Transform = Transform {
+        xx: 1.0,
+        yx: 0.0,
+        xy: 0.0,
+        yy: 1.0,
+        dx: 0.0,
+        dy: 0.0,
+    };
+
+    /// Creates the transform moving points `x` pixels right and `y` pixels down.
+    pub fn translation(x: f32, y: f32) -> Self {
+        Self {
+            dx: x,
+            dy: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Creates the transform turning points around the origin by `angle` radians, clockwise on
+    /// screen since rows go down.
+    #[cfg(feature = "std")]
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            xx: cos,
+            yx: sin,
+            xy: -sin,
+            yy: cos,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Creates the transform stretching points away from the origin `x` times horizontally and
+    /// `y` times vertically. Negative factors mirror.
+    pub fn scaling(x: f32, y: f32) -> Self {
+        Self {
+            xx: x,
+            yy: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Returns the transform applying this transform, then `next`.
+    pub fn then(self, next: Transform) -> Self {
+        Self {
+            xx: next.xx * self.xx + next.xy * self.yx,
+            yx: next.yx * self.xx + next.yy * self.yx,
+            xy: next.xx * self.xy + next.xy * self.yy,
+            yy: next.yx * self.xy + next.yy * self.yy,
+            dx: next.xx * self.dx + next.xy * self.dy + next.dx,
+            dy: next.yx * self.dx + next.yy * self.dy + next.dy,
+        }
+    }
+
+    /// Returns the transform undoing this transform, or `None` if it flattens points onto a
+    /// line or a point, as scaling by `0` does.
+    pub fn inverse(&self) -> Option<Transform> {
+        let determinant = self.determinant();
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let (xx, yx) = (self.yy / determinant, -self.yx / determinant);
+        let (xy, yy) = (-self.xy / determinant, self.xx / determinant);
+        Some(Self {
+            xx,
+            yx,
+            xy,
+            yy,
+            dx: -(xx * self.dx + xy * self.dy),
+            dy: -(yx * self.dx + yy * self.dy),
+        })
+    }
+
+    /// Returns where the transform takes the point (`x`, `y`).
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.xx * x + self.xy * y + self.dx,
+            self.yx * x + self.yy * y + self.dy,
+        )
+    }
+
+    /// Returns the factor by which the transform scales areas, negative when it mirrors.
+    fn determinant(&self) -> f32 {
+        self.xx * self.yy - self.xy * self.yx
+    }
+
+    /// Returns whether the transform only moves points, without turning or resizing them.
+    #[cfg(feature = "std")]
+    fn is_translation(&self) -> bool {
+        self.xx == 1.0 && self.yx == 0.0 && self.xy == 0.0 && self.yy == 1.0
+    }
+}
+
+impl Canvas {
+    /// Returns the transform the drawing primitives apply to what they draw.
+    ///
+    /// Lines, rectangles, circles, ellipses, polygons, curves, paths, flood fills and sprites
+    /// are drawn through the transform, so a shape can be turned around a pivot, or a whole
+    /// scene moved like a camera, without changing the code drawing it. Setting pixels one at a
+    /// time with [`Canvas::set_pixel`] ignores it. Defaults to [`Transform::IDENTITY`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rael::{Canvas, Color};
+    ///
+    /// let white = Color { r: 255, g: 255, b: 255 };
+    /// let mut canvas = Canvas::new(20, 10, Color { r: 0, g: 0, b: 0 });
+    /// // A bar turned a quarter turn around its center, at (10, 10).
+    /// canvas.push_transform();
+    /// canvas.translate(10.0, 10.0);
+    /// canvas.rotate(std::f32::consts::FRAC_PI_2);
+    /// canvas.translate(-10.0, -10.0);
+    /// canvas.fill_rect(4, 9, 12, 2, 0, white);
+    /// canvas.pop_transform();
+    /// assert_eq!(canvas.composited_cell(9, 2).top_color, white);
+    /// assert_eq!(canvas.composited_cell(5, 5).top_color, Color { r: 0, g: 0, b: 0 });
+    /// ```
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// Replaces the transform the drawing primitives apply with `transform`.
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    /// Saves the current transform, to be restored by the matching [`Canvas::pop_transform`].
+    pub fn push_transform(&mut self) {
+        self.transform_stack.push(self.transform);
+    }
+
+    /// Restores the transform saved by the last [`Canvas::push_transform`] not yet popped, if
+    /// any.
+    pub fn pop_transform(&mut self) {
+        if let Some(transform) = self.transform_stack.pop() {
+            self.transform = transform;
+        }
+    }
+
+    /// Moves what is drawn next `x` pixels right and `y` pixels down, in the coordinates of the
+    /// current transform.
+    pub fn translate(&mut self, x: f32, y: f32) {
+        self.transform = Transform::translation(x, y).then(self.transform);
+    }
+
+    /// Turns what is drawn next by `angle` radians, clockwise on screen, around the origin of
+    /// the current transform.
+    #[cfg(feature = "std")]
+    pub fn rotate(&mut self, angle: f32) {
+        self.transform = Transform::rotation(angle).then(self.transform);
+    }
+
+    /// Stretches what is drawn next `x` times horizontally and `y` times vertically, away from
+    /// the origin of the current transform.
+    pub fn scale(&mut self, x: f32, y: f32) {
+        self.transform = Transform::scaling(x, y).then(self.transform);
+    }
+
+    /// Returns where the current transform takes `point`.
+    #[cfg(feature = "std")]
+    pub(crate) fn transformed(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let t = &self.transform;
+        let (xx, yx, xy, yy) = (t.xx as f64, t.yx as f64, t.xy as f64, t.yy as f64);
+        (xx * x + xy * y + t.dx as f64, yx * x + yy * y + t.dy as f64)
+    }
+
+    /// Returns the pixel the current transform takes the pixel at (`x`, `y`) to.
+    #[cfg(feature = "std")]
+    pub(crate) fn transformed_pixel(&self, x: isize, y: isize) -> (isize, isize) {
+        let (x, y) = self.transformed((x as f64, y as f64));
+        (x.round() as isize, y.round() as isize)
+    }
+
+    /// Returns the whole pixels the current transform moves points by, if it only moves them,
+    /// so shapes can be drawn as they are, only moved.
+    #[cfg(feature = "std")]
+    pub(crate) fn pixel_offset(&self) -> Option<(isize, isize)> {
+        self.transform.is_translation().then(|| {
+            (
+                self.transform.dx.round() as isize,
+                self.transform.dy.round() as isize,
+            )
+        })
+    }
+
+    /// Returns the factor by which the current transform scales lengths on average, which
+    /// widths are scaled by.
+    #[cfg(feature = "std")]
+    pub(crate) fn length_scale(&self) -> f64 {
+        (self.transform.determinant() as f64).abs().sqrt()
+    }
+}
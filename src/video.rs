@@ -0,0 +1,319 @@
+//! Video playback onto the canvas, decoded by ffmpeg, behind the `video` feature.
+
+use std::fmt;
+use std::io::{self, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::dither::dither;
+use crate::{Canvas, Color, DitherMode};
+
+/// An error raised while playing a video.
+#[derive(Debug)]
+pub enum VideoError {
+    /// ffmpeg could not be started or its output could not be read.
+    Io(io::Error),
+    /// ffmpeg failed to decode the video, with what it reported.
+    Ffmpeg(String),
+}
+
+impl fmt::Display for VideoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VideoError::Io(error) => write!(f, "failed to run ffmpeg: {error}"),
+            VideoError::Ffmpeg(message) => write!(f, "ffmpeg failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for VideoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VideoError::Io(error) => Some(error),
+            VideoError::Ffmpeg(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for VideoError {
+    fn from(error: io::Error) -> Self {
+        VideoError::Io(error)
+    }
+}
+
+/// A video file played onto the canvas in real time.
+///
+/// Decoding is done by an `ffmpeg` process, which must be installed, so every format ffmpeg
+/// reads is supported. ffmpeg scales the video to fit the requested size in half-block pixels,
+/// keeping its aspect ratio with black bars, and resamples it to the requested frame rate.
+/// Frames that are late are skipped so playback keeps up with the clock. Audio is ignored.
+///
+/// # Example
+///
+/// ```no_run
+/// use rael::{Canvas, Color, DitherMode, Video};
+/// use std::io::Write;
+///
+/// let mut canvas = Canvas::new(80, 30, Color { r: 0, g: 0, b: 0 });
+/// let mut video = Video::new("bad_apple.mp4", 80, 60).dither(DitherMode::Ordered, 2);
+/// video.play()?;
+/// while video.update()? {
+///     video.draw(&mut canvas, 0, 0, 0);
+///     print!("{}", canvas.render());
+///     std::io::stdout().flush()?;
+///     std::thread::sleep(video.frame_duration() / 2);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Video {
+    path: PathBuf,
+    program: PathBuf,
+    width: usize,
+    height: usize,
+    fps: f64,
+    dither: Option<(DitherMode, u8)>,
+    looping: bool,
+    decoder: Option<Decoder>,
+    started: Instant,
+    frames_read: u64,
+    raw: Vec<u8>,
+    pixels: Vec<Color>,
+    finished: bool,
+}
+
+/// The most of what ffmpeg reports on its standard error that is kept, its last bytes, to
+/// explain a failure.
+const MAX_ERROR_OUTPUT: usize = 4096;
+
+/// A running ffmpeg process writing raw RGB frames to its standard output.
+#[derive(Debug)]
+struct Decoder {
+    child: Child,
+    output: BufReader<ChildStdout>,
+    /// The thread draining the standard error of ffmpeg, returning its last bytes.
+    errors: Option<JoinHandle<Vec<u8>>>,
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Video {
+    /// Creates a player for the video at `path`, shown at `width` by `height` half-block pixels.
+    /// Nothing is decoded until [`Video::play`] is called.
+    pub fn new(path: impl Into<PathBuf>, width: usize, height: usize) -> Self {
+        Self {
+            path: path.into(),
+            program: PathBuf::from("ffmpeg"),
+            width,
+            height,
+            fps: 30.0,
+            dither: None,
+            looping: false,
+            decoder: None,
+            started: Instant::now(),
+            frames_read: 0,
+            raw: vec![0; width * height * 3],
+            pixels: vec![Color { r: 0, g: 0, b: 0 }; width * height],
+            finished: false,
+        }
+    }
+
+    /// Sets the frame rate the video is played at. Defaults to `30.0`.
+    pub fn fps(mut self, fps: f64) -> Self {
+        self.fps = fps.max(0.001);
+        self
+    }
+
+    /// Reduces each color channel to `levels` levels, at least `2`, mixed by `mode`, such as
+    /// for a retro look. By default colors are shown as decoded.
+    pub fn dither(mut self, mode: DitherMode, levels: u8) -> Self {
+        self.dither = Some((mode, levels.max(2)));
+        self
+    }
+
+    /// Sets whether the video starts over when it ends. Defaults to `false`.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Sets the ffmpeg executable to run. Defaults to `ffmpeg`, looked up on the `PATH`.
+    pub fn program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Returns the width of the video in half-block pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the video in half-block pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns how long each frame is shown.
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.fps)
+    }
+
+    /// Returns how long the video has been playing.
+    pub fn position(&self) -> Duration {
+        self.frame_duration()
+            .mul_f64(self.frames_read.saturating_sub(1) as f64)
+    }
+
+    /// Returns whether the video has ended. Looping videos never end.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Starts ffmpeg and the playback clock, from the start of the video.
+    pub fn play(&mut self) -> Result<(), VideoError> {
+        // Kill the previous process first, so it does not hold its output pipe.
+        self.decoder = None;
+        let (width, height) = (self.width, self.height);
+        let filter = format!(
+            "fps={},scale={width}:{height}:force_original_aspect_ratio=decrease,pad={width}:{height}:(ow-iw)/2:(oh-ih)/2",
+            self.fps
+        );
+        let mut child = Command::new(&self.program)
+            .args(["-nostdin", "-loglevel", "error", "-i"])
+            .arg(&self.path)
+            .args([
+                "-an", "-vf", &filter, "-f", "rawvideo", "-pix_fmt", "rgb24", "-",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let output = BufReader::new(child.stdout.take().expect("ffmpeg output is piped"));
+        let mut stderr = child.stderr.take().expect("ffmpeg errors are piped");
+        // Drained as it comes, or ffmpeg would block on a full pipe while frames are read.
+        let errors = thread::spawn(move || {
+            let (mut kept, mut chunk) = (Vec::new(), [0; 1024]);
+            while let Ok(read @ 1..) = stderr.read(&mut chunk) {
+                kept.extend_from_slice(&chunk[..read]);
+                kept.drain(..kept.len().saturating_sub(MAX_ERROR_OUTPUT));
+            }
+            kept
+        });
+        self.decoder = Some(Decoder {
+            child,
+            output,
+            errors: Some(errors),
+        });
+        self.started = Instant::now();
+        self.frames_read = 0;
+        self.finished = false;
+        Ok(())
+    }
+
+    /// Reads the frame due at the current time, skipping late frames, and returns whether the
+    /// video is still playing. Starts playback if [`Video::play`] was not called.
+    pub fn update(&mut self) -> Result<bool, VideoError> {
+        if self.finished {
+            return Ok(false);
+        }
+        if self.decoder.is_none() {
+            self.play()?;
+        }
+        let due = (self.started.elapsed().as_secs_f64() * self.fps) as u64 + 1;
+        if self.frames_read >= due {
+            return Ok(true);
+        }
+        while self.frames_read < due {
+            if !self.read_frame()? {
+                if !self.looping {
+                    self.finished = true;
+                    return Ok(false);
+                }
+                self.play()?;
+                if !self.read_frame()? {
+                    // An empty video would otherwise restart forever.
+                    self.finished = true;
+                    return Ok(false);
+                }
+                break;
+            }
+        }
+        self.convert_frame();
+        Ok(true)
+    }
+
+    /// Draws the current frame onto layer `z` of `canvas`, with its top-left corner at pixel
+    /// (`x`, `y`). Pixels outside the canvas are skipped.
+    pub fn draw(&self, canvas: &mut Canvas, x: usize, y: usize, z: usize) {
+        for (index, color) in self.pixels.iter().enumerate() {
+            canvas.set_pixel(x + index % self.width, y + index / self.width, z, *color);
+        }
+    }
+
+    /// Reads the next frame into `raw`, returning `false` at the end of the video.
+    fn read_frame(&mut self) -> Result<bool, VideoError> {
+        let decoder = self.decoder.as_mut().expect("ffmpeg is running");
+        match decoder.output.read_exact(&mut self.raw) {
+            Ok(()) => {
+                self.frames_read += 1;
+                Ok(true)
+            }
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                let status = decoder.child.wait()?;
+                if status.success() {
+                    return Ok(false);
+                }
+                let message = decoder
+                    .errors
+                    .take()
+                    .and_then(|errors| errors.join().ok())
+                    .unwrap_or_default();
+                let message = String::from_utf8_lossy(&message);
+                let message = message.trim();
+                Err(VideoError::Ffmpeg(if message.is_empty() {
+                    status.to_string()
+                } else {
+                    message.to_string()
+                }))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Converts the raw frame to colors, dithering them.
+    fn convert_frame(&mut self) {
+        for (color, rgb) in self.pixels.iter_mut().zip(self.raw.chunks_exact(3)) {
+            *color = Color {
+                r: rgb[0],
+                g: rgb[1],
+                b: rgb[2],
+            };
+        }
+        let Some((mode, levels)) = self.dither else {
+            return;
+        };
+        let steps = levels as f32 - 1.0;
+        let level =
+            |value: u8| ((value as f32 / 255.0 * steps).round() / steps * 255.0).round() as u8;
+        let quantize = |color: Color| Color {
+            r: level(color.r),
+            g: level(color.g),
+            b: level(color.b),
+        };
+        dither(
+            &mut self.pixels,
+            self.width,
+            quantize,
+            255.0 / steps,
+            mode,
+            None,
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Embedding canvases in [ratatui](https://ratatui.rs) apps, behind the `ratatui` feature.
+
+use ratatui_core::buffer::Buffer;
+use ratatui_core::layout::Rect;
+use ratatui_core::style::Color as RatatuiColor;
+use ratatui_core::widgets::{StatefulWidget, Widget};
+
+use crate::{Canvas, Color};
+
+fn ratatui_color(color: Color) -> RatatuiColor {
+    RatatuiColor::Rgb(color.r, color.g, color.b)
+}
+
+/// Draws the canvas cells starting at (`column`, `row`) into `area` of a ratatui buffer.
+fn draw_canvas(canvas: &Canvas, column: usize, row: usize, area: Rect, buf: &mut Buffer) {
+    let area = area.intersection(buf.area);
+    for y in 0..area.height {
+        for x in 0..area.width {
+            let (canvas_column, canvas_row) = (column + x as usize, row + y as usize);
+            if canvas_column >= canvas.width || canvas_row >= canvas.height {
+                continue;
+            }
+            let Some(cell) = buf.cell_mut((area.x + x, area.y + y)) else {
+                continue;
+            };
+            let (composited, glyph) = canvas.composited(canvas_column, canvas_row);
+            match glyph {
+                // Ratatui leaves the cells covered by wide characters blank.
+                Some(glyph) if glyph.is_continuation() => cell
+                    .set_char(' ')
+                    .set_bg(ratatui_color(composited.top_color)),
+                Some(glyph) => {
+                    let mut symbol = String::from(glyph.ch);
+                    symbol.extend(glyph.mark);
+                    cell.set_symbol(&symbol)
+                        .set_fg(ratatui_color(glyph.color))
+                        .set_bg(ratatui_color(composited.top_color))
+                }
+                None => cell
+                    .set_char('▀')
+                    .set_fg(ratatui_color(composited.top_color))
+                    .set_bg(ratatui_color(composited.bottom_color)),
+            };
+        }
+    }
+}
+
+/// Draws the canvas into a ratatui buffer, one canvas cell per buffer cell, from its top-left
+/// corner. Parts of the canvas that do not fit the area are cut off.
+impl Widget for &Canvas {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        draw_canvas(self, 0, 0, area, buf);
+    }
+}
+
+/// A ratatui widget showing part of a canvas, scrolled by an offset, for canvases larger than
+/// the area they are shown in.
+///
+/// The canvas is the widget state, so it lives in the app while the view is created anew on
+/// every draw, as is usual with ratatui. Plain canvases can also be rendered directly, since
+/// `&Canvas` implements [`Widget`].
+///
+/// # Example
+///
+/// ```
+/// use rael::{Canvas, CanvasView, Color};
+/// use ratatui_core::buffer::Buffer;
+/// use ratatui_core::layout::Rect;
+/// use ratatui_core::widgets::StatefulWidget;
+///
+/// let mut canvas = Canvas::new(200, 100, Color { r: 0, g: 0, b: 0 });
+/// canvas.set_pixel(120, 80, 0, Color { r: 0, g: 255, b: 0 });
+///
+/// // In an app: `frame.render_stateful_widget(CanvasView::new().offset(100, 30), area, &mut canvas)`.
+/// let area = Rect::new(0, 0, 40, 20);
+/// let mut buffer = Buffer::empty(area);
+/// CanvasView::new().offset(100, 30).render(area, &mut buffer, &mut canvas);
+/// assert_eq!(buffer[(20, 10)].symbol(), "▀");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CanvasView {
+    column: usize,
+    row: usize,
+}
+
+impl CanvasView {
+    /// Creates a view showing the canvas from its top-left corner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the canvas cell shown at the top-left corner of the area. Defaults to `(0, 0)`.
+    pub fn offset(mut self, column: usize, row: usize) -> Self {
+        self.column = column;
+        self.row = row;
+        self
+    }
+}
+
+impl StatefulWidget for CanvasView {
+    type State = Canvas;
+
+    fn render(self, area: Rect, buf: &mut Buffer, canvas: &mut Canvas) {
+        draw_canvas(canvas, self.column, self.row, area, buf);
+    }
+}
@@ -0,0 +1,285 @@
+//! Chunked worlds: tile maps too large to keep in memory, streamed in chunks around the camera.
+
+use std::collections::HashMap;
+
+use crate::{Camera, Canvas, Tileset};
+
+/// A square block of tiles of a [`ChunkedWorld`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    size: usize,
+    tiles: Vec<Option<usize>>,
+}
+
+impl Chunk {
+    /// Creates an empty chunk of `size` by `size` tiles.
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            tiles: vec![None; size * size],
+        }
+    }
+
+    /// Returns the width and height of the chunk in tiles.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the tile index at (`x`, `y`) within the chunk, or `None` if the cell is empty or
+    /// out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.size || y >= self.size {
+            return None;
+        }
+        self.tiles[y * self.size + x]
+    }
+
+    /// Sets the tile index at (`x`, `y`) within the chunk. Out of bounds cells are ignored.
+    pub fn set(&mut self, x: usize, y: usize, tile: Option<usize>) {
+        if x < self.size && y < self.size {
+            self.tiles[y * self.size + x] = tile;
+        }
+    }
+
+    /// Sets every cell of the chunk to `tile`.
+    pub fn fill(&mut self, tile: Option<usize>) {
+        self.tiles.fill(tile);
+    }
+}
+
+/// Produces the chunks of a [`ChunkedWorld`] and optionally stores them when they are unloaded.
+///
+/// Any `FnMut(isize, isize, &mut Chunk)` closure is a source that fills the empty chunk at the
+/// given chunk coordinates, which suits procedural worlds.
+pub trait ChunkSource {
+    /// Fills `chunk`, an empty chunk at chunk coordinates (`x`, `y`), by generating it or
+    /// loading it from storage.
+    fn load(&mut self, x: isize, y: isize, chunk: &mut Chunk);
+
+    /// Called with a chunk that is dropped from memory, for example to save the changes made to
+    /// it. Does nothing by default.
+    fn unload(&mut self, x: isize, y: isize, chunk: Chunk) {
+        let _ = (x, y, chunk);
+    }
+}
+
+impl<F: FnMut(isize, isize, &mut Chunk)> ChunkSource for F {
+    fn load(&mut self, x: isize, y: isize, chunk: &mut Chunk) {
+        self(x, y, chunk)
+    }
+}
+
+/// An unbounded tile map split into square chunks, of which only those near the camera are kept
+/// in memory.
+///
+/// Call [`ChunkedWorld::update`] whenever the camera moves to load the chunks coming into view
+/// and unload those left far behind, then draw the world with [`ChunkedWorld::draw`].
+///
+/// # Example
+///
+/// ```
+/// use rael::{Camera, Canvas, ChunkedWorld, Chunk, Color, Sprite, Tileset};
+///
+/// let mut tileset = Tileset::new(4, 4);
+/// let grass = tileset.push(Sprite::filled(4, 4, Color { r: 40, g: 160, b: 40 }));
+/// let water = tileset.push(Sprite::filled(4, 4, Color { r: 40, g: 80, b: 200 }));
+///
+/// // An endless checkerboard of grass and water chunks.
+/// let mut world = ChunkedWorld::new(16, tileset, move |x: isize, y: isize, chunk: &mut Chunk| {
+///     chunk.fill(Some(if (x + y) % 2 == 0 { grass } else { water }));
+/// });
+///
+/// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+/// let camera = Camera::new(-1000, 5000);
+/// world.update(&camera, 40, 40);
+/// world.draw(&mut canvas, &camera, 0);
+/// ```
+pub struct ChunkedWorld<S: ChunkSource> {
+    chunk_size: usize,
+    tileset: Tileset,
+    source: S,
+    chunks: HashMap<(isize, isize), Chunk>,
+    keep_distance: usize,
+}
+
+impl<S: ChunkSource> ChunkedWorld<S> {
+    /// Creates a world of `chunk_size` by `chunk_size` tile chunks, drawn with `tileset` and
+    /// produced by `source`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn new(chunk_size: usize, tileset: Tileset, source: S) -> Self {
+        assert!(chunk_size > 0, "chunks must hold at least one tile");
+        Self {
+            chunk_size,
+            tileset,
+            source,
+            chunks: HashMap::new(),
+            keep_distance: 1,
+        }
+    }
+
+    /// Sets how many chunks beyond the visible ones stay loaded around the viewport, so that
+    /// moving back and forth does not reload the same chunks. Defaults to 1.
+    pub fn keep_distance(mut self, chunks: usize) -> Self {
+        self.keep_distance = chunks;
+        self
+    }
+
+    /// Returns the width and height of a chunk in tiles.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the tileset used to draw the world.
+    pub fn tileset(&self) -> &Tileset {
+        &self.tileset
+    }
+
+    /// Returns the chunk source.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns the chunk source, for example to change the settings of a generator.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Returns the number of chunks currently in memory.
+    pub fn loaded_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns the chunk at chunk coordinates (`x`, `y`) if it is loaded.
+    pub fn chunk(&self, x: isize, y: isize) -> Option<&Chunk> {
+        self.chunks.get(&(x, y))
+    }
+
+    /// Returns the chunk at chunk coordinates (`x`, `y`), loading it if needed.
+    pub fn chunk_mut(&mut self, x: isize, y: isize) -> &mut Chunk {
+        let (chunk_size, source) = (self.chunk_size, &mut self.source);
+        self.chunks.entry((x, y)).or_insert_with(|| {
+            let mut chunk = Chunk::new(chunk_size);
+            source.load(x, y, &mut chunk);
+            chunk
+        })
+    }
+
+    /// Splits world tile coordinates into chunk coordinates and coordinates within the chunk.
+    fn split(&self, x: isize, y: isize) -> ((isize, isize), (usize, usize)) {
+        let size = self.chunk_size as isize;
+        (
+            (x.div_euclid(size), y.div_euclid(size)),
+            (x.rem_euclid(size) as usize, y.rem_euclid(size) as usize),
+        )
+    }
+
+    /// Returns the tile index at world tile (`x`, `y`), or `None` if the cell is empty or its
+    /// chunk is not loaded.
+    pub fn get(&self, x: isize, y: isize) -> Option<usize> {
+        let (chunk, (cx, cy)) = self.split(x, y);
+        self.chunk(chunk.0, chunk.1)?.get(cx, cy)
+    }
+
+    /// Sets the tile index at world tile (`x`, `y`), loading its chunk if needed.
+    pub fn set(&mut self, x: isize, y: isize, tile: Option<usize>) {
+        let (chunk, (cx, cy)) = self.split(x, y);
+        self.chunk_mut(chunk.0, chunk.1).set(cx, cy, tile);
+    }
+
+    /// Returns the world tile containing the world pixel (`world_x`, `world_y`).
+    pub fn cell_at(&self, world_x: isize, world_y: isize) -> (isize, isize) {
+        (
+            world_x.div_euclid(self.tileset.tile_width().max(1) as isize),
+            world_y.div_euclid(self.tileset.tile_height().max(1) as isize),
+        )
+    }
+
+    /// Returns the inclusive range of chunk coordinates covering a viewport of
+    /// `viewport_width` by `viewport_height` pixels seen through `camera`, widened by `margin`
+    /// chunks.
+    fn chunk_range(
+        &self,
+        camera: &Camera,
+        viewport_width: usize,
+        viewport_height: usize,
+        margin: isize,
+    ) -> ((isize, isize), (isize, isize)) {
+        let chunk_width = (self.tileset.tile_width() * self.chunk_size).max(1) as isize;
+        let chunk_height = (self.tileset.tile_height() * self.chunk_size).max(1) as isize;
+        let first = (
+            camera.x.div_euclid(chunk_width) - margin,
+            camera.y.div_euclid(chunk_height) - margin,
+        );
+        let last = (
+            (camera.x + viewport_width as isize).div_euclid(chunk_width) + margin,
+            (camera.y + viewport_height as isize).div_euclid(chunk_height) + margin,
+        );
+        (first, last)
+    }
+
+    /// Loads the chunks visible in a viewport of `viewport_width` by `viewport_height` pixels
+    /// seen through `camera`, and unloads the chunks further than the keep distance from it.
+    pub fn update(&mut self, camera: &Camera, viewport_width: usize, viewport_height: usize) {
+        let (first, last) = self.chunk_range(camera, viewport_width, viewport_height, 0);
+        for y in first.1..=last.1 {
+            for x in first.0..=last.0 {
+                self.chunk_mut(x, y);
+            }
+        }
+
+        let keep = self.keep_distance as isize;
+        let (first, last) = self.chunk_range(camera, viewport_width, viewport_height, keep);
+        let far: Vec<_> = self
+            .chunks
+            .keys()
+            .filter(|(x, y)| *x < first.0 || *x > last.0 || *y < first.1 || *y > last.1)
+            .copied()
+            .collect();
+        for (x, y) in far {
+            if let Some(chunk) = self.chunks.remove(&(x, y)) {
+                self.source.unload(x, y, chunk);
+            }
+        }
+    }
+
+    /// Unloads every chunk, handing each one to the source.
+    pub fn unload_all(&mut self) {
+        for ((x, y), chunk) in self.chunks.drain() {
+            self.source.unload(x, y, chunk);
+        }
+    }
+
+    /// Draws the loaded tiles visible through `camera` onto layer `z`.
+    ///
+    /// Chunks are not loaded while drawing, so call [`ChunkedWorld::update`] first.
+    pub fn draw(&self, canvas: &mut Canvas, camera: &Camera, z: usize) {
+        let (tile_width, tile_height) = (
+            self.tileset.tile_width() as isize,
+            self.tileset.tile_height() as isize,
+        );
+        if tile_width == 0 || tile_height == 0 {
+            return;
+        }
+        let (first_column, first_row) = self.cell_at(camera.x, camera.y);
+        let (last_column, last_row) = self.cell_at(
+            camera.x + canvas.final_width as isize,
+            camera.y + canvas.final_height as isize,
+        );
+
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                let Some(sprite) = self
+                    .get(column, row)
+                    .and_then(|tile| self.tileset.get(tile))
+                else {
+                    continue;
+                };
+                let (x, y) = camera.world_to_screen(column * tile_width, row * tile_height);
+                canvas.draw_sprite(sprite, x, y, z);
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! The xterm 256-color palette, for terminals without 24-bit colors and for retro-palette art.
+//!
+//! The palette holds the 16 system colors, a 6x6x6 color cube and a ramp of 24 grays. Most
+//! terminals let themes change the 16 system colors, so they are given here with the xterm
+//! defaults and [`nearest`] only picks from the fixed colors, indices 16 to 255.
+//!
+//! # Example
+//!
+//! ```
+//! use rael::{xterm, Color};
+//!
+//! let orange = Color { r: 255, g: 135, b: 0 };
+//! assert_eq!(xterm::nearest(orange), 208);
+//! assert_eq!(xterm::color(208), orange);
+//!
+//! // Snap a color to the palette, for art limited to the 256 colors.
+//! let snapped = xterm::color(xterm::nearest(Color { r: 100, g: 149, b: 237 }));
+//! assert_eq!(snapped, Color { r: 95, g: 135, b: 255 });
+//! ```
+
+use crate::palette::ansi;
+use crate::Color;
+
+/// The channel levels of the 6x6x6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The index of the first color of the 6x6x6 color cube.
+pub const CUBE_START: u8 = 16;
+
+/// The index of the first color of the gray ramp.
+pub const GRAY_START: u8 = 232;
+
+/// The 256 colors of the palette, by index, with the xterm defaults for the 16 system colors.
+pub const PALETTE: [Color; 256] = build_palette();
+
+const fn build_palette() -> [Color; 256] {
+    let mut palette = [Color { r: 0, g: 0, b: 0 }; 256];
+    let mut index = 0;
+    while index < 256 {
+        palette[index] = if index < CUBE_START as usize {
+            ansi::COLORS[index].1
+        } else if index < GRAY_START as usize {
+            let cube = index - CUBE_START as usize;
+            Color {
+                r: CUBE_LEVELS[cube / 36],
+                g: CUBE_LEVELS[cube / 6 % 6],
+                b: CUBE_LEVELS[cube % 6],
+            }
+        } else {
+            let level = 8 + 10 * (index - GRAY_START as usize) as u8;
+            Color {
+                r: level,
+                g: level,
+                b: level,
+            }
+        };
+        index += 1;
+    }
+    palette
+}
+
+/// Returns the color of palette entry `index`.
+pub const fn color(index: u8) -> Color {
+    PALETTE[index as usize]
+}
+
+/// Returns the index of the fixed palette color, from 16 to 255, closest to `color`.
+pub fn nearest(color: Color) -> u8 {
+    // The cube is the product of its channel levels, so its closest color is found channel by
+    // channel, and the closest gray is the one closest to the average of the channels.
+    let level = |channel: u8| match channel {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (channel - 35) / 40,
+    };
+    let (r, g, b) = (level(color.r), level(color.g), level(color.b));
+    let cube = CUBE_START + 36 * r + 6 * g + b;
+    let average = (color.r as u16 + color.g as u16 + color.b as u16) / 3;
+    let gray = GRAY_START + (average.saturating_sub(3) / 10).min(23) as u8;
+    if distance(color, PALETTE[gray as usize]) < distance(color, PALETTE[cube as usize]) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// Returns the squared distance between two colors.
+pub(crate) fn distance(a: Color, b: Color) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(a.r, b.r) + channel(a.g, b.g) + channel(a.b, b.b)
+}